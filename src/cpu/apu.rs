@@ -0,0 +1,496 @@
+//! APU: two square channels (the first one with a frequency sweep), a wave channel playing the wave RAM and a noise
+//! channel, mixed into stereo samples at `SAMPLE_RATE`. The frame sequencer clocks their length counters, volume
+//! envelopes and sweep at 512 Hz.
+
+use crate::cpu::execution::CPU_FREQUENCY;
+use crate::cpu::io::{NR10, NR13, NR14, NR30, NR32, NR43, NR50, NR51, NR52};
+use crate::cpu::memory::Memory;
+use crate::state::savestate::{StateError, StateReader, StateWriter, Stateful};
+use crate::utils::bits::get_bit;
+use crate::utils::log::log;
+use crate::utils::types::{FarAddress, Value};
+
+/// Output rate of the mixer, in Hz
+pub const SAMPLE_RATE: u32 = 48_000;
+/// Left, right
+pub type StereoSample = (i16, i16);
+
+/// Samples nobody took within a second are dropped, when the sound is neither played nor recorded
+const MAX_BUFFERED_SAMPLES: usize = SAMPLE_RATE as usize;
+/// Clock ticks between two steps of the frame sequencer, which runs at 512 Hz
+const SEQUENCER_TICKS: u64 = CPU_FREQUENCY / 512;
+const CHANNEL_COUNT: usize = 4;
+const WAVE_CHANNEL: usize = 2;
+const NOISE_CHANNEL: usize = 3;
+/// Registers of a channel, from `NRx0` to `NRx4`
+const CHANNEL_REGISTERS: FarAddress = 5;
+const TRIGGER_BIT: usize = 7;
+const LENGTH_ENABLE_BIT: usize = 6;
+const POWER_BIT: usize = 7;
+const MAX_FREQUENCY: u16 = 0x7FF;
+/// Waveforms of the duty cycles of the square channels (12.5%, 25%, 50% and 75%), played from bit 0
+const DUTY_WAVEFORMS: [Value; 4] = [0b1000_0000, 0b1000_0001, 0b1110_0001, 0b0111_1110];
+/// Bits of NR10-NR52 which read as 1: the unused and the write-only ones
+const READ_MASKS: [Value; 23] = [
+    0x80, 0x3F, 0x00, 0xFF, 0xBF,
+    0xFF, 0x3F, 0x00, 0xFF, 0xBF,
+    0x7F, 0xFF, 0x9F, 0xFF, 0xBF,
+    0xFF, 0xFF, 0x00, 0x00, 0xBF,
+    0x00, 0x00, 0x70,
+];
+/// Part of the output kept by the high-pass filter at each sample, removing the DC offset of the DACs as the capacitor
+/// of the hardware does (0.999958 per clock tick)
+const HIGH_PASS_CHARGE: f32 = 0.996;
+
+/// State of a channel, each kind of channel using part of it
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+struct Channel {
+    enabled: bool,
+    /// Clocked by the frame sequencer when enabled in `NRx4`, the channel stops when it reaches 0
+    length: u16,
+    /// Clock ticks until the next step of the waveform
+    timer: u32,
+    /// Step of the duty waveform (square), or sample of the wave RAM (wave)
+    position: u8,
+    /// Volume of the envelope (square and noise)
+    volume: u8,
+    envelope_timer: u8,
+    /// Linear-feedback shift register, whose bit 0 is the output when clear (noise)
+    lfsr: u16,
+}
+
+/// Frequency sweep of the first square channel
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+struct Sweep {
+    enabled: bool,
+    /// Frequency the sweep computes from
+    shadow: u16,
+    timer: u8,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Apu {
+    /// Bit 7 of NR52, kept apart as the register is stored before its write is handled
+    powered: bool,
+    channels: [Channel; CHANNEL_COUNT],
+    sweep: Sweep,
+    sequencer_timer: u64,
+    sequencer_step: u8,
+    /// Clock ticks elapsed since the last sample, times `SAMPLE_RATE`
+    sample_clock: u64,
+    /// Charge of the high-pass filter capacitors, left and right
+    capacitors: (f32, f32),
+    /// Mixed samples not taken yet by the sound output or the recording, which aren't part of the save states
+    pub samples: Vec<StereoSample>,
+}
+
+impl Stateful for Apu {
+    fn save_state(&self, writer: &mut StateWriter) {
+        for channel in &self.channels {
+            writer.write_bytes(&[u8::from(channel.enabled), channel.position, channel.volume, channel.envelope_timer]);
+            writer.write_wide(channel.length);
+            writer.write_wide(channel.lfsr);
+            writer.write_u32(channel.timer);
+        }
+        writer.write_bytes(&[u8::from(self.powered), u8::from(self.sweep.enabled), self.sweep.timer, self.sequencer_step]);
+        writer.write_wide(self.sweep.shadow);
+        writer.write_u64(self.sequencer_timer);
+        writer.write_u64(self.sample_clock);
+        writer.write_u32(self.capacitors.0.to_bits());
+        writer.write_u32(self.capacitors.1.to_bits());
+    }
+
+    fn load_state(&mut self, reader: &mut StateReader) -> Result<(), StateError> {
+        for channel in &mut self.channels {
+            let flags = reader.read_bytes(4)?;
+            channel.enabled = flags[0] != 0;
+            channel.position = flags[1];
+            channel.volume = flags[2];
+            channel.envelope_timer = flags[3];
+            channel.length = reader.read_wide()?;
+            channel.lfsr = reader.read_wide()?;
+            channel.timer = reader.read_u32()?;
+        }
+        let flags = reader.read_bytes(4)?;
+        self.powered = flags[0] != 0;
+        self.sweep.enabled = flags[1] != 0;
+        self.sweep.timer = flags[2];
+        self.sequencer_step = flags[3] % 8;
+        self.sweep.shadow = reader.read_wide()?;
+        self.sequencer_timer = reader.read_u64()?;
+        self.sample_clock = reader.read_u64()?;
+        self.capacitors = (f32::from_bits(reader.read_u32()?), f32::from_bits(reader.read_u32()?));
+
+        return Ok(());
+    }
+}
+
+fn register(channel: usize, index: FarAddress) -> usize {
+    #[allow(clippy::cast_possible_truncation)]
+    return usize::from(NR10 + channel as FarAddress * CHANNEL_REGISTERS + index);
+}
+
+fn powered(memory: &Memory) -> bool {
+    return memory.apu.powered;
+}
+
+fn frequency(memory: &Memory, channel: usize) -> u16 {
+    return u16::from(memory.memory[register(channel, 4)] & 0x07) << 8 | u16::from(memory.memory[register(channel, 3)]);
+}
+
+/// Clock ticks between two steps of the waveform
+fn period(memory: &Memory, channel: usize) -> u32 {
+    return match channel {
+        WAVE_CHANNEL => 2 * u32::from(2048 - frequency(memory, channel)),
+        NOISE_CHANNEL => {
+            let shape = memory.memory[NR43 as usize];
+            let divisor = match shape & 0x07 { 0 => 8, divisor => 16 * u32::from(divisor) };
+            divisor << (shape >> 4)
+        }
+        _ => 4 * u32::from(2048 - frequency(memory, channel)),
+    }
+}
+
+/// The DAC of the wave channel is switched in NR30, the others are off when their envelope is silent and decreasing
+fn dac_enabled(memory: &Memory, channel: usize) -> bool {
+    if channel == WAVE_CHANNEL { return get_bit(memory.memory[NR30 as usize], 7); }
+    return memory.memory[register(channel, 2)] & 0xF8 != 0;
+}
+
+fn max_length(channel: usize) -> u16 {
+    return if channel == WAVE_CHANNEL { 256 } else { 64 };
+}
+
+/// Digital output of the channel, from 0 to 15
+fn output(memory: &Memory, channel: usize) -> u8 {
+    let state = &memory.apu.channels[channel];
+    if !state.enabled { return 0; }
+
+    return match channel {
+        WAVE_CHANNEL => {
+            let byte = memory.memory[usize::from(crate::cpu::io::WAVE_RAM_START) + usize::from(state.position / 2)];
+            let sample = if state.position.is_multiple_of(2) { byte >> 4 } else { byte & 0x0F };
+            match (memory.memory[NR32 as usize] >> 5) & 0x03 {
+                0 => 0,
+                level => sample >> (level - 1),
+            }
+        }
+        NOISE_CHANNEL => if state.lfsr & 0x01 == 0 { state.volume } else { 0 },
+        _ => {
+            let duty = DUTY_WAVEFORMS[usize::from(memory.memory[register(channel, 1)] >> 6)];
+            if get_bit(duty, usize::from(state.position)) { state.volume } else { 0 }
+        }
+    }
+}
+
+/// Moves to the next step of the waveform
+fn step_waveform(memory: &mut Memory, channel: usize) {
+    let width_7 = get_bit(memory.memory[NR43 as usize], 3);
+    let state = &mut memory.apu.channels[channel];
+
+    match channel {
+        WAVE_CHANNEL => state.position = (state.position + 1) % 32,
+        NOISE_CHANNEL => {
+            let feedback = (state.lfsr ^ (state.lfsr >> 1)) & 0x01;
+            state.lfsr = (state.lfsr >> 1) | (feedback << 14);
+            if width_7 { state.lfsr = (state.lfsr & !(1 << 6)) | (feedback << 6); }
+        }
+        _ => state.position = (state.position + 1) % 8,
+    }
+}
+
+/// Frequency the sweep moves to from its shadow frequency, over `MAX_FREQUENCY` when it overflows
+fn sweep_target(memory: &Memory) -> u16 {
+    let sweep = memory.memory[NR10 as usize];
+    let delta = memory.apu.sweep.shadow >> (sweep & 0x07);
+    return if get_bit(sweep, 3) { memory.apu.sweep.shadow.wrapping_sub(delta) } else { memory.apu.sweep.shadow + delta };
+}
+
+/// Sweep steps, in 128 Hz ticks, the sweep being stopped when 0
+fn sweep_pace(memory: &Memory) -> u8 {
+    return (memory.memory[NR10 as usize] >> 4) & 0x07;
+}
+
+fn sweep_period(memory: &Memory) -> u8 {
+    return match sweep_pace(memory) { 0 => 8, period => period };
+}
+
+/// Restarts the channel, which only plays if its DAC is on
+fn trigger(memory: &mut Memory, channel: usize) {
+    let enabled = dac_enabled(memory, channel);
+    let timer = period(memory, channel);
+    let envelope = memory.memory[register(channel, 2)];
+
+    let state = &mut memory.apu.channels[channel];
+    state.enabled = enabled;
+    if state.length == 0 { state.length = max_length(channel); }
+    state.timer = timer;
+    state.volume = envelope >> 4;
+    state.envelope_timer = envelope & 0x07;
+    state.position = 0;
+    state.lfsr = 0x7FFF;
+
+    if channel == 0 {
+        let shift = memory.memory[NR10 as usize] & 0x07;
+        memory.apu.sweep = Sweep {
+            enabled: sweep_pace(memory) != 0 || shift != 0,
+            shadow: frequency(memory, 0),
+            timer: sweep_period(memory),
+        };
+        if shift != 0 && sweep_target(memory) > MAX_FREQUENCY { memory.apu.channels[0].enabled = false; }
+    }
+}
+
+fn clock_length(memory: &mut Memory, channel: usize) {
+    if !get_bit(memory.memory[register(channel, 4)], LENGTH_ENABLE_BIT) { return; }
+
+    let state = &mut memory.apu.channels[channel];
+    if state.length == 0 { return; }
+    state.length -= 1;
+    if state.length == 0 { state.enabled = false; }
+}
+
+fn clock_envelope(memory: &mut Memory, channel: usize) {
+    let envelope = memory.memory[register(channel, 2)];
+    let period = envelope & 0x07;
+    if period == 0 { return; }
+
+    let state = &mut memory.apu.channels[channel];
+    state.envelope_timer = state.envelope_timer.saturating_sub(1);
+    if state.envelope_timer > 0 { return; }
+
+    state.envelope_timer = period;
+    if get_bit(envelope, 3) && state.volume < 15 { state.volume += 1; }
+    if !get_bit(envelope, 3) && state.volume > 0 { state.volume -= 1; }
+}
+
+/// Moves the frequency of the first square channel, silencing it once it overflows
+fn clock_sweep(memory: &mut Memory) {
+    memory.apu.sweep.timer = memory.apu.sweep.timer.saturating_sub(1);
+    if memory.apu.sweep.timer > 0 { return; }
+
+    memory.apu.sweep.timer = sweep_period(memory);
+    let sweep = memory.memory[NR10 as usize];
+    if !memory.apu.sweep.enabled || sweep_pace(memory) == 0 { return; }
+
+    let target = sweep_target(memory);
+    if target > MAX_FREQUENCY {
+        memory.apu.channels[0].enabled = false;
+    } else if sweep & 0x07 != 0 {
+        memory.apu.sweep.shadow = target;
+        let [high, low] = target.to_be_bytes();
+        memory.memory[NR13 as usize] = low;
+        memory.memory[NR14 as usize] = (memory.memory[NR14 as usize] & !0x07) | high;
+        if sweep_target(memory) > MAX_FREQUENCY { memory.apu.channels[0].enabled = false; }
+    }
+}
+
+fn step_sequencer(memory: &mut Memory) {
+    let step = memory.apu.sequencer_step;
+    if step.is_multiple_of(2) {
+        for channel in 0..CHANNEL_COUNT { clock_length(memory, channel); }
+    }
+    if step == 2 || step == 6 { clock_sweep(memory); }
+    if step == 7 {
+        for channel in [0, 1, NOISE_CHANNEL] { clock_envelope(memory, channel); }
+    }
+    memory.apu.sequencer_step = (step + 1) % 8;
+}
+
+/// Mixes the analog outputs of the DACs through the panning and the master volume, then removes their DC offset
+#[allow(clippy::cast_possible_truncation)]
+fn mix(memory: &mut Memory) -> StereoSample {
+    let panning = memory.memory[NR51 as usize];
+    let (mut left, mut right) = (0.0, 0.0);
+    for channel in 0..CHANNEL_COUNT {
+        if !powered(memory) || !dac_enabled(memory, channel) { continue; }
+
+        let analog = f32::from(output(memory, channel)) / 7.5 - 1.0;
+        if get_bit(panning, channel + 4) { left += analog; }
+        if get_bit(panning, channel) { right += analog; }
+    }
+
+    // 4 channels at most, times a master volume from 1 to 8
+    let volumes = memory.memory[NR50 as usize];
+    let left = left * f32::from(((volumes >> 4) & 0x07) + 1) / 32.0;
+    let right = right * f32::from((volumes & 0x07) + 1) / 32.0;
+
+    let (left_capacitor, right_capacitor) = memory.apu.capacitors;
+    let (left, right) = (left - left_capacitor, right - right_capacitor);
+    memory.apu.capacitors = (left_capacitor + left * (1.0 - HIGH_PASS_CHARGE), right_capacitor + right * (1.0 - HIGH_PASS_CHARGE));
+
+    return ((left * f32::from(i16::MAX)) as i16, (right * f32::from(i16::MAX)) as i16);
+}
+
+/// Advances the APU by the clock ticks elapsed, adding the samples produced meanwhile to `Apu::samples`. The APU runs
+/// at the same speed in double speed.
+pub fn tick(memory: &mut Memory, ticks: u64) {
+    let mut remaining = ticks;
+    while remaining > 0 {
+        // Clock ticks until the next sample is due, rounded up
+        let elapsed = remaining.min((CPU_FREQUENCY - memory.apu.sample_clock).div_ceil(u64::from(SAMPLE_RATE)));
+        remaining -= elapsed;
+        if powered(memory) { advance(memory, elapsed); }
+
+        memory.apu.sample_clock += elapsed * u64::from(SAMPLE_RATE);
+        if memory.apu.sample_clock >= CPU_FREQUENCY {
+            memory.apu.sample_clock -= CPU_FREQUENCY;
+            let sample = mix(memory);
+            if memory.apu.samples.len() < MAX_BUFFERED_SAMPLES { memory.apu.samples.push(sample); }
+        }
+    }
+}
+
+/// Advances the waveforms of the channels and the frame sequencer
+fn advance(memory: &mut Memory, ticks: u64) {
+    for channel in 0..CHANNEL_COUNT {
+        let mut remaining = ticks;
+        while remaining >= u64::from(memory.apu.channels[channel].timer) {
+            remaining -= u64::from(memory.apu.channels[channel].timer);
+            step_waveform(memory, channel);
+            memory.apu.channels[channel].timer = period(memory, channel);
+        }
+        #[allow(clippy::cast_possible_truncation)]
+        { memory.apu.channels[channel].timer -= remaining as u32; }
+    }
+
+    memory.apu.sequencer_timer += ticks;
+    while memory.apu.sequencer_timer >= SEQUENCER_TICKS {
+        memory.apu.sequencer_timer -= SEQUENCER_TICKS;
+        step_sequencer(memory);
+    }
+}
+
+/// A sound register was written, once the value is stored. They are read-only, and cleared, while the APU is off.
+pub fn write_register(memory: &mut Memory, addr: FarAddress, value: Value) {
+    if addr == NR52 {
+        power(memory, get_bit(value, POWER_BIT));
+        return;
+    }
+    if !powered(memory) {
+        memory.memory[usize::from(addr)] = 0;
+        return;
+    }
+
+    let (channel, index) = (usize::from((addr - NR10) / CHANNEL_REGISTERS), (addr - NR10) % CHANNEL_REGISTERS);
+    if channel >= CHANNEL_COUNT { return; }
+
+    match index {
+        1 => {
+            let mask = if channel == WAVE_CHANNEL { 0xFF } else { 0x3F };
+            memory.apu.channels[channel].length = max_length(channel) - u16::from(value & mask);
+        }
+        4 if get_bit(value, TRIGGER_BIT) => trigger(memory, channel),
+        _ => {}
+    }
+    if !dac_enabled(memory, channel) { memory.apu.channels[channel].enabled = false; }
+}
+
+/// Turning the APU off clears its registers and stops the channels, the wave RAM is kept
+fn power(memory: &mut Memory, on: bool) {
+    if on != powered(memory) { log!("MEMORY", format!("Sound turned {}", if on { "on" } else { "off" })); }
+
+    if !on {
+        memory.memory[usize::from(NR10)..usize::from(NR52)].fill(0);
+        memory.apu.channels = [Channel::default(); CHANNEL_COUNT];
+        memory.apu.sweep = Sweep::default();
+    } else if !powered(memory) {
+        memory.apu.sequencer_step = 0;
+        memory.apu.sequencer_timer = 0;
+    }
+    memory.apu.powered = on;
+    memory.memory[NR52 as usize] = if on { 1 << POWER_BIT } else { 0 };
+}
+
+/// Value read by the CPU from a sound register: the unused and write-only bits read as 1, and NR52 tells which
+/// channels are playing
+pub fn read_register(memory: &Memory, addr: FarAddress, stored: Value) -> Value {
+    if addr == NR52 {
+        let playing = (0..CHANNEL_COUNT).filter(|channel| memory.apu.channels[*channel].enabled).fold(0, |bits, channel| bits | 1 << channel);
+        return stored | READ_MASKS[usize::from(addr - NR10)] | playing;
+    }
+    return stored | READ_MASKS[usize::from(addr - NR10)];
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cpu::io::{NR10, NR30, NR50, NR51, NR52};
+    use crate::cpu::memory::Memory;
+    use super::{tick, SEQUENCER_TICKS};
+
+    fn powered_on() -> Memory {
+        let mut memory = Memory::new(0x10000);
+        memory.write_far_addr(NR52, 0x80);
+        memory.write_far_addr(NR50, 0x77);
+        memory.write_far_addr(NR51, 0xFF);
+        return memory;
+    }
+
+    #[test]
+    fn test_registers() {
+        let mut memory = Memory::new(0x10000);
+        memory.write_far_addr(NR10, 0x12);
+        assert_eq!((memory.read_far_addr(NR10), memory.read_far_addr(NR52)), (0x80, 0x70));
+
+        memory = powered_on();
+        memory.write_far_addr(NR10, 0x12);
+        assert_eq!(memory.read_far_addr(NR10), 0x92);
+        // Square 1 at full volume, triggered
+        memory.write_far_addr(0xFF12, 0xF0);
+        memory.write_far_addr(0xFF14, 0x80);
+        assert_eq!(memory.read_far_addr(NR52), 0xF1);
+        // Frequency and trigger are write-only
+        assert_eq!(memory.read_far_addr(0xFF14), 0xBF);
+
+        // Turning the DAC off stops the channel
+        memory.write_far_addr(0xFF12, 0x00);
+        assert_eq!(memory.read_far_addr(NR52), 0xF0);
+        // The sweep overflows right away, from 0x700 to 0x8C0
+        memory.write_far_addr(0xFF12, 0xF0);
+        memory.write_far_addr(0xFF14, 0x87);
+        assert_eq!(memory.read_far_addr(NR52), 0xF0);
+
+        memory.write_far_addr(NR52, 0x00);
+        assert_eq!((memory.read_far_addr(NR10), memory.read_far_addr(NR50)), (0x80, 0x00));
+    }
+
+    #[test]
+    fn test_length() {
+        let mut memory = powered_on();
+        // Wave channel with a length of 2, enabled
+        memory.write_far_addr(NR30, 0x80);
+        memory.write_far_addr(0xFF1B, 0xFE);
+        memory.write_far_addr(0xFF1E, 0xC0);
+        assert_eq!(memory.read_far_addr(NR52), 0xF4);
+
+        tick(&mut memory, 2 * SEQUENCER_TICKS);
+        assert_eq!(memory.read_far_addr(NR52), 0xF4);
+        tick(&mut memory, 2 * SEQUENCER_TICKS);
+        assert_eq!(memory.read_far_addr(NR52), 0xF0);
+    }
+
+    #[test]
+    fn test_samples() {
+        let mut memory = powered_on();
+        tick(&mut memory, 70_224);
+        // 48000 Hz over a frame of 70224 ticks at 4194304 Hz
+        assert_eq!(memory.apu.samples.len(), 803);
+        assert!(memory.apu.samples.iter().all(|sample| *sample == (0, 0)));
+
+        // A square wave at 50% duty, around 1 kHz
+        memory.apu.samples.clear();
+        memory.write_far_addr(0xFF16, 0x80);
+        memory.write_far_addr(0xFF17, 0xF0);
+        memory.write_far_addr(0xFF18, 0x83);
+        memory.write_far_addr(0xFF19, 0x87);
+        tick(&mut memory, 70_224);
+        let positive = memory.apu.samples.iter().filter(|(left, _)| *left > 0).count();
+        let negative = memory.apu.samples.iter().filter(|(left, _)| *left < 0).count();
+        assert!(positive > 300 && negative > 300, "{positive} positive and {negative} negative samples");
+        assert!(memory.apu.samples.iter().all(|(left, right)| left == right));
+
+        // Samples which aren't taken are dropped after a second
+        for _ in 0..100 { tick(&mut memory, 70_224); }
+        assert_eq!(memory.apu.samples.len(), 48_000);
+    }
+}
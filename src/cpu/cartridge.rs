@@ -0,0 +1,323 @@
+//! Cartridge ROM and RAM, banked by the memory bank controller (MBC) of the cartridge
+
+use std::rc::Rc;
+use crate::state::savestate::{StateError, StateReader, StateWriter, Stateful};
+use crate::utils::log::log;
+use crate::utils::types::{Byte, FarAddress, Value};
+
+/// Cartridge header fields
+const CARTRIDGE_TYPE_ADDR: usize = 0x0147;
+const RAM_SIZE_ADDR: usize = 0x0149;
+
+pub const ROM_BANK_SIZE: usize = 0x4000;
+pub const RAM_BANK_SIZE: usize = 0x2000;
+const ROMX_START: FarAddress = 0x4000;
+const RAM_START: FarAddress = 0xA000;
+
+/// Value written to the low nibble of the RAM enable register to enable the RAM
+const RAM_ENABLE_VALUE: Value = 0x0A;
+/// MBC1: BANK1 holds the low 5 bits of the ROM bank, BANK2 the 2 bits above them (or the RAM bank)
+const BANK1_MASK: Value = 0x1F;
+const BANK1_BITS: usize = 5;
+const BANK2_MASK: Value = 0x03;
+/// MBC5: BANK1 holds the low 8 bits of the ROM bank, the 9th bit being written apart, and BANK2 the RAM bank
+const MBC5_ROM_HIGH_BITS: usize = 8;
+const MBC5_RAM_BANK_MASK: Value = 0x0F;
+/// Bit 3 of the RAM bank register drives the motor of the rumble cartridges instead
+const MBC5_RUMBLE_RAM_BANK_MASK: Value = 0x07;
+
+/// Memory bank controller, the chip of the cartridge switching the banks
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mbc {
+    /// 32 KiB of ROM, and up to 8 KiB of RAM
+    None,
+    Mbc1,
+    Mbc5 { rumble: bool },
+}
+
+impl Mbc {
+    /// MBC of the cartridge type in the header, or an error naming the type if it isn't emulated
+    fn from_type(cartridge_type: Byte) -> Result<Mbc, String> {
+        let name = match cartridge_type {
+            0x00 | 0x08 | 0x09 => return Ok(Mbc::None),
+            0x01..=0x03 => return Ok(Mbc::Mbc1),
+            0x19..=0x1B => return Ok(Mbc::Mbc5 { rumble: false }),
+            0x1C..=0x1E => return Ok(Mbc::Mbc5 { rumble: true }),
+            0x05 | 0x06 => "MBC2",
+            0x0B..=0x0D => "MMM01",
+            0x0F..=0x13 => "MBC3",
+            0x20 => "MBC6",
+            0x22 => "MBC7",
+            0xFC => "Pocket Camera",
+            0xFD => "TAMA5",
+            0xFE => "HuC3",
+            0xFF => "HuC1",
+            _ => "unknown",
+        };
+
+        return Err(format!("Cartridge type {cartridge_type:#04X} ({name}) isn't supported"));
+    }
+}
+
+pub struct Cartridge {
+    mbc: Mbc,
+    /// Shared with the machines restored from save states
+    rom: Rc<[Byte]>,
+    ram: Box<[Byte]>,
+    ram_enabled: bool,
+    /// BANK1 register: low bits of the ROM bank mapped at 0x4000
+    bank1: Value,
+    /// BANK2 register: high bits of the ROM banks (MBC1), or the RAM bank
+    bank2: Value,
+    /// Mode register (MBC1): BANK2 also selects the bank mapped at 0x0000 and the RAM bank
+    advanced_banking: bool,
+    /// 9th bit of the ROM bank mapped at 0x4000 (MBC5)
+    rom_bank_high: Value,
+}
+
+impl Cartridge {
+    /// Cartridge described by the header of the ROM, which is padded to 2 banks at least. Fails if its MBC isn't
+    /// emulated, its banks would be switched wrongly.
+    pub fn new(rom: &[Byte]) -> Result<Cartridge, String> {
+        let header = |addr: usize| rom.get(addr).copied().unwrap_or(0);
+        let mbc = Mbc::from_type(header(CARTRIDGE_TYPE_ADDR))?;
+        let ram_size = match header(RAM_SIZE_ADDR) {
+            0x02 => RAM_BANK_SIZE,
+            0x03 => 4 * RAM_BANK_SIZE,
+            0x04 => 16 * RAM_BANK_SIZE,
+            0x05 => 8 * RAM_BANK_SIZE,
+            _ => 0,
+        };
+
+        let mut padded = rom.to_vec();
+        padded.resize(rom.len().next_multiple_of(ROM_BANK_SIZE).max(2 * ROM_BANK_SIZE), 0xFF);
+
+        log!("MEMORY", format!("Inserting {mbc:?} cartridge of {} ROM banks and {ram_size} bytes of RAM", padded.len() / ROM_BANK_SIZE));
+
+        return Ok(Cartridge {
+            mbc,
+            rom: Rc::from(padded),
+            ram: vec![0; ram_size].into_boxed_slice(),
+            ram_enabled: mbc == Mbc::None,
+            bank1: 1,
+            bank2: 0,
+            advanced_banking: false,
+            rom_bank_high: 0,
+        })
+    }
+
+    /// The same cartridge with the MBC and RAM as at power-on, to restore a save state into
+    pub fn reinserted(&self) -> Cartridge {
+        return Cartridge {
+            mbc: self.mbc,
+            rom: Rc::clone(&self.rom),
+            ram: vec![0; self.ram.len()].into_boxed_slice(),
+            ram_enabled: self.mbc == Mbc::None,
+            bank1: 1,
+            bank2: 0,
+            advanced_banking: false,
+            rom_bank_high: 0,
+        }
+    }
+
+    pub fn rom_bank_count(&self) -> usize {
+        return self.rom.len() / ROM_BANK_SIZE;
+    }
+
+    /// ROM bank mapped at 0x0000 (`romx` false) or at 0x4000 (`romx` true)
+    pub fn rom_bank(&self, romx: bool) -> usize {
+        let high = usize::from(self.bank2) << BANK1_BITS;
+        let bank = match (self.mbc, romx) {
+            (Mbc::None, romx) => usize::from(romx),
+            (Mbc::Mbc1, true) => high | usize::from(self.bank1),
+            (Mbc::Mbc1, false) => if self.advanced_banking { high } else { 0 },
+            (Mbc::Mbc5 { .. }, true) => usize::from(self.rom_bank_high) << MBC5_ROM_HIGH_BITS | usize::from(self.bank1),
+            (Mbc::Mbc5 { .. }, false) => 0,
+        };
+        return bank % self.rom_bank_count();
+    }
+
+    /// RAM bank mapped at 0xA000
+    pub fn ram_bank(&self) -> usize {
+        let bank_count = self.ram.len().div_ceil(RAM_BANK_SIZE).max(1);
+        return match self.mbc {
+            Mbc::Mbc1 if !self.advanced_banking => 0,
+            _ => usize::from(self.bank2) % bank_count,
+        }
+    }
+
+    /// Byte of a ROM bank, whether it is mapped or not
+    pub fn peek_rom(&self, bank: usize, addr: FarAddress) -> Byte {
+        let offset = usize::from(addr) % ROM_BANK_SIZE;
+        return self.rom.get(bank * ROM_BANK_SIZE + offset).copied().unwrap_or(0xFF);
+    }
+
+    /// Byte of a RAM bank, whether it is mapped or not. `None` if the cartridge has no such bank.
+    pub fn peek_ram(&self, bank: usize, addr: FarAddress) -> Option<Byte> {
+        return self.ram.get(bank * RAM_BANK_SIZE + usize::from(addr.wrapping_sub(RAM_START))).copied();
+    }
+
+    pub fn read_rom(&self, addr: FarAddress) -> Byte {
+        return self.peek_rom(self.rom_bank(addr >= ROMX_START), addr);
+    }
+
+    /// Disabled or missing RAM reads as 0xFF
+    pub fn read_ram(&self, addr: FarAddress) -> Byte {
+        if !self.ram_enabled { return 0xFF; }
+        return self.peek_ram(self.ram_bank(), addr).unwrap_or(0xFF);
+    }
+
+    pub fn write_ram(&mut self, addr: FarAddress, value: Value) {
+        if !self.ram_enabled { return; }
+
+        let offset = self.ram_bank() * RAM_BANK_SIZE + usize::from(addr.wrapping_sub(RAM_START));
+        if let Some(byte) = self.ram.get_mut(offset) { *byte = value; }
+    }
+
+    /// Writes to the ROM area set the MBC registers, the ROM itself being read-only
+    pub fn write_register(&mut self, addr: FarAddress, value: Value) {
+        if self.mbc == Mbc::None { return; }
+
+        match (self.mbc, addr) {
+            (_, 0x0000..=0x1FFF) => self.ram_enabled = value & 0x0F == RAM_ENABLE_VALUE,
+            // Bank 0 can't be selected in BANK1, it selects bank 1 instead
+            (Mbc::Mbc1, 0x2000..=0x3FFF) => self.bank1 = (value & BANK1_MASK).max(1),
+            (Mbc::Mbc1, 0x4000..=0x5FFF) => self.bank2 = value & BANK2_MASK,
+            (Mbc::Mbc1, _) => self.advanced_banking = value & 0x01 != 0,
+            // Unlike on the MBC1, the bank 0 can be mapped at 0x4000
+            (Mbc::Mbc5 { .. }, 0x2000..=0x2FFF) => self.bank1 = value,
+            (Mbc::Mbc5 { .. }, 0x3000..=0x3FFF) => self.rom_bank_high = value & 0x01,
+            (Mbc::Mbc5 { rumble }, 0x4000..=0x5FFF) => self.bank2 = value & if rumble { MBC5_RUMBLE_RAM_BANK_MASK } else { MBC5_RAM_BANK_MASK },
+            (Mbc::Mbc5 { .. } | Mbc::None, _) => {}
+        }
+
+        log!(Trace, "MEMORY", format!("MBC register write {value:#04X} at {addr:#06X}, ROM bank {:#04X}", self.rom_bank(true)));
+    }
+}
+
+impl Stateful for Cartridge {
+    /// The ROM isn't saved, it is loaded again with the state
+    #[allow(clippy::cast_possible_truncation)]
+    fn save_state(&self, writer: &mut StateWriter) {
+        writer.write_bytes(&[u8::from(self.ram_enabled), self.bank1, self.bank2, u8::from(self.advanced_banking), self.rom_bank_high]);
+        writer.write_u32(self.ram.len() as u32);
+        writer.write_bytes(&self.ram);
+    }
+
+    fn load_state(&mut self, reader: &mut StateReader) -> Result<(), StateError> {
+        let registers = reader.read_bytes(5)?;
+        self.ram_enabled = registers[0] != 0;
+        self.bank1 = registers[1];
+        self.bank2 = registers[2];
+        self.advanced_banking = registers[3] != 0;
+        self.rom_bank_high = registers[4] & 0x01;
+        if self.mbc == Mbc::Mbc1 {
+            self.bank1 = (self.bank1 & BANK1_MASK).max(1);
+            self.bank2 &= BANK2_MASK;
+        }
+
+        let size = reader.read_u32()? as usize;
+        if size != self.ram.len() { return Err(StateError::SizeMismatch { expected: self.ram.len(), found: size }); }
+        self.ram.copy_from_slice(reader.read_bytes(size)?);
+
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Cartridge, ROM_BANK_SIZE};
+
+    /// Banks numbered in all their bytes (modulo 256), with 32 KiB of RAM
+    fn banked_rom(banks: usize, cartridge_type: u8) -> Vec<u8> {
+        let mut rom: Vec<u8> = (0..banks).flat_map(|bank| vec![u8::try_from(bank % 0x100).unwrap_or(0xFF); ROM_BANK_SIZE]).collect();
+        rom[0x0147] = cartridge_type;
+        rom[0x0149] = 0x03;
+        return rom;
+    }
+
+    fn mbc1_rom(banks: usize) -> Vec<u8> {
+        return banked_rom(banks, 0x03);
+    }
+
+    fn insert(rom: &[u8]) -> Cartridge {
+        return Cartridge::new(rom).unwrap_or_else(|error| panic!("{error}"));
+    }
+
+    #[test]
+    fn test_mbc1_rom_banks() {
+        let mut cartridge = insert(&mbc1_rom(64));
+        assert_eq!((cartridge.read_rom(0x0000), cartridge.read_rom(0x4000)), (0, 1));
+
+        cartridge.write_register(0x2000, 0x05);
+        assert_eq!(cartridge.read_rom(0x7FFF), 5);
+        // Bank 0 selects bank 1, and so does 0x20 for bank 0x21
+        cartridge.write_register(0x2000, 0x00);
+        assert_eq!(cartridge.read_rom(0x4000), 1);
+        cartridge.write_register(0x4000, 0x01);
+        assert_eq!(cartridge.read_rom(0x4000), 0x21);
+        assert_eq!(cartridge.read_rom(0x0000), 0);
+
+        cartridge.write_register(0x6000, 0x01);
+        assert_eq!(cartridge.read_rom(0x0000), 0x20);
+        // Larger banks wrap around the ROM size
+        cartridge.write_register(0x4000, 0x03);
+        assert_eq!(cartridge.read_rom(0x4000), 0x21);
+    }
+
+    #[test]
+    fn test_mbc1_ram() {
+        let mut cartridge = insert(&mbc1_rom(4));
+        cartridge.write_ram(0xA000, 0x42);
+        assert_eq!(cartridge.read_ram(0xA000), 0xFF);
+
+        cartridge.write_register(0x0000, 0x0A);
+        cartridge.write_ram(0xA000, 0x42);
+        assert_eq!(cartridge.read_ram(0xA000), 0x42);
+
+        // RAM banks are only switched in advanced banking mode
+        cartridge.write_register(0x4000, 0x02);
+        assert_eq!(cartridge.read_ram(0xA000), 0x42);
+        cartridge.write_register(0x6000, 0x01);
+        assert_eq!(cartridge.read_ram(0xA000), 0x00);
+        assert_eq!(cartridge.peek_ram(0, 0xA000), Some(0x42));
+
+        cartridge.write_register(0x0000, 0x00);
+        assert_eq!(cartridge.read_ram(0xA000), 0xFF);
+    }
+
+    #[test]
+    fn test_mbc5() {
+        let mut cartridge = insert(&banked_rom(512, 0x1B));
+        assert_eq!((cartridge.read_rom(0x0000), cartridge.read_rom(0x4000)), (0, 1));
+
+        // Bank 0 can be mapped at 0x4000, and 0x3000 holds the 9th bit of the bank
+        cartridge.write_register(0x2000, 0x00);
+        assert_eq!(cartridge.rom_bank(true), 0);
+        cartridge.write_register(0x2000, 0x05);
+        cartridge.write_register(0x3000, 0x01);
+        assert_eq!((cartridge.rom_bank(true), cartridge.read_rom(0x4000)), (0x105, 0x05));
+        assert_eq!(cartridge.rom_bank(false), 0);
+
+        cartridge.write_register(0x0000, 0x0A);
+        cartridge.write_register(0x4000, 0x03);
+        cartridge.write_ram(0xA000, 0x42);
+        assert_eq!((cartridge.ram_bank(), cartridge.peek_ram(3, 0xA000)), (3, Some(0x42)));
+    }
+
+    #[test]
+    fn test_unsupported_mbc() {
+        let mut rom = vec![0x00; 0x8000];
+        rom[0x0147] = 0x13;
+        assert_eq!(Cartridge::new(&rom).err().as_deref(), Some("Cartridge type 0x13 (MBC3) isn't supported"));
+    }
+
+    #[test]
+    fn test_rom_only() {
+        let mut cartridge = insert(&[0x00; 0x100]);
+        assert_eq!(cartridge.rom_bank_count(), 2);
+        cartridge.write_register(0x2000, 0x05);
+        assert_eq!((cartridge.rom_bank(false), cartridge.rom_bank(true)), (0, 1));
+        assert_eq!(cartridge.read_rom(0x4000), 0xFF);
+    }
+}
@@ -0,0 +1,269 @@
+//! Game Boy Color extensions of the memory map: VRAM and WRAM banks, color palettes, VRAM DMA, and the double speed
+//! mode
+
+use crate::cpu::hdma::Hdma;
+use crate::cpu::io::PALETTE_INCREMENT_BIT;
+use crate::state::savestate::{StateError, StateReader, StateWriter, Stateful};
+use crate::utils::bits::get_bit;
+use crate::utils::types::{Byte, FarAddress, Value, WideValue};
+
+/// CGB flag of the cartridge header
+pub const CGB_FLAG_ADDR: FarAddress = 0x0143;
+/// Bit 7 of the CGB flag: the cartridge uses the CGB features
+const CGB_FLAG_SUPPORTED: Byte = 0x80;
+/// Bits 2 and 3 set mean a PGB mode cartridge, which isn't a CGB one
+const CGB_FLAG_PGB_MASK: Byte = 0x0C;
+const CGB_FLAG_ONLY: Byte = 0xC0;
+
+pub const VRAM_BANKS: usize = 2;
+pub const WRAM_BANKS: usize = 8;
+const VRAM_START: usize = 0x8000;
+const VRAM_SIZE: usize = 0x2000;
+/// Only the second half of WRAM is switchable, the first half always being bank 0
+const WRAMX_START: usize = 0xD000;
+const WRAMX_SIZE: usize = 0x1000;
+
+pub const PALETTE_COUNT: usize = 8;
+pub const COLORS_PER_PALETTE: usize = 4;
+/// Colors are 2 bytes each
+const PALETTE_RAM_SIZE: usize = PALETTE_COUNT * COLORS_PER_PALETTE * 2;
+/// Byte index of the palette RAM in bits 0-5 of BCPS and OCPS
+const PALETTE_INDEX_MASK: Value = 0x3F;
+/// Unused bit of BCPS and OCPS, which reads as 1
+const PALETTE_UNUSED_BIT: Value = 0x40;
+
+/// What the cartridge header says about the CGB features
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CgbSupport {
+    /// DMG cartridge, run in compatibility mode by a CGB
+    None,
+    /// Uses the CGB features, but also runs on a DMG
+    Enhanced,
+    Only,
+}
+
+impl CgbSupport {
+    pub fn from_flag(flag: Byte) -> CgbSupport {
+        if flag & CGB_FLAG_SUPPORTED == 0 || flag & CGB_FLAG_PGB_MASK != 0 { return CgbSupport::None; }
+        if flag & CGB_FLAG_ONLY == CGB_FLAG_ONLY { return CgbSupport::Only; }
+        return CgbSupport::Enhanced;
+    }
+}
+
+/// Background or object palette RAM, accessed a byte at a time through an index register (BCPS or OCPS) and a data
+/// register (BCPD or OCPD). Colors are stored little-endian as 15-bit BGR: red in bits 0-4, green 5-9 and blue 10-14.
+pub struct PaletteRam {
+    /// Index register, without its unused bit
+    select: Value,
+    data: [Byte; PALETTE_RAM_SIZE],
+}
+
+impl PaletteRam {
+    /// Every color white, as set by the boot ROM
+    fn white() -> PaletteRam {
+        return PaletteRam { select: 0, data: [0xFF; PALETTE_RAM_SIZE] };
+    }
+
+    pub fn select(&mut self, value: Value) {
+        self.select = value & !PALETTE_UNUSED_BIT;
+    }
+
+    /// Value read from the index register
+    pub fn index_register(&self) -> Value {
+        return self.select | PALETTE_UNUSED_BIT;
+    }
+
+    /// Byte at the index
+    pub fn data(&self) -> Byte {
+        return self.data[usize::from(self.select & PALETTE_INDEX_MASK)];
+    }
+
+    /// Writes the byte at the index unless the palettes are locked, then increments the index if requested (even when
+    /// locked)
+    pub fn write(&mut self, value: Value, locked: bool) {
+        let index = self.select & PALETTE_INDEX_MASK;
+        if !locked { self.data[usize::from(index)] = value; }
+        if get_bit(self.select, PALETTE_INCREMENT_BIT) { self.select = (self.select & !PALETTE_INDEX_MASK) | ((index + 1) & PALETTE_INDEX_MASK); }
+    }
+
+    /// 15-bit color of a palette
+    pub fn color(&self, palette: usize, color: usize) -> WideValue {
+        let offset = (palette * COLORS_PER_PALETTE + color) * 2;
+        return WideValue::from_le_bytes([self.data[offset], self.data[offset + 1]]);
+    }
+
+    pub fn set_color(&mut self, palette: usize, color: usize, value: WideValue) {
+        let offset = (palette * COLORS_PER_PALETTE + color) * 2;
+        self.data[offset..offset + 2].copy_from_slice(&value.to_le_bytes());
+    }
+}
+
+/// State of the CGB mode. The mapped VRAM and WRAM banks live in the address space, the other ones are kept here and
+/// swapped in when the game switches banks.
+pub struct Cgb {
+    /// The CGB registers are plain memory as on the DMG when disabled
+    pub enabled: bool,
+    /// DMG cartridge on a CGB, BGP, OBP0 and OBP1 picking colors of the palettes set by the boot ROM
+    pub compatibility: bool,
+    pub double_speed: bool,
+    vram_bank: usize,
+    wram_bank: usize,
+    /// Copies of the banks which aren't mapped, the copy of the mapped bank being stale
+    vram: Box<[Byte]>,
+    wram: Box<[Byte]>,
+    pub background_palettes: PaletteRam,
+    pub object_palettes: PaletteRam,
+    pub hdma: Hdma,
+}
+
+impl Default for Cgb {
+    fn default() -> Cgb {
+        return Cgb {
+            enabled: false,
+            compatibility: false,
+            double_speed: false,
+            vram_bank: 0,
+            wram_bank: 1,
+            vram: vec![0; VRAM_BANKS * VRAM_SIZE].into_boxed_slice(),
+            wram: vec![0; WRAM_BANKS * WRAMX_SIZE].into_boxed_slice(),
+            background_palettes: PaletteRam::white(),
+            object_palettes: PaletteRam::white(),
+            hdma: Hdma::default(),
+        }
+    }
+}
+
+impl Cgb {
+    pub fn vram_bank(&self) -> usize {
+        return self.vram_bank;
+    }
+
+    pub fn wram_bank(&self) -> usize {
+        return self.wram_bank;
+    }
+
+    /// Stores the mapped bank of a region and maps another one
+    fn swap(memory: &mut [Byte], banks: &mut [Byte], start: usize, size: usize, (mapped, bank): (usize, usize)) {
+        if mapped == bank { return; }
+
+        let region = &mut memory[start..start + size];
+        banks[mapped * size..(mapped + 1) * size].copy_from_slice(region);
+        region.copy_from_slice(&banks[bank * size..(bank + 1) * size]);
+    }
+
+    pub fn map_vram(&mut self, memory: &mut [Byte], bank: usize) {
+        Self::swap(memory, &mut self.vram, VRAM_START, VRAM_SIZE, (self.vram_bank, bank));
+        self.vram_bank = bank;
+    }
+
+    /// Bank 0 can't be mapped at 0xD000, selecting it maps bank 1
+    pub fn map_wram(&mut self, memory: &mut [Byte], bank: usize) {
+        let bank = bank.max(1);
+        Self::swap(memory, &mut self.wram, WRAMX_START, WRAMX_SIZE, (self.wram_bank, bank));
+        self.wram_bank = bank;
+    }
+
+    /// Byte of a VRAM bank, whether it is mapped or not
+    pub fn peek_vram(&self, memory: &[Byte], bank: usize, addr: FarAddress) -> Byte {
+        let offset = usize::from(addr).wrapping_sub(VRAM_START);
+        if bank == self.vram_bank || offset >= VRAM_SIZE { return memory.get(usize::from(addr)).copied().unwrap_or(0xFF); }
+
+        return self.vram[bank * VRAM_SIZE + offset];
+    }
+}
+
+impl Stateful for Cgb {
+    #[allow(clippy::cast_possible_truncation)]
+    fn save_state(&self, writer: &mut StateWriter) {
+        writer.write_bytes(&[u8::from(self.enabled), u8::from(self.compatibility), u8::from(self.double_speed), self.vram_bank as u8, self.wram_bank as u8]);
+        writer.write_bytes(&self.vram);
+        writer.write_bytes(&self.wram);
+        for palettes in [&self.background_palettes, &self.object_palettes] {
+            writer.write_bytes(&[palettes.select]);
+            writer.write_bytes(&palettes.data);
+        }
+        self.hdma.save_state(writer);
+    }
+
+    fn load_state(&mut self, reader: &mut StateReader) -> Result<(), StateError> {
+        let flags = reader.read_bytes(5)?;
+        self.enabled = flags[0] != 0;
+        self.compatibility = flags[1] != 0;
+        self.double_speed = flags[2] != 0;
+        self.vram_bank = usize::from(flags[3]) % VRAM_BANKS;
+        self.wram_bank = usize::from(flags[4]).clamp(1, WRAM_BANKS - 1);
+        self.vram.copy_from_slice(reader.read_bytes(self.vram.len())?);
+        self.wram.copy_from_slice(reader.read_bytes(self.wram.len())?);
+        for palettes in [&mut self.background_palettes, &mut self.object_palettes] {
+            palettes.select(reader.read_bytes(1)?[0]);
+            palettes.data.copy_from_slice(reader.read_bytes(PALETTE_RAM_SIZE)?);
+        }
+
+        return self.hdma.load_state(reader);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Cgb, CgbSupport, PaletteRam};
+
+    #[test]
+    fn test_cgb_flag() {
+        assert_eq!(CgbSupport::from_flag(0x00), CgbSupport::None);
+        assert_eq!(CgbSupport::from_flag(0x80), CgbSupport::Enhanced);
+        assert_eq!(CgbSupport::from_flag(0xC0), CgbSupport::Only);
+        // PGB mode
+        assert_eq!(CgbSupport::from_flag(0x84), CgbSupport::None);
+    }
+
+    #[test]
+    fn test_banks() {
+        let mut memory = vec![0; 0x10000];
+        let mut cgb = Cgb::default();
+        memory[0x8000] = 0x11;
+        memory[0xD000] = 0x21;
+
+        cgb.map_vram(&mut memory, 1);
+        cgb.map_wram(&mut memory, 3);
+        assert_eq!((memory[0x8000], memory[0xD000]), (0, 0));
+        memory[0x8000] = 0x12;
+        memory[0xD000] = 0x23;
+        assert_eq!(cgb.peek_vram(&memory, 0, 0x8000), 0x11);
+        assert_eq!(cgb.peek_vram(&memory, 1, 0x8000), 0x12);
+
+        cgb.map_vram(&mut memory, 0);
+        cgb.map_wram(&mut memory, 0);
+        assert_eq!((memory[0x8000], memory[0xD000]), (0x11, 0x21));
+        assert_eq!(cgb.wram_bank(), 1);
+        assert_eq!(cgb.peek_vram(&memory, 1, 0x8000), 0x12);
+
+        cgb.map_wram(&mut memory, 3);
+        assert_eq!(memory[0xD000], 0x23);
+    }
+
+    #[test]
+    fn test_palette_ram() {
+        let mut palettes = PaletteRam::white();
+        assert_eq!(palettes.color(7, 3), 0xFFFF);
+
+        // Auto-increment from the last color of palette 0
+        palettes.select(0x86);
+        assert_eq!(palettes.index_register(), 0xC6);
+        palettes.write(0x1F, false);
+        palettes.write(0x7C, false);
+        assert_eq!(palettes.color(0, 3), 0x7C1F);
+        assert_eq!(palettes.index_register(), 0xC8);
+
+        // Locked writes are dropped, but still increment the index
+        palettes.write(0x00, true);
+        assert_eq!((palettes.color(1, 0), palettes.index_register()), (0xFFFF, 0xC9));
+
+        // Without auto-increment, and wrapping at the end
+        palettes.select(0x3F);
+        palettes.write(0x12, false);
+        assert_eq!((palettes.data(), palettes.index_register()), (0x12, 0x7F));
+        palettes.select(0xBF);
+        palettes.write(0x34, false);
+        assert_eq!(palettes.index_register(), 0xC0);
+    }
+}
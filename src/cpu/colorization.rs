@@ -0,0 +1,255 @@
+//! Palettes the CGB boot ROM gives to DMG cartridges: picked from the title of Nintendo games, else from the buttons held
+//! during the boot logo, else the default one. In this compatibility mode BGP, OBP0 and OBP1 select colors of the CGB
+//! palettes 0 (background) and 0 and 1 (objects) instead of the DMG shades.
+
+use crate::cpu::cgb::COLORS_PER_PALETTE;
+use crate::cpu::execution::CPU_FREQUENCY;
+use crate::cpu::memory::Memory;
+use crate::utils::log::log;
+use crate::utils::types::{Byte, FarAddress, WideValue};
+
+const TITLE_START: FarAddress = 0x0134;
+/// The checksum covers the 16 bytes of the title area, including the manufacturer code and CGB flag
+const TITLE_END: FarAddress = 0x0143;
+/// The 4th letter of the title tells apart the games with the same checksum
+const TITLE_FOURTH_LETTER: FarAddress = 0x0137;
+const NEW_LICENSEE_ADDR: FarAddress = 0x0144;
+const OLD_LICENSEE_ADDR: FarAddress = 0x014B;
+/// Old licensee code meaning the new one is used instead
+const USE_NEW_LICENSEE: Byte = 0x33;
+const NINTENDO_OLD_LICENSEE: Byte = 0x01;
+const NINTENDO_NEW_LICENSEE: [Byte; 2] = *b"01";
+
+/// Buttons can be held this long after power-on to pick a palette, about as long as the boot logo is shown
+pub const SELECTION_TICKS: u64 = 2 * CPU_FREQUENCY;
+
+/// Combination used for unlisted games, also selected by Right + A
+const DEFAULT_COMBINATION: usize = 0;
+
+/// The 30 palettes of the boot ROM, as 15-bit colors
+const PALETTES: [WideValue; 30 * COLORS_PER_PALETTE] = [
+    0x7FFF, 0x32BF, 0x00D0, 0x0000,
+    0x639F, 0x4279, 0x15B0, 0x04CB,
+    0x7FFF, 0x6E31, 0x454A, 0x0000,
+    0x7FFF, 0x1BEF, 0x0200, 0x0000,
+    0x7FFF, 0x421F, 0x1CF2, 0x0000,
+    0x7FFF, 0x5294, 0x294A, 0x0000,
+    0x7FFF, 0x03FF, 0x012F, 0x0000,
+    0x7FFF, 0x03EF, 0x01D6, 0x0000,
+    0x7FFF, 0x42B5, 0x3DC8, 0x0000,
+    0x7E74, 0x03FF, 0x0180, 0x0000,
+    0x67FF, 0x77AC, 0x1A13, 0x2D6B,
+    0x7ED6, 0x4BFF, 0x2175, 0x0000,
+    0x53FF, 0x4A5F, 0x7E52, 0x0000,
+    0x4FFF, 0x7ED2, 0x3A4C, 0x1CE0,
+    0x03ED, 0x7FFF, 0x255F, 0x0000,
+    0x036A, 0x021F, 0x03FF, 0x7FFF,
+    0x7FFF, 0x01DF, 0x0112, 0x0000,
+    0x231F, 0x035F, 0x00F2, 0x0009,
+    0x7FFF, 0x03EA, 0x011F, 0x0000,
+    0x299F, 0x001A, 0x000C, 0x0000,
+    0x7FFF, 0x027F, 0x001F, 0x0000,
+    0x7FFF, 0x03E0, 0x0206, 0x0120,
+    0x7FFF, 0x7EEB, 0x001F, 0x7C00,
+    0x7FFF, 0x3FFF, 0x7E00, 0x001F,
+    0x7FFF, 0x03FF, 0x001F, 0x0000,
+    0x03FF, 0x001F, 0x000C, 0x0000,
+    0x7FFF, 0x033F, 0x0193, 0x0000,
+    0x0000, 0x4200, 0x037F, 0x7FFF,
+    0x7FFF, 0x7E8C, 0x7C00, 0x0000,
+    0x7FFF, 0x1BEF, 0x6180, 0x0000,
+];
+
+/// Offset of the first color of a palette in `PALETTES`
+const fn palette(index: usize) -> usize {
+    return index * COLORS_PER_PALETTE;
+}
+
+/// Offsets in `PALETTES` of the OBP0, OBP1 and BGP colors. A few start one color early, straddling two palettes, as
+/// done by the boot ROM.
+const COMBINATIONS: [(usize, usize, usize); 51] = [
+    (palette(4), palette(4), palette(29)),
+    (palette(18), palette(18), palette(18)),
+    (palette(20), palette(20), palette(20)),
+    (palette(24), palette(24), palette(24)),
+    (palette(9), palette(9), palette(9)),
+    (palette(0), palette(0), palette(0)),
+    (palette(27), palette(27), palette(27)),
+    (palette(5), palette(5), palette(5)),
+    (palette(12), palette(12), palette(12)),
+    (palette(26), palette(26), palette(26)),
+    (palette(16), palette(8), palette(8)),
+    (palette(4), palette(28), palette(28)),
+    (palette(4), palette(2), palette(2)),
+    (palette(3), palette(4), palette(4)),
+    (palette(4), palette(29), palette(29)),
+    (palette(28), palette(4), palette(28)),
+    (palette(2), palette(17), palette(2)),
+    (palette(16), palette(16), palette(8)),
+    (palette(4), palette(4), palette(7)),
+    (palette(4), palette(4), palette(18)),
+    (palette(4), palette(4), palette(20)),
+    (palette(19), palette(19), palette(9)),
+    (palette(4) - 1, palette(4) - 1, palette(11)),
+    (palette(17), palette(17), palette(2)),
+    (palette(4), palette(4), palette(2)),
+    (palette(4), palette(4), palette(3)),
+    (palette(28), palette(28), palette(0)),
+    (palette(3), palette(3), palette(0)),
+    (palette(0), palette(0), palette(1)),
+    (palette(18), palette(22), palette(18)),
+    (palette(20), palette(22), palette(20)),
+    (palette(24), palette(22), palette(24)),
+    (palette(16), palette(22), palette(8)),
+    (palette(17), palette(4), palette(13)),
+    (palette(28) - 1, palette(0), palette(14)),
+    (palette(28) - 1, palette(4), palette(15)),
+    (palette(19), palette(22), palette(9)),
+    (palette(16), palette(28), palette(10)),
+    (palette(4), palette(23), palette(28)),
+    (palette(17), palette(22), palette(2)),
+    (palette(4), palette(0), palette(2)),
+    (palette(4), palette(28), palette(3)),
+    (palette(28), palette(3), palette(0)),
+    (palette(3), palette(28), palette(4)),
+    (palette(21), palette(28), palette(4)),
+    (palette(3), palette(28), palette(0)),
+    (palette(25), palette(3), palette(28)),
+    (palette(0), palette(28), palette(8)),
+    (palette(4), palette(3), palette(28)),
+    (palette(28), palette(3), palette(6)),
+    (palette(4), palette(28), palette(29)),
+];
+
+/// Wrapping sums of the titles of the colorized games, with the combination of each in `TITLE_COMBINATIONS`
+const TITLE_CHECKSUMS: [Byte; 93] = [
+    0x88, 0x16, 0x36, 0xD1, 0xDB, 0xF2, 0x3C, 0x8C, 0x92, 0x3D, 0x5C, 0x58, 0xC9, 0x3E, 0x70, 0x1D,
+    0x59, 0x69, 0x19, 0x35, 0xA8, 0x14, 0xAA, 0x75, 0x95, 0x99, 0x34, 0x6F, 0x15, 0xFF, 0x97, 0x4B,
+    0x90, 0x17, 0x10, 0x39, 0xF7, 0xF6, 0xA2, 0x49, 0x4E, 0x43, 0x68, 0xE0, 0x8B, 0xF0, 0xCE, 0x0C,
+    0x29, 0xE8, 0xB7, 0x86, 0x9A, 0x52, 0x01, 0x9D, 0x71, 0x9C, 0xBD, 0x5D, 0x6D, 0x67, 0x3F, 0x6B,
+    // Shared by several games, see `FOURTH_LETTERS`
+    0xB3, 0x46, 0x28, 0xA5, 0xC6, 0xD3, 0x27, 0x61, 0x18, 0x66, 0x6A, 0xBF, 0x0D, 0xF4,
+    0xB3, 0x46, 0x28, 0xA5, 0xC6, 0xD3, 0x27, 0x61, 0x18, 0x66, 0x6A, 0xBF, 0x0D, 0xF4,
+    0xB3,
+];
+const TITLE_COMBINATIONS: [usize; 93] = [
+    4, 5, 35, 34, 3, 31, 15, 10, 5, 19, 36, 7, 37, 30, 44, 21,
+    32, 31, 20, 5, 33, 13, 14, 5, 29, 5, 18, 9, 3, 2, 26, 25,
+    25, 41, 42, 26, 45, 42, 45, 36, 38, 26, 42, 30, 41, 34, 34, 5,
+    42, 6, 5, 33, 25, 42, 42, 40, 2, 16, 25, 42, 42, 5, 0, 39,
+    36, 22, 25, 6, 32, 12, 36, 11, 39, 18, 39, 24, 31, 50,
+    17, 46, 6, 27, 0, 47, 41, 41, 0, 0, 19, 34, 23, 18,
+    29,
+];
+/// Index of the first checksum which also needs the 4th letter of the title to match
+const FIRST_SHARED_CHECKSUM: usize = 64;
+const FOURTH_LETTERS: [Byte; 29] = *b"BEFAARBEKEK R-URAR INAILICE R";
+
+/// Direction held at boot to pick a palette, optionally with A or B
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    Right,
+    Left,
+    Up,
+    Down,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Button {
+    None,
+    A,
+    B,
+}
+
+/// Combination picked by the buttons held at boot
+pub fn button_combination(direction: Direction, button: Button) -> usize {
+    let combinations = match button {
+        Button::None => [1, 48, 5, 8],
+        Button::A => [DEFAULT_COMBINATION, 40, 43, 3],
+        Button::B => [6, 7, 28, 49],
+    };
+
+    return combinations[direction as usize];
+}
+
+fn licensed_by_nintendo(memory: &Memory) -> bool {
+    return match memory.peek(OLD_LICENSEE_ADDR) {
+        USE_NEW_LICENSEE => [memory.peek(NEW_LICENSEE_ADDR), memory.peek(NEW_LICENSEE_ADDR + 1)] == NINTENDO_NEW_LICENSEE,
+        licensee => licensee == NINTENDO_OLD_LICENSEE,
+    }
+}
+
+/// Combination the boot ROM picks for the cartridge from its header, only Nintendo games being recognized
+pub fn title_combination(memory: &Memory) -> usize {
+    if !licensed_by_nintendo(memory) { return DEFAULT_COMBINATION; }
+
+    let checksum = (TITLE_START..=TITLE_END).fold(0, |sum: Byte, addr| sum.wrapping_add(memory.peek(addr)));
+    let fourth_letter = memory.peek(TITLE_FOURTH_LETTER);
+
+    return TITLE_CHECKSUMS.iter().enumerate()
+        .position(|(index, title)| {
+            *title == checksum && (index < FIRST_SHARED_CHECKSUM || FOURTH_LETTERS[index - FIRST_SHARED_CHECKSUM] == fourth_letter)
+        })
+        .map_or(DEFAULT_COMBINATION, |index| TITLE_COMBINATIONS[index]);
+}
+
+/// Runs the DMG cartridge in the compatibility mode of the CGB, with the palettes of a combination
+pub fn colorize(memory: &mut Memory, combination: usize) {
+    let (object0, object1, background) = COMBINATIONS[combination];
+    let cgb = &mut memory.cgb;
+    cgb.compatibility = true;
+
+    for color in 0..COLORS_PER_PALETTE {
+        cgb.background_palettes.set_color(0, color, PALETTES[background + color]);
+        cgb.object_palettes.set_color(0, color, PALETTES[object0 + color]);
+        cgb.object_palettes.set_color(1, color, PALETTES[object1 + color]);
+    }
+
+    log!(Info, "MEMORY", format!("Colorized with the palette combination {combination}"));
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cpu::cgb::PaletteRam;
+    use crate::cpu::memory::Memory;
+    use super::{button_combination, colorize, title_combination, Button, Direction};
+
+    fn cartridge(title: &str, old_licensee: u8, new_licensee: [u8; 2]) -> Memory {
+        let mut memory = Memory::new(0x10000);
+        memory.memory[0x0134..0x0134 + title.len()].copy_from_slice(title.as_bytes());
+        memory.memory[0x0144..0x0146].copy_from_slice(&new_licensee);
+        memory.memory[0x014B] = old_licensee;
+        return memory;
+    }
+
+    #[test]
+    fn test_title_combination() {
+        assert_eq!(title_combination(&cartridge("ZELDA", 0x01, *b"\0\0")), 44);
+        assert_eq!(title_combination(&cartridge("POKEMON RED", 0x33, *b"01")), 13);
+        // Same checksum, told apart by the 4th letter
+        assert_eq!(title_combination(&cartridge("POKEMON BLUE", 0x01, *b"\0\0")), 11);
+        assert_eq!(title_combination(&cartridge("VEGAS STAKES", 0x01, *b"\0\0")), 41);
+        assert_eq!(title_combination(&cartridge("VEGSA STAKES", 0x01, *b"\0\0")), 0);
+
+        // Only for Nintendo
+        assert_eq!(title_combination(&cartridge("ZELDA", 0x33, *b"08")), 0);
+        assert_eq!(title_combination(&cartridge("ZELDA", 0x08, *b"01")), 0);
+    }
+
+    #[test]
+    fn test_colorize() {
+        let mut memory = Memory::new(0x10000);
+        colorize(&mut memory, button_combination(Direction::Up, Button::A));
+        assert!(memory.cgb.compatibility);
+
+        // Red background, green OBP0 and blue OBP1
+        let colors = |palettes: &PaletteRam, palette| (0..4).map(|color| palettes.color(palette, color)).collect::<Vec<_>>();
+        assert_eq!(colors(&memory.cgb.background_palettes, 0), [0x7FFF, 0x421F, 0x1CF2, 0x0000]);
+        assert_eq!(colors(&memory.cgb.object_palettes, 0), [0x7FFF, 0x1BEF, 0x0200, 0x0000]);
+        assert_eq!(colors(&memory.cgb.object_palettes, 1), [0x7FFF, 0x7E8C, 0x7C00, 0x0000]);
+
+        // Starting one color early, in the last color of the previous palette
+        colorize(&mut memory, 22);
+        assert_eq!(colors(&memory.cgb.object_palettes, 0), [0x0000, 0x7FFF, 0x421F, 0x1CF2]);
+    }
+}
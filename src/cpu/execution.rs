@@ -0,0 +1,91 @@
+use crate::cpu::apu;
+use crate::cpu::hdma;
+use crate::cpu::interrupts;
+use crate::cpu::instruction::{GenericInstruction, instruction_from_opcode, PREFIXED_OPCODE};
+use crate::cpu::lcd;
+use crate::cpu::memory::Memory;
+use crate::cpu::timer;
+use crate::debug::coverage::Access;
+use crate::utils::conversions::pair_to_wide;
+use crate::utils::types::{AddressOffset, Value, WideValue};
+
+pub const CPU_FREQUENCY: u64 = 4_194_304;
+/// Clock ticks to draw a whole frame (154 lines of 456 ticks)
+pub const TICKS_PER_FRAME: u64 = 70_224;
+
+fn fetch_as(memory: &mut Memory, access: Access) -> Value {
+    let value = memory.read_as(memory.registers.PC, access);
+    memory.registers.PC = memory.registers.PC.wrapping_add(1);
+
+    return value;
+}
+
+fn fetch(memory: &mut Memory) -> Value {
+    return fetch_as(memory, Access::Operand);
+}
+
+// Operands are stored little-endian
+fn fetch_wide(memory: &mut Memory) -> WideValue {
+    let low = fetch(memory);
+    let high = fetch(memory);
+
+    return pair_to_wide(high, low);
+}
+
+/// Catches the timer and the LCD up with the clock ticks elapsed since the clock tick, once the instruction is done.
+/// The memory accesses of the instruction thus all see the hardware as it was before it. The H-Blank transfers pause
+/// the CPU, the hardware keeps running meanwhile.
+fn tick_hardware(memory: &mut Memory, mut since: u64) {
+    if memory.flat { return; }
+
+    while memory.cycles > since {
+        let elapsed = memory.cycles - since;
+        since = memory.cycles;
+        timer::tick(memory, elapsed);
+        apu::tick(memory, elapsed);
+        if lcd::tick(memory, elapsed) && memory.cgb.enabled { hdma::hblank(memory); }
+    }
+}
+
+/// Fetches, decodes and executes the instruction at PC, leaving PC on the next instruction (unless it jumped).
+/// Services the pending interrupt instead if any, or waits for one while halted (except on a flat bus).
+pub fn step(memory: &mut Memory) {
+    let cycles = memory.cycles;
+    if !memory.flat && !interrupts::service(memory) {
+        tick_hardware(memory, cycles);
+        return;
+    }
+
+    let mut opcode = fetch_as(memory, Access::Opcode);
+    // The HALT bug reads the byte following HALT twice
+    if memory.interrupts.take_halt_bug() { memory.registers.PC = memory.registers.PC.wrapping_sub(1); }
+    let prefixed = opcode == PREFIXED_OPCODE;
+    if prefixed { opcode = fetch_as(memory, Access::Opcode); }
+
+    match instruction_from_opcode(opcode, prefixed) {
+        GenericInstruction::Void(instr) => instr.execute(memory, ()),
+        GenericInstruction::Value(instr) => {
+            let value = fetch(memory);
+            instr.execute(memory, value);
+        }
+        GenericInstruction::Wide(instr) => {
+            let value = fetch_wide(memory);
+            instr.execute(memory, value);
+        }
+        GenericInstruction::Near(instr) => {
+            let value = fetch(memory);
+            instr.execute(memory, value);
+        }
+        GenericInstruction::Far(instr) => {
+            let value = fetch_wide(memory);
+            instr.execute(memory, value);
+        }
+        GenericInstruction::Offset(instr) => {
+            let value = AddressOffset::from_le_bytes([fetch(memory)]);
+            instr.execute(memory, value);
+        }
+    }
+
+    memory.interrupts.end_instruction();
+    tick_hardware(memory, cycles);
+}
@@ -0,0 +1,193 @@
+//! CGB VRAM DMA: general-purpose transfers copy everything at once, H-Blank transfers copy a block at each H-Blank
+
+use crate::cpu::io::{HDMA1, HDMA2, HDMA3, HDMA5, HDMA5_HBLANK_BIT, VRAM_START};
+use crate::cpu::memory::Memory;
+use crate::debug::watchpoint::AccessSource;
+use crate::state::savestate::{StateError, StateReader, StateWriter, Stateful};
+use crate::utils::bits::get_bit;
+use crate::utils::conversions::pair_to_wide;
+use crate::utils::log::log;
+use crate::utils::types::{FarAddress, Value};
+
+/// Transfers are made of 16-byte blocks
+const BLOCK_SIZE: FarAddress = 0x10;
+/// Time the CPU is paused per block: 8 M-cycles in single speed and 16 in double speed, which last as long
+pub const TICKS_PER_BLOCK: u64 = 32;
+/// The low 4 bits of the addresses are ignored, and the destination is an offset in VRAM
+const SOURCE_MASK: FarAddress = 0xFFF0;
+const DESTINATION_MASK: FarAddress = 0x1FF0;
+/// Number of blocks minus 1 in bits 0-6 of HDMA5
+const LENGTH_MASK: Value = 0x7F;
+
+/// Addresses and progress of the current or last transfer
+#[derive(Default)]
+pub struct Hdma {
+    source: FarAddress,
+    /// Offset in VRAM
+    destination: FarAddress,
+    /// Blocks left to copy, kept when an H-Blank transfer is cancelled
+    remaining: u8,
+    /// An H-Blank transfer is in progress
+    active: bool,
+}
+
+impl Hdma {
+    /// Sets a byte of the source or destination address (HDMA1-HDMA4)
+    pub fn write_address(&mut self, addr: FarAddress, value: Value) {
+        let address = if addr == HDMA1 || addr == HDMA2 { &mut self.source } else { &mut self.destination };
+        let [high, low] = address.to_be_bytes();
+        *address = if addr == HDMA1 || addr == HDMA3 { pair_to_wide(value, low) } else { pair_to_wide(high, value) };
+    }
+
+    /// Value read from HDMA5: the remaining length while an H-Blank transfer is active, bit 7 set once cancelled, 0xFF
+    /// once done
+    pub fn status(&self) -> Value {
+        if self.remaining == 0 { return 0xFF; }
+
+        let length = self.remaining - 1;
+        return if self.active { length } else { 0x80 | length };
+    }
+}
+
+impl Stateful for Hdma {
+    fn save_state(&self, writer: &mut StateWriter) {
+        writer.write_wide(self.source);
+        writer.write_wide(self.destination);
+        writer.write_bytes(&[self.remaining, u8::from(self.active)]);
+    }
+
+    fn load_state(&mut self, reader: &mut StateReader) -> Result<(), StateError> {
+        self.source = reader.read_wide()?;
+        self.destination = reader.read_wide()?;
+        let progress = reader.read_bytes(2)?;
+        self.remaining = progress[0];
+        self.active = progress[1] != 0;
+
+        return Ok(());
+    }
+}
+
+/// Copies the next block to the mapped VRAM bank, pausing the CPU
+fn copy_block(memory: &mut Memory) {
+    let hdma = &mut memory.cgb.hdma;
+    let (source, destination) = (hdma.source, VRAM_START | (hdma.destination & DESTINATION_MASK));
+    hdma.source = hdma.source.wrapping_add(BLOCK_SIZE);
+    hdma.destination = hdma.destination.wrapping_add(BLOCK_SIZE) & DESTINATION_MASK;
+    hdma.remaining -= 1;
+    if hdma.remaining == 0 { hdma.active = false; }
+
+    for offset in 0..BLOCK_SIZE {
+        let (from, to) = (source.wrapping_add(offset), destination + offset);
+        let value = memory.peek(from);
+        memory.watchpoints.check_write(to, memory.peek(to), value, AccessSource::Hdma);
+        memory.memory[usize::from(to)] = value;
+    }
+
+    memory.cycles += TICKS_PER_BLOCK;
+    memory.memory[HDMA5 as usize] = memory.cgb.hdma.status();
+}
+
+/// Write to HDMA5: starts a general-purpose or H-Blank transfer, or cancels the active H-Blank transfer
+pub fn start(memory: &mut Memory, value: Value) {
+    let hblank = get_bit(value, HDMA5_HBLANK_BIT);
+    let hdma = &mut memory.cgb.hdma;
+
+    if hdma.active && !hblank {
+        hdma.active = false;
+        log!(Debug, "MEMORY", format!("H-Blank DMA cancelled with {} blocks left", hdma.remaining));
+    } else {
+        hdma.source &= SOURCE_MASK;
+        hdma.destination &= DESTINATION_MASK;
+        hdma.remaining = (value & LENGTH_MASK) + 1;
+        hdma.active = hblank;
+        log!(Debug, "MEMORY", format!(
+            "{} DMA of {} blocks from ${:04X} to ${:04X}",
+            if hblank { "H-Blank" } else { "General-purpose" }, hdma.remaining, hdma.source, VRAM_START | hdma.destination,
+        ));
+
+        if !hblank {
+            while memory.cgb.hdma.remaining > 0 { copy_block(memory); }
+        }
+    }
+
+    memory.memory[HDMA5 as usize] = memory.cgb.hdma.status();
+}
+
+/// Copies a block of the active H-Blank transfer, the LCD having entered H-Blank
+pub fn hblank(memory: &mut Memory) {
+    if !memory.cgb.hdma.active { return; }
+
+    copy_block(memory);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cpu::execution::step;
+    use crate::cpu::io::{HDMA1, HDMA2, HDMA3, HDMA4, HDMA5, KEY1, LCDC};
+    use crate::cpu::memory::Memory;
+    use super::TICKS_PER_BLOCK;
+
+    fn setup(source: u16, destination: u16) -> Memory {
+        let mut memory = Memory::new(0x10000);
+        memory.set_cgb_mode(true);
+        for (byte, value) in memory.memory[0xC000..0xC100].iter_mut().zip(0..=0xFF) { *byte = value; }
+
+        let [source_high, source_low] = source.to_be_bytes();
+        let [destination_high, destination_low] = destination.to_be_bytes();
+        for (register, value) in [(HDMA1, source_high), (HDMA2, source_low), (HDMA3, destination_high), (HDMA4, destination_low)] {
+            memory.write_far_addr(register, value);
+        }
+        return memory;
+    }
+
+    #[test]
+    fn test_general_purpose() {
+        // The low bits of the addresses are ignored, and the destination is always in VRAM
+        let mut memory = setup(0xC01F, 0xE105);
+        assert_eq!(memory.peek(HDMA1), 0xFF);
+        memory.write_far_addr(HDMA5, 0x01);
+
+        assert_eq!(memory.memory[0x8100..0x8120], memory.memory[0xC010..0xC030]);
+        assert_eq!(memory.memory[0x8120], 0x00);
+        assert_eq!(memory.peek(HDMA5), 0xFF);
+        assert_eq!(memory.cycles, 2 * TICKS_PER_BLOCK);
+
+        // Continues from the last addresses, pausing the CPU for twice as many M-cycles in double speed
+        memory.registers.PC = 0x0000;
+        memory.memory[0x0000] = 0x10;
+        memory.write_far_addr(KEY1, 0x01);
+        step(&mut memory);
+        let cycles = memory.cycles;
+        memory.write_far_addr(HDMA5, 0x00);
+        assert_eq!(memory.memory[0x8120..0x8130], memory.memory[0xC030..0xC040]);
+        assert_eq!(memory.cycles - cycles, TICKS_PER_BLOCK);
+    }
+
+    #[test]
+    fn test_hblank() {
+        let mut memory = setup(0xC000, 0x8000);
+        memory.memory[LCDC as usize] = 0x80;
+        // NOPs, from the start of a line
+        memory.registers.PC = 0x0000;
+        memory.write_far_addr(HDMA5, 0x82);
+        assert_eq!(memory.peek(HDMA5), 0x02);
+
+        while memory.cycles < 252 { step(&mut memory); }
+        assert_eq!(memory.memory[0x8000..0x8010], memory.memory[0xC000..0xC010]);
+        assert_eq!(memory.memory[0x8010], 0x00);
+        assert_eq!((memory.peek(HDMA5), memory.cycles), (0x01, 252 + TICKS_PER_BLOCK));
+
+        // Only once per H-Blank
+        while memory.cycles < 456 { step(&mut memory); }
+        assert_eq!(memory.memory[0x8010], 0x00);
+
+        while memory.cycles < 456 + 252 { step(&mut memory); }
+        assert_eq!(memory.memory[0x8010..0x8020], memory.memory[0xC010..0xC020]);
+
+        // Cancelled with a block left
+        memory.write_far_addr(HDMA5, 0x00);
+        assert_eq!(memory.peek(HDMA5), 0x80);
+        while memory.cycles < 2 * 456 + 252 { step(&mut memory); }
+        assert_eq!(memory.memory[0x8020], 0x00);
+    }
+}
@@ -13,6 +13,7 @@ pub struct Instruction<T> {
     pub opcode: OpCode,
     pub disassembly: &'static str,
     pub byte_size: usize,
+    /// For conditional jumps, the ticks when the condition doesn't hold: the operation charges the extra ones
     pub clock_tick: u8,
     pub function: InstructionFn<T>,
 }
@@ -44,10 +45,34 @@ impl<T> std::fmt::Debug for Instruction<T> {
     }
 }
 
+impl GenericInstruction {
+    pub fn disassembly(&self) -> &'static str {
+        return match self {
+            GenericInstruction::Void(instr) => instr.disassembly,
+            GenericInstruction::Value(instr) => instr.disassembly,
+            GenericInstruction::Wide(instr) => instr.disassembly,
+            GenericInstruction::Near(instr) => instr.disassembly,
+            GenericInstruction::Far(instr) => instr.disassembly,
+            GenericInstruction::Offset(instr) => instr.disassembly,
+        }
+    }
+
+    /// Number of operand bytes following the opcode
+    pub fn operand_size(&self) -> u16 {
+        return match self {
+            GenericInstruction::Void(_) => 0,
+            GenericInstruction::Value(_) | GenericInstruction::Near(_) | GenericInstruction::Offset(_) => 1,
+            GenericInstruction::Wide(_) | GenericInstruction::Far(_) => 2,
+        }
+    }
+}
+
 impl<T> Instruction<T> {
     pub fn execute(&self, memory: &mut Memory, value: T) {
-        log!("INSTRUCTION", format!("Executing {self:?}"));
+        log!(Trace, "CPU", format!("Executing {self:?}"));
         (self.function)(memory, value);
+        // Cycles are counted at the normal speed clock, which the CPU runs twice as fast in double speed
+        memory.cycles += u64::from(self.clock_tick) >> u8::from(memory.cgb.double_speed);
     }
 }
 
@@ -0,0 +1,231 @@
+//! Interrupts: requested in IF, enabled in IE, and serviced while the IME flag is set. HALT waits for them.
+
+use crate::cpu::io::{IE, IF};
+use crate::cpu::memory::Memory;
+use crate::state::savestate::{StateError, StateReader, StateWriter, Stateful};
+use crate::utils::log::log;
+use crate::utils::types::{FarAddress, Value};
+
+/// V-Blank, LCD STAT, Timer, Serial and Joypad, in bits 0-4 of IE and IF by decreasing priority
+const INTERRUPT_MASK: Value = 0x1F;
+pub const VBLANK_INTERRUPT_BIT: usize = 0;
+pub const STAT_INTERRUPT_BIT: usize = 1;
+pub const TIMER_INTERRUPT_BIT: usize = 2;
+pub const JOYPAD_INTERRUPT_BIT: usize = 4;
+const FIRST_VECTOR: FarAddress = 0x0040;
+const VECTOR_SPACING: FarAddress = 0x08;
+/// Clock ticks to push PC and jump to the vector
+const DISPATCH_TICKS: u64 = 20;
+/// Clock ticks of a M-cycle, spent waiting while halted
+const HALTED_TICKS: u64 = 4;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct Interrupts {
+    /// Interrupt master enable flag
+    pub ime: bool,
+    /// Instructions left before IME is set by EI, which takes effect after the next instruction
+    ime_delay: u8,
+    /// Set by HALT until an interrupt is pending
+    pub halted: bool,
+    /// Set by STOP (without a speed switch) until a button is pressed
+    pub stopped: bool,
+    /// HALT with an interrupt already pending while IME is clear: the next byte is read twice
+    halt_bug: bool,
+}
+
+impl Interrupts {
+    pub fn enable(&mut self) {
+        self.ime_delay = 2;
+    }
+
+    pub fn disable(&mut self) {
+        self.ime = false;
+        self.ime_delay = 0;
+    }
+
+    /// Called at the end of every instruction, sets IME once the instruction following EI is done
+    pub fn end_instruction(&mut self) {
+        if self.ime_delay == 0 { return; }
+
+        self.ime_delay -= 1;
+        if self.ime_delay == 0 { self.ime = true; }
+    }
+
+    /// Whether PC must not be incremented after fetching the opcode, because of the HALT bug. Cleared once read.
+    pub fn take_halt_bug(&mut self) -> bool {
+        return std::mem::take(&mut self.halt_bug);
+    }
+}
+
+/// Sets the interrupt's bit in IF
+pub fn request(memory: &mut Memory, bit: usize) {
+    memory.memory[IF as usize] |= 1 << bit;
+}
+
+/// Interrupts both requested and enabled
+pub fn pending(memory: &Memory) -> Value {
+    return memory.peek(IE) & memory.peek(IF) & INTERRUPT_MASK;
+}
+
+/// Stops the CPU until an interrupt is pending, which is serviced if IME is set
+pub fn halt(memory: &mut Memory) {
+    if !memory.interrupts.ime && pending(memory) != 0 {
+        memory.interrupts.halt_bug = true;
+    } else {
+        memory.interrupts.halted = true;
+    }
+}
+
+/// Stops the CPU until a button of the lines selected in P1 is pressed, which requests the joypad interrupt.
+/// A joypad interrupt requested before STOP is acknowledged, so that only a new press wakes the CPU up.
+pub fn stop(memory: &mut Memory) {
+    memory.interrupts.stopped = true;
+    memory.memory[IF as usize] &= !(1 << JOYPAD_INTERRUPT_BIT);
+}
+
+/// Called before every instruction: wakes the CPU up once an interrupt is pending, and services the pending interrupt
+/// of highest priority if IME is set. Returns whether the CPU can execute the next instruction.
+pub fn service(memory: &mut Memory) -> bool {
+    if memory.interrupts.stopped {
+        if memory.peek(IF) & (1 << JOYPAD_INTERRUPT_BIT) == 0 {
+            memory.cycles += HALTED_TICKS >> u8::from(memory.cgb.double_speed);
+            return false;
+        }
+        memory.interrupts.stopped = false;
+    }
+
+    let pending = pending(memory);
+    if memory.interrupts.halted {
+        if pending == 0 {
+            memory.cycles += HALTED_TICKS >> u8::from(memory.cgb.double_speed);
+            return false;
+        }
+        memory.interrupts.halted = false;
+    }
+    if !memory.interrupts.ime || pending == 0 { return true; }
+
+    let bit = pending.trailing_zeros();
+    memory.interrupts.disable();
+    memory.memory[IF as usize] &= !(1 << bit);
+    memory.push_wide(memory.registers.PC);
+    #[allow(clippy::cast_possible_truncation)]
+    { memory.registers.PC = FIRST_VECTOR + bit as FarAddress * VECTOR_SPACING; }
+    memory.cycles += DISPATCH_TICKS >> u8::from(memory.cgb.double_speed);
+
+    log!("CPU", format!("Servicing interrupt {bit}, jumping to {:#06X}", memory.registers.PC));
+    return false;
+}
+
+impl Stateful for Interrupts {
+    fn save_state(&self, writer: &mut StateWriter) {
+        writer.write_bytes(&[u8::from(self.ime), self.ime_delay, u8::from(self.halted), u8::from(self.halt_bug), u8::from(self.stopped)]);
+    }
+
+    fn load_state(&mut self, reader: &mut StateReader) -> Result<(), StateError> {
+        let flags = reader.read_bytes(5)?;
+        self.ime = flags[0] != 0;
+        self.ime_delay = flags[1].min(2);
+        self.halted = flags[2] != 0;
+        self.halt_bug = flags[3] != 0;
+        self.stopped = flags[4] != 0;
+
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cpu::execution::step;
+    use crate::cpu::io::{IE, IF, P1};
+    use crate::cpu::joypad::press;
+    use crate::cpu::memory::Memory;
+
+    fn setup(program: &[u8]) -> Memory {
+        let mut memory = Memory::new(0x10000);
+        memory.memory[0x0100..0x0100 + program.len()].copy_from_slice(program);
+        memory.registers.PC = 0x0100;
+        memory.registers.SP = 0xFFFE;
+        return memory;
+    }
+
+    #[test]
+    fn test_ei_delay() {
+        // EI, NOP, NOP with the timer interrupt requested and enabled
+        let mut memory = setup(&[0xFB, 0x00, 0x00]);
+        memory.memory[IE as usize] = 0x04;
+        memory.memory[IF as usize] = 0x04;
+
+        step(&mut memory);
+        step(&mut memory);
+        assert_eq!(memory.registers.PC, 0x0102);
+        step(&mut memory);
+        assert_eq!((memory.registers.PC, memory.peek(IF)), (0x0050, 0x00));
+        assert!(!memory.interrupts.ime);
+        // Return address on the stack
+        assert_eq!((memory.peek(0xFFFD), memory.peek(0xFFFC)), (0x01, 0x02));
+    }
+
+    #[test]
+    fn test_di() {
+        // EI, DI, NOP
+        let mut memory = setup(&[0xFB, 0xF3, 0x00]);
+        memory.memory[IE as usize] = 0x01;
+        memory.memory[IF as usize] = 0x01;
+
+        for _ in 0..3 { step(&mut memory); }
+        assert_eq!(memory.registers.PC, 0x0103);
+        assert!(!memory.interrupts.ime);
+    }
+
+    #[test]
+    fn test_halt() {
+        // HALT, INC A
+        let mut memory = setup(&[0x76, 0x3C]);
+        memory.memory[IE as usize] = 0x01;
+
+        step(&mut memory);
+        let cycles = memory.cycles;
+        step(&mut memory);
+        assert!(memory.interrupts.halted);
+        assert_eq!((memory.registers.PC, memory.cycles - cycles), (0x0101, 4));
+
+        // Woken up without servicing the interrupt, IME being clear
+        memory.memory[IF as usize] = 0x01;
+        step(&mut memory);
+        assert!(!memory.interrupts.halted);
+        assert_eq!((memory.registers.PC, memory.registers.get_a()), (0x0102, 1));
+    }
+
+    #[test]
+    fn test_halt_bug() {
+        // HALT with an interrupt pending and IME clear, then INC A read twice
+        let mut memory = setup(&[0x76, 0x3C, 0x00]);
+        memory.memory[IE as usize] = 0x01;
+        memory.memory[IF as usize] = 0x01;
+
+        for _ in 0..3 { step(&mut memory); }
+        assert!(!memory.interrupts.halted);
+        assert_eq!((memory.registers.PC, memory.registers.get_a()), (0x0102, 2));
+    }
+
+    #[test]
+    fn test_stop() {
+        // STOP, INC A, with the joypad interrupt disabled and requested by an earlier press
+        let mut memory = setup(&[0x10, 0x00, 0x3C]);
+        memory.memory[IF as usize] = 0x10;
+
+        step(&mut memory);
+        let cycles = memory.cycles;
+        step(&mut memory);
+        assert!(memory.interrupts.stopped);
+        assert_eq!((memory.registers.PC, memory.cycles - cycles), (0x0102, 4));
+
+        // Start pressed, with the buttons selected
+        memory.write_far_addr(P1, 0x10);
+        press(&mut memory, 0x80);
+        step(&mut memory);
+        assert!(!memory.interrupts.stopped);
+        assert_eq!((memory.registers.PC, memory.registers.get_a()), (0x0103, 1));
+    }
+}
@@ -0,0 +1,158 @@
+use crate::utils::types::{FarAddress, Value};
+
+//  #############################
+//  #          Regions          #
+//  #############################
+
+pub const VRAM_START: FarAddress = 0x8000;
+pub const OAM_START: FarAddress = 0xFE00;
+
+//  #############################
+//  #         Registers         #
+//  #############################
+
+/// Joypad, also used to send SGB command packets
+pub const P1: FarAddress = 0xFF00;
+/// Serial transfer data
+pub const SB: FarAddress = 0xFF01;
+/// Serial transfer control
+pub const SC: FarAddress = 0xFF02;
+/// Divider, the high byte of the clock counter which drives the timer
+pub const DIV: FarAddress = 0xFF04;
+/// Timer counter, reloaded from TMA when it overflows
+pub const TIMA: FarAddress = 0xFF05;
+pub const TMA: FarAddress = 0xFF06;
+/// Timer control
+pub const TAC: FarAddress = 0xFF07;
+/// Interrupts requested
+pub const IF: FarAddress = 0xFF0F;
+/// Sound registers: 5 per channel from NR10 (sweep, length & duty, envelope, frequency low, trigger & frequency high),
+/// the wave channel having its DAC switch in NR30 and its volume in NR32, and the noise channel its shape in NR43
+pub const NR10: FarAddress = 0xFF10;
+pub const NR13: FarAddress = 0xFF13;
+pub const NR14: FarAddress = 0xFF14;
+pub const NR30: FarAddress = 0xFF1A;
+pub const NR32: FarAddress = 0xFF1C;
+pub const NR43: FarAddress = 0xFF22;
+/// Master volume of the left (bits 4-6) and right (bits 0-2) outputs
+pub const NR50: FarAddress = 0xFF24;
+/// Channels sent to the left (bits 4-7) and right (bits 0-3) outputs
+pub const NR51: FarAddress = 0xFF25;
+/// Sound on/off, and the channels playing (read-only)
+pub const NR52: FarAddress = 0xFF26;
+/// 32 4-bit samples played by the wave channel, high nibbles first
+pub const WAVE_RAM_START: FarAddress = 0xFF30;
+/// LCD control
+pub const LCDC: FarAddress = 0xFF40;
+/// LCD status: mode, LY = LYC and the sources of the STAT interrupt
+pub const STAT: FarAddress = 0xFF41;
+pub const SCY: FarAddress = 0xFF42;
+pub const SCX: FarAddress = 0xFF43;
+/// Line being drawn, read-only
+pub const LY: FarAddress = 0xFF44;
+/// Line compared to LY
+pub const LYC: FarAddress = 0xFF45;
+/// OAM DMA source address, high byte
+pub const DMA: FarAddress = 0xFF46;
+/// Background palette
+pub const BGP: FarAddress = 0xFF47;
+/// Object palettes
+pub const OBP0: FarAddress = 0xFF48;
+pub const OBP1: FarAddress = 0xFF49;
+pub const WY: FarAddress = 0xFF4A;
+/// Window X position, plus 7
+pub const WX: FarAddress = 0xFF4B;
+/// CGB speed switch
+pub const KEY1: FarAddress = 0xFF4D;
+/// CGB VRAM bank
+pub const VBK: FarAddress = 0xFF4F;
+/// CGB VRAM DMA source (high, low) and destination (high, low)
+pub const HDMA1: FarAddress = 0xFF51;
+pub const HDMA2: FarAddress = 0xFF52;
+pub const HDMA3: FarAddress = 0xFF53;
+pub const HDMA4: FarAddress = 0xFF54;
+/// CGB VRAM DMA length, mode and start
+pub const HDMA5: FarAddress = 0xFF55;
+/// CGB background palette index and data
+pub const BCPS: FarAddress = 0xFF68;
+pub const BCPD: FarAddress = 0xFF69;
+/// CGB object palette index and data
+pub const OCPS: FarAddress = 0xFF6A;
+pub const OCPD: FarAddress = 0xFF6B;
+/// CGB WRAM bank
+pub const SVBK: FarAddress = 0xFF70;
+/// Interrupts enabled
+pub const IE: FarAddress = 0xFFFF;
+
+//  #############################
+//  #          SC bits          #
+//  #############################
+
+/// Set to start a transfer, cleared by the hardware once done
+pub const SC_TRANSFER_BIT: usize = 7;
+/// Set when this Game Boy drives the serial clock
+pub const SC_CLOCK_BIT: usize = 0;
+
+//  #############################
+//  #         TAC bits          #
+//  #############################
+
+/// Clock select in bits 0-1
+pub const TAC_CLOCK_MASK: Value = 0x03;
+pub const TAC_ENABLE_BIT: usize = 2;
+
+//  #############################
+//  #         STAT bits         #
+//  #############################
+
+/// Mode of the LCD in bits 0-1
+pub const STAT_MODE_MASK: Value = 0x03;
+/// Set while LY = LYC, read-only
+pub const STAT_LYC_EQUAL_BIT: usize = 2;
+/// Sources of the STAT interrupt: the H-Blank, V-Blank and OAM scan modes, and LY = LYC
+pub const STAT_HBLANK_INTERRUPT_BIT: usize = 3;
+pub const STAT_VBLANK_INTERRUPT_BIT: usize = 4;
+pub const STAT_OAM_INTERRUPT_BIT: usize = 5;
+pub const STAT_LYC_INTERRUPT_BIT: usize = 6;
+
+//  #############################
+//  #         KEY1 bits         #
+//  #############################
+
+/// Set by the game before STOP to switch speed
+pub const KEY1_PREPARE_BIT: usize = 0;
+/// Set while in double speed mode, read-only
+pub const KEY1_SPEED_BIT: usize = 7;
+
+//  #############################
+//  #        HDMA5 bits         #
+//  #############################
+
+/// Written set for an H-Blank transfer, else general-purpose. Reads as 0 while an H-Blank transfer is active.
+pub const HDMA5_HBLANK_BIT: usize = 7;
+
+//  #############################
+//  #     BCPS & OCPS bits      #
+//  #############################
+
+/// Set to move to the next byte after each write of the data register
+pub const PALETTE_INCREMENT_BIT: usize = 7;
+
+//  #############################
+//  #         LCDC bits         #
+//  #############################
+
+/// Background and window shown, else blank
+pub const LCDC_BG_ENABLE_BIT: usize = 0;
+pub const LCDC_OBJ_ENABLE_BIT: usize = 1;
+
+/// Objects are 8x16 instead of 8x8
+pub const LCDC_OBJ_SIZE_BIT: usize = 2;
+/// Background tile map at 0x9C00 instead of 0x9800
+pub const LCDC_BG_MAP_BIT: usize = 3;
+/// Tile data addressed from 0x8000 (unsigned indexes) instead of 0x9000 (signed indexes)
+pub const LCDC_TILE_DATA_BIT: usize = 4;
+pub const LCDC_WINDOW_ENABLE_BIT: usize = 5;
+/// Window tile map at 0x9C00 instead of 0x9800
+pub const LCDC_WINDOW_MAP_BIT: usize = 6;
+pub const LCDC_LCD_ENABLE_BIT: usize = 7;
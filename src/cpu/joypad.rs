@@ -0,0 +1,79 @@
+//! Joypad: the buttons are read through P1, the game driving P14 low to read the directions and P15 low to read the
+//! other buttons
+
+use crate::cpu::interrupts::{self, JOYPAD_INTERRUPT_BIT};
+use crate::cpu::io::P1;
+use crate::cpu::memory::Memory;
+use crate::cpu::sgb;
+use crate::utils::bits::get_bit;
+use crate::utils::types::Value;
+
+/// P14 and P15 lines of P1, selecting the directions and the other buttons when low
+pub const LINES_MASK: Value = 0x30;
+const DIRECTIONS_LINE_BIT: usize = 4;
+const BUTTONS_LINE_BIT: usize = 5;
+/// Bits 6-7 of P1 always read as 1, as the buttons which aren't pressed in bits 0-3
+pub const P1_UNUSED_BITS: Value = 0xC0;
+pub const NO_BUTTONS: Value = 0x0F;
+
+/// Bits 0-3 of P1 for the selected lines, cleared for the buttons pressed
+pub fn selected_buttons(memory: &Memory, lines: Value) -> Value {
+    let mut pressed = 0;
+    if !get_bit(lines, DIRECTIONS_LINE_BIT) { pressed |= memory.joypad & 0x0F; }
+    if !get_bit(lines, BUTTONS_LINE_BIT) { pressed |= memory.joypad >> 4; }
+
+    return NO_BUTTONS & !pressed;
+}
+
+/// Write to P1: selects the lines to read, or talks to the SGB
+pub fn write_p1(memory: &mut Memory, value: Value) {
+    if memory.sgb.enabled {
+        sgb::write_p1(memory, value);
+        return;
+    }
+
+    let lines = value & LINES_MASK;
+    memory.memory[P1 as usize] = P1_UNUSED_BITS | lines | selected_buttons(memory, lines);
+}
+
+/// Holds the buttons of the state (Right, Left, Up, Down, A, B, Select and Start from bit 0) until the next call.
+/// Pressing a button of the selected lines requests the joypad interrupt.
+pub fn press(memory: &mut Memory, state: Value) {
+    let previous = memory.memory[P1 as usize];
+    memory.joypad = state;
+    // Rewriting the same lines doesn't send anything to the SGB
+    write_p1(memory, previous);
+
+    let buttons = memory.memory[P1 as usize];
+    if previous & !buttons & NO_BUTTONS != 0 { interrupts::request(memory, JOYPAD_INTERRUPT_BIT); }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cpu::interrupts::pending;
+    use crate::cpu::io::{IE, P1};
+    use crate::cpu::memory::Memory;
+    use super::press;
+
+    #[test]
+    fn test_p1() {
+        let mut memory = Memory::new(0x10000);
+        memory.write_far_addr(IE, 0x10);
+        memory.write_far_addr(P1, 0x30);
+        // Right and Start held
+        press(&mut memory, 0x81);
+
+        memory.write_far_addr(P1, 0x20);
+        assert_eq!(memory.peek(P1), 0xEE);
+        memory.write_far_addr(P1, 0x10);
+        assert_eq!(memory.peek(P1), 0xD7);
+        memory.write_far_addr(P1, 0x30);
+        assert_eq!(memory.peek(P1), 0xFF);
+        // Not selected
+        assert_eq!(pending(&memory), 0x00);
+
+        memory.write_far_addr(P1, 0x20);
+        press(&mut memory, 0x82);
+        assert_eq!((memory.peek(P1), pending(&memory)), (0xED, 0x10));
+    }
+}
@@ -0,0 +1,215 @@
+//! Timing of the LCD: the line (LY) and mode (STAT) of the PPU, and the V-Blank and STAT interrupts. The pixels are
+//! drawn from the memory once per frame, the modes last as long as drawing without scrolling, window nor objects.
+
+use crate::cpu::execution::TICKS_PER_FRAME;
+use crate::cpu::interrupts::{self, STAT_INTERRUPT_BIT, VBLANK_INTERRUPT_BIT};
+use crate::cpu::io::{LCDC, LCDC_LCD_ENABLE_BIT, LY, LYC, STAT, STAT_HBLANK_INTERRUPT_BIT, STAT_LYC_EQUAL_BIT,
+    STAT_LYC_INTERRUPT_BIT, STAT_MODE_MASK, STAT_OAM_INTERRUPT_BIT, STAT_VBLANK_INTERRUPT_BIT};
+use crate::cpu::memory::Memory;
+use crate::state::savestate::{StateError, StateReader, StateWriter, Stateful};
+use crate::utils::bits::{assign_bit, get_bit};
+use crate::utils::types::Value;
+
+pub const TICKS_PER_LINE: u64 = 456;
+pub const VISIBLE_LINES: u64 = 144;
+const OAM_SCAN_TICKS: u64 = 80;
+/// Shortest drawing time, without scrolling, window nor objects
+const DRAWING_TICKS: u64 = 172;
+/// Bit 7 of STAT always reads as 1
+const STAT_UNUSED_BITS: Value = 0x80;
+/// Interrupt sources of STAT, the only bits which can be written
+const STAT_WRITABLE_BITS: Value = 0x78;
+
+/// Mode of the LCD, as reported in the low bits of STAT
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mode {
+    HBlank,
+    VBlank,
+    OamScan,
+    /// Pixels are sent to the LCD, VRAM and palettes being inaccessible
+    Drawing,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Lcd {
+    /// Clock ticks since the start of the frame, which restarts when the LCD is turned on
+    frame_ticks: u64,
+    /// The STAT interrupt is requested when a source of STAT becomes active while none was
+    stat_line: bool,
+}
+
+impl Stateful for Lcd {
+    #[allow(clippy::cast_possible_truncation)]
+    fn save_state(&self, writer: &mut StateWriter) {
+        writer.write_u32(self.frame_ticks as u32);
+        writer.write_bytes(&[u8::from(self.stat_line)]);
+    }
+
+    fn load_state(&mut self, reader: &mut StateReader) -> Result<(), StateError> {
+        self.frame_ticks = u64::from(reader.read_u32()?) % TICKS_PER_FRAME;
+        self.stat_line = reader.read_bytes(1)?[0] != 0;
+
+        return Ok(());
+    }
+}
+
+fn enabled(memory: &Memory) -> bool {
+    return get_bit(memory.peek(LCDC), LCDC_LCD_ENABLE_BIT);
+}
+
+/// Mode of the LCD at the current clock tick. A disabled LCD stays in mode 0.
+pub fn mode(memory: &Memory) -> Mode {
+    if !enabled(memory) { return Mode::HBlank; }
+
+    let tick = memory.lcd.frame_ticks;
+    if tick / TICKS_PER_LINE >= VISIBLE_LINES { return Mode::VBlank; }
+
+    return match tick % TICKS_PER_LINE {
+        dot if dot < OAM_SCAN_TICKS => Mode::OamScan,
+        dot if dot < OAM_SCAN_TICKS + DRAWING_TICKS => Mode::Drawing,
+        _ => Mode::HBlank,
+    }
+}
+
+impl Mode {
+    /// Mode reported in STAT, as of its last refresh
+    fn from_stat(stat: Value) -> Mode {
+        return match stat & STAT_MODE_MASK {
+            0 => Mode::HBlank,
+            1 => Mode::VBlank,
+            2 => Mode::OamScan,
+            _ => Mode::Drawing,
+        }
+    }
+
+    fn stat_bits(self) -> Value {
+        return match self {
+            Mode::HBlank => 0,
+            Mode::VBlank => 1,
+            Mode::OamScan => 2,
+            Mode::Drawing => 3,
+        }
+    }
+}
+
+/// Refreshes LY and the read-only bits of STAT, and requests the STAT interrupt on the rising edge of its sources
+fn update_status(memory: &mut Memory) {
+    #[allow(clippy::cast_possible_truncation)]
+    let line = (memory.lcd.frame_ticks / TICKS_PER_LINE) as Value;
+    let mode = mode(memory);
+    let lyc_equal = line == memory.peek(LYC);
+
+    let stat = memory.peek(STAT);
+    memory.memory[LY as usize] = line;
+    memory.memory[STAT as usize] = assign_bit(STAT_UNUSED_BITS | (stat & STAT_WRITABLE_BITS) | mode.stat_bits(), STAT_LYC_EQUAL_BIT, lyc_equal);
+
+    let stat_line = enabled(memory) && (
+        (lyc_equal && get_bit(stat, STAT_LYC_INTERRUPT_BIT))
+        || (mode == Mode::HBlank && get_bit(stat, STAT_HBLANK_INTERRUPT_BIT))
+        || (mode == Mode::VBlank && get_bit(stat, STAT_VBLANK_INTERRUPT_BIT))
+        || (mode == Mode::OamScan && get_bit(stat, STAT_OAM_INTERRUPT_BIT)));
+    if stat_line && !memory.lcd.stat_line { interrupts::request(memory, STAT_INTERRUPT_BIT); }
+    memory.lcd.stat_line = stat_line;
+}
+
+/// Advances the LCD by the clock ticks elapsed, mode by mode. Returns whether it entered H-Blank.
+pub fn tick(memory: &mut Memory, ticks: u64) -> bool {
+    if !enabled(memory) { return false; }
+
+    let mut entered_hblank = false;
+    let mut remaining = ticks;
+    while remaining > 0 {
+        let dot = memory.lcd.frame_ticks % TICKS_PER_LINE;
+        let next_mode = [OAM_SCAN_TICKS, OAM_SCAN_TICKS + DRAWING_TICKS, TICKS_PER_LINE].into_iter().find(|start| *start > dot).unwrap_or(TICKS_PER_LINE);
+        let elapsed = remaining.min(next_mode - dot);
+        remaining -= elapsed;
+
+        memory.lcd.frame_ticks = (memory.lcd.frame_ticks + elapsed) % TICKS_PER_FRAME;
+        if elapsed < next_mode - dot { continue; }
+
+        let (before, after) = (Mode::from_stat(memory.peek(STAT)), mode(memory));
+
+        if after == Mode::VBlank && before != Mode::VBlank { interrupts::request(memory, VBLANK_INTERRUPT_BIT); }
+        entered_hblank |= after == Mode::HBlank && before != Mode::HBlank;
+        update_status(memory);
+    }
+
+    return entered_hblank;
+}
+
+/// LCDC was written: turning the LCD off resets it to the first line in mode 0, and the next frame starts when it is
+/// turned on
+pub fn write_lcdc(memory: &mut Memory) {
+    if !enabled(memory) { memory.lcd.frame_ticks = 0; }
+    update_status(memory);
+}
+
+/// STAT or LYC was written, or LY which is read-only: refreshes STAT, which may request the STAT interrupt
+pub fn write_status(memory: &mut Memory) {
+    update_status(memory);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cpu::interrupts::pending;
+    use crate::cpu::io::{IE, IF, LCDC, LY, LYC, STAT};
+    use crate::cpu::memory::Memory;
+    use super::{mode, tick, Mode, TICKS_PER_LINE, VISIBLE_LINES};
+
+    #[test]
+    fn test_mode() {
+        let mut memory = Memory::new(0x10000);
+        assert_eq!(mode(&memory), Mode::HBlank);
+        assert!(!tick(&mut memory, 1000));
+
+        memory.write_far_addr(LCDC, 0x80);
+        assert_eq!(mode(&memory), Mode::OamScan);
+        assert!(!tick(&mut memory, 80));
+        assert_eq!(mode(&memory), Mode::Drawing);
+        assert!(!tick(&mut memory, 171));
+        assert_eq!(mode(&memory), Mode::Drawing);
+        assert!(tick(&mut memory, 1));
+        assert_eq!((mode(&memory), memory.peek(STAT) & 0x03), (Mode::HBlank, 0));
+
+        // Ticks spanning several modes
+        assert!(tick(&mut memory, TICKS_PER_LINE));
+        assert_eq!((mode(&memory), memory.peek(LY)), (Mode::HBlank, 1));
+        tick(&mut memory, (VISIBLE_LINES - 1) * TICKS_PER_LINE - 252);
+        assert_eq!((mode(&memory), memory.peek(LY), memory.peek(STAT) & 0x03), (Mode::VBlank, 144, 1));
+
+        // Turned off, then on at the start of a frame
+        memory.write_far_addr(LCDC, 0x00);
+        assert_eq!((mode(&memory), memory.peek(LY)), (Mode::HBlank, 0));
+        memory.write_far_addr(LCDC, 0x80);
+        assert_eq!((mode(&memory), memory.peek(LY)), (Mode::OamScan, 0));
+    }
+
+    #[test]
+    fn test_interrupts() {
+        let mut memory = Memory::new(0x10000);
+        memory.memory[IE as usize] = 0x03;
+        // LY = LYC and H-Blank sources
+        memory.write_far_addr(LYC, 2);
+        memory.write_far_addr(STAT, 0x48);
+        memory.write_far_addr(LCDC, 0x80);
+
+        tick(&mut memory, 252);
+        assert_eq!(pending(&memory), 0x02);
+        memory.memory[IF as usize] = 0;
+        tick(&mut memory, TICKS_PER_LINE);
+        assert_eq!(pending(&memory), 0x02);
+        memory.memory[IF as usize] = 0;
+
+        // LY = LYC right after H-Blank keeps the line high, no new interrupt until it falls
+        tick(&mut memory, 204);
+        assert_eq!((memory.peek(LY), memory.peek(STAT) & 0x04, pending(&memory)), (2, 0x04, 0x00));
+        tick(&mut memory, 252);
+        assert_eq!(pending(&memory), 0x00);
+        tick(&mut memory, TICKS_PER_LINE);
+        assert_eq!(pending(&memory), 0x02);
+
+        memory.memory[IF as usize] = 0;
+        tick(&mut memory, (VISIBLE_LINES - 3) * TICKS_PER_LINE - 252);
+        assert_eq!((memory.peek(LY), pending(&memory) & 0x01), (144, 0x01));
+    }
+}
@@ -1,18 +1,101 @@
+use crate::cpu::apu::{self, Apu};
+use crate::cpu::cartridge::Cartridge;
+use crate::cpu::cgb::{Cgb, CGB_FLAG_ADDR, CgbSupport};
+use crate::cpu::colorization::{colorize, title_combination};
+use crate::cpu::hdma;
+use crate::cpu::interrupts::Interrupts;
+use crate::cpu::joypad;
+use crate::cpu::io::{BCPD, BCPS, BGP, DIV, DMA, HDMA1, HDMA4, HDMA5, KEY1, KEY1_PREPARE_BIT, KEY1_SPEED_BIT, LCDC, LY, LYC, NR10, NR50, NR51, NR52, OAM_START, OCPD, OCPS, P1, SB, SC, SC_CLOCK_BIT, SC_TRANSFER_BIT, STAT, SVBK, TAC, VBK};
+use crate::cpu::lcd::{self, Lcd, Mode};
 use crate::cpu::register::RegisterGroup;
-use crate::cpu::stack::Stack;
-use crate::utils::conversions::wide_to_pair;
+use crate::cpu::sgb::Sgb;
+use crate::cpu::timer::{self, Timer};
+use crate::debug::coverage::{Access, Coverage};
+use crate::debug::watchpoint::{AccessSource, Watchpoints};
+use crate::state::savestate::{StateError, StateReader, StateWriter, Stateful};
+use crate::utils::bits::{assign_bit, clear_bit, get_bit};
+use crate::utils::conversions::{pair_to_wide, wide_to_pair};
 use crate::utils::log::log;
 use crate::utils::types::{Byte, FarAddress, NearAddress, Value, WideValue};
 
 const NEAR_ADDR_START: usize = 0xFF00;
+const ROMX_START: FarAddress = 0x4000;
+const ROMX_END: FarAddress = 0x7FFF;
+/// PC and SP as left by the boot ROM
+const ENTRY_POINT: FarAddress = 0x0100;
+const INITIAL_SP: FarAddress = 0xFFFE;
+/// AF, BC, DE and HL as left by the DMG boot ROM
+const DMG_BOOT_REGISTERS: [WideValue; 4] = [0x01B0, 0x0013, 0x00D8, 0x014D];
+/// LCD on with the background shown from the tiles at 0x8000, and the identity palette, as left by the boot ROM
+const BOOT_LCDC: Value = 0x91;
+const BOOT_BGP: Value = 0xFC;
+/// Sound on at full volume, with the square channel of the boot sound on both sides and the others on the left
+const BOOT_SOUND: [(FarAddress, Value); 3] = [(NR52, 0x80), (NR50, 0x77), (NR51, 0xF3)];
+/// LY read by the CPU when stubbed, the first line of V-Blank
+const STUB_LY: Value = 0x90;
+const SRAM_START: FarAddress = 0xA000;
+const SRAM_END: FarAddress = 0xBFFF;
+/// Unused bits of the CGB registers, which read as 1
+const VBK_UNUSED_BITS: Value = 0xFE;
+const SVBK_UNUSED_BITS: Value = 0xF8;
+const KEY1_UNUSED_BITS: Value = 0x7E;
+/// Time the CPU is stopped while the speed switches
+const SPEED_SWITCH_TICKS: u64 = 8200;
+/// Bytes copied by an OAM DMA transfer, 40 objects of 4 bytes
+const OAM_DMA_SIZE: FarAddress = 0xA0;
+/// The whole address space
+const ADDRESS_SPACE_SIZE: usize = 0x10000;
+
+/// Regions of the memory map, with their first address
+pub const REGIONS: [(FarAddress, &str); 9] = [
+    (0x0000, "ROM0"),
+    (ROMX_START, "ROMX"),
+    (0x8000, "VRAM"),
+    (0xA000, "SRAM"),
+    (0xC000, "WRAM"),
+    (0xE000, "ECHO"),
+    // Including the unusable area up to 0xFEFF
+    (0xFE00, "OAM"),
+    (0xFF00, "IO"),
+    // Including IE at 0xFFFF
+    (0xFF80, "HRAM"),
+];
 
 type MemoryPtr = Box<[Byte]>;
 
+/// Hardware the machine runs as, chosen on the command line
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PowerOnOptions {
+    /// Run on a SGB
+    pub sgb: bool,
+    /// Run DMG cartridges on a CGB, colorized from their title
+    pub colorize: bool,
+}
+
 pub struct Memory {
     pub size: usize,
     pub memory: MemoryPtr,
-    pub stack: Stack,
+    /// ROM and external RAM, in place of the memory at 0x0000-0x7FFF and 0xA000-0xBFFF. `None` for a flat test bus.
+    pub cartridge: Option<Cartridge>,
     pub registers: RegisterGroup,
+    /// Clock ticks elapsed since power-on
+    pub cycles: u64,
+    pub watchpoints: Watchpoints,
+    pub coverage: Coverage,
+    /// Bytes sent through the serial port, where test ROMs print their results
+    pub serial_output: Vec<Byte>,
+    pub cgb: Cgb,
+    pub sgb: Sgb,
+    pub interrupts: Interrupts,
+    pub timer: Timer,
+    pub lcd: Lcd,
+    pub apu: Apu,
+    /// Buttons held, as fed by the GUI each frame (see `joypad::press`). Not part of the save states, being an input.
+    pub joypad: Value,
+    /// Plain RAM over the whole address space: the registers have no side effects and interrupts are never serviced
+    pub flat: bool,
+    /// LY reads as 0x90 whatever the line, as in the reference logs of Gameboy Doctor which are taken without a PPU
+    pub stub_ly: bool,
 }
 
 impl Memory {
@@ -23,15 +106,122 @@ impl Memory {
         Memory {
             size,
             memory: vec![0; size].into_boxed_slice(),
-            stack: Stack::new(u16::MAX),
-            registers: RegisterGroup::new()
+            cartridge: None,
+            registers: RegisterGroup::new(),
+            cycles: 0,
+            watchpoints: Watchpoints::default(),
+            coverage: Coverage::default(),
+            serial_output: Vec::new(),
+            cgb: Cgb::default(),
+            sgb: Sgb::default(),
+            interrupts: Interrupts::default(),
+            timer: Timer::default(),
+            lcd: Lcd::default(),
+            apu: Apu::default(),
+            joypad: 0,
+            flat: false,
+            stub_ly: false,
+        }
+    }
+
+    /// Flat 64 KiB bus without a cartridge nor hardware registers, for the CPU tests
+    #[cfg(test)]
+    pub fn flat_bus() -> Memory {
+        let mut memory = Memory::new(ADDRESS_SPACE_SIZE);
+        memory.flat = true;
+
+        return memory;
+    }
+
+    /// DMG with the cartridge of the ROM inserted, at its entry point with the registers and the LCD as left by the boot
+    /// ROM. Fails if the MBC of the cartridge isn't emulated.
+    pub fn from_rom(rom: &[Byte]) -> Result<Memory, String> {
+        let mut memory = Memory::new(ADDRESS_SPACE_SIZE);
+        memory.cartridge = Some(Cartridge::new(rom)?);
+        memory.registers.PC = ENTRY_POINT;
+        memory.registers.SP = INITIAL_SP;
+        let [af, bc, de, hl] = DMG_BOOT_REGISTERS;
+        memory.registers.set_af(af);
+        memory.registers.set_bc(bc);
+        memory.registers.set_de(de);
+        memory.registers.set_hl(hl);
+        memory.write_far_addr(LCDC, BOOT_LCDC);
+        memory.write_far_addr(BGP, BOOT_BGP);
+        for (addr, value) in BOOT_SOUND { memory.write_far_addr(addr, value); }
+        // P1 reads $CF once the boot ROM is done
+        joypad::write_p1(&mut memory, 0x00);
+
+        return Ok(memory);
+    }
+
+    /// Machine at power-on with the cartridge of the ROM: in CGB mode for the CGB cartridges unless run on a SGB, and
+    /// with the SGB functions or colorized as requested if the cartridge allows it
+    pub fn power_on(rom: &[Byte], options: PowerOnOptions) -> Result<Memory, String> {
+        let mut memory = Memory::from_rom(rom)?;
+
+        let cgb_cartridge = CgbSupport::from_flag(memory.peek(CGB_FLAG_ADDR)) != CgbSupport::None;
+        memory.sgb.enabled = options.sgb && Sgb::supported(&memory);
+        memory.set_cgb_mode(cgb_cartridge && !options.sgb);
+        if options.colorize && !cgb_cartridge {
+            let combination = title_combination(&memory);
+            colorize(&mut memory, combination);
+        }
+
+        return Ok(memory);
+    }
+
+    /// Bank mapped at the address, 0 for regions which aren't banked
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn bank(&self, addr: FarAddress) -> u8 {
+        return match (addr, &self.cartridge) {
+            (0..=ROMX_END, Some(cartridge)) => cartridge.rom_bank(addr >= ROMX_START) as u8,
+            (SRAM_START..=SRAM_END, Some(cartridge)) => cartridge.ram_bank() as u8,
+            (ROMX_START..=ROMX_END, None) => 1,
+            _ => 0,
+        }
+    }
+
+    /// First address and name of the region containing the address
+    pub fn region(addr: FarAddress) -> (FarAddress, &'static str) {
+        return REGIONS.iter().rev().find(|(start, _)| *start <= addr).copied().unwrap_or(REGIONS[0]);
+    }
+
+    /// Reads without logging nor side effects, for debugging tools. Out of range addresses read as 0xFF (open bus).
+    pub fn peek(&self, addr: FarAddress) -> Value {
+        return match (addr, &self.cartridge) {
+            (0..=ROMX_END, Some(cartridge)) => cartridge.read_rom(addr),
+            (SRAM_START..=SRAM_END, Some(cartridge)) => cartridge.read_ram(addr),
+            _ => self.memory.get(addr as usize).copied().unwrap_or(0xFF),
+        }
+    }
+
+    /// Writes the address, the cartridge handling the writes to the ROM and external RAM areas
+    fn store(&mut self, addr: FarAddress, value: Value) {
+        match (addr, &mut self.cartridge) {
+            (0..=ROMX_END, Some(cartridge)) => cartridge.write_register(addr, value),
+            (SRAM_START..=SRAM_END, Some(cartridge)) => cartridge.write_ram(addr, value),
+            _ => self.memory[addr as usize] = value,
+        }
+    }
+
+    /// Reads VRAM in a specific bank, whether it is mapped or not, without side effects
+    pub fn peek_vram(&self, bank: usize, addr: FarAddress) -> Value {
+        return self.cgb.peek_vram(&self.memory, bank, addr);
+    }
+
+    /// Reads the address in a specific bank, without side effects. `None` if the bank isn't available.
+    pub fn peek_bank(&self, addr: FarAddress, bank: u8) -> Option<Value> {
+        return match (addr, &self.cartridge) {
+            (0..=ROMX_END, Some(cartridge)) => (usize::from(bank) < cartridge.rom_bank_count()).then(|| cartridge.peek_rom(usize::from(bank), addr)),
+            (SRAM_START..=SRAM_END, Some(cartridge)) => cartridge.peek_ram(usize::from(bank), addr),
+            _ => (bank == self.bank(addr)).then(|| self.peek(addr)),
         }
     }
 
     fn near_to_far(addr: NearAddress) -> usize {
         let far_addr = (addr as usize) + NEAR_ADDR_START;
 
-        log!("MEMORY", format!("Near Address {addr:#x} + {NEAR_ADDR_START:#x} = {far_addr:#x}"));
+        log!(Trace, "MEMORY", format!("Near Address {addr:#x} + {NEAR_ADDR_START:#x} = {far_addr:#x}"));
 
         return far_addr;
     }
@@ -40,48 +230,203 @@ impl Memory {
         let addr = Self::near_to_far(addr);
         debug_assert!(addr < self.size);
 
-        log!("MEMORY", format!("Write {value:#x} at address ${addr:#x}"));
+        log!(Trace, "MEMORY", format!("Write {value:#x} at address ${addr:#x}"));
 
+        #[allow(clippy::cast_possible_truncation)]
+        self.watchpoints.check_write(addr as FarAddress, self.memory[addr], value, AccessSource::Cpu);
         self.memory[addr] = value;
+        #[allow(clippy::cast_possible_truncation)]
+        self.io_write(addr as FarAddress, value);
     }
 
     pub fn write_far_addr(&mut self, addr: FarAddress, value: Value) {
-        let addr = addr as usize;
-        debug_assert!(addr< self.size);
+        self.watchpoints.check_write(addr, self.peek(addr), value, AccessSource::Cpu);
 
-        log!("MEMORY", format!("Write {value:#x} at address ${addr:#x}"));
+        debug_assert!((addr as usize) < self.size);
 
-        self.memory[addr] = value;
+        log!(Trace, "MEMORY", format!("Write {value:#x} at address ${addr:#x}"));
+
+        self.store(addr, value);
+        self.io_write(addr, value);
+    }
+
+    /// Side effects of writing the registers, once the value is stored
+    fn io_write(&mut self, addr: FarAddress, value: Value) {
+        if self.flat { return; }
+        self.serial_write(addr, value);
+        if addr == DMA { self.oam_dma(value); }
+        if self.cgb.enabled { self.cgb_write(addr, value); }
+        match addr {
+            P1 => joypad::write_p1(self, value),
+            DIV => timer::write_div(self),
+            TAC => timer::write_tac(self, value),
+            LCDC => lcd::write_lcdc(self),
+            STAT | LY | LYC => lcd::write_status(self),
+            NR10..=NR52 => apu::write_register(self, addr, value),
+            _ => {}
+        }
+    }
+
+    /// Copies the 160 bytes of object attributes from `value` * 0x100 to the OAM.
+    /// The copy is instantaneous, the CPU isn't held to HRAM for the 160 M-cycles it takes on hardware.
+    fn oam_dma(&mut self, value: Value) {
+        let source = FarAddress::from(value) << 8;
+        for offset in 0..OAM_DMA_SIZE {
+            let (from, to) = (source.wrapping_add(offset), OAM_START + offset);
+            let read = self.peek(from);
+            self.watchpoints.check_read(from, read, AccessSource::OamDma);
+            self.watchpoints.check_write(to, self.peek(to), read, AccessSource::OamDma);
+            self.memory[usize::from(to)] = read;
+        }
+    }
+
+    /// Nothing is ever connected to the serial port: a transfer clocked by this Game Boy completes right away
+    // TODO: Take 8 bits at 8192 Hz and request the serial interrupt
+    fn serial_write(&mut self, addr: FarAddress, value: Value) {
+        if addr != SC || !get_bit(value, SC_TRANSFER_BIT) || !get_bit(value, SC_CLOCK_BIT) { return; }
+
+        self.serial_output.push(self.peek(SB));
+        self.memory[SC as usize] = clear_bit(value, SC_TRANSFER_BIT);
+    }
+
+    fn cgb_write(&mut self, addr: FarAddress, value: Value) {
+        match addr {
+            VBK => {
+                self.cgb.map_vram(&mut self.memory, usize::from(value & 0x01));
+                #[allow(clippy::cast_possible_truncation)]
+                { self.memory[VBK as usize] = VBK_UNUSED_BITS | self.cgb.vram_bank() as Value; }
+            }
+            SVBK => {
+                self.cgb.map_wram(&mut self.memory, usize::from(value & 0x07));
+                #[allow(clippy::cast_possible_truncation)]
+                { self.memory[SVBK as usize] = SVBK_UNUSED_BITS | self.cgb.wram_bank() as Value; }
+            }
+            KEY1 => self.memory[KEY1 as usize] = assign_bit(KEY1_UNUSED_BITS | (value & 0x01), KEY1_SPEED_BIT, self.cgb.double_speed),
+            HDMA1..=HDMA4 => {
+                self.cgb.hdma.write_address(addr, value);
+                // Write-only
+                self.memory[addr as usize] = 0xFF;
+            }
+            HDMA5 => hdma::start(self, value),
+            BCPS | BCPD | OCPS | OCPD => self.palette_write(addr, value),
+            _ => {}
+        }
+    }
+
+    /// Writes the palette RAM through its index or data register, writes to the data being ignored while drawing
+    fn palette_write(&mut self, addr: FarAddress, value: Value) {
+        let locked = lcd::mode(self) == Mode::Drawing;
+        let (palettes, index_register) = match addr {
+            BCPS | BCPD => (&mut self.cgb.background_palettes, BCPS),
+            _ => (&mut self.cgb.object_palettes, OCPS),
+        };
+
+        if addr == index_register { palettes.select(value); } else { palettes.write(value, locked); }
+        self.memory[index_register as usize] = palettes.index_register();
+        self.memory[index_register as usize + 1] = palettes.data();
+    }
+
+    /// Value read by the CPU from a register, the stored one unless the register is inaccessible
+    fn io_read(&self, addr: FarAddress, stored: Value) -> Value {
+        if self.flat { return stored; }
+        if addr == LY && self.stub_ly { return STUB_LY; }
+        let palette_data = addr == BCPD || addr == OCPD;
+        if self.cgb.enabled && palette_data && lcd::mode(self) == Mode::Drawing { return 0xFF; }
+        if (NR10..=NR52).contains(&addr) { return apu::read_register(self, addr, stored); }
+
+        return stored;
+    }
+
+    /// Enables the CGB features, as done by the CGB boot ROM for cartridges which support them
+    pub fn set_cgb_mode(&mut self, enabled: bool) {
+        self.cgb.enabled = enabled;
+        if !enabled { return; }
+
+        for (register, value) in [(VBK, 0), (SVBK, 1), (KEY1, 0), (BCPS, 0), (OCPS, 0)] { self.cgb_write(register, value); }
+        self.memory[HDMA1 as usize..=HDMA5 as usize].fill(0xFF);
+    }
+
+    /// Whether STOP switches the CPU speed instead of stopping it
+    pub fn speed_switch_armed(&self) -> bool {
+        return self.cgb.enabled && get_bit(self.peek(KEY1), KEY1_PREPARE_BIT);
+    }
+
+    /// Switches between normal and double speed, as done by STOP once armed through KEY1
+    pub fn switch_speed(&mut self) {
+        self.cgb.double_speed = !self.cgb.double_speed;
+        self.cgb_write(KEY1, 0);
+        self.cycles += SPEED_SWITCH_TICKS;
+
+        log!(Info, "MEMORY", format!("Switched to {} speed", if self.cgb.double_speed { "double" } else { "normal" }));
     }
 
     pub fn read_near_addr(&self, addr: NearAddress) -> Value {
         let addr = Self::near_to_far(addr);
         debug_assert!(addr < self.size);
 
-        let read = self.memory[addr];
+        #[allow(clippy::cast_possible_truncation)]
+        let read = self.io_read(addr as FarAddress, self.memory[addr]);
 
-        log!("MEMORY", format!("Read {read:#x} at address ${addr:#x}"));
+        log!(Trace, "MEMORY", format!("Read {read:#x} at address ${addr:#x}"));
 
+        #[allow(clippy::cast_possible_truncation)]
+        self.watchpoints.check_read(addr as FarAddress, read, AccessSource::Cpu);
         return read;
     }
 
     pub fn read_far_addr(&self, addr: FarAddress) -> Value {
-        let addr = addr as usize;
-        debug_assert!(addr < self.size);
+        return self.read_as(addr, Access::Data);
+    }
 
-        let read = self.memory[addr];
+    /// Reads the address, recording the kind of access in the ROM coverage
+    pub fn read_as(&self, addr: FarAddress, access: Access) -> Value {
+        debug_assert!((addr as usize) < self.size);
+        let read = self.io_read(addr, self.peek(addr));
 
-        log!("MEMORY", format!("Read {read:#x} at address ${addr:#x}"));
+        log!(Trace, "MEMORY", format!("Read {read:#x} at address ${addr:#x}"));
 
+        self.watchpoints.check_read(addr, read, AccessSource::Cpu);
+        self.coverage.mark(self.bank(addr), addr, access);
         return read;
     }
 
+    /// Pushes on the stack at SP, high byte first, reporting the two written bytes to the watchpoints
+    pub fn push_wide(&mut self, value: WideValue) {
+        let (high, low) = wide_to_pair(value);
+
+        for byte in [high, low] {
+            self.registers.SP = self.registers.SP.wrapping_sub(1);
+            let addr = self.registers.SP;
+            self.watchpoints.check_write(addr, self.peek(addr), byte, AccessSource::Stack);
+            self.store(addr, byte);
+        }
+
+        log!(Trace, "STACK", format!("Push {value:#06x}, SP = {:#06x}", self.registers.SP));
+    }
+
+    /// Pops from the stack at SP, low byte first, reporting the two read bytes to the watchpoints
+    pub fn pop_wide(&mut self) -> WideValue {
+        let mut bytes = [0; 2];
+
+        for byte in &mut bytes {
+            let addr = self.registers.SP;
+            *byte = self.peek(addr);
+            self.watchpoints.check_read(addr, *byte, AccessSource::Stack);
+            self.registers.SP = self.registers.SP.wrapping_add(1);
+        }
+
+        let value = pair_to_wide(bytes[1], bytes[0]);
+        log!(Trace, "STACK", format!("Pop {value:#06x}, SP = {:#06x}", self.registers.SP));
+
+        return value;
+    }
+
     // TODO: Check endianness
     // pub fn write_wide_near_addr(&mut self, addr: NearAddress, value: WideValue) {
     //     let addr = Self::near_to_far(addr);
     //     debug_assert!((addr + 1) < self.size);
     //
-    //     log!("MEMORY", format!("Write {value:#x} at address ${addr:#x}"));
+    //     log!(Trace, "MEMORY", format!("Write {value:#x} at address ${addr:#x}"));
     //
     //     let values = wide_to_pair(value);
     //
@@ -91,15 +436,16 @@ impl Memory {
 
     // TODO: Check endianness
     pub fn write_wide_far_addr(&mut self, addr: FarAddress, value: WideValue) {
-        let addr = addr as usize;
-        debug_assert!((addr + 1) < self.size);
+        let values = wide_to_pair(value);
+        self.watchpoints.check_write(addr, self.peek(addr), values.0, AccessSource::Cpu);
+        self.watchpoints.check_write(addr.wrapping_add(1), self.peek(addr.wrapping_add(1)), values.1, AccessSource::Cpu);
 
-        log!("MEMORY", format!("Write {value:#x} at address ${addr:#x}"));
+        debug_assert!((addr as usize + 1) < self.size);
 
-        let values = wide_to_pair(value);
+        log!(Trace, "MEMORY", format!("Write {value:#x} at address ${addr:#x}"));
 
-        self.memory[addr] = values.0;
-        self.memory[addr + 1] = values.1;
+        self.store(addr, values.0);
+        self.store(addr.wrapping_add(1), values.1);
     }
 
     // TODO: Check endianness
@@ -109,7 +455,7 @@ impl Memory {
     //
     //     let read = pair_to_wide(self.memory[addr], self.memory[addr + 1]);
     //
-    //     log!("MEMORY", format!("Read {read:#x} at address ${addr:#x}"));
+    //     log!(Trace, "MEMORY", format!("Read {read:#x} at address ${addr:#x}"));
     //
     //     return read;
     // }
@@ -121,10 +467,164 @@ impl Memory {
     //
     //     let read = pair_to_wide(self.memory[addr], self.memory[addr + 1]);
     //
-    //     log!("MEMORY", format!("Read {read:#x} at address ${addr:#x}"));
+    //     log!(Trace, "MEMORY", format!("Read {read:#x} at address ${addr:#x}"));
 
     //     return read;
     // }
 }
 
-// TODO: Add tests
\ No newline at end of file
+impl Stateful for Memory {
+    #[allow(clippy::cast_possible_truncation)]
+    fn save_state(&self, writer: &mut StateWriter) {
+        self.registers.save_state(writer);
+        writer.write_u64(self.cycles);
+        writer.write_u32(self.size as u32);
+        writer.write_bytes(&self.memory);
+        self.cgb.save_state(writer);
+        self.sgb.save_state(writer);
+        self.interrupts.save_state(writer);
+        self.timer.save_state(writer);
+        self.lcd.save_state(writer);
+        self.apu.save_state(writer);
+        writer.write_bytes(&[u8::from(self.cartridge.is_some())]);
+        if let Some(cartridge) = &self.cartridge { cartridge.save_state(writer); }
+    }
+
+    fn load_state(&mut self, reader: &mut StateReader) -> Result<(), StateError> {
+        self.registers.load_state(reader)?;
+        self.cycles = reader.read_u64()?;
+
+        let size = reader.read_u32()? as usize;
+        if size != self.size { return Err(StateError::SizeMismatch { expected: self.size, found: size }); }
+        self.memory.copy_from_slice(reader.read_bytes(size)?);
+
+        self.cgb.load_state(reader)?;
+        self.sgb.load_state(reader)?;
+        self.interrupts.load_state(reader)?;
+        self.timer.load_state(reader)?;
+        self.lcd.load_state(reader)?;
+        self.apu.load_state(reader)?;
+
+        let has_cartridge = reader.read_bytes(1)?[0] != 0;
+        return match &mut self.cartridge {
+            Some(cartridge) if has_cartridge => cartridge.load_state(reader),
+            None if !has_cartridge => Ok(()),
+            _ => Err(StateError::CartridgeMismatch),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cpu::execution::step;
+    use crate::cpu::io::{BCPD, BCPS, BGP, DMA, KEY1, LCDC, LY, NR52, OCPD, OCPS, SVBK, VBK};
+    use crate::cpu::lcd;
+    use crate::debug::watchpoint::AccessSource;
+    use crate::state::savestate::{load, save};
+    use super::{Memory, SPEED_SWITCH_TICKS};
+
+    #[test]
+    fn test_cgb_banks() {
+        let mut memory = Memory::new(0x10000);
+        // Plain memory in DMG mode
+        memory.write_far_addr(VBK, 0x01);
+        memory.write_far_addr(0x8000, 0x11);
+        assert_eq!(memory.peek_vram(1, 0x8000), 0x00);
+
+        memory.set_cgb_mode(true);
+        assert_eq!((memory.peek(VBK), memory.peek(SVBK)), (0xFE, 0xF9));
+        memory.write_far_addr(VBK, 0x01);
+        memory.write_far_addr(SVBK, 0x05);
+        assert_eq!((memory.peek(VBK), memory.peek(SVBK)), (0xFF, 0xFD));
+        memory.write_far_addr(0x8000, 0x22);
+        memory.write_far_addr(0xD000, 0x55);
+        assert_eq!((memory.peek_vram(0, 0x8000), memory.peek_vram(1, 0x8000)), (0x11, 0x22));
+
+        // Restored with the banks
+        let mut restored = Memory::new(0x10000);
+        assert!(load(&mut restored, 0, &save(&memory, 0)).is_ok());
+        restored.write_far_addr(VBK, 0x00);
+        restored.write_far_addr(SVBK, 0x00);
+        assert_eq!((restored.peek(0x8000), restored.peek(0xD000), restored.peek(SVBK)), (0x11, 0x00, 0xF9));
+        restored.write_far_addr(SVBK, 0x05);
+        assert_eq!(restored.peek(0xD000), 0x55);
+    }
+
+    #[test]
+    fn test_palette_registers() {
+        let mut memory = Memory::new(0x10000);
+        memory.set_cgb_mode(true);
+        assert_eq!((memory.peek(BCPS), memory.peek(BCPD)), (0x40, 0xFF));
+
+        memory.write_far_addr(OCPS, 0x82);
+        memory.write_far_addr(OCPD, 0x34);
+        memory.write_far_addr(OCPD, 0x12);
+        assert_eq!(memory.cgb.object_palettes.color(0, 1), 0x1234);
+        assert_eq!(memory.peek(OCPS), 0xC4);
+        memory.write_far_addr(OCPS, 0x02);
+        assert_eq!(memory.read_far_addr(OCPD), 0x34);
+
+        // Locked while drawing (LCD on, 100 ticks into a line), the index still increments
+        memory.write_far_addr(LCDC, 0x80);
+        lcd::tick(&mut memory, 100);
+        memory.write_far_addr(BCPS, 0x80);
+        memory.write_far_addr(BCPD, 0x00);
+        assert_eq!(memory.read_far_addr(BCPD), 0xFF);
+        assert_eq!((memory.cgb.background_palettes.color(0, 0), memory.peek(BCPS)), (0xFFFF, 0xC1));
+
+        lcd::tick(&mut memory, 200);
+        memory.write_far_addr(BCPD, 0x00);
+        assert_eq!(memory.cgb.background_palettes.color(0, 0), 0x00FF);
+    }
+
+    #[test]
+    fn test_speed_switch() {
+        let mut memory = Memory::new(0x10000);
+        memory.set_cgb_mode(true);
+        // STOP, then NOP
+        memory.memory[0x0100..0x0103].copy_from_slice(&[0x10, 0x00, 0x00]);
+        memory.registers.PC = 0x0100;
+        memory.write_far_addr(KEY1, 0x01);
+        assert_eq!(memory.peek(KEY1), 0x7F);
+
+        step(&mut memory);
+        assert!(memory.cgb.double_speed);
+        assert_eq!(memory.peek(KEY1), 0xFE);
+        assert_eq!(memory.cycles, SPEED_SWITCH_TICKS + 2);
+
+        step(&mut memory);
+        assert_eq!(memory.cycles, SPEED_SWITCH_TICKS + 4);
+    }
+
+    #[test]
+    fn test_boot_state() {
+        let Ok(mut memory) = Memory::from_rom(&vec![0x00; 0x8000]) else { panic!("ROM only cartridge was rejected") };
+        let registers = &memory.registers;
+        assert_eq!([registers.get_af(), registers.get_bc(), registers.get_de(), registers.get_hl()], [0x01B0, 0x0013, 0x00D8, 0x014D]);
+        assert_eq!((registers.PC, registers.SP), (0x0100, 0xFFFE));
+        assert_eq!((memory.peek(LCDC), memory.peek(BGP), memory.read_far_addr(LY)), (0x91, 0xFC, 0x00));
+        assert_eq!(memory.read_far_addr(NR52), 0xF0);
+
+        memory.stub_ly = true;
+        assert_eq!((memory.read_far_addr(LY), memory.peek(LY)), (0x90, 0x00));
+    }
+
+    #[test]
+    fn test_oam_dma() {
+        let mut memory = Memory::new(0x10000);
+        for (byte, value) in memory.memory[0xC100..0xC1A0].iter_mut().zip(0..=0xFF) { *byte = value; }
+        memory.watchpoints.add("r:C105".parse().unwrap_or_else(|error| panic!("{error}")));
+        memory.write_far_addr(DMA, 0xC1);
+
+        assert_eq!(memory.memory[0xFE00..0xFEA0], memory.memory[0xC100..0xC1A0]);
+        assert_eq!(memory.memory[0xFEA0], 0x00);
+        let hit = memory.watchpoints.take_hit();
+        assert_eq!(hit.map(|hit| (hit.addr, hit.value, hit.source)), Some((0xC105, 0x05, AccessSource::OamDma)));
+
+        memory.watchpoints.add("c:FE9F".parse().unwrap_or_else(|error| panic!("{error}")));
+        memory.memory[0xC29F] = 0x42;
+        memory.write_far_addr(DMA, 0xC2);
+        let hit = memory.watchpoints.take_hit();
+        assert_eq!(hit.map(|hit| (hit.addr, hit.old, hit.value, hit.source)), Some((0xFE9F, 0x9F, 0x42, AccessSource::OamDma)));
+    }
+}
\ No newline at end of file
@@ -1,5 +1,16 @@
+pub mod apu;
+pub mod cartridge;
+pub mod cgb;
+pub mod colorization;
+pub mod execution;
+pub mod hdma;
 pub mod instruction;
+pub mod interrupts;
+pub mod io;
+pub mod joypad;
+pub mod lcd;
 pub mod memory;
 mod operations;
 mod register;
-mod stack;
\ No newline at end of file
+pub mod sgb;
+pub mod timer;
\ No newline at end of file
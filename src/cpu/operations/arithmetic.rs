@@ -142,31 +142,31 @@ macro_rules! template_sub_a_unsafe {
 //  ########## 8-bits ###########
 
 pub fn inc_a(memory: &mut Memory, _value: Void) {
-    template_inc_value!(memory, memory.registers.AF.as_pair.0);
+    template_inc_value!(memory, memory.registers.AF.as_pair.high);
 }
 
 pub fn inc_b(memory: &mut Memory, _value: Void) {
-    template_inc_value!(memory, memory.registers.BC.as_pair.0);
+    template_inc_value!(memory, memory.registers.BC.as_pair.high);
 }
 
 pub fn inc_c(memory: &mut Memory, _value: Void) {
-    template_inc_value!(memory, memory.registers.BC.as_pair.1);
+    template_inc_value!(memory, memory.registers.BC.as_pair.low);
 }
 
 pub fn inc_d(memory: &mut Memory, _value: Void) {
-    template_inc_value!(memory, memory.registers.DE.as_pair.0);
+    template_inc_value!(memory, memory.registers.DE.as_pair.high);
 }
 
 pub fn inc_e(memory: &mut Memory, _value: Void) {
-    template_inc_value!(memory, memory.registers.DE.as_pair.1);
+    template_inc_value!(memory, memory.registers.DE.as_pair.low);
 }
 
 pub fn inc_h(memory: &mut Memory, _value: Void) {
-    template_inc_value!(memory, memory.registers.HL.as_pair.0);
+    template_inc_value!(memory, memory.registers.HL.as_pair.high);
 }
 
 pub fn inc_l(memory: &mut Memory, _value: Void) {
-    template_inc_value!(memory, memory.registers.HL.as_pair.1);
+    template_inc_value!(memory, memory.registers.HL.as_pair.low);
 }
 
 pub fn inc_hl_addr(memory: &mut Memory, _value: Void) {
@@ -205,31 +205,31 @@ pub fn inc_sp(memory: &mut Memory, _value: Void) {
 //  ########## 8-bits ###########
 
 pub fn dec_a(memory: &mut Memory, _value: Void) {
-    template_dec_value!(memory, memory.registers.AF.as_pair.0);
+    template_dec_value!(memory, memory.registers.AF.as_pair.high);
 }
 
 pub fn dec_b(memory: &mut Memory, _value: Void) {
-    template_dec_value!(memory, memory.registers.BC.as_pair.0);
+    template_dec_value!(memory, memory.registers.BC.as_pair.high);
 }
 
 pub fn dec_c(memory: &mut Memory, _value: Void) {
-    template_dec_value!(memory, memory.registers.BC.as_pair.1);
+    template_dec_value!(memory, memory.registers.BC.as_pair.low);
 }
 
 pub fn dec_d(memory: &mut Memory, _value: Void) {
-    template_dec_value!(memory, memory.registers.DE.as_pair.0);
+    template_dec_value!(memory, memory.registers.DE.as_pair.high);
 }
 
 pub fn dec_e(memory: &mut Memory, _value: Void) {
-    template_dec_value!(memory, memory.registers.DE.as_pair.1);
+    template_dec_value!(memory, memory.registers.DE.as_pair.low);
 }
 
 pub fn dec_h(memory: &mut Memory, _value: Void) {
-    template_dec_value!(memory, memory.registers.HL.as_pair.0);
+    template_dec_value!(memory, memory.registers.HL.as_pair.high);
 }
 
 pub fn dec_l(memory: &mut Memory, _value: Void) {
-    template_dec_value!(memory, memory.registers.HL.as_pair.1);
+    template_dec_value!(memory, memory.registers.HL.as_pair.low);
 }
 
 pub fn dec_hl_addr(memory: &mut Memory, _value: Void) {
@@ -272,31 +272,31 @@ pub fn add_a_d8(memory: &mut Memory, value: Value) {
 }
 
 pub fn add_a_a(memory: &mut Memory, _value: Void) {
-    template_add_a_unsafe!(memory, memory.registers.AF.as_pair.0);
+    template_add_a_unsafe!(memory, memory.registers.AF.as_pair.high);
 }
 
 pub fn add_a_b(memory: &mut Memory, _value: Void) {
-    template_add_a_unsafe!(memory, memory.registers.BC.as_pair.0);
+    template_add_a_unsafe!(memory, memory.registers.BC.as_pair.high);
 }
 
 pub fn add_a_c(memory: &mut Memory, _value: Void) {
-    template_add_a_unsafe!(memory, memory.registers.BC.as_pair.1);
+    template_add_a_unsafe!(memory, memory.registers.BC.as_pair.low);
 }
 
 pub fn add_a_d(memory: &mut Memory, _value: Void) {
-    template_add_a_unsafe!(memory, memory.registers.DE.as_pair.0);
+    template_add_a_unsafe!(memory, memory.registers.DE.as_pair.high);
 }
 
 pub fn add_a_e(memory: &mut Memory, _value: Void) {
-    template_add_a_unsafe!(memory, memory.registers.DE.as_pair.1);
+    template_add_a_unsafe!(memory, memory.registers.DE.as_pair.low);
 }
 
 pub fn add_a_h(memory: &mut Memory, _value: Void) {
-    template_add_a_unsafe!(memory, memory.registers.HL.as_pair.0);
+    template_add_a_unsafe!(memory, memory.registers.HL.as_pair.high);
 }
 
 pub fn add_a_l(memory: &mut Memory, _value: Void) {
-    template_add_a_unsafe!(memory, memory.registers.HL.as_pair.1);
+    template_add_a_unsafe!(memory, memory.registers.HL.as_pair.low);
 }
 
 pub fn add_a_hl_addr(memory: &mut Memory, _value: Void) {
@@ -347,31 +347,31 @@ pub fn sub_a_d8(memory: &mut Memory, value: Value) {
 }
 
 pub fn sub_a_a(memory: &mut Memory, _value: Void) {
-    template_sub_a_unsafe!(memory, memory.registers.AF.as_pair.0);
+    template_sub_a_unsafe!(memory, memory.registers.AF.as_pair.high);
 }
 
 pub fn sub_a_b(memory: &mut Memory, _value: Void) {
-    template_sub_a_unsafe!(memory, memory.registers.BC.as_pair.0);
+    template_sub_a_unsafe!(memory, memory.registers.BC.as_pair.high);
 }
 
 pub fn sub_a_c(memory: &mut Memory, _value: Void) {
-    template_sub_a_unsafe!(memory, memory.registers.BC.as_pair.1);
+    template_sub_a_unsafe!(memory, memory.registers.BC.as_pair.low);
 }
 
 pub fn sub_a_d(memory: &mut Memory, _value: Void) {
-    template_sub_a_unsafe!(memory, memory.registers.DE.as_pair.0);
+    template_sub_a_unsafe!(memory, memory.registers.DE.as_pair.high);
 }
 
 pub fn sub_a_e(memory: &mut Memory, _value: Void) {
-    template_sub_a_unsafe!(memory, memory.registers.DE.as_pair.1);
+    template_sub_a_unsafe!(memory, memory.registers.DE.as_pair.low);
 }
 
 pub fn sub_a_h(memory: &mut Memory, _value: Void) {
-    template_sub_a_unsafe!(memory, memory.registers.HL.as_pair.0);
+    template_sub_a_unsafe!(memory, memory.registers.HL.as_pair.high);
 }
 
 pub fn sub_a_l(memory: &mut Memory, _value: Void) {
-    template_sub_a_unsafe!(memory, memory.registers.HL.as_pair.1);
+    template_sub_a_unsafe!(memory, memory.registers.HL.as_pair.low);
 }
 
 pub fn sub_a_hl_addr(memory: &mut Memory, _value: Void) {
@@ -387,31 +387,31 @@ pub fn adc_a_d8(memory: &mut Memory, value: Value) {
 }
 
 pub fn adc_a_a(memory: &mut Memory, _value: Void) {
-    template_add_a_unsafe!(memory, memory.registers.AF.as_pair.0 + (Value::from(memory.registers.get_carry_flag())));
+    template_add_a_unsafe!(memory, memory.registers.AF.as_pair.high + (Value::from(memory.registers.get_carry_flag())));
 }
 
 pub fn adc_a_b(memory: &mut Memory, _value: Void) {
-    template_add_a_unsafe!(memory, memory.registers.BC.as_pair.0 + (Value::from(memory.registers.get_carry_flag())));
+    template_add_a_unsafe!(memory, memory.registers.BC.as_pair.high + (Value::from(memory.registers.get_carry_flag())));
 }
 
 pub fn adc_a_c(memory: &mut Memory, _value: Void) {
-    template_add_a_unsafe!(memory, memory.registers.BC.as_pair.1 + (Value::from(memory.registers.get_carry_flag())));
+    template_add_a_unsafe!(memory, memory.registers.BC.as_pair.low + (Value::from(memory.registers.get_carry_flag())));
 }
 
 pub fn adc_a_d(memory: &mut Memory, _value: Void) {
-    template_add_a_unsafe!(memory, memory.registers.DE.as_pair.0 + (Value::from(memory.registers.get_carry_flag())));
+    template_add_a_unsafe!(memory, memory.registers.DE.as_pair.high + (Value::from(memory.registers.get_carry_flag())));
 }
 
 pub fn adc_a_e(memory: &mut Memory, _value: Void) {
-    template_add_a_unsafe!(memory, memory.registers.DE.as_pair.1 + (Value::from(memory.registers.get_carry_flag())));
+    template_add_a_unsafe!(memory, memory.registers.DE.as_pair.low + (Value::from(memory.registers.get_carry_flag())));
 }
 
 pub fn adc_a_h(memory: &mut Memory, _value: Void) {
-    template_add_a_unsafe!(memory, memory.registers.HL.as_pair.0 + (Value::from(memory.registers.get_carry_flag())));
+    template_add_a_unsafe!(memory, memory.registers.HL.as_pair.high + (Value::from(memory.registers.get_carry_flag())));
 }
 
 pub fn adc_a_l(memory: &mut Memory, _value: Void) {
-    template_add_a_unsafe!(memory, memory.registers.HL.as_pair.1 + (Value::from(memory.registers.get_carry_flag())));
+    template_add_a_unsafe!(memory, memory.registers.HL.as_pair.low + (Value::from(memory.registers.get_carry_flag())));
 }
 
 pub fn adc_a_hl_addr(memory: &mut Memory, _value: Void) {
@@ -427,31 +427,31 @@ pub fn sbc_a_d8(memory: &mut Memory, value: Value) {
 }
 
 pub fn sbc_a_a(memory: &mut Memory, _value: Void) {
-    template_sub_a_unsafe!(memory, memory.registers.AF.as_pair.0 + (Value::from(memory.registers.get_carry_flag())));
+    template_sub_a_unsafe!(memory, memory.registers.AF.as_pair.high + (Value::from(memory.registers.get_carry_flag())));
 }
 
 pub fn sbc_a_b(memory: &mut Memory, _value: Void) {
-    template_sub_a_unsafe!(memory, memory.registers.BC.as_pair.0 + (Value::from(memory.registers.get_carry_flag())));
+    template_sub_a_unsafe!(memory, memory.registers.BC.as_pair.high + (Value::from(memory.registers.get_carry_flag())));
 }
 
 pub fn sbc_a_c(memory: &mut Memory, _value: Void) {
-    template_sub_a_unsafe!(memory, memory.registers.BC.as_pair.1 + (Value::from(memory.registers.get_carry_flag())));
+    template_sub_a_unsafe!(memory, memory.registers.BC.as_pair.low + (Value::from(memory.registers.get_carry_flag())));
 }
 
 pub fn sbc_a_d(memory: &mut Memory, _value: Void) {
-    template_sub_a_unsafe!(memory, memory.registers.DE.as_pair.0 + (Value::from(memory.registers.get_carry_flag())));
+    template_sub_a_unsafe!(memory, memory.registers.DE.as_pair.high + (Value::from(memory.registers.get_carry_flag())));
 }
 
 pub fn sbc_a_e(memory: &mut Memory, _value: Void) {
-    template_sub_a_unsafe!(memory, memory.registers.DE.as_pair.1 + (Value::from(memory.registers.get_carry_flag())));
+    template_sub_a_unsafe!(memory, memory.registers.DE.as_pair.low + (Value::from(memory.registers.get_carry_flag())));
 }
 
 pub fn sbc_a_h(memory: &mut Memory, _value: Void) {
-    template_sub_a_unsafe!(memory, memory.registers.HL.as_pair.0 + (Value::from(memory.registers.get_carry_flag())));
+    template_sub_a_unsafe!(memory, memory.registers.HL.as_pair.high + (Value::from(memory.registers.get_carry_flag())));
 }
 
 pub fn sbc_a_l(memory: &mut Memory, _value: Void) {
-    template_sub_a_unsafe!(memory, memory.registers.HL.as_pair.1 + (Value::from(memory.registers.get_carry_flag())));
+    template_sub_a_unsafe!(memory, memory.registers.HL.as_pair.low + (Value::from(memory.registers.get_carry_flag())));
 }
 
 pub fn sbc_a_hl_addr(memory: &mut Memory, _value: Void) {
@@ -512,5 +512,5 @@ pub fn daa(memory: &mut Memory, _value: Void) {
     memory.registers.set_zero_flag(new_value == 0);
     memory.registers.set_half_carry_flag(false);
 
-    log!("OPERATION", format!("{} ({:#0width$b}) + C={} + H={} => {} ({:#0width$b}) + C={} + Z={}", old_value, old_value, u8::from(old_carry_flag), u8::from(old_half_carry_flag), new_value, new_value, u8::from(memory.registers.get_carry_flag()), u8::from(memory.registers.get_zero_flag()), width = bit_size(old_value) + 2));
+    log!(Trace, "CPU", format!("{} ({:#0width$b}) + C={} + H={} => {} ({:#0width$b}) + C={} + Z={}", old_value, old_value, u8::from(old_carry_flag), u8::from(old_half_carry_flag), new_value, new_value, u8::from(memory.registers.get_carry_flag()), u8::from(memory.registers.get_zero_flag()), width = bit_size(old_value) + 2));
 }
\ No newline at end of file
@@ -15,7 +15,7 @@ macro_rules! template_rl {
             let popped_value = get_bit(old_value, max_bit_index(old_value));
             let new_value = assign_bit(old_value << 1, 0, old_carry);
 
-            log!("OPERATION", format!("{:#0width$b} + carry: {} => {:#0width$b} + carry: {}", old_value, old_carry as u8, new_value, popped_value as u8, width = bit_size(old_value) + 2));
+            log!(Trace, "CPU", format!("{:#0width$b} + carry: {} => {:#0width$b} + carry: {}", old_value, old_carry as u8, new_value, popped_value as u8, width = bit_size(old_value) + 2));
 
             $field = new_value;
 
@@ -35,7 +35,7 @@ macro_rules! template_rlc {
             // Left shift and put back top bit in the lowest bit
             let new_value = assign_bit(old_value << 1, 0, popped_value);
 
-            log!("OPERATION", format!("{:#0width$b} => {:#0width$b} + carry: {}", old_value, new_value, popped_value as u8, width = bit_size(old_value) + 2));
+            log!(Trace, "CPU", format!("{:#0width$b} => {:#0width$b} + carry: {}", old_value, new_value, popped_value as u8, width = bit_size(old_value) + 2));
 
             $field = new_value;
 
@@ -55,7 +55,7 @@ macro_rules! template_rr {
             let popped_value = get_bit(old_value, 0);
             let new_value = assign_bit(old_value >> 1, max_bit_index(old_value), old_carry);
 
-            log!("OPERATION", format!("{:#0width$b} + carry: {} => {:#0width$b} + carry: {}", old_value, old_carry as u8, new_value, popped_value as u8, width = bit_size(old_value) + 2));
+            log!(Trace, "CPU", format!("{:#0width$b} + carry: {} => {:#0width$b} + carry: {}", old_value, old_carry as u8, new_value, popped_value as u8, width = bit_size(old_value) + 2));
 
             $field = new_value;
 
@@ -74,7 +74,7 @@ macro_rules! template_rrc {
             let popped_value = get_bit(old_value, 0);
             let new_value = assign_bit(old_value >> 1, max_bit_index(old_value), popped_value);
 
-            log!("OPERATION", format!("{:#0width$b} => {:#0width$b} + carry: {}", old_value, new_value, popped_value as u8, width = bit_size(old_value) + 2));
+            log!(Trace, "CPU", format!("{:#0width$b} => {:#0width$b} + carry: {}", old_value, new_value, popped_value as u8, width = bit_size(old_value) + 2));
 
             // Left shift and put back top bit in the lowest bit
             $field = new_value;
@@ -94,7 +94,7 @@ macro_rules! template_sla {
             let popped_value = get_bit(old_value, max_bit_index(old_value));
             let new_value = old_value << 1;
 
-            log!("OPERATION", format!("{:#0width$b} => {:#0width$b} + carry: {}", old_value, new_value, popped_value as u8, width = bit_size(old_value) + 2));
+            log!(Trace, "CPU", format!("{:#0width$b} => {:#0width$b} + carry: {}", old_value, new_value, popped_value as u8, width = bit_size(old_value) + 2));
 
             $field = new_value;
 
@@ -114,7 +114,7 @@ macro_rules! template_sra {
             let popped_value = get_bit(old_value, 0);
             let new_value = assign_bit(old_value >> 1, max_bit_index(old_value), top_duplicate);
 
-            log!("OPERATION", format!("{:#0width$b} => {:#0width$b} + carry: {}", old_value, new_value, popped_value as u8, width = bit_size(old_value) + 2));
+            log!(Trace, "CPU", format!("{:#0width$b} => {:#0width$b} + carry: {}", old_value, new_value, popped_value as u8, width = bit_size(old_value) + 2));
 
             $field = new_value;
 
@@ -133,7 +133,7 @@ macro_rules! template_srl {
             let popped_value = get_bit(old_value, 0);
             let new_value = old_value >> 1;
 
-            log!("OPERATION", format!("{:#0width$b} => {:#0width$b} + carry: {}", old_value, new_value, popped_value as u8, width = bit_size(old_value) + 2));
+            log!(Trace, "CPU", format!("{:#0width$b} => {:#0width$b} + carry: {}", old_value, new_value, popped_value as u8, width = bit_size(old_value) + 2));
 
             $field = new_value;
 
@@ -238,147 +238,147 @@ fn template_set_hl_addr(memory: &mut Memory, index: usize) {
 //  ############# A #############
 
 pub fn rla(memory: &mut Memory, _value: Void) {
-    template_rl!(memory, memory.registers.AF.as_pair.0);
+    template_rl!(memory, memory.registers.AF.as_pair.high);
     memory.registers.set_zero_flag(false);
 }
 
 pub fn rlca(memory: &mut Memory, _value: Void) {
-    template_rlc!(memory, memory.registers.AF.as_pair.0);
+    template_rlc!(memory, memory.registers.AF.as_pair.high);
     memory.registers.set_zero_flag(false);
 }
 
 pub fn rra(memory: &mut Memory, _value: Void) {
-    template_rr!(memory, memory.registers.AF.as_pair.0);
+    template_rr!(memory, memory.registers.AF.as_pair.high);
     memory.registers.set_zero_flag(false);
 }
 
 pub fn rrca(memory: &mut Memory, _value: Void) {
-    template_rrc!(memory, memory.registers.AF.as_pair.0);
+    template_rrc!(memory, memory.registers.AF.as_pair.high);
     memory.registers.set_zero_flag(false);
 }
 
 pub fn rl_a(memory: &mut Memory, _value: Void) {
-    template_rl!(memory, memory.registers.AF.as_pair.0);
+    template_rl!(memory, memory.registers.AF.as_pair.high);
 }
 
 pub fn rlc_a(memory: &mut Memory, _value: Void) {
-    template_rlc!(memory, memory.registers.AF.as_pair.0);
+    template_rlc!(memory, memory.registers.AF.as_pair.high);
 }
 
 pub fn rr_a(memory: &mut Memory, _value: Void) {
-    template_rr!(memory, memory.registers.AF.as_pair.0);
+    template_rr!(memory, memory.registers.AF.as_pair.high);
 }
 
 pub fn rrc_a(memory: &mut Memory, _value: Void) {
-    template_rrc!(memory, memory.registers.AF.as_pair.0);
+    template_rrc!(memory, memory.registers.AF.as_pair.high);
 }
 
 //  ############# B #############
 
 pub fn rl_b(memory: &mut Memory, _value: Void) {
-    template_rl!(memory, memory.registers.BC.as_pair.0);
+    template_rl!(memory, memory.registers.BC.as_pair.high);
 }
 
 pub fn rlc_b(memory: &mut Memory, _value: Void) {
-    template_rlc!(memory, memory.registers.BC.as_pair.0);
+    template_rlc!(memory, memory.registers.BC.as_pair.high);
 }
 
 pub fn rr_b(memory: &mut Memory, _value: Void) {
-    template_rr!(memory, memory.registers.BC.as_pair.0);
+    template_rr!(memory, memory.registers.BC.as_pair.high);
 }
 
 pub fn rrc_b(memory: &mut Memory, _value: Void) {
-    template_rrc!(memory, memory.registers.BC.as_pair.0);
+    template_rrc!(memory, memory.registers.BC.as_pair.high);
 }
 
 //  ############# C #############
 
 pub fn rl_c(memory: &mut Memory, _value: Void) {
-    template_rl!(memory, memory.registers.BC.as_pair.1);
+    template_rl!(memory, memory.registers.BC.as_pair.low);
 }
 
 pub fn rlc_c(memory: &mut Memory, _value: Void) {
-    template_rlc!(memory, memory.registers.BC.as_pair.1);
+    template_rlc!(memory, memory.registers.BC.as_pair.low);
 }
 
 pub fn rr_c(memory: &mut Memory, _value: Void) {
-    template_rr!(memory, memory.registers.BC.as_pair.1);
+    template_rr!(memory, memory.registers.BC.as_pair.low);
 }
 
 pub fn rrc_c(memory: &mut Memory, _value: Void) {
-    template_rrc!(memory, memory.registers.BC.as_pair.1);
+    template_rrc!(memory, memory.registers.BC.as_pair.low);
 }
 
 //  ############# D #############
 
 pub fn rl_d(memory: &mut Memory, _value: Void) {
-    template_rl!(memory, memory.registers.DE.as_pair.0);
+    template_rl!(memory, memory.registers.DE.as_pair.high);
 }
 
 pub fn rlc_d(memory: &mut Memory, _value: Void) {
-    template_rlc!(memory, memory.registers.DE.as_pair.0);
+    template_rlc!(memory, memory.registers.DE.as_pair.high);
 }
 
 pub fn rr_d(memory: &mut Memory, _value: Void) {
-    template_rr!(memory, memory.registers.DE.as_pair.0);
+    template_rr!(memory, memory.registers.DE.as_pair.high);
 }
 
 pub fn rrc_d(memory: &mut Memory, _value: Void) {
-    template_rrc!(memory, memory.registers.DE.as_pair.0);
+    template_rrc!(memory, memory.registers.DE.as_pair.high);
 }
 
 //  ############# E #############
 
 pub fn rl_e(memory: &mut Memory, _value: Void) {
-    template_rl!(memory, memory.registers.DE.as_pair.1);
+    template_rl!(memory, memory.registers.DE.as_pair.low);
 }
 
 pub fn rlc_e(memory: &mut Memory, _value: Void) {
-    template_rlc!(memory, memory.registers.DE.as_pair.1);
+    template_rlc!(memory, memory.registers.DE.as_pair.low);
 }
 
 pub fn rr_e(memory: &mut Memory, _value: Void) {
-    template_rr!(memory, memory.registers.DE.as_pair.1);
+    template_rr!(memory, memory.registers.DE.as_pair.low);
 }
 
 pub fn rrc_e(memory: &mut Memory, _value: Void) {
-    template_rrc!(memory, memory.registers.DE.as_pair.1);
+    template_rrc!(memory, memory.registers.DE.as_pair.low);
 }
 
 //  ############# H #############
 
 pub fn rl_h(memory: &mut Memory, _value: Void) {
-    template_rl!(memory, memory.registers.HL.as_pair.0);
+    template_rl!(memory, memory.registers.HL.as_pair.high);
 }
 
 pub fn rlc_h(memory: &mut Memory, _value: Void) {
-    template_rlc!(memory, memory.registers.HL.as_pair.0);
+    template_rlc!(memory, memory.registers.HL.as_pair.high);
 }
 
 pub fn rr_h(memory: &mut Memory, _value: Void) {
-    template_rr!(memory, memory.registers.HL.as_pair.0);
+    template_rr!(memory, memory.registers.HL.as_pair.high);
 }
 
 pub fn rrc_h(memory: &mut Memory, _value: Void) {
-    template_rrc!(memory, memory.registers.HL.as_pair.0);
+    template_rrc!(memory, memory.registers.HL.as_pair.high);
 }
 
 //  ############# L #############
 
 pub fn rl_l(memory: &mut Memory, _value: Void) {
-    template_rl!(memory, memory.registers.HL.as_pair.1);
+    template_rl!(memory, memory.registers.HL.as_pair.low);
 }
 
 pub fn rlc_l(memory: &mut Memory, _value: Void) {
-    template_rlc!(memory, memory.registers.HL.as_pair.1);
+    template_rlc!(memory, memory.registers.HL.as_pair.low);
 }
 
 pub fn rr_l(memory: &mut Memory, _value: Void) {
-    template_rr!(memory, memory.registers.HL.as_pair.1);
+    template_rr!(memory, memory.registers.HL.as_pair.low);
 }
 
 pub fn rrc_l(memory: &mut Memory, _value: Void) {
-    template_rrc!(memory, memory.registers.HL.as_pair.1);
+    template_rrc!(memory, memory.registers.HL.as_pair.low);
 }
 
 //  ############# HL #############
@@ -390,7 +390,7 @@ pub fn rl_hl_addr(memory: &mut Memory, _value: Void) {
     let popped_value = get_bit(old_value, max_bit_index(old_value));
     let new_value = assign_bit(old_value << 1, 0, old_carry);
 
-    log!("OPERATION", format!("{:#0width$b} + carry: {} => {:#0width$b} + carry: {}", old_value, u8::from(old_carry), new_value, u8::from(popped_value), width = bit_size(old_value) + 2));
+    log!(Trace, "CPU", format!("{:#0width$b} + carry: {} => {:#0width$b} + carry: {}", old_value, u8::from(old_carry), new_value, u8::from(popped_value), width = bit_size(old_value) + 2));
 
     memory.write_far_addr(hl_value, new_value);
 
@@ -407,7 +407,7 @@ pub fn rlc_hl_addr(memory: &mut Memory, _value: Void) {
     // Left shift and put back top bit in the lowest bit
     let new_value = assign_bit(old_value << 1, 0, popped_value);
 
-    log!("OPERATION", format!("{:#0width$b} => {:#0width$b} + carry: {}", old_value, new_value, u8::from(popped_value), width = bit_size(old_value) + 2));
+    log!(Trace, "CPU", format!("{:#0width$b} => {:#0width$b} + carry: {}", old_value, new_value, u8::from(popped_value), width = bit_size(old_value) + 2));
 
     memory.write_far_addr(hl_value, new_value);
 
@@ -424,7 +424,7 @@ pub fn rr_hl_addr(memory: &mut Memory, _value: Void) {
     let popped_value = get_bit(old_value, 0);
     let new_value = assign_bit(old_value >> 1, max_bit_index(old_value), old_carry);
 
-    log!("OPERATION", format!("{:#0width$b} + carry: {} => {:#0width$b} + carry: {}", old_value, u8::from(old_carry), new_value, u8::from(popped_value), width = bit_size(old_value) + 2));
+    log!(Trace, "CPU", format!("{:#0width$b} + carry: {} => {:#0width$b} + carry: {}", old_value, u8::from(old_carry), new_value, u8::from(popped_value), width = bit_size(old_value) + 2));
 
     memory.write_far_addr(hl_value, new_value);
 
@@ -440,7 +440,7 @@ pub fn rrc_hl_addr(memory: &mut Memory, _value: Void) {
     let popped_value = get_bit(old_value, 0);
     let new_value = assign_bit(old_value >> 1, max_bit_index(old_value), popped_value);
 
-    log!("OPERATION", format!("{:#0width$b} => {:#0width$b} + carry: {}", old_value, new_value, u8::from(popped_value), width = bit_size(old_value) + 2));
+    log!(Trace, "CPU", format!("{:#0width$b} => {:#0width$b} + carry: {}", old_value, new_value, u8::from(popped_value), width = bit_size(old_value) + 2));
 
     // Left shift and put back top bit in the lowest bit
     memory.write_far_addr(hl_value, new_value);
@@ -458,99 +458,99 @@ pub fn rrc_hl_addr(memory: &mut Memory, _value: Void) {
 //  ############# A #############
 
 pub fn sla_a(memory: &mut Memory, _value: Void) {
-    template_sla!(memory, memory.registers.AF.as_pair.0);
+    template_sla!(memory, memory.registers.AF.as_pair.high);
 }
 
 pub fn sra_a(memory: &mut Memory, _value: Void) {
-    template_sra!(memory, memory.registers.AF.as_pair.0);
+    template_sra!(memory, memory.registers.AF.as_pair.high);
 }
 
 pub fn srl_a(memory: &mut Memory, _value: Void) {
-    template_srl!(memory, memory.registers.AF.as_pair.0);
+    template_srl!(memory, memory.registers.AF.as_pair.high);
 }
 
 //  ############# B #############
 
 pub fn sla_b(memory: &mut Memory, _value: Void) {
-    template_sla!(memory, memory.registers.BC.as_pair.0);
+    template_sla!(memory, memory.registers.BC.as_pair.high);
 }
 
 pub fn sra_b(memory: &mut Memory, _value: Void) {
-    template_sra!(memory, memory.registers.BC.as_pair.0);
+    template_sra!(memory, memory.registers.BC.as_pair.high);
 }
 
 pub fn srl_b(memory: &mut Memory, _value: Void) {
-    template_srl!(memory, memory.registers.BC.as_pair.0);
+    template_srl!(memory, memory.registers.BC.as_pair.high);
 }
 
 //  ############# C #############
 
 pub fn sla_c(memory: &mut Memory, _value: Void) {
-    template_sla!(memory, memory.registers.BC.as_pair.1);
+    template_sla!(memory, memory.registers.BC.as_pair.low);
 }
 
 pub fn sra_c(memory: &mut Memory, _value: Void) {
-    template_sra!(memory, memory.registers.BC.as_pair.1);
+    template_sra!(memory, memory.registers.BC.as_pair.low);
 }
 
 pub fn srl_c(memory: &mut Memory, _value: Void) {
-    template_srl!(memory, memory.registers.BC.as_pair.1);
+    template_srl!(memory, memory.registers.BC.as_pair.low);
 }
 
 //  ############# D #############
 
 pub fn sla_d(memory: &mut Memory, _value: Void) {
-    template_sla!(memory, memory.registers.DE.as_pair.0);
+    template_sla!(memory, memory.registers.DE.as_pair.high);
 }
 
 pub fn sra_d(memory: &mut Memory, _value: Void) {
-    template_sra!(memory, memory.registers.DE.as_pair.0);
+    template_sra!(memory, memory.registers.DE.as_pair.high);
 }
 
 pub fn srl_d(memory: &mut Memory, _value: Void) {
-    template_srl!(memory, memory.registers.DE.as_pair.0);
+    template_srl!(memory, memory.registers.DE.as_pair.high);
 }
 
 //  ############# E #############
 
 pub fn sla_e(memory: &mut Memory, _value: Void) {
-    template_sla!(memory, memory.registers.DE.as_pair.1);
+    template_sla!(memory, memory.registers.DE.as_pair.low);
 }
 
 pub fn sra_e(memory: &mut Memory, _value: Void) {
-    template_sra!(memory, memory.registers.DE.as_pair.1);
+    template_sra!(memory, memory.registers.DE.as_pair.low);
 }
 
 pub fn srl_e(memory: &mut Memory, _value: Void) {
-    template_srl!(memory, memory.registers.DE.as_pair.1);
+    template_srl!(memory, memory.registers.DE.as_pair.low);
 }
 
 //  ############# H #############
 
 pub fn sla_h(memory: &mut Memory, _value: Void) {
-    template_sla!(memory, memory.registers.HL.as_pair.0);
+    template_sla!(memory, memory.registers.HL.as_pair.high);
 }
 
 pub fn sra_h(memory: &mut Memory, _value: Void) {
-    template_sra!(memory, memory.registers.HL.as_pair.0);
+    template_sra!(memory, memory.registers.HL.as_pair.high);
 }
 
 pub fn srl_h(memory: &mut Memory, _value: Void) {
-    template_srl!(memory, memory.registers.HL.as_pair.0);
+    template_srl!(memory, memory.registers.HL.as_pair.high);
 }
 
 //  ############# L #############
 
 pub fn sla_l(memory: &mut Memory, _value: Void) {
-    template_sla!(memory, memory.registers.HL.as_pair.1);
+    template_sla!(memory, memory.registers.HL.as_pair.low);
 }
 
 pub fn sra_l(memory: &mut Memory, _value: Void) {
-    template_sra!(memory, memory.registers.HL.as_pair.1);
+    template_sra!(memory, memory.registers.HL.as_pair.low);
 }
 
 pub fn srl_l(memory: &mut Memory, _value: Void) {
-    template_srl!(memory, memory.registers.HL.as_pair.1);
+    template_srl!(memory, memory.registers.HL.as_pair.low);
 }
 
 //  ############# HL #############
@@ -561,7 +561,7 @@ pub fn sla_hl_addr(memory: &mut Memory, _value: Void) {
     let popped_value = get_bit(old_value, max_bit_index(old_value));
     let new_value = old_value << 1;
 
-    log!("OPERATION", format!("{:#0width$b} => {:#0width$b} + carry: {}", old_value, new_value, u8::from(popped_value), width = bit_size(old_value) + 2));
+    log!(Trace, "CPU", format!("{:#0width$b} => {:#0width$b} + carry: {}", old_value, new_value, u8::from(popped_value), width = bit_size(old_value) + 2));
 
     memory.write_far_addr(hl_value, new_value);
 
@@ -578,7 +578,7 @@ pub fn sra_hl_addr(memory: &mut Memory, _value: Void) {
     let popped_value = get_bit(old_value, 0);
     let new_value = assign_bit(old_value >> 1, max_bit_index(old_value), top_duplicate);
 
-    log!("OPERATION", format!("{:#0width$b} => {:#0width$b} + carry: {}", old_value, new_value, u8::from(popped_value), width = bit_size(old_value) + 2));
+    log!(Trace, "CPU", format!("{:#0width$b} => {:#0width$b} + carry: {}", old_value, new_value, u8::from(popped_value), width = bit_size(old_value) + 2));
 
     memory.write_far_addr(hl_value, new_value);
 
@@ -594,7 +594,7 @@ pub fn srl_hl_addr(memory: &mut Memory, _value: Void) {
     let popped_value = get_bit(old_value, 0);
     let new_value = old_value >> 1;
 
-    log!("OPERATION", format!("{:#0width$b} => {:#0width$b} + carry: {}", old_value, new_value, u8::from(popped_value), width = bit_size(old_value) + 2));
+    log!(Trace, "CPU", format!("{:#0width$b} => {:#0width$b} + carry: {}", old_value, new_value, u8::from(popped_value), width = bit_size(old_value) + 2));
 
     memory.write_far_addr(hl_value, new_value);
 
@@ -609,31 +609,31 @@ pub fn srl_hl_addr(memory: &mut Memory, _value: Void) {
 //  #############################
 
 pub fn swap_a(memory: &mut Memory, _value: Void) {
-    template_swap!(memory, memory.registers.AF.as_pair.0);
+    template_swap!(memory, memory.registers.AF.as_pair.high);
 }
 
 pub fn swap_b(memory: &mut Memory, _value: Void) {
-    template_swap!(memory, memory.registers.BC.as_pair.0);
+    template_swap!(memory, memory.registers.BC.as_pair.high);
 }
 
 pub fn swap_c(memory: &mut Memory, _value: Void) {
-    template_swap!(memory, memory.registers.BC.as_pair.1);
+    template_swap!(memory, memory.registers.BC.as_pair.low);
 }
 
 pub fn swap_d(memory: &mut Memory, _value: Void) {
-    template_swap!(memory, memory.registers.DE.as_pair.0);
+    template_swap!(memory, memory.registers.DE.as_pair.high);
 }
 
 pub fn swap_e(memory: &mut Memory, _value: Void) {
-    template_swap!(memory, memory.registers.DE.as_pair.1);
+    template_swap!(memory, memory.registers.DE.as_pair.low);
 }
 
 pub fn swap_h(memory: &mut Memory, _value: Void) {
-    template_swap!(memory, memory.registers.HL.as_pair.0);
+    template_swap!(memory, memory.registers.HL.as_pair.high);
 }
 
 pub fn swap_l(memory: &mut Memory, _value: Void) {
-    template_swap!(memory, memory.registers.HL.as_pair.1);
+    template_swap!(memory, memory.registers.HL.as_pair.low);
 }
 
 pub fn swap_hl_addr(memory: &mut Memory, _value: Void) {
@@ -647,7 +647,7 @@ pub fn swap_hl_addr(memory: &mut Memory, _value: Void) {
 
     let new_value = (low << bit_size_half) + high;
 
-    log!("OPERATION", format!("{:#0width$b}=> {:#0width$b}", old_value, new_value, width = bit_size(old_value) + 2));
+    log!(Trace, "CPU", format!("{:#0width$b}=> {:#0width$b}", old_value, new_value, width = bit_size(old_value) + 2));
 
     memory.write_far_addr(hl_value, new_value);
 
@@ -661,239 +661,239 @@ pub fn swap_hl_addr(memory: &mut Memory, _value: Void) {
 //  ############# A #############
 
 pub fn bit_0_a(memory: &mut Memory, _value: Void) {
-    template_bit!(memory, memory.registers.AF.as_pair.0, 0);
+    template_bit!(memory, memory.registers.AF.as_pair.high, 0);
 }
 
 pub fn bit_1_a(memory: &mut Memory, _value: Void) {
-    template_bit!(memory, memory.registers.AF.as_pair.0, 1);
+    template_bit!(memory, memory.registers.AF.as_pair.high, 1);
 }
 
 pub fn bit_2_a(memory: &mut Memory, _value: Void) {
-    template_bit!(memory, memory.registers.AF.as_pair.0, 2);
+    template_bit!(memory, memory.registers.AF.as_pair.high, 2);
 }
 
 pub fn bit_3_a(memory: &mut Memory, _value: Void) {
-    template_bit!(memory, memory.registers.AF.as_pair.0, 3);
+    template_bit!(memory, memory.registers.AF.as_pair.high, 3);
 }
 
 pub fn bit_4_a(memory: &mut Memory, _value: Void) {
-    template_bit!(memory, memory.registers.AF.as_pair.0, 4);
+    template_bit!(memory, memory.registers.AF.as_pair.high, 4);
 }
 
 pub fn bit_5_a(memory: &mut Memory, _value: Void) {
-    template_bit!(memory, memory.registers.AF.as_pair.0, 5);
+    template_bit!(memory, memory.registers.AF.as_pair.high, 5);
 }
 
 pub fn bit_6_a(memory: &mut Memory, _value: Void) {
-    template_bit!(memory, memory.registers.AF.as_pair.0, 6);
+    template_bit!(memory, memory.registers.AF.as_pair.high, 6);
 }
 
 pub fn bit_7_a(memory: &mut Memory, _value: Void) {
-    template_bit!(memory, memory.registers.AF.as_pair.0, 7);
+    template_bit!(memory, memory.registers.AF.as_pair.high, 7);
 }
 
 //  ############# B #############
 
 pub fn bit_0_b(memory: &mut Memory, _value: Void) {
-    template_bit!(memory, memory.registers.BC.as_pair.0, 0);
+    template_bit!(memory, memory.registers.BC.as_pair.high, 0);
 }
 
 pub fn bit_1_b(memory: &mut Memory, _value: Void) {
-    template_bit!(memory, memory.registers.BC.as_pair.0, 1);
+    template_bit!(memory, memory.registers.BC.as_pair.high, 1);
 }
 
 pub fn bit_2_b(memory: &mut Memory, _value: Void) {
-    template_bit!(memory, memory.registers.BC.as_pair.0, 2);
+    template_bit!(memory, memory.registers.BC.as_pair.high, 2);
 }
 
 pub fn bit_3_b(memory: &mut Memory, _value: Void) {
-    template_bit!(memory, memory.registers.BC.as_pair.0, 3);
+    template_bit!(memory, memory.registers.BC.as_pair.high, 3);
 }
 
 pub fn bit_4_b(memory: &mut Memory, _value: Void) {
-    template_bit!(memory, memory.registers.BC.as_pair.0, 4);
+    template_bit!(memory, memory.registers.BC.as_pair.high, 4);
 }
 
 pub fn bit_5_b(memory: &mut Memory, _value: Void) {
-    template_bit!(memory, memory.registers.BC.as_pair.0, 5);
+    template_bit!(memory, memory.registers.BC.as_pair.high, 5);
 }
 
 pub fn bit_6_b(memory: &mut Memory, _value: Void) {
-    template_bit!(memory, memory.registers.BC.as_pair.0, 6);
+    template_bit!(memory, memory.registers.BC.as_pair.high, 6);
 }
 
 pub fn bit_7_b(memory: &mut Memory, _value: Void) {
-    template_bit!(memory, memory.registers.BC.as_pair.0, 7);
+    template_bit!(memory, memory.registers.BC.as_pair.high, 7);
 }
 
 //  ############# C #############
 
 pub fn bit_0_c(memory: &mut Memory, _value: Void) {
-    template_bit!(memory, memory.registers.BC.as_pair.1, 0);
+    template_bit!(memory, memory.registers.BC.as_pair.low, 0);
 }
 
 pub fn bit_1_c(memory: &mut Memory, _value: Void) {
-    template_bit!(memory, memory.registers.BC.as_pair.1, 1);
+    template_bit!(memory, memory.registers.BC.as_pair.low, 1);
 }
 
 pub fn bit_2_c(memory: &mut Memory, _value: Void) {
-    template_bit!(memory, memory.registers.BC.as_pair.1, 2);
+    template_bit!(memory, memory.registers.BC.as_pair.low, 2);
 }
 
 pub fn bit_3_c(memory: &mut Memory, _value: Void) {
-    template_bit!(memory, memory.registers.BC.as_pair.1, 3);
+    template_bit!(memory, memory.registers.BC.as_pair.low, 3);
 }
 
 pub fn bit_4_c(memory: &mut Memory, _value: Void) {
-    template_bit!(memory, memory.registers.BC.as_pair.1, 4);
+    template_bit!(memory, memory.registers.BC.as_pair.low, 4);
 }
 
 pub fn bit_5_c(memory: &mut Memory, _value: Void) {
-    template_bit!(memory, memory.registers.BC.as_pair.1, 5);
+    template_bit!(memory, memory.registers.BC.as_pair.low, 5);
 }
 
 pub fn bit_6_c(memory: &mut Memory, _value: Void) {
-    template_bit!(memory, memory.registers.BC.as_pair.1, 6);
+    template_bit!(memory, memory.registers.BC.as_pair.low, 6);
 }
 
 pub fn bit_7_c(memory: &mut Memory, _value: Void) {
-    template_bit!(memory, memory.registers.BC.as_pair.1, 7);
+    template_bit!(memory, memory.registers.BC.as_pair.low, 7);
 }
 
 //  ############# D #############
 
 pub fn bit_0_d(memory: &mut Memory, _value: Void) {
-    template_bit!(memory, memory.registers.DE.as_pair.0, 0);
+    template_bit!(memory, memory.registers.DE.as_pair.high, 0);
 }
 
 pub fn bit_1_d(memory: &mut Memory, _value: Void) {
-    template_bit!(memory, memory.registers.DE.as_pair.0, 1);
+    template_bit!(memory, memory.registers.DE.as_pair.high, 1);
 }
 
 pub fn bit_2_d(memory: &mut Memory, _value: Void) {
-    template_bit!(memory, memory.registers.DE.as_pair.0, 2);
+    template_bit!(memory, memory.registers.DE.as_pair.high, 2);
 }
 
 pub fn bit_3_d(memory: &mut Memory, _value: Void) {
-    template_bit!(memory, memory.registers.DE.as_pair.0, 3);
+    template_bit!(memory, memory.registers.DE.as_pair.high, 3);
 }
 
 pub fn bit_4_d(memory: &mut Memory, _value: Void) {
-    template_bit!(memory, memory.registers.DE.as_pair.0, 4);
+    template_bit!(memory, memory.registers.DE.as_pair.high, 4);
 }
 
 pub fn bit_5_d(memory: &mut Memory, _value: Void) {
-    template_bit!(memory, memory.registers.DE.as_pair.0, 5);
+    template_bit!(memory, memory.registers.DE.as_pair.high, 5);
 }
 
 pub fn bit_6_d(memory: &mut Memory, _value: Void) {
-    template_bit!(memory, memory.registers.DE.as_pair.0, 6);
+    template_bit!(memory, memory.registers.DE.as_pair.high, 6);
 }
 
 pub fn bit_7_d(memory: &mut Memory, _value: Void) {
-    template_bit!(memory, memory.registers.DE.as_pair.0, 7);
+    template_bit!(memory, memory.registers.DE.as_pair.high, 7);
 }
 
 //  ############# E #############
 
 pub fn bit_0_e(memory: &mut Memory, _value: Void) {
-    template_bit!(memory, memory.registers.DE.as_pair.1, 0);
+    template_bit!(memory, memory.registers.DE.as_pair.low, 0);
 }
 
 pub fn bit_1_e(memory: &mut Memory, _value: Void) {
-    template_bit!(memory, memory.registers.DE.as_pair.1, 1);
+    template_bit!(memory, memory.registers.DE.as_pair.low, 1);
 }
 
 pub fn bit_2_e(memory: &mut Memory, _value: Void) {
-    template_bit!(memory, memory.registers.DE.as_pair.1, 2);
+    template_bit!(memory, memory.registers.DE.as_pair.low, 2);
 }
 
 pub fn bit_3_e(memory: &mut Memory, _value: Void) {
-    template_bit!(memory, memory.registers.DE.as_pair.1, 3);
+    template_bit!(memory, memory.registers.DE.as_pair.low, 3);
 }
 
 pub fn bit_4_e(memory: &mut Memory, _value: Void) {
-    template_bit!(memory, memory.registers.DE.as_pair.1, 4);
+    template_bit!(memory, memory.registers.DE.as_pair.low, 4);
 }
 
 pub fn bit_5_e(memory: &mut Memory, _value: Void) {
-    template_bit!(memory, memory.registers.DE.as_pair.1, 5);
+    template_bit!(memory, memory.registers.DE.as_pair.low, 5);
 }
 
 pub fn bit_6_e(memory: &mut Memory, _value: Void) {
-    template_bit!(memory, memory.registers.DE.as_pair.1, 6);
+    template_bit!(memory, memory.registers.DE.as_pair.low, 6);
 }
 
 pub fn bit_7_e(memory: &mut Memory, _value: Void) {
-    template_bit!(memory, memory.registers.DE.as_pair.1, 7);
+    template_bit!(memory, memory.registers.DE.as_pair.low, 7);
 }
 
 //  ############# H #############
 
 pub fn bit_0_h(memory: &mut Memory, _value: Void) {
-    template_bit!(memory, memory.registers.HL.as_pair.0, 0);
+    template_bit!(memory, memory.registers.HL.as_pair.high, 0);
 }
 
 pub fn bit_1_h(memory: &mut Memory, _value: Void) {
-    template_bit!(memory, memory.registers.HL.as_pair.0, 1);
+    template_bit!(memory, memory.registers.HL.as_pair.high, 1);
 }
 
 pub fn bit_2_h(memory: &mut Memory, _value: Void) {
-    template_bit!(memory, memory.registers.HL.as_pair.0, 2);
+    template_bit!(memory, memory.registers.HL.as_pair.high, 2);
 }
 
 pub fn bit_3_h(memory: &mut Memory, _value: Void) {
-    template_bit!(memory, memory.registers.HL.as_pair.0, 3);
+    template_bit!(memory, memory.registers.HL.as_pair.high, 3);
 }
 
 pub fn bit_4_h(memory: &mut Memory, _value: Void) {
-    template_bit!(memory, memory.registers.HL.as_pair.0, 4);
+    template_bit!(memory, memory.registers.HL.as_pair.high, 4);
 }
 
 pub fn bit_5_h(memory: &mut Memory, _value: Void) {
-    template_bit!(memory, memory.registers.HL.as_pair.0, 5);
+    template_bit!(memory, memory.registers.HL.as_pair.high, 5);
 }
 
 pub fn bit_6_h(memory: &mut Memory, _value: Void) {
-    template_bit!(memory, memory.registers.HL.as_pair.0, 6);
+    template_bit!(memory, memory.registers.HL.as_pair.high, 6);
 }
 
 pub fn bit_7_h(memory: &mut Memory, _value: Void) {
-    template_bit!(memory, memory.registers.HL.as_pair.0, 7);
+    template_bit!(memory, memory.registers.HL.as_pair.high, 7);
 }
 
 //  ############# L #############
 
 pub fn bit_0_l(memory: &mut Memory, _value: Void) {
-    template_bit!(memory, memory.registers.HL.as_pair.1, 0);
+    template_bit!(memory, memory.registers.HL.as_pair.low, 0);
 }
 
 pub fn bit_1_l(memory: &mut Memory, _value: Void) {
-    template_bit!(memory, memory.registers.HL.as_pair.1, 1);
+    template_bit!(memory, memory.registers.HL.as_pair.low, 1);
 }
 
 pub fn bit_2_l(memory: &mut Memory, _value: Void) {
-    template_bit!(memory, memory.registers.HL.as_pair.1, 2);
+    template_bit!(memory, memory.registers.HL.as_pair.low, 2);
 }
 
 pub fn bit_3_l(memory: &mut Memory, _value: Void) {
-    template_bit!(memory, memory.registers.HL.as_pair.1, 3);
+    template_bit!(memory, memory.registers.HL.as_pair.low, 3);
 }
 
 pub fn bit_4_l(memory: &mut Memory, _value: Void) {
-    template_bit!(memory, memory.registers.HL.as_pair.1, 4);
+    template_bit!(memory, memory.registers.HL.as_pair.low, 4);
 }
 
 pub fn bit_5_l(memory: &mut Memory, _value: Void) {
-    template_bit!(memory, memory.registers.HL.as_pair.1, 5);
+    template_bit!(memory, memory.registers.HL.as_pair.low, 5);
 }
 
 pub fn bit_6_l(memory: &mut Memory, _value: Void) {
-    template_bit!(memory, memory.registers.HL.as_pair.1, 6);
+    template_bit!(memory, memory.registers.HL.as_pair.low, 6);
 }
 
 pub fn bit_7_l(memory: &mut Memory, _value: Void) {
-    template_bit!(memory, memory.registers.HL.as_pair.1, 7);
+    template_bit!(memory, memory.registers.HL.as_pair.low, 7);
 }
 
 //  ############# HL #############
@@ -937,239 +937,239 @@ pub fn bit_7_hl_addr(memory: &mut Memory, _value: Void) {
 //  ############# A #############
 
 pub fn res_0_a(memory: &mut Memory, _value: Void) {
-    template_res!(memory.registers.AF.as_pair.0, 0);
+    template_res!(memory.registers.AF.as_pair.high, 0);
 }
 
 pub fn res_1_a(memory: &mut Memory, _value: Void) {
-    template_res!(memory.registers.AF.as_pair.0, 1);
+    template_res!(memory.registers.AF.as_pair.high, 1);
 }
 
 pub fn res_2_a(memory: &mut Memory, _value: Void) {
-    template_res!(memory.registers.AF.as_pair.0, 2);
+    template_res!(memory.registers.AF.as_pair.high, 2);
 }
 
 pub fn res_3_a(memory: &mut Memory, _value: Void) {
-    template_res!(memory.registers.AF.as_pair.0, 3);
+    template_res!(memory.registers.AF.as_pair.high, 3);
 }
 
 pub fn res_4_a(memory: &mut Memory, _value: Void) {
-    template_res!(memory.registers.AF.as_pair.0, 4);
+    template_res!(memory.registers.AF.as_pair.high, 4);
 }
 
 pub fn res_5_a(memory: &mut Memory, _value: Void) {
-    template_res!(memory.registers.AF.as_pair.0, 5);
+    template_res!(memory.registers.AF.as_pair.high, 5);
 }
 
 pub fn res_6_a(memory: &mut Memory, _value: Void) {
-    template_res!(memory.registers.AF.as_pair.0, 6);
+    template_res!(memory.registers.AF.as_pair.high, 6);
 }
 
 pub fn res_7_a(memory: &mut Memory, _value: Void) {
-    template_res!(memory.registers.AF.as_pair.0, 7);
+    template_res!(memory.registers.AF.as_pair.high, 7);
 }
 
 //  ############# B #############
 
 pub fn res_0_b(memory: &mut Memory, _value: Void) {
-    template_res!(memory.registers.BC.as_pair.0, 0);
+    template_res!(memory.registers.BC.as_pair.high, 0);
 }
 
 pub fn res_1_b(memory: &mut Memory, _value: Void) {
-    template_res!(memory.registers.BC.as_pair.0, 1);
+    template_res!(memory.registers.BC.as_pair.high, 1);
 }
 
 pub fn res_2_b(memory: &mut Memory, _value: Void) {
-    template_res!(memory.registers.BC.as_pair.0, 2);
+    template_res!(memory.registers.BC.as_pair.high, 2);
 }
 
 pub fn res_3_b(memory: &mut Memory, _value: Void) {
-    template_res!(memory.registers.BC.as_pair.0, 3);
+    template_res!(memory.registers.BC.as_pair.high, 3);
 }
 
 pub fn res_4_b(memory: &mut Memory, _value: Void) {
-    template_res!(memory.registers.BC.as_pair.0, 4);
+    template_res!(memory.registers.BC.as_pair.high, 4);
 }
 
 pub fn res_5_b(memory: &mut Memory, _value: Void) {
-    template_res!(memory.registers.BC.as_pair.0, 5);
+    template_res!(memory.registers.BC.as_pair.high, 5);
 }
 
 pub fn res_6_b(memory: &mut Memory, _value: Void) {
-    template_res!(memory.registers.BC.as_pair.0, 6);
+    template_res!(memory.registers.BC.as_pair.high, 6);
 }
 
 pub fn res_7_b(memory: &mut Memory, _value: Void) {
-    template_res!(memory.registers.BC.as_pair.0, 7);
+    template_res!(memory.registers.BC.as_pair.high, 7);
 }
 
 //  ############# C #############
 
 pub fn res_0_c(memory: &mut Memory, _value: Void) {
-    template_res!(memory.registers.BC.as_pair.1, 0);
+    template_res!(memory.registers.BC.as_pair.low, 0);
 }
 
 pub fn res_1_c(memory: &mut Memory, _value: Void) {
-    template_res!(memory.registers.BC.as_pair.1, 1);
+    template_res!(memory.registers.BC.as_pair.low, 1);
 }
 
 pub fn res_2_c(memory: &mut Memory, _value: Void) {
-    template_res!(memory.registers.BC.as_pair.1, 2);
+    template_res!(memory.registers.BC.as_pair.low, 2);
 }
 
 pub fn res_3_c(memory: &mut Memory, _value: Void) {
-    template_res!(memory.registers.BC.as_pair.1, 3);
+    template_res!(memory.registers.BC.as_pair.low, 3);
 }
 
 pub fn res_4_c(memory: &mut Memory, _value: Void) {
-    template_res!(memory.registers.BC.as_pair.1, 4);
+    template_res!(memory.registers.BC.as_pair.low, 4);
 }
 
 pub fn res_5_c(memory: &mut Memory, _value: Void) {
-    template_res!(memory.registers.BC.as_pair.1, 5);
+    template_res!(memory.registers.BC.as_pair.low, 5);
 }
 
 pub fn res_6_c(memory: &mut Memory, _value: Void) {
-    template_res!(memory.registers.BC.as_pair.1, 6);
+    template_res!(memory.registers.BC.as_pair.low, 6);
 }
 
 pub fn res_7_c(memory: &mut Memory, _value: Void) {
-    template_res!(memory.registers.BC.as_pair.1, 7);
+    template_res!(memory.registers.BC.as_pair.low, 7);
 }
 
 //  ############# D #############
 
 pub fn res_0_d(memory: &mut Memory, _value: Void) {
-    template_res!(memory.registers.DE.as_pair.0, 0);
+    template_res!(memory.registers.DE.as_pair.high, 0);
 }
 
 pub fn res_1_d(memory: &mut Memory, _value: Void) {
-    template_res!(memory.registers.DE.as_pair.0, 1);
+    template_res!(memory.registers.DE.as_pair.high, 1);
 }
 
 pub fn res_2_d(memory: &mut Memory, _value: Void) {
-    template_res!(memory.registers.DE.as_pair.0, 2);
+    template_res!(memory.registers.DE.as_pair.high, 2);
 }
 
 pub fn res_3_d(memory: &mut Memory, _value: Void) {
-    template_res!(memory.registers.DE.as_pair.0, 3);
+    template_res!(memory.registers.DE.as_pair.high, 3);
 }
 
 pub fn res_4_d(memory: &mut Memory, _value: Void) {
-    template_res!(memory.registers.DE.as_pair.0, 4);
+    template_res!(memory.registers.DE.as_pair.high, 4);
 }
 
 pub fn res_5_d(memory: &mut Memory, _value: Void) {
-    template_res!(memory.registers.DE.as_pair.0, 5);
+    template_res!(memory.registers.DE.as_pair.high, 5);
 }
 
 pub fn res_6_d(memory: &mut Memory, _value: Void) {
-    template_res!(memory.registers.DE.as_pair.0, 6);
+    template_res!(memory.registers.DE.as_pair.high, 6);
 }
 
 pub fn res_7_d(memory: &mut Memory, _value: Void) {
-    template_res!(memory.registers.DE.as_pair.0, 7);
+    template_res!(memory.registers.DE.as_pair.high, 7);
 }
 
 //  ############# E #############
 
 pub fn res_0_e(memory: &mut Memory, _value: Void) {
-    template_res!(memory.registers.DE.as_pair.1, 0);
+    template_res!(memory.registers.DE.as_pair.low, 0);
 }
 
 pub fn res_1_e(memory: &mut Memory, _value: Void) {
-    template_res!(memory.registers.DE.as_pair.1, 1);
+    template_res!(memory.registers.DE.as_pair.low, 1);
 }
 
 pub fn res_2_e(memory: &mut Memory, _value: Void) {
-    template_res!(memory.registers.DE.as_pair.1, 2);
+    template_res!(memory.registers.DE.as_pair.low, 2);
 }
 
 pub fn res_3_e(memory: &mut Memory, _value: Void) {
-    template_res!(memory.registers.DE.as_pair.1, 3);
+    template_res!(memory.registers.DE.as_pair.low, 3);
 }
 
 pub fn res_4_e(memory: &mut Memory, _value: Void) {
-    template_res!(memory.registers.DE.as_pair.1, 4);
+    template_res!(memory.registers.DE.as_pair.low, 4);
 }
 
 pub fn res_5_e(memory: &mut Memory, _value: Void) {
-    template_res!(memory.registers.DE.as_pair.1, 5);
+    template_res!(memory.registers.DE.as_pair.low, 5);
 }
 
 pub fn res_6_e(memory: &mut Memory, _value: Void) {
-    template_res!(memory.registers.DE.as_pair.1, 6);
+    template_res!(memory.registers.DE.as_pair.low, 6);
 }
 
 pub fn res_7_e(memory: &mut Memory, _value: Void) {
-    template_res!(memory.registers.DE.as_pair.1, 7);
+    template_res!(memory.registers.DE.as_pair.low, 7);
 }
 
 //  ############# H #############
 
 pub fn res_0_h(memory: &mut Memory, _value: Void) {
-    template_res!(memory.registers.HL.as_pair.0, 0);
+    template_res!(memory.registers.HL.as_pair.high, 0);
 }
 
 pub fn res_1_h(memory: &mut Memory, _value: Void) {
-    template_res!(memory.registers.HL.as_pair.0, 1);
+    template_res!(memory.registers.HL.as_pair.high, 1);
 }
 
 pub fn res_2_h(memory: &mut Memory, _value: Void) {
-    template_res!(memory.registers.HL.as_pair.0, 2);
+    template_res!(memory.registers.HL.as_pair.high, 2);
 }
 
 pub fn res_3_h(memory: &mut Memory, _value: Void) {
-    template_res!(memory.registers.HL.as_pair.0, 3);
+    template_res!(memory.registers.HL.as_pair.high, 3);
 }
 
 pub fn res_4_h(memory: &mut Memory, _value: Void) {
-    template_res!(memory.registers.HL.as_pair.0, 4);
+    template_res!(memory.registers.HL.as_pair.high, 4);
 }
 
 pub fn res_5_h(memory: &mut Memory, _value: Void) {
-    template_res!(memory.registers.HL.as_pair.0, 5);
+    template_res!(memory.registers.HL.as_pair.high, 5);
 }
 
 pub fn res_6_h(memory: &mut Memory, _value: Void) {
-    template_res!(memory.registers.HL.as_pair.0, 6);
+    template_res!(memory.registers.HL.as_pair.high, 6);
 }
 
 pub fn res_7_h(memory: &mut Memory, _value: Void) {
-    template_res!(memory.registers.HL.as_pair.0, 7);
+    template_res!(memory.registers.HL.as_pair.high, 7);
 }
 
 //  ############# L #############
 
 pub fn res_0_l(memory: &mut Memory, _value: Void) {
-    template_res!(memory.registers.HL.as_pair.1, 0);
+    template_res!(memory.registers.HL.as_pair.low, 0);
 }
 
 pub fn res_1_l(memory: &mut Memory, _value: Void) {
-    template_res!(memory.registers.HL.as_pair.1, 1);
+    template_res!(memory.registers.HL.as_pair.low, 1);
 }
 
 pub fn res_2_l(memory: &mut Memory, _value: Void) {
-    template_res!(memory.registers.HL.as_pair.1, 2);
+    template_res!(memory.registers.HL.as_pair.low, 2);
 }
 
 pub fn res_3_l(memory: &mut Memory, _value: Void) {
-    template_res!(memory.registers.HL.as_pair.1, 3);
+    template_res!(memory.registers.HL.as_pair.low, 3);
 }
 
 pub fn res_4_l(memory: &mut Memory, _value: Void) {
-    template_res!(memory.registers.HL.as_pair.1, 4);
+    template_res!(memory.registers.HL.as_pair.low, 4);
 }
 
 pub fn res_5_l(memory: &mut Memory, _value: Void) {
-    template_res!(memory.registers.HL.as_pair.1, 5);
+    template_res!(memory.registers.HL.as_pair.low, 5);
 }
 
 pub fn res_6_l(memory: &mut Memory, _value: Void) {
-    template_res!(memory.registers.HL.as_pair.1, 6);
+    template_res!(memory.registers.HL.as_pair.low, 6);
 }
 
 pub fn res_7_l(memory: &mut Memory, _value: Void) {
-    template_res!(memory.registers.HL.as_pair.1, 7);
+    template_res!(memory.registers.HL.as_pair.low, 7);
 }
 
 //  ############# HL #############
@@ -1213,239 +1213,239 @@ pub fn res_7_hl_addr(memory: &mut Memory, _value: Void) {
 //  ############# A #############
 
 pub fn set_0_a(memory: &mut Memory, _value: Void) {
-    template_set!(memory.registers.AF.as_pair.0, 0);
+    template_set!(memory.registers.AF.as_pair.high, 0);
 }
 
 pub fn set_1_a(memory: &mut Memory, _value: Void) {
-    template_set!(memory.registers.AF.as_pair.0, 1);
+    template_set!(memory.registers.AF.as_pair.high, 1);
 }
 
 pub fn set_2_a(memory: &mut Memory, _value: Void) {
-    template_set!(memory.registers.AF.as_pair.0, 2);
+    template_set!(memory.registers.AF.as_pair.high, 2);
 }
 
 pub fn set_3_a(memory: &mut Memory, _value: Void) {
-    template_set!(memory.registers.AF.as_pair.0, 3);
+    template_set!(memory.registers.AF.as_pair.high, 3);
 }
 
 pub fn set_4_a(memory: &mut Memory, _value: Void) {
-    template_set!(memory.registers.AF.as_pair.0, 4);
+    template_set!(memory.registers.AF.as_pair.high, 4);
 }
 
 pub fn set_5_a(memory: &mut Memory, _value: Void) {
-    template_set!(memory.registers.AF.as_pair.0, 5);
+    template_set!(memory.registers.AF.as_pair.high, 5);
 }
 
 pub fn set_6_a(memory: &mut Memory, _value: Void) {
-    template_set!(memory.registers.AF.as_pair.0, 6);
+    template_set!(memory.registers.AF.as_pair.high, 6);
 }
 
 pub fn set_7_a(memory: &mut Memory, _value: Void) {
-    template_set!(memory.registers.AF.as_pair.0, 7);
+    template_set!(memory.registers.AF.as_pair.high, 7);
 }
 
 //  ############# B #############
 
 pub fn set_0_b(memory: &mut Memory, _value: Void) {
-    template_set!(memory.registers.BC.as_pair.0, 0);
+    template_set!(memory.registers.BC.as_pair.high, 0);
 }
 
 pub fn set_1_b(memory: &mut Memory, _value: Void) {
-    template_set!(memory.registers.BC.as_pair.0, 1);
+    template_set!(memory.registers.BC.as_pair.high, 1);
 }
 
 pub fn set_2_b(memory: &mut Memory, _value: Void) {
-    template_set!(memory.registers.BC.as_pair.0, 2);
+    template_set!(memory.registers.BC.as_pair.high, 2);
 }
 
 pub fn set_3_b(memory: &mut Memory, _value: Void) {
-    template_set!(memory.registers.BC.as_pair.0, 3);
+    template_set!(memory.registers.BC.as_pair.high, 3);
 }
 
 pub fn set_4_b(memory: &mut Memory, _value: Void) {
-    template_set!(memory.registers.BC.as_pair.0, 4);
+    template_set!(memory.registers.BC.as_pair.high, 4);
 }
 
 pub fn set_5_b(memory: &mut Memory, _value: Void) {
-    template_set!(memory.registers.BC.as_pair.0, 5);
+    template_set!(memory.registers.BC.as_pair.high, 5);
 }
 
 pub fn set_6_b(memory: &mut Memory, _value: Void) {
-    template_set!(memory.registers.BC.as_pair.0, 6);
+    template_set!(memory.registers.BC.as_pair.high, 6);
 }
 
 pub fn set_7_b(memory: &mut Memory, _value: Void) {
-    template_set!(memory.registers.BC.as_pair.0, 7);
+    template_set!(memory.registers.BC.as_pair.high, 7);
 }
 
 //  ############# C #############
 
 pub fn set_0_c(memory: &mut Memory, _value: Void) {
-    template_set!(memory.registers.BC.as_pair.1, 0);
+    template_set!(memory.registers.BC.as_pair.low, 0);
 }
 
 pub fn set_1_c(memory: &mut Memory, _value: Void) {
-    template_set!(memory.registers.BC.as_pair.1, 1);
+    template_set!(memory.registers.BC.as_pair.low, 1);
 }
 
 pub fn set_2_c(memory: &mut Memory, _value: Void) {
-    template_set!(memory.registers.BC.as_pair.1, 2);
+    template_set!(memory.registers.BC.as_pair.low, 2);
 }
 
 pub fn set_3_c(memory: &mut Memory, _value: Void) {
-    template_set!(memory.registers.BC.as_pair.1, 3);
+    template_set!(memory.registers.BC.as_pair.low, 3);
 }
 
 pub fn set_4_c(memory: &mut Memory, _value: Void) {
-    template_set!(memory.registers.BC.as_pair.1, 4);
+    template_set!(memory.registers.BC.as_pair.low, 4);
 }
 
 pub fn set_5_c(memory: &mut Memory, _value: Void) {
-    template_set!(memory.registers.BC.as_pair.1, 5);
+    template_set!(memory.registers.BC.as_pair.low, 5);
 }
 
 pub fn set_6_c(memory: &mut Memory, _value: Void) {
-    template_set!(memory.registers.BC.as_pair.1, 6);
+    template_set!(memory.registers.BC.as_pair.low, 6);
 }
 
 pub fn set_7_c(memory: &mut Memory, _value: Void) {
-    template_set!(memory.registers.BC.as_pair.1, 7);
+    template_set!(memory.registers.BC.as_pair.low, 7);
 }
 
 //  ############# D #############
 
 pub fn set_0_d(memory: &mut Memory, _value: Void) {
-    template_set!(memory.registers.DE.as_pair.0, 0);
+    template_set!(memory.registers.DE.as_pair.high, 0);
 }
 
 pub fn set_1_d(memory: &mut Memory, _value: Void) {
-    template_set!(memory.registers.DE.as_pair.0, 1);
+    template_set!(memory.registers.DE.as_pair.high, 1);
 }
 
 pub fn set_2_d(memory: &mut Memory, _value: Void) {
-    template_set!(memory.registers.DE.as_pair.0, 2);
+    template_set!(memory.registers.DE.as_pair.high, 2);
 }
 
 pub fn set_3_d(memory: &mut Memory, _value: Void) {
-    template_set!(memory.registers.DE.as_pair.0, 3);
+    template_set!(memory.registers.DE.as_pair.high, 3);
 }
 
 pub fn set_4_d(memory: &mut Memory, _value: Void) {
-    template_set!(memory.registers.DE.as_pair.0, 4);
+    template_set!(memory.registers.DE.as_pair.high, 4);
 }
 
 pub fn set_5_d(memory: &mut Memory, _value: Void) {
-    template_set!(memory.registers.DE.as_pair.0, 5);
+    template_set!(memory.registers.DE.as_pair.high, 5);
 }
 
 pub fn set_6_d(memory: &mut Memory, _value: Void) {
-    template_set!(memory.registers.DE.as_pair.0, 6);
+    template_set!(memory.registers.DE.as_pair.high, 6);
 }
 
 pub fn set_7_d(memory: &mut Memory, _value: Void) {
-    template_set!(memory.registers.DE.as_pair.0, 7);
+    template_set!(memory.registers.DE.as_pair.high, 7);
 }
 
 //  ############# E #############
 
 pub fn set_0_e(memory: &mut Memory, _value: Void) {
-    template_set!(memory.registers.DE.as_pair.1, 0);
+    template_set!(memory.registers.DE.as_pair.low, 0);
 }
 
 pub fn set_1_e(memory: &mut Memory, _value: Void) {
-    template_set!(memory.registers.DE.as_pair.1, 1);
+    template_set!(memory.registers.DE.as_pair.low, 1);
 }
 
 pub fn set_2_e(memory: &mut Memory, _value: Void) {
-    template_set!(memory.registers.DE.as_pair.1, 2);
+    template_set!(memory.registers.DE.as_pair.low, 2);
 }
 
 pub fn set_3_e(memory: &mut Memory, _value: Void) {
-    template_set!(memory.registers.DE.as_pair.1, 3);
+    template_set!(memory.registers.DE.as_pair.low, 3);
 }
 
 pub fn set_4_e(memory: &mut Memory, _value: Void) {
-    template_set!(memory.registers.DE.as_pair.1, 4);
+    template_set!(memory.registers.DE.as_pair.low, 4);
 }
 
 pub fn set_5_e(memory: &mut Memory, _value: Void) {
-    template_set!(memory.registers.DE.as_pair.1, 5);
+    template_set!(memory.registers.DE.as_pair.low, 5);
 }
 
 pub fn set_6_e(memory: &mut Memory, _value: Void) {
-    template_set!(memory.registers.DE.as_pair.1, 6);
+    template_set!(memory.registers.DE.as_pair.low, 6);
 }
 
 pub fn set_7_e(memory: &mut Memory, _value: Void) {
-    template_set!(memory.registers.DE.as_pair.1, 7);
+    template_set!(memory.registers.DE.as_pair.low, 7);
 }
 
 //  ############# H #############
 
 pub fn set_0_h(memory: &mut Memory, _value: Void) {
-    template_set!(memory.registers.HL.as_pair.0, 0);
+    template_set!(memory.registers.HL.as_pair.high, 0);
 }
 
 pub fn set_1_h(memory: &mut Memory, _value: Void) {
-    template_set!(memory.registers.HL.as_pair.0, 1);
+    template_set!(memory.registers.HL.as_pair.high, 1);
 }
 
 pub fn set_2_h(memory: &mut Memory, _value: Void) {
-    template_set!(memory.registers.HL.as_pair.0, 2);
+    template_set!(memory.registers.HL.as_pair.high, 2);
 }
 
 pub fn set_3_h(memory: &mut Memory, _value: Void) {
-    template_set!(memory.registers.HL.as_pair.0, 3);
+    template_set!(memory.registers.HL.as_pair.high, 3);
 }
 
 pub fn set_4_h(memory: &mut Memory, _value: Void) {
-    template_set!(memory.registers.HL.as_pair.0, 4);
+    template_set!(memory.registers.HL.as_pair.high, 4);
 }
 
 pub fn set_5_h(memory: &mut Memory, _value: Void) {
-    template_set!(memory.registers.HL.as_pair.0, 5);
+    template_set!(memory.registers.HL.as_pair.high, 5);
 }
 
 pub fn set_6_h(memory: &mut Memory, _value: Void) {
-    template_set!(memory.registers.HL.as_pair.0, 6);
+    template_set!(memory.registers.HL.as_pair.high, 6);
 }
 
 pub fn set_7_h(memory: &mut Memory, _value: Void) {
-    template_set!(memory.registers.HL.as_pair.0, 7);
+    template_set!(memory.registers.HL.as_pair.high, 7);
 }
 
 //  ############# L #############
 
 pub fn set_0_l(memory: &mut Memory, _value: Void) {
-    template_set!(memory.registers.HL.as_pair.1, 0);
+    template_set!(memory.registers.HL.as_pair.low, 0);
 }
 
 pub fn set_1_l(memory: &mut Memory, _value: Void) {
-    template_set!(memory.registers.HL.as_pair.1, 1);
+    template_set!(memory.registers.HL.as_pair.low, 1);
 }
 
 pub fn set_2_l(memory: &mut Memory, _value: Void) {
-    template_set!(memory.registers.HL.as_pair.1, 2);
+    template_set!(memory.registers.HL.as_pair.low, 2);
 }
 
 pub fn set_3_l(memory: &mut Memory, _value: Void) {
-    template_set!(memory.registers.HL.as_pair.1, 3);
+    template_set!(memory.registers.HL.as_pair.low, 3);
 }
 
 pub fn set_4_l(memory: &mut Memory, _value: Void) {
-    template_set!(memory.registers.HL.as_pair.1, 4);
+    template_set!(memory.registers.HL.as_pair.low, 4);
 }
 
 pub fn set_5_l(memory: &mut Memory, _value: Void) {
-    template_set!(memory.registers.HL.as_pair.1, 5);
+    template_set!(memory.registers.HL.as_pair.low, 5);
 }
 
 pub fn set_6_l(memory: &mut Memory, _value: Void) {
-    template_set!(memory.registers.HL.as_pair.1, 6);
+    template_set!(memory.registers.HL.as_pair.low, 6);
 }
 
 pub fn set_7_l(memory: &mut Memory, _value: Void) {
-    template_set!(memory.registers.HL.as_pair.1, 7);
+    template_set!(memory.registers.HL.as_pair.low, 7);
 }
 
 //  ############# HL #############
@@ -1,41 +1,54 @@
 use crate::cpu::memory::Memory;
-use crate::cpu::operations::misc::ei;
 use crate::utils::conversions::offset_to_far_address;
 use crate::utils::types::{AddressOffset, FarAddress, Void};
 
+// Extra ticks of the conditional jumps when the condition holds, the tables list the cost when it doesn't
+const JR_TAKEN_TICKS: u64 = 4;
+const JP_TAKEN_TICKS: u64 = 4;
+const CALL_TAKEN_TICKS: u64 = 12;
+const RET_TAKEN_TICKS: u64 = 12;
+
 //  #############################
 //  #         Template          #
 //  #############################
 
 // TODO: Check
 fn template_rst(memory: &mut Memory, value: FarAddress) {
-    memory.stack.push_wide(&mut memory.registers.SP, memory.registers.PC);
+    memory.push_wide(memory.registers.PC);
     memory.registers.PC = value;
 }
 
+/// Jumps if the condition holds, charging the extra ticks of the taken jump
+fn jump_if<T>(memory: &mut Memory, condition: bool, taken_ticks: u64, jump: fn(&mut Memory, T), value: T) {
+    if !condition { return; }
+
+    jump(memory, value);
+    memory.cycles += taken_ticks >> u8::from(memory.cgb.double_speed);
+}
+
 //  #############################
 //  #           Call            #
 //  #############################
 
 pub fn call_a16(memory: &mut Memory, value: FarAddress) {
-    memory.stack.push_wide(&mut memory.registers.SP, memory.registers.PC);
+    memory.push_wide(memory.registers.PC);
     memory.registers.PC = value;
 }
 
 pub fn call_z_a16(memory: &mut Memory, value: FarAddress) {
-    if memory.registers.get_zero_flag() { call_a16(memory, value); }
+    jump_if(memory, memory.registers.get_zero_flag(), CALL_TAKEN_TICKS, call_a16, value);
 }
 
 pub fn call_nz_a16(memory: &mut Memory, value: FarAddress) {
-    if !memory.registers.get_zero_flag() { call_a16(memory, value); }
+    jump_if(memory, !memory.registers.get_zero_flag(), CALL_TAKEN_TICKS, call_a16, value);
 }
 
 pub fn call_c_a16(memory: &mut Memory, value: FarAddress) {
-    if memory.registers.get_carry_flag() { call_a16(memory, value); }
+    jump_if(memory, memory.registers.get_carry_flag(), CALL_TAKEN_TICKS, call_a16, value);
 }
 
 pub fn call_nc_a16(memory: &mut Memory, value: FarAddress) {
-    if !memory.registers.get_carry_flag() { call_a16(memory, value); }
+    jump_if(memory, !memory.registers.get_carry_flag(), CALL_TAKEN_TICKS, call_a16, value);
 }
 
 //  #############################
@@ -44,28 +57,30 @@ pub fn call_nc_a16(memory: &mut Memory, value: FarAddress) {
 
 // TODO: Check
 pub fn ret(memory: &mut Memory, _value: Void) {
-    memory.registers.PC = memory.stack.pop_wide(&mut memory.registers.SP);
+    memory.registers.PC = memory.pop_wide();
 }
 
 pub fn reti(memory: &mut Memory, value: Void) {
-    ei(memory, value);
+    // https://rgbds.gbdev.io/docs/v0.6.1/gbz80.7#RETI
+    // Unlike EI, IME is set right away
+    memory.interrupts.ime = true;
     ret(memory, value);
 }
 
 pub fn ret_z(memory: &mut Memory, value: Void) {
-    if memory.registers.get_zero_flag() { ret(memory, value) }
+    jump_if(memory, memory.registers.get_zero_flag(), RET_TAKEN_TICKS, ret, value);
 }
 
 pub fn ret_nz(memory: &mut Memory, value: Void) {
-    if !memory.registers.get_zero_flag() { ret(memory, value); }
+    jump_if(memory, !memory.registers.get_zero_flag(), RET_TAKEN_TICKS, ret, value);
 }
 
 pub fn ret_c(memory: &mut Memory, value: Void) {
-    if memory.registers.get_carry_flag() { ret(memory, value); }
+    jump_if(memory, memory.registers.get_carry_flag(), RET_TAKEN_TICKS, ret, value);
 }
 
 pub fn ret_nc(memory: &mut Memory, value: Void) {
-    if !memory.registers.get_carry_flag() { ret(memory, value); }
+    jump_if(memory, !memory.registers.get_carry_flag(), RET_TAKEN_TICKS, ret, value);
 }
 
 //  #############################
@@ -81,19 +96,19 @@ pub fn jp_hl(memory: &mut Memory, _value: Void) {
 }
 
 pub fn jp_z_a16(memory: &mut Memory, value: FarAddress) {
-    if memory.registers.get_zero_flag() { jp_a16(memory, value); }
+    jump_if(memory, memory.registers.get_zero_flag(), JP_TAKEN_TICKS, jp_a16, value);
 }
 
 pub fn jp_nz_a16(memory: &mut Memory, value: FarAddress) {
-    if !memory.registers.get_zero_flag() { jp_a16(memory, value); }
+    jump_if(memory, !memory.registers.get_zero_flag(), JP_TAKEN_TICKS, jp_a16, value);
 }
 
 pub fn jp_c_a16(memory: &mut Memory, value: FarAddress) {
-    if memory.registers.get_carry_flag() { jp_a16(memory, value); }
+    jump_if(memory, memory.registers.get_carry_flag(), JP_TAKEN_TICKS, jp_a16, value);
 }
 
 pub fn jp_nc_a16(memory: &mut Memory, value: FarAddress) {
-    if !memory.registers.get_carry_flag() { jp_a16(memory, value); }
+    jump_if(memory, !memory.registers.get_carry_flag(), JP_TAKEN_TICKS, jp_a16, value);
 }
 
 //  #############################
@@ -111,19 +126,19 @@ pub fn jr_r8(memory: &mut Memory, value: AddressOffset) {
 }
 
 pub fn jr_z_r8(memory: &mut Memory, value: AddressOffset) {
-    if memory.registers.get_zero_flag() { jr_r8(memory, value); }
+    jump_if(memory, memory.registers.get_zero_flag(), JR_TAKEN_TICKS, jr_r8, value);
 }
 
 pub fn jr_nz_r8(memory: &mut Memory, value: AddressOffset) {
-    if !memory.registers.get_zero_flag() { jr_r8(memory, value); }
+    jump_if(memory, !memory.registers.get_zero_flag(), JR_TAKEN_TICKS, jr_r8, value);
 }
 
 pub fn jr_c_r8(memory: &mut Memory, value: AddressOffset) {
-    if memory.registers.get_carry_flag() { jr_r8(memory, value); }
+    jump_if(memory, memory.registers.get_carry_flag(), JR_TAKEN_TICKS, jr_r8, value);
 }
 
 pub fn jr_nc_r8(memory: &mut Memory, value: AddressOffset) {
-    if !memory.registers.get_carry_flag() { jr_r8(memory, value); }
+    jump_if(memory, !memory.registers.get_carry_flag(), JR_TAKEN_TICKS, jr_r8, value);
 }
 
 //  #############################
@@ -160,4 +175,37 @@ pub fn rst_30h(memory: &mut Memory, _value: Void) {
 
 pub fn rst_38h(memory: &mut Memory, _value: Void) {
     template_rst(memory, 0x38);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cpu::execution::step;
+    use crate::cpu::memory::Memory;
+
+    /// Clock ticks of the conditional instruction at C000, with the zero flag set or not
+    fn ticks(program: &[u8], zero: bool) -> u64 {
+        let mut memory = Memory::flat_bus();
+        memory.memory[0xC000..0xC000 + program.len()].copy_from_slice(program);
+        memory.registers.PC = 0xC000;
+        memory.registers.SP = 0xDFF0;
+        memory.registers.set_zero_flag(zero);
+
+        step(&mut memory);
+        return memory.cycles;
+    }
+
+    #[test]
+    fn test_taken_ticks() {
+        let programs: [(&[u8], u64, u64); 4] = [
+            (&[0x28, 0x05], 12, 8),         // JR Z, r8
+            (&[0xCA, 0x00, 0xD0], 16, 12),  // JP Z, a16
+            (&[0xCC, 0x00, 0xD0], 24, 12),  // CALL Z, a16
+            (&[0xC8], 20, 8),               // RET Z
+        ];
+
+        for (program, taken, not_taken) in programs {
+            assert_eq!(ticks(program, true), taken, "{:#04X} taken", program[0]);
+            assert_eq!(ticks(program, false), not_taken, "{:#04X} not taken", program[0]);
+        }
+    }
 }
\ No newline at end of file
@@ -26,41 +26,41 @@ macro_rules! template_ld_unsafe {
 //  ############# A #############
 
 pub fn ld_a_d8(memory: &mut Memory, value: Value) {
-    template_ld!(memory.registers.AF.as_pair.0, value);
+    template_ld!(memory.registers.AF.as_pair.high, value);
 }
 
 pub fn ld_a_a(_memory: &mut Memory, _value: Void) {
     // Equivalent to a NOP
     // https://retrocomputing.stackexchange.com/questions/19632/what-could-be-the-reason-an-ld-b-b-instruction-was-used-in-this-busy-loop
-    // template_ld!(memory.registers.AF.as_pair.0, memory.registers.AF.as_pair.0);
+    // template_ld!(memory.registers.AF.as_pair.high, memory.registers.AF.as_pair.high);
 }
 
 pub fn ld_a_b(memory: &mut Memory, _value: Void) {
-    template_ld_unsafe!(memory.registers.AF.as_pair.0, memory.registers.BC.as_pair.0);
+    template_ld_unsafe!(memory.registers.AF.as_pair.high, memory.registers.BC.as_pair.high);
 }
 
 pub fn ld_a_c(memory: &mut Memory, _value: Void) {
-    template_ld_unsafe!(memory.registers.AF.as_pair.0, memory.registers.BC.as_pair.1);
+    template_ld_unsafe!(memory.registers.AF.as_pair.high, memory.registers.BC.as_pair.low);
 }
 
 pub fn ld_a_d(memory: &mut Memory, _value: Void) {
-    template_ld_unsafe!(memory.registers.AF.as_pair.0, memory.registers.DE.as_pair.0);
+    template_ld_unsafe!(memory.registers.AF.as_pair.high, memory.registers.DE.as_pair.high);
 }
 
 pub fn ld_a_e(memory: &mut Memory, _value: Void) {
-    template_ld_unsafe!(memory.registers.AF.as_pair.0, memory.registers.DE.as_pair.1);
+    template_ld_unsafe!(memory.registers.AF.as_pair.high, memory.registers.DE.as_pair.low);
 }
 
 pub fn ld_a_h(memory: &mut Memory, _value: Void) {
-    template_ld_unsafe!(memory.registers.AF.as_pair.0, memory.registers.HL.as_pair.0);
+    template_ld_unsafe!(memory.registers.AF.as_pair.high, memory.registers.HL.as_pair.high);
 }
 
 pub fn ld_a_l(memory: &mut Memory, _value: Void) {
-    template_ld_unsafe!(memory.registers.AF.as_pair.0, memory.registers.HL.as_pair.1);
+    template_ld_unsafe!(memory.registers.AF.as_pair.high, memory.registers.HL.as_pair.low);
 }
 
 pub fn ldh_a_a8_addr(memory: &mut Memory, value: NearAddress) {
-    template_ld!(memory.registers.AF.as_pair.0, memory.read_near_addr(value));
+    template_ld!(memory.registers.AF.as_pair.high, memory.read_near_addr(value));
 }
 
 pub fn ld_a_c_addr(memory: &mut Memory, _value: Void) {
@@ -68,7 +68,7 @@ pub fn ld_a_c_addr(memory: &mut Memory, _value: Void) {
 }
 
 pub fn ld_a_a16_addr(memory: &mut Memory, value: FarAddress) {
-    template_ld!(memory.registers.AF.as_pair.0, memory.read_far_addr(value));
+    template_ld!(memory.registers.AF.as_pair.high, memory.read_far_addr(value));
 }
 
 pub fn ld_a_bc_addr(memory: &mut Memory, _value: Void) {
@@ -80,7 +80,7 @@ pub fn ld_a_de_addr(memory: &mut Memory, _value: Void) {
 }
 
 pub fn ld_a_hl_addr(memory: &mut Memory, _value: Void) {
-    template_ld!(memory.registers.AF.as_pair.0, memory.read_far_addr(memory.registers.get_hl()));
+    template_ld!(memory.registers.AF.as_pair.high, memory.read_far_addr(memory.registers.get_hl()));
 }
 
 pub fn ld_a_hli_addr(memory: &mut Memory, _value: Void) {
@@ -110,81 +110,81 @@ pub fn ld_a16_addr_a(memory: &mut Memory, value: FarAddress) {
 //  ############# B #############
 
 pub fn ld_b_d8(memory: &mut Memory, value: Value) {
-    template_ld!(memory.registers.BC.as_pair.0, value);
+    template_ld!(memory.registers.BC.as_pair.high, value);
 }
 
 pub fn ld_b_a(memory: &mut Memory, _value: Void) {
-    template_ld_unsafe!(memory.registers.BC.as_pair.0, memory.registers.AF.as_pair.0);
+    template_ld_unsafe!(memory.registers.BC.as_pair.high, memory.registers.AF.as_pair.high);
 }
 
 pub fn ld_b_b(_memory: &mut Memory, _value: Void) {
     // Equivalent to a NOP
     // https://retrocomputing.stackexchange.com/questions/19632/what-could-be-the-reason-an-ld-b-b-instruction-was-used-in-this-busy-loop
-    // template_ld!(memory.registers.BC.as_pair.0, memory.registers.BC.as_pair.0);
+    // template_ld!(memory.registers.BC.as_pair.high, memory.registers.BC.as_pair.high);
 }
 
 pub fn ld_b_c(memory: &mut Memory, _value: Void) {
-    template_ld_unsafe!(memory.registers.BC.as_pair.0, memory.registers.BC.as_pair.1);
+    template_ld_unsafe!(memory.registers.BC.as_pair.high, memory.registers.BC.as_pair.low);
 }
 
 pub fn ld_b_d(memory: &mut Memory, _value: Void) {
-    template_ld_unsafe!(memory.registers.BC.as_pair.0, memory.registers.DE.as_pair.0);
+    template_ld_unsafe!(memory.registers.BC.as_pair.high, memory.registers.DE.as_pair.high);
 }
 
 pub fn ld_b_e(memory: &mut Memory, _value: Void) {
-    template_ld_unsafe!(memory.registers.BC.as_pair.0, memory.registers.DE.as_pair.1);
+    template_ld_unsafe!(memory.registers.BC.as_pair.high, memory.registers.DE.as_pair.low);
 }
 
 pub fn ld_b_h(memory: &mut Memory, _value: Void) {
-    template_ld_unsafe!(memory.registers.BC.as_pair.0, memory.registers.HL.as_pair.0);
+    template_ld_unsafe!(memory.registers.BC.as_pair.high, memory.registers.HL.as_pair.high);
 }
 
 pub fn ld_b_l(memory: &mut Memory, _value: Void) {
-    template_ld_unsafe!(memory.registers.BC.as_pair.0, memory.registers.HL.as_pair.1);
+    template_ld_unsafe!(memory.registers.BC.as_pair.high, memory.registers.HL.as_pair.low);
 }
 
 pub fn ld_b_hl_addr(memory: &mut Memory, _value: Void) {
-    template_ld!(memory.registers.BC.as_pair.0, memory.read_far_addr(memory.registers.get_hl()));
+    template_ld!(memory.registers.BC.as_pair.high, memory.read_far_addr(memory.registers.get_hl()));
 }
 
 //  ############# C #############
 
 pub fn ld_c_d8(memory: &mut Memory, value: Value) {
-    template_ld!(memory.registers.BC.as_pair.1, value);
+    template_ld!(memory.registers.BC.as_pair.low, value);
 }
 
 pub fn ld_c_a(memory: &mut Memory, _value: Void) {
-    template_ld_unsafe!(memory.registers.BC.as_pair.1, memory.registers.AF.as_pair.0);
+    template_ld_unsafe!(memory.registers.BC.as_pair.low, memory.registers.AF.as_pair.high);
 }
 
 pub fn ld_c_b(memory: &mut Memory, _value: Void) {
-    template_ld_unsafe!(memory.registers.BC.as_pair.1, memory.registers.BC.as_pair.0);
+    template_ld_unsafe!(memory.registers.BC.as_pair.low, memory.registers.BC.as_pair.high);
 }
 
 pub fn ld_c_c(_memory: &mut Memory, _value: Void) {
     // Equivalent to a NOP
     // https://retrocomputing.stackexchange.com/questions/19632/what-could-be-the-reason-an-ld-b-b-instruction-was-used-in-this-busy-loop
-    // template_ld!(memory.registers.BC.as_pair.1, memory.registers.BC.as_pair.1);
+    // template_ld!(memory.registers.BC.as_pair.low, memory.registers.BC.as_pair.low);
 }
 
 pub fn ld_c_d(memory: &mut Memory, _value: Void) {
-    template_ld_unsafe!(memory.registers.BC.as_pair.1, memory.registers.DE.as_pair.0);
+    template_ld_unsafe!(memory.registers.BC.as_pair.low, memory.registers.DE.as_pair.high);
 }
 
 pub fn ld_c_e(memory: &mut Memory, _value: Void) {
-    template_ld_unsafe!(memory.registers.BC.as_pair.1, memory.registers.DE.as_pair.1);
+    template_ld_unsafe!(memory.registers.BC.as_pair.low, memory.registers.DE.as_pair.low);
 }
 
 pub fn ld_c_h(memory: &mut Memory, _value: Void) {
-    template_ld_unsafe!(memory.registers.BC.as_pair.1, memory.registers.HL.as_pair.0);
+    template_ld_unsafe!(memory.registers.BC.as_pair.low, memory.registers.HL.as_pair.high);
 }
 
 pub fn ld_c_l(memory: &mut Memory, _value: Void) {
-    template_ld_unsafe!(memory.registers.BC.as_pair.1, memory.registers.HL.as_pair.1);
+    template_ld_unsafe!(memory.registers.BC.as_pair.low, memory.registers.HL.as_pair.low);
 }
 
 pub fn ld_c_hl_addr(memory: &mut Memory, _value: Void) {
-    template_ld!(memory.registers.BC.as_pair.1, memory.read_far_addr(memory.registers.get_hl()));
+    template_ld!(memory.registers.BC.as_pair.low, memory.read_far_addr(memory.registers.get_hl()));
 }
 
 pub fn ld_c_addr_a(memory: &mut Memory, _value: Void) {
@@ -200,81 +200,81 @@ pub fn ld_bc_addr_a(memory: &mut Memory, _value: Void) {
 //  ############# D #############
 
 pub fn ld_d_d8(memory: &mut Memory, value: Value) {
-    template_ld!(memory.registers.DE.as_pair.0, value);
+    template_ld!(memory.registers.DE.as_pair.high, value);
 }
 
 pub fn ld_d_a(memory: &mut Memory, _value: Void) {
-    template_ld_unsafe!(memory.registers.DE.as_pair.0, memory.registers.AF.as_pair.0);
+    template_ld_unsafe!(memory.registers.DE.as_pair.high, memory.registers.AF.as_pair.high);
 }
 
 pub fn ld_d_b(memory: &mut Memory, _value: Void) {
-    template_ld_unsafe!(memory.registers.DE.as_pair.0, memory.registers.BC.as_pair.0);
+    template_ld_unsafe!(memory.registers.DE.as_pair.high, memory.registers.BC.as_pair.high);
 }
 
 pub fn ld_d_c(memory: &mut Memory, _value: Void) {
-    template_ld_unsafe!(memory.registers.DE.as_pair.0, memory.registers.BC.as_pair.1);
+    template_ld_unsafe!(memory.registers.DE.as_pair.high, memory.registers.BC.as_pair.low);
 }
 
 pub fn ld_d_d(_memory: &mut Memory, _value: Void) {
     // Equivalent to a NOP
     // https://retrocomputing.stackexchange.com/questions/19632/what-could-be-the-reason-an-ld-b-b-instruction-was-used-in-this-busy-loop
-    // template_ld!(memory.registers.DE.as_pair.0, memory.registers.DE.as_pair.0);
+    // template_ld!(memory.registers.DE.as_pair.high, memory.registers.DE.as_pair.high);
 }
 
 pub fn ld_d_e(memory: &mut Memory, _value: Void) {
-    template_ld_unsafe!(memory.registers.DE.as_pair.0, memory.registers.DE.as_pair.1);
+    template_ld_unsafe!(memory.registers.DE.as_pair.high, memory.registers.DE.as_pair.low);
 }
 
 pub fn ld_d_h(memory: &mut Memory, _value: Void) {
-    template_ld_unsafe!(memory.registers.DE.as_pair.0, memory.registers.HL.as_pair.0);
+    template_ld_unsafe!(memory.registers.DE.as_pair.high, memory.registers.HL.as_pair.high);
 }
 
 pub fn ld_d_l(memory: &mut Memory, _value: Void) {
-    template_ld_unsafe!(memory.registers.DE.as_pair.0, memory.registers.HL.as_pair.1);
+    template_ld_unsafe!(memory.registers.DE.as_pair.high, memory.registers.HL.as_pair.low);
 }
 
 pub fn ld_d_hl_addr(memory: &mut Memory, _value: Void) {
-    template_ld!(memory.registers.DE.as_pair.0, memory.read_far_addr(memory.registers.get_hl()));
+    template_ld!(memory.registers.DE.as_pair.high, memory.read_far_addr(memory.registers.get_hl()));
 }
 
 //  ############# E #############
 
 pub fn ld_e_d8(memory: &mut Memory, value: Value) {
-    template_ld!(memory.registers.DE.as_pair.1, value);
+    template_ld!(memory.registers.DE.as_pair.low, value);
 }
 
 pub fn ld_e_a(memory: &mut Memory, _value: Void) {
-    template_ld_unsafe!(memory.registers.DE.as_pair.1, memory.registers.AF.as_pair.0);
+    template_ld_unsafe!(memory.registers.DE.as_pair.low, memory.registers.AF.as_pair.high);
 }
 
 pub fn ld_e_b(memory: &mut Memory, _value: Void) {
-    template_ld_unsafe!(memory.registers.DE.as_pair.1, memory.registers.BC.as_pair.0);
+    template_ld_unsafe!(memory.registers.DE.as_pair.low, memory.registers.BC.as_pair.high);
 }
 
 pub fn ld_e_c(memory: &mut Memory, _value: Void) {
-    template_ld_unsafe!(memory.registers.DE.as_pair.1, memory.registers.BC.as_pair.1);
+    template_ld_unsafe!(memory.registers.DE.as_pair.low, memory.registers.BC.as_pair.low);
 }
 
 pub fn ld_e_d(memory: &mut Memory, _value: Void) {
-    template_ld_unsafe!(memory.registers.DE.as_pair.1, memory.registers.DE.as_pair.0);
+    template_ld_unsafe!(memory.registers.DE.as_pair.low, memory.registers.DE.as_pair.high);
 }
 
 pub fn ld_e_e(_memory: &mut Memory, _value: Void) {
     // Equivalent to a NOP
     // https://retrocomputing.stackexchange.com/questions/19632/what-could-be-the-reason-an-ld-b-b-instruction-was-used-in-this-busy-loop
-    // template_ld!(memory.registers.DE.as_pair.1, memory.registers.DE.as_pair.1);
+    // template_ld!(memory.registers.DE.as_pair.low, memory.registers.DE.as_pair.low);
 }
 
 pub fn ld_e_h(memory: &mut Memory, _value: Void) {
-    template_ld_unsafe!(memory.registers.DE.as_pair.1, memory.registers.HL.as_pair.0);
+    template_ld_unsafe!(memory.registers.DE.as_pair.low, memory.registers.HL.as_pair.high);
 }
 
 pub fn ld_e_l(memory: &mut Memory, _value: Void) {
-    template_ld_unsafe!(memory.registers.DE.as_pair.1, memory.registers.HL.as_pair.1);
+    template_ld_unsafe!(memory.registers.DE.as_pair.low, memory.registers.HL.as_pair.low);
 }
 
 pub fn ld_e_hl_addr(memory: &mut Memory, _value: Void) {
-    template_ld!(memory.registers.DE.as_pair.1, memory.read_far_addr(memory.registers.get_hl()));
+    template_ld!(memory.registers.DE.as_pair.low, memory.read_far_addr(memory.registers.get_hl()));
 }
 
 //  ############ DE #############
@@ -286,81 +286,81 @@ pub fn ld_de_addr_a(memory: &mut Memory, _value: Void) {
 //  ############# H #############
 
 pub fn ld_h_d8(memory: &mut Memory, value: Value) {
-    template_ld!(memory.registers.HL.as_pair.0, value);
+    template_ld!(memory.registers.HL.as_pair.high, value);
 }
 
 pub fn ld_h_a(memory: &mut Memory, _value: Void) {
-    template_ld_unsafe!(memory.registers.HL.as_pair.0, memory.registers.AF.as_pair.0);
+    template_ld_unsafe!(memory.registers.HL.as_pair.high, memory.registers.AF.as_pair.high);
 }
 
 pub fn ld_h_b(memory: &mut Memory, _value: Void) {
-    template_ld_unsafe!(memory.registers.HL.as_pair.0, memory.registers.BC.as_pair.0);
+    template_ld_unsafe!(memory.registers.HL.as_pair.high, memory.registers.BC.as_pair.high);
 }
 
 pub fn ld_h_c(memory: &mut Memory, _value: Void) {
-    template_ld_unsafe!(memory.registers.HL.as_pair.0, memory.registers.BC.as_pair.1);
+    template_ld_unsafe!(memory.registers.HL.as_pair.high, memory.registers.BC.as_pair.low);
 }
 
 pub fn ld_h_d(memory: &mut Memory, _value: Void) {
-    template_ld_unsafe!(memory.registers.HL.as_pair.0, memory.registers.DE.as_pair.0);
+    template_ld_unsafe!(memory.registers.HL.as_pair.high, memory.registers.DE.as_pair.high);
 }
 
 pub fn ld_h_e(memory: &mut Memory, _value: Void) {
-    template_ld_unsafe!(memory.registers.HL.as_pair.0, memory.registers.DE.as_pair.1);
+    template_ld_unsafe!(memory.registers.HL.as_pair.high, memory.registers.DE.as_pair.low);
 }
 
 pub fn ld_h_h(_memory: &mut Memory, _value: Void) {
     // Equivalent to a NOP
     // https://retrocomputing.stackexchange.com/questions/19632/what-could-be-the-reason-an-ld-b-b-instruction-was-used-in-this-busy-loop
-    // template_ld!(memory.registers.HL.as_pair.0, memory.registers.HL.as_pair.0);
+    // template_ld!(memory.registers.HL.as_pair.high, memory.registers.HL.as_pair.high);
 }
 
 pub fn ld_h_l(memory: &mut Memory, _value: Void) {
-    template_ld_unsafe!(memory.registers.HL.as_pair.0, memory.registers.HL.as_pair.1);
+    template_ld_unsafe!(memory.registers.HL.as_pair.high, memory.registers.HL.as_pair.low);
 }
 
 pub fn ld_h_hl_addr(memory: &mut Memory, _value: Void) {
-    template_ld!(memory.registers.HL.as_pair.0, memory.read_far_addr(memory.registers.get_hl()));
+    template_ld!(memory.registers.HL.as_pair.high, memory.read_far_addr(memory.registers.get_hl()));
 }
 
 //  ############# L #############
 
 pub fn ld_l_d8(memory: &mut Memory, value: Value) {
-    template_ld!(memory.registers.HL.as_pair.1, value);
+    template_ld!(memory.registers.HL.as_pair.low, value);
 }
 
 pub fn ld_l_a(memory: &mut Memory, _value: Void) {
-    template_ld_unsafe!(memory.registers.HL.as_pair.1, memory.registers.AF.as_pair.0);
+    template_ld_unsafe!(memory.registers.HL.as_pair.low, memory.registers.AF.as_pair.high);
 }
 
 pub fn ld_l_b(memory: &mut Memory, _value: Void) {
-    template_ld_unsafe!(memory.registers.HL.as_pair.1, memory.registers.BC.as_pair.0);
+    template_ld_unsafe!(memory.registers.HL.as_pair.low, memory.registers.BC.as_pair.high);
 }
 
 pub fn ld_l_c(memory: &mut Memory, _value: Void) {
-    template_ld_unsafe!(memory.registers.HL.as_pair.1, memory.registers.BC.as_pair.1);
+    template_ld_unsafe!(memory.registers.HL.as_pair.low, memory.registers.BC.as_pair.low);
 }
 
 pub fn ld_l_d(memory: &mut Memory, _value: Void) {
-    template_ld_unsafe!(memory.registers.HL.as_pair.1, memory.registers.DE.as_pair.0);
+    template_ld_unsafe!(memory.registers.HL.as_pair.low, memory.registers.DE.as_pair.high);
 }
 
 pub fn ld_l_e(memory: &mut Memory, _value: Void) {
-    template_ld_unsafe!(memory.registers.HL.as_pair.1, memory.registers.DE.as_pair.1);
+    template_ld_unsafe!(memory.registers.HL.as_pair.low, memory.registers.DE.as_pair.low);
 }
 
 pub fn ld_l_h(memory: &mut Memory, _value: Void) {
-    template_ld_unsafe!(memory.registers.HL.as_pair.1, memory.registers.HL.as_pair.0);
+    template_ld_unsafe!(memory.registers.HL.as_pair.low, memory.registers.HL.as_pair.high);
 }
 
 pub fn ld_l_l(_memory: &mut Memory, _value: Void) {
     // Equivalent to a NOP
     // https://retrocomputing.stackexchange.com/questions/19632/what-could-be-the-reason-an-ld-b-b-instruction-was-used-in-this-busy-loop
-    // template_ld!(memory.registers.HL.as_pair.1, memory.registers.HL.as_pair.1);
+    // template_ld!(memory.registers.HL.as_pair.low, memory.registers.HL.as_pair.low);
 }
 
 pub fn ld_l_hl_addr(memory: &mut Memory, _value: Void) {
-    template_ld!(memory.registers.HL.as_pair.1, memory.read_far_addr(memory.registers.get_hl()));
+    template_ld!(memory.registers.HL.as_pair.low, memory.read_far_addr(memory.registers.get_hl()));
 }
 
 //  ############ HL #############
@@ -472,32 +472,32 @@ pub fn ld_a16_addr_sp(memory: &mut Memory, value: FarAddress) {
 // TODO: Check
 pub fn push_af(memory: &mut Memory, _value: Void) {
     let af_value = memory.registers.get_af();
-    memory.stack.push_wide(&mut memory.registers.SP, af_value);
+    memory.push_wide(af_value);
 }
 
 // TODO: Check
 pub fn push_bc(memory: &mut Memory, _value: Void) {
     let bc_value = memory.registers.get_bc();
-    memory.stack.push_wide(&mut memory.registers.SP, bc_value);
+    memory.push_wide(bc_value);
 }
 
 // TODO: Check
 pub fn push_de(memory: &mut Memory, _value: Void) {
     let de_value = memory.registers.get_de();
-    memory.stack.push_wide(&mut memory.registers.SP, de_value);
+    memory.push_wide(de_value);
 }
 
 // TODO: Check
 pub fn push_hl(memory: &mut Memory, _value: Void) {
     let hl_value = memory.registers.get_hl();
-    memory.stack.push_wide(&mut memory.registers.SP, hl_value);
+    memory.push_wide(hl_value);
 }
 
 //  ########### Pop #############
 
 // TODO: Check
 pub fn pop_af(memory: &mut Memory, _value: Void) {
-    let value = memory.stack.pop_wide(&mut memory.registers.SP);
+    let value = memory.pop_wide();
     memory.registers.set_af(value);
 
     // No need to set the bits, as F already contains them
@@ -505,16 +505,16 @@ pub fn pop_af(memory: &mut Memory, _value: Void) {
 
 // TODO: Check
 pub fn pop_bc(memory: &mut Memory, _value: Void) {
-    let value = memory.stack.pop_wide(&mut memory.registers.SP);
+    let value = memory.pop_wide();
     memory.registers.set_bc(value);
 }
 
 pub fn pop_de(memory: &mut Memory, _value: Void) {
-    let value = memory.stack.pop_wide(&mut memory.registers.SP);
+    let value = memory.pop_wide();
     memory.registers.set_de(value);
 }
 
 pub fn pop_hl(memory: &mut Memory, _value: Void) {
-    let value = memory.stack.pop_wide(&mut memory.registers.SP);
+    let value = memory.pop_wide();
     memory.registers.set_hl(value);
 }
\ No newline at end of file
@@ -105,31 +105,31 @@ pub fn and_a_d8(memory: &mut Memory, value: Value) {
 }
 
 pub fn and_a_a(memory: &mut Memory, _value: Void) {
-    template_and_a_unsafe!(memory, memory.registers.AF.as_pair.0);
+    template_and_a_unsafe!(memory, memory.registers.AF.as_pair.high);
 }
 
 pub fn and_a_b(memory: &mut Memory, _value: Void) {
-    template_and_a_unsafe!(memory, memory.registers.BC.as_pair.0);
+    template_and_a_unsafe!(memory, memory.registers.BC.as_pair.high);
 }
 
 pub fn and_a_c(memory: &mut Memory, _value: Void) {
-    template_and_a_unsafe!(memory, memory.registers.BC.as_pair.1);
+    template_and_a_unsafe!(memory, memory.registers.BC.as_pair.low);
 }
 
 pub fn and_a_d(memory: &mut Memory, _value: Void) {
-    template_and_a_unsafe!(memory, memory.registers.DE.as_pair.0);
+    template_and_a_unsafe!(memory, memory.registers.DE.as_pair.high);
 }
 
 pub fn and_a_e(memory: &mut Memory, _value: Void) {
-    template_and_a_unsafe!(memory, memory.registers.DE.as_pair.1);
+    template_and_a_unsafe!(memory, memory.registers.DE.as_pair.low);
 }
 
 pub fn and_a_h(memory: &mut Memory, _value: Void) {
-    template_and_a_unsafe!(memory, memory.registers.HL.as_pair.0);
+    template_and_a_unsafe!(memory, memory.registers.HL.as_pair.high);
 }
 
 pub fn and_a_l(memory: &mut Memory, _value: Void) {
-    template_and_a_unsafe!(memory, memory.registers.HL.as_pair.1);
+    template_and_a_unsafe!(memory, memory.registers.HL.as_pair.low);
 }
 
 pub fn and_a_hl_addr(memory: &mut Memory, _value: Void) {
@@ -145,31 +145,31 @@ pub fn or_a_d8(memory: &mut Memory, value: Value) {
 }
 
 pub fn or_a_a(memory: &mut Memory, _value: Void) {
-    template_or_a_unsafe!(memory, memory.registers.AF.as_pair.0);
+    template_or_a_unsafe!(memory, memory.registers.AF.as_pair.high);
 }
 
 pub fn or_a_b(memory: &mut Memory, _value: Void) {
-    template_or_a_unsafe!(memory, memory.registers.BC.as_pair.0);
+    template_or_a_unsafe!(memory, memory.registers.BC.as_pair.high);
 }
 
 pub fn or_a_c(memory: &mut Memory, _value: Void) {
-    template_or_a_unsafe!(memory, memory.registers.BC.as_pair.1);
+    template_or_a_unsafe!(memory, memory.registers.BC.as_pair.low);
 }
 
 pub fn or_a_d(memory: &mut Memory, _value: Void) {
-    template_or_a_unsafe!(memory, memory.registers.DE.as_pair.0);
+    template_or_a_unsafe!(memory, memory.registers.DE.as_pair.high);
 }
 
 pub fn or_a_e(memory: &mut Memory, _value: Void) {
-    template_or_a_unsafe!(memory, memory.registers.DE.as_pair.1);
+    template_or_a_unsafe!(memory, memory.registers.DE.as_pair.low);
 }
 
 pub fn or_a_h(memory: &mut Memory, _value: Void) {
-    template_or_a_unsafe!(memory, memory.registers.HL.as_pair.0);
+    template_or_a_unsafe!(memory, memory.registers.HL.as_pair.high);
 }
 
 pub fn or_a_l(memory: &mut Memory, _value: Void) {
-    template_or_a_unsafe!(memory, memory.registers.HL.as_pair.1);
+    template_or_a_unsafe!(memory, memory.registers.HL.as_pair.low);
 }
 
 pub fn or_a_hl_addr(memory: &mut Memory, _value: Void) {
@@ -185,31 +185,31 @@ pub fn xor_a_d8(memory: &mut Memory, value: Value) {
 }
 
 pub fn xor_a_a(memory: &mut Memory, _value: Void) {
-    template_xor_a_unsafe!(memory, memory.registers.AF.as_pair.0);
+    template_xor_a_unsafe!(memory, memory.registers.AF.as_pair.high);
 }
 
 pub fn xor_a_b(memory: &mut Memory, _value: Void) {
-    template_xor_a_unsafe!(memory, memory.registers.BC.as_pair.0);
+    template_xor_a_unsafe!(memory, memory.registers.BC.as_pair.high);
 }
 
 pub fn xor_a_c(memory: &mut Memory, _value: Void) {
-    template_xor_a_unsafe!(memory, memory.registers.BC.as_pair.1);
+    template_xor_a_unsafe!(memory, memory.registers.BC.as_pair.low);
 }
 
 pub fn xor_a_d(memory: &mut Memory, _value: Void) {
-    template_xor_a_unsafe!(memory, memory.registers.DE.as_pair.0);
+    template_xor_a_unsafe!(memory, memory.registers.DE.as_pair.high);
 }
 
 pub fn xor_a_e(memory: &mut Memory, _value: Void) {
-    template_xor_a_unsafe!(memory, memory.registers.DE.as_pair.1);
+    template_xor_a_unsafe!(memory, memory.registers.DE.as_pair.low);
 }
 
 pub fn xor_a_h(memory: &mut Memory, _value: Void) {
-    template_xor_a_unsafe!(memory, memory.registers.HL.as_pair.0);
+    template_xor_a_unsafe!(memory, memory.registers.HL.as_pair.high);
 }
 
 pub fn xor_a_l(memory: &mut Memory, _value: Void) {
-    template_xor_a_unsafe!(memory, memory.registers.HL.as_pair.1);
+    template_xor_a_unsafe!(memory, memory.registers.HL.as_pair.low);
 }
 
 pub fn xor_a_hl_addr(memory: &mut Memory, _value: Void) {
@@ -225,31 +225,31 @@ pub fn cp_a_d8(memory: &mut Memory, value: Value) {
 }
 
 pub fn cp_a_a(memory: &mut Memory, _value: Void) {
-    template_cp_a_unsafe!(memory, memory.registers.AF.as_pair.0);
+    template_cp_a_unsafe!(memory, memory.registers.AF.as_pair.high);
 }
 
 pub fn cp_a_b(memory: &mut Memory, _value: Void) {
-    template_cp_a_unsafe!(memory, memory.registers.BC.as_pair.0);
+    template_cp_a_unsafe!(memory, memory.registers.BC.as_pair.high);
 }
 
 pub fn cp_a_c(memory: &mut Memory, _value: Void) {
-    template_cp_a_unsafe!(memory, memory.registers.BC.as_pair.1);
+    template_cp_a_unsafe!(memory, memory.registers.BC.as_pair.low);
 }
 
 pub fn cp_a_d(memory: &mut Memory, _value: Void) {
-    template_cp_a_unsafe!(memory, memory.registers.DE.as_pair.0);
+    template_cp_a_unsafe!(memory, memory.registers.DE.as_pair.high);
 }
 
 pub fn cp_a_e(memory: &mut Memory, _value: Void) {
-    template_cp_a_unsafe!(memory, memory.registers.DE.as_pair.1);
+    template_cp_a_unsafe!(memory, memory.registers.DE.as_pair.low);
 }
 
 pub fn cp_a_h(memory: &mut Memory, _value: Void) {
-    template_cp_a_unsafe!(memory, memory.registers.HL.as_pair.0);
+    template_cp_a_unsafe!(memory, memory.registers.HL.as_pair.high);
 }
 
 pub fn cp_a_l(memory: &mut Memory, _value: Void) {
-    template_cp_a_unsafe!(memory, memory.registers.HL.as_pair.1);
+    template_cp_a_unsafe!(memory, memory.registers.HL.as_pair.low);
 }
 
 pub fn cp_a_hl_addr(memory: &mut Memory, _value: Void) {
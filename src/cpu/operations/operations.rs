@@ -6,7 +6,7 @@ use crate::cpu::operations::logical::*;
 use crate::cpu::operations::misc::*;
 use crate::cpu::operations::arithmetic::*;
 
-// TODO: Check/test
+/// Checked against the reference of the tests below, which any change to the tables must keep passing
 pub static INSTRUCTIONS: [GenericInstruction; 256] = [
     GenericInstruction::Void(  Instruction { opcode: 0x00, disassembly: "NOP"           , byte_size: 1, clock_tick: 4 , function: noop }),
     GenericInstruction::Wide(  Instruction { opcode: 0x01, disassembly: "LD BC, d16"    , byte_size: 3, clock_tick: 12, function: ld_bc_d16 }),
@@ -17,7 +17,7 @@ pub static INSTRUCTIONS: [GenericInstruction; 256] = [
     GenericInstruction::Value( Instruction { opcode: 0x06, disassembly: "LD B, d8"      , byte_size: 2, clock_tick: 8 , function: ld_b_d8 }),
     GenericInstruction::Void(  Instruction { opcode: 0x07, disassembly: "RLCA"          , byte_size: 1, clock_tick: 4 , function: rlca }),
     GenericInstruction::Far(   Instruction { opcode: 0x08, disassembly: "LD (a16), SP"  , byte_size: 3, clock_tick: 20, function: ld_a16_addr_sp }),
-    GenericInstruction::Void(  Instruction { opcode: 0x09, disassembly: "AD HL, BC"     , byte_size: 1, clock_tick: 8 , function: add_hl_bc }),
+    GenericInstruction::Void(  Instruction { opcode: 0x09, disassembly: "ADD HL, BC"    , byte_size: 1, clock_tick: 8 , function: add_hl_bc }),
     GenericInstruction::Void(  Instruction { opcode: 0x0A, disassembly: "LD A, (BC)"    , byte_size: 1, clock_tick: 8 , function: ld_a_bc_addr }),
     GenericInstruction::Void(  Instruction { opcode: 0x0B, disassembly: "DEC BC"        , byte_size: 1, clock_tick: 8 , function: dec_bc }),
     GenericInstruction::Void(  Instruction { opcode: 0x0C, disassembly: "INC C"         , byte_size: 1, clock_tick: 4 , function: inc_c }),
@@ -40,7 +40,7 @@ pub static INSTRUCTIONS: [GenericInstruction; 256] = [
     GenericInstruction::Void(  Instruction { opcode: 0x1D, disassembly: "DEC E"         , byte_size: 1, clock_tick: 4 , function: dec_e }),
     GenericInstruction::Value( Instruction { opcode: 0x1E, disassembly: "LD E, d8"      , byte_size: 2, clock_tick: 8 , function: ld_e_d8 }),
     GenericInstruction::Void(  Instruction { opcode: 0x1F, disassembly: "RRA"           , byte_size: 1, clock_tick: 4 , function: rra }),
-    GenericInstruction::Offset(Instruction { opcode: 0x20, disassembly: "JR NZ, r8"     , byte_size: 1, clock_tick: 8 , function: jr_nz_r8 }),
+    GenericInstruction::Offset(Instruction { opcode: 0x20, disassembly: "JR NZ, r8"     , byte_size: 2, clock_tick: 8 , function: jr_nz_r8 }),
     GenericInstruction::Wide(  Instruction { opcode: 0x21, disassembly: "LD HL, d16"    , byte_size: 3, clock_tick: 12, function: ld_hl_d16 }),
     GenericInstruction::Void(  Instruction { opcode: 0x22, disassembly: "LD (HL+), A"   , byte_size: 1, clock_tick: 8 , function: ld_hli_addr_a }),
     GenericInstruction::Void(  Instruction { opcode: 0x23, disassembly: "INC HL"        , byte_size: 1, clock_tick: 8 , function: inc_hl }),
@@ -215,8 +215,8 @@ pub static INSTRUCTIONS: [GenericInstruction; 256] = [
     GenericInstruction::Far(   Instruction { opcode: 0xCC, disassembly: "CALL Z, a16"   , byte_size: 3, clock_tick: 12, function: call_z_a16 }),
     GenericInstruction::Far(   Instruction { opcode: 0xCD, disassembly: "CALL a16"      , byte_size: 3, clock_tick: 24, function: call_a16 }),
     GenericInstruction::Value( Instruction { opcode: 0xCE, disassembly: "ADC A, d8"     , byte_size: 2, clock_tick: 8 , function: adc_a_d8 }),
-    GenericInstruction::Void(  Instruction { opcode: 0xCF, disassembly: "RST 00H"       , byte_size: 1, clock_tick: 16, function: rst_08h }),
-    GenericInstruction::Void(  Instruction { opcode: 0xD0, disassembly: "RET NC"        , byte_size: 1, clock_tick: 9 , function: ret_nc }),
+    GenericInstruction::Void(  Instruction { opcode: 0xCF, disassembly: "RST 08H"       , byte_size: 1, clock_tick: 16, function: rst_08h }),
+    GenericInstruction::Void(  Instruction { opcode: 0xD0, disassembly: "RET NC"        , byte_size: 1, clock_tick: 8 , function: ret_nc }),
     GenericInstruction::Void(  Instruction { opcode: 0xD1, disassembly: "POP DE"        , byte_size: 1, clock_tick: 12, function: pop_de }),
     GenericInstruction::Far(   Instruction { opcode: 0xD2, disassembly: "JP NC, a16"    , byte_size: 3, clock_tick: 12, function: jp_nc_a16 }),
     GenericInstruction::Void(  Instruction { opcode: 0xD3, disassembly: "X"             , byte_size: 0, clock_tick: 0 , function: none }),
@@ -256,7 +256,7 @@ pub static INSTRUCTIONS: [GenericInstruction; 256] = [
     GenericInstruction::Void(  Instruction { opcode: 0xF5, disassembly: "PUSH AF"       , byte_size: 1, clock_tick: 16, function: push_af }),
     GenericInstruction::Value( Instruction { opcode: 0xF6, disassembly: "OR A, d8"      , byte_size: 2, clock_tick: 8 , function: or_a_d8 }),
     GenericInstruction::Void(  Instruction { opcode: 0xF7, disassembly: "RST 30H"       , byte_size: 1, clock_tick: 16, function: rst_30h }),
-    GenericInstruction::Offset(Instruction { opcode: 0xF8, disassembly: "LD HL, SP + r8", byte_size: 1, clock_tick: 16, function: ld_hl_sp_plus_r8 }),
+    GenericInstruction::Offset(Instruction { opcode: 0xF8, disassembly: "LD HL, SP + r8", byte_size: 2, clock_tick: 12, function: ld_hl_sp_plus_r8 }),
     GenericInstruction::Void(  Instruction { opcode: 0xF9, disassembly: "LD SP, HL"     , byte_size: 1, clock_tick: 8 , function: ld_sp_hl }),
     GenericInstruction::Far(   Instruction { opcode: 0xFA, disassembly: "LD A, (a16)"   , byte_size: 3, clock_tick: 16, function: ld_a_a16_addr }),
     GenericInstruction::Void(  Instruction { opcode: 0xFB, disassembly: "EI"            , byte_size: 1, clock_tick: 4 , function: ei }),
@@ -312,7 +312,7 @@ pub static PREFIXED: [GenericInstruction; 256] = [
     GenericInstruction::Void(  Instruction { opcode: 0x2A, disassembly: "SRA D"         , byte_size: 2, clock_tick: 8 , function: sra_d }),
     GenericInstruction::Void(  Instruction { opcode: 0x2B, disassembly: "SRA E"         , byte_size: 2, clock_tick: 8 , function: sra_e }),
     GenericInstruction::Void(  Instruction { opcode: 0x2C, disassembly: "SRA H"         , byte_size: 2, clock_tick: 8 , function: sra_h }),
-    GenericInstruction::Void(  Instruction { opcode: 0x2E, disassembly: "SRA L"         , byte_size: 2, clock_tick: 8 , function: sra_l }),
+    GenericInstruction::Void(  Instruction { opcode: 0x2D, disassembly: "SRA L"         , byte_size: 2, clock_tick: 8 , function: sra_l }),
     GenericInstruction::Void(  Instruction { opcode: 0x2E, disassembly: "SRA (HL)"      , byte_size: 2, clock_tick: 16, function: sra_hl_addr }),
     GenericInstruction::Void(  Instruction { opcode: 0x2F, disassembly: "SRA A"         , byte_size: 2, clock_tick: 8 , function: sra_a }),
     GenericInstruction::Void(  Instruction { opcode: 0x30, disassembly: "SWAP B"        , byte_size: 2, clock_tick: 8 , function: swap_b }),
@@ -328,7 +328,7 @@ pub static PREFIXED: [GenericInstruction; 256] = [
     GenericInstruction::Void(  Instruction { opcode: 0x3A, disassembly: "SRL D"         , byte_size: 2, clock_tick: 8 , function: srl_d }),
     GenericInstruction::Void(  Instruction { opcode: 0x3B, disassembly: "SRL E"         , byte_size: 2, clock_tick: 8 , function: srl_e }),
     GenericInstruction::Void(  Instruction { opcode: 0x3C, disassembly: "SRL H"         , byte_size: 2, clock_tick: 8 , function: srl_h }),
-    GenericInstruction::Void(  Instruction { opcode: 0x3E, disassembly: "SRL L"         , byte_size: 2, clock_tick: 8 , function: srl_l }),
+    GenericInstruction::Void(  Instruction { opcode: 0x3D, disassembly: "SRL L"         , byte_size: 2, clock_tick: 8 , function: srl_l }),
     GenericInstruction::Void(  Instruction { opcode: 0x3E, disassembly: "SRL (HL)"      , byte_size: 2, clock_tick: 16, function: srl_hl_addr }),
     GenericInstruction::Void(  Instruction { opcode: 0x3F, disassembly: "SRL A"         , byte_size: 2, clock_tick: 8 , function: srl_a }),
     GenericInstruction::Void(  Instruction { opcode: 0x40, disassembly: "BIT 0, B"      , byte_size: 2, clock_tick: 8 , function: bit_0_b }),
@@ -525,4 +525,159 @@ pub static PREFIXED: [GenericInstruction; 256] = [
     GenericInstruction::Void(  Instruction { opcode: 0xFF, disassembly: "SET 7, A"      , byte_size: 2, clock_tick: 8 , function: set_7_a }),
     ];
 
-// TODO: add tests
\ No newline at end of file
+#[cfg(test)]
+mod tests {
+    use crate::cpu::instruction::{GenericInstruction, OpCode};
+    use super::{INSTRUCTIONS, PREFIXED};
+
+    /// Mnemonic, size in bytes and clock ticks of every unprefixed opcode. Conditional jumps, calls and returns list the
+    /// ticks when the condition doesn't hold, "X" marks the opcodes which don't exist.
+    // https://gbdev.io/gb-opcodes/optables/
+    const REFERENCE: [(&str, usize, u8); 256] = [
+        ("NOP",             1,  4), ("LD BC, d16",      3, 12), ("LD (BC), A",      1,  8), ("INC BC",          1,  8),
+        ("INC B",           1,  4), ("DEC B",           1,  4), ("LD B, d8",        2,  8), ("RLCA",            1,  4),
+        ("LD (a16), SP",    3, 20), ("ADD HL, BC",      1,  8), ("LD A, (BC)",      1,  8), ("DEC BC",          1,  8),
+        ("INC C",           1,  4), ("DEC C",           1,  4), ("LD C, d8",        2,  8), ("RRCA",            1,  4),
+        ("STOP d8",         2,  4), ("LD DE, d16",      3, 12), ("LD (DE), A",      1,  8), ("INC DE",          1,  8),
+        ("INC D",           1,  4), ("DEC D",           1,  4), ("LD D, d8",        2,  8), ("RLA",             1,  4),
+        ("JR r8",           2, 12), ("ADD HL, DE",      1,  8), ("LD A, (DE)",      1,  8), ("DEC DE",          1,  8),
+        ("INC E",           1,  4), ("DEC E",           1,  4), ("LD E, d8",        2,  8), ("RRA",             1,  4),
+        ("JR NZ, r8",       2,  8), ("LD HL, d16",      3, 12), ("LD (HL+), A",     1,  8), ("INC HL",          1,  8),
+        ("INC H",           1,  4), ("DEC H",           1,  4), ("LD H, d8",        2,  8), ("DAA",             1,  4),
+        ("JR Z, r8",        2,  8), ("ADD HL, HL",      1,  8), ("LD A, (HL+)",     1,  8), ("DEC HL",          1,  8),
+        ("INC L",           1,  4), ("DEC L",           1,  4), ("LD L, d8",        2,  8), ("CPL",             1,  4),
+        ("JR NC, r8",       2,  8), ("LD SP, d16",      3, 12), ("LD (HL-), A",     1,  8), ("INC SP",          1,  8),
+        ("INC (HL)",        1, 12), ("DEC (HL)",        1, 12), ("LD (HL), d8",     2, 12), ("SCF",             1,  4),
+        ("JR C, r8",        2,  8), ("ADD HL, SP",      1,  8), ("LD A, (HL-)",     1,  8), ("DEC SP",          1,  8),
+        ("INC A",           1,  4), ("DEC A",           1,  4), ("LD A, d8",        2,  8), ("CCF",             1,  4),
+        ("LD B, B",         1,  4), ("LD B, C",         1,  4), ("LD B, D",         1,  4), ("LD B, E",         1,  4),
+        ("LD B, H",         1,  4), ("LD B, L",         1,  4), ("LD B, (HL)",      1,  8), ("LD B, A",         1,  4),
+        ("LD C, B",         1,  4), ("LD C, C",         1,  4), ("LD C, D",         1,  4), ("LD C, E",         1,  4),
+        ("LD C, H",         1,  4), ("LD C, L",         1,  4), ("LD C, (HL)",      1,  8), ("LD C, A",         1,  4),
+        ("LD D, B",         1,  4), ("LD D, C",         1,  4), ("LD D, D",         1,  4), ("LD D, E",         1,  4),
+        ("LD D, H",         1,  4), ("LD D, L",         1,  4), ("LD D, (HL)",      1,  8), ("LD D, A",         1,  4),
+        ("LD E, B",         1,  4), ("LD E, C",         1,  4), ("LD E, D",         1,  4), ("LD E, E",         1,  4),
+        ("LD E, H",         1,  4), ("LD E, L",         1,  4), ("LD E, (HL)",      1,  8), ("LD E, A",         1,  4),
+        ("LD H, B",         1,  4), ("LD H, C",         1,  4), ("LD H, D",         1,  4), ("LD H, E",         1,  4),
+        ("LD H, H",         1,  4), ("LD H, L",         1,  4), ("LD H, (HL)",      1,  8), ("LD H, A",         1,  4),
+        ("LD L, B",         1,  4), ("LD L, C",         1,  4), ("LD L, D",         1,  4), ("LD L, E",         1,  4),
+        ("LD L, H",         1,  4), ("LD L, L",         1,  4), ("LD L, (HL)",      1,  8), ("LD L, A",         1,  4),
+        ("LD (HL), B",      1,  8), ("LD (HL), C",      1,  8), ("LD (HL), D",      1,  8), ("LD (HL), E",      1,  8),
+        ("LD (HL), H",      1,  8), ("LD (HL), L",      1,  8), ("HALT",            1,  4), ("LD (HL), A",      1,  8),
+        ("LD A, B",         1,  4), ("LD A, C",         1,  4), ("LD A, D",         1,  4), ("LD A, E",         1,  4),
+        ("LD A, H",         1,  4), ("LD A, L",         1,  4), ("LD A, (HL)",      1,  8), ("LD A, A",         1,  4),
+        ("ADD A, B",        1,  4), ("ADD A, C",        1,  4), ("ADD A, D",        1,  4), ("ADD A, E",        1,  4),
+        ("ADD A, H",        1,  4), ("ADD A, L",        1,  4), ("ADD A, (HL)",     1,  8), ("ADD A, A",        1,  4),
+        ("ADC A, B",        1,  4), ("ADC A, C",        1,  4), ("ADC A, D",        1,  4), ("ADC A, E",        1,  4),
+        ("ADC A, H",        1,  4), ("ADC A, L",        1,  4), ("ADC A, (HL)",     1,  8), ("ADC A, A",        1,  4),
+        ("SUB A, B",        1,  4), ("SUB A, C",        1,  4), ("SUB A, D",        1,  4), ("SUB A, E",        1,  4),
+        ("SUB A, H",        1,  4), ("SUB A, L",        1,  4), ("SUB A, (HL)",     1,  8), ("SUB A, A",        1,  4),
+        ("SBC A, B",        1,  4), ("SBC A, C",        1,  4), ("SBC A, D",        1,  4), ("SBC A, E",        1,  4),
+        ("SBC A, H",        1,  4), ("SBC A, L",        1,  4), ("SBC A, (HL)",     1,  8), ("SBC A, A",        1,  4),
+        ("AND A, B",        1,  4), ("AND A, C",        1,  4), ("AND A, D",        1,  4), ("AND A, E",        1,  4),
+        ("AND A, H",        1,  4), ("AND A, L",        1,  4), ("AND A, (HL)",     1,  8), ("AND A, A",        1,  4),
+        ("XOR A, B",        1,  4), ("XOR A, C",        1,  4), ("XOR A, D",        1,  4), ("XOR A, E",        1,  4),
+        ("XOR A, H",        1,  4), ("XOR A, L",        1,  4), ("XOR A, (HL)",     1,  8), ("XOR A, A",        1,  4),
+        ("OR A, B",         1,  4), ("OR A, C",         1,  4), ("OR A, D",         1,  4), ("OR A, E",         1,  4),
+        ("OR A, H",         1,  4), ("OR A, L",         1,  4), ("OR A, (HL)",      1,  8), ("OR A, A",         1,  4),
+        ("CP A, B",         1,  4), ("CP A, C",         1,  4), ("CP A, D",         1,  4), ("CP A, E",         1,  4),
+        ("CP A, H",         1,  4), ("CP A, L",         1,  4), ("CP A, (HL)",      1,  8), ("CP A, A",         1,  4),
+        ("RET NZ",          1,  8), ("POP BC",          1, 12), ("JP NZ, a16",      3, 12), ("JP a16",          3, 16),
+        ("CALL NZ, a16",    3, 12), ("PUSH BC",         1, 16), ("ADD A, d8",       2,  8), ("RST 00H",         1, 16),
+        ("RET Z",           1,  8), ("RET",             1, 16), ("JP Z, a16",       3, 12), ("PREFIX",          1,  4),
+        ("CALL Z, a16",     3, 12), ("CALL a16",        3, 24), ("ADC A, d8",       2,  8), ("RST 08H",         1, 16),
+        ("RET NC",          1,  8), ("POP DE",          1, 12), ("JP NC, a16",      3, 12), ("X",               0,  0),
+        ("CALL NC, a16",    3, 12), ("PUSH DE",         1, 16), ("SUB A, d8",       2,  8), ("RST 10H",         1, 16),
+        ("RET C",           1,  8), ("RETI",            1, 16), ("JP C, a16",       3, 12), ("X",               0,  0),
+        ("CALL C, a16",     3, 12), ("X",               0,  0), ("SBC A, d8",       2,  8), ("RST 18H",         1, 16),
+        ("LDH (a8), A",     2, 12), ("POP HL",          1, 12), ("LD (C), A",       1,  8), ("X",               0,  0),
+        ("X",               0,  0), ("PUSH HL",         1, 16), ("AND A, d8",       2,  8), ("RST 20H",         1, 16),
+        ("ADD SP, r8",      2, 16), ("JP HL",           1,  4), ("LD (a16), A",     3, 16), ("X",               0,  0),
+        ("X",               0,  0), ("X",               0,  0), ("XOR A, d8",       2,  8), ("RST 28H",         1, 16),
+        ("LDH A, (a8)",     2, 12), ("POP AF",          1, 12), ("LD A, (C)",       1,  8), ("DI",              1,  4),
+        ("X",               0,  0), ("PUSH AF",         1, 16), ("OR A, d8",        2,  8), ("RST 30H",         1, 16),
+        ("LD HL, SP + r8",  2, 12), ("LD SP, HL",       1,  8), ("LD A, (a16)",     3, 16), ("EI",              1,  4),
+        ("X",               0,  0), ("X",               0,  0), ("CP A, d8",        2,  8), ("RST 38H",         1, 16),
+    ];
+
+    const CB_OPERATIONS: [&str; 8] = ["RLC", "RRC", "RL", "RR", "SLA", "SRA", "SWAP", "SRL"];
+    const CB_REGISTERS: [&str; 8] = ["B", "C", "D", "E", "H", "L", "(HL)", "A"];
+
+    fn reference(opcode: OpCode) -> (String, usize, u8) {
+        let (mnemonic, size, ticks) = REFERENCE[usize::from(opcode)];
+        return (mnemonic.to_string(), size, ticks);
+    }
+
+    /// Mnemonic, size and clock ticks of a prefixed opcode, the size and ticks including the prefix: the table is a
+    /// rotation/shift row followed by BIT, RES and SET blocks, each one cycling through the registers
+    fn prefixed_reference(opcode: OpCode) -> (String, usize, u8) {
+        let register = CB_REGISTERS[usize::from(opcode & 0x07)];
+        let bit = (opcode >> 3) & 0x07;
+        let on_hl = register == "(HL)";
+
+        return match opcode >> 6 {
+            0 => (format!("{} {register}", CB_OPERATIONS[usize::from(bit)]), 2, if on_hl { 16 } else { 8 }),
+            1 => (format!("BIT {bit}, {register}"), 2, if on_hl { 12 } else { 8 }),
+            2 => (format!("RES {bit}, {register}"), 2, if on_hl { 16 } else { 8 }),
+            _ => (format!("SET {bit}, {register}"), 2, if on_hl { 16 } else { 8 }),
+        }
+    }
+
+    /// Opcode, byte size, clock ticks and the operand written in the mnemonic for the variant
+    fn fields(instruction: &GenericInstruction) -> (OpCode, usize, u8, Option<&'static str>) {
+        return match instruction {
+            GenericInstruction::Void(instr) => (instr.opcode, instr.byte_size, instr.clock_tick, None),
+            GenericInstruction::Value(instr) => (instr.opcode, instr.byte_size, instr.clock_tick, Some("d8")),
+            GenericInstruction::Wide(instr) => (instr.opcode, instr.byte_size, instr.clock_tick, Some("d16")),
+            GenericInstruction::Near(instr) => (instr.opcode, instr.byte_size, instr.clock_tick, Some("a8")),
+            GenericInstruction::Far(instr) => (instr.opcode, instr.byte_size, instr.clock_tick, Some("a16")),
+            GenericInstruction::Offset(instr) => (instr.opcode, instr.byte_size, instr.clock_tick, Some("r8")),
+        }
+    }
+
+    /// Differences between a table and its reference, one line per wrong field
+    fn check(table: &[GenericInstruction; 256], reference: impl Fn(OpCode) -> (String, usize, u8), prefix_size: usize) -> Vec<String> {
+        let mut errors = Vec::new();
+
+        for (index, instruction) in (0..=OpCode::MAX).zip(table) {
+            let (opcode, byte_size, clock_tick, operand) = fields(instruction);
+            let disassembly = instruction.disassembly();
+            let (mnemonic, size, ticks) = reference(index);
+            let mut error = |message: String| errors.push(format!("{index:#04X} \"{disassembly}\": {message}"));
+
+            if opcode != index { error(format!("opcode is {opcode:#04X}")); }
+            if disassembly != mnemonic { error(format!("mnemonic should be \"{mnemonic}\"")); }
+            if byte_size != size { error(format!("byte size is {byte_size}, expected {size}")); }
+            if clock_tick != ticks { error(format!("clock ticks are {clock_tick}, expected {ticks}")); }
+
+            // Opcodes which don't exist have no size at all
+            let expected_size = if mnemonic == "X" { 0 } else { prefix_size + usize::from(instruction.operand_size()) };
+            if byte_size != expected_size { error(format!("byte size is {byte_size} but the variant takes {expected_size}")); }
+            let operands: Vec<&str> = ["d8", "d16", "a8", "a16", "r8"].into_iter()
+                .filter(|operand| disassembly.split([' ', ',', '(', ')']).any(|word| word == *operand))
+                .collect();
+            if operands != operand.into_iter().collect::<Vec<_>>() { error(format!("variant takes {operand:?}, mnemonic has {operands:?}")); }
+        }
+
+        return errors;
+    }
+
+    #[test]
+    fn test_instructions() {
+        assert_eq!(check(&INSTRUCTIONS, reference, 1), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_prefixed() {
+        assert_eq!(check(&PREFIXED, prefixed_reference, 2), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_check_finds_errors() {
+        let mut table = INSTRUCTIONS;
+        table.swap(0x00, 0x01);
+        let errors = check(&table, reference, 1);
+        assert!(errors.iter().any(|error| error.starts_with("0x00 \"LD BC, d16\": opcode is 0x01")));
+        assert!(errors.iter().any(|error| error.starts_with("0x01 \"NOP\": mnemonic should be \"LD BC, d16\"")));
+    }
+}
\ No newline at end of file
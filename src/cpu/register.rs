@@ -1,9 +1,27 @@
+use crate::state::savestate::{StateError, StateReader, StateWriter, Stateful};
 use crate::utils::bits::{assign_bit, get_bit};
-use crate::utils::types::{FarAddress, PairRegister, Value, WideRegister, WideValue};
+use crate::utils::types::{FarAddress, Value, WideRegister, WideValue};
+
+/// 8-bit halves of a register, ordered so that `high` overlaps the most significant byte of the wide value
+#[cfg(target_endian = "little")]
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct RegisterHalves {
+    pub low: Value,
+    pub high: Value,
+}
+
+#[cfg(target_endian = "big")]
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct RegisterHalves {
+    pub high: Value,
+    pub low: Value,
+}
 
 pub union Register {
     pub as_wide: WideRegister,
-    pub as_pair: PairRegister
+    pub as_pair: RegisterHalves
 }
 
 const ZERO_FLAG_OFFSET: usize = 7;
@@ -27,10 +45,10 @@ pub struct RegisterGroup {
 impl RegisterGroup {
     pub fn new() -> RegisterGroup {
         RegisterGroup {
-            AF: Register { as_pair: (0, 0) },
-            BC: Register { as_pair: (0, 0) },
-            DE: Register { as_pair: (0, 0) },
-            HL: Register { as_pair: (0, 0) },
+            AF: Register { as_pair: RegisterHalves { high: 0, low: 0 } },
+            BC: Register { as_pair: RegisterHalves { high: 0, low: 0 } },
+            DE: Register { as_pair: RegisterHalves { high: 0, low: 0 } },
+            HL: Register { as_pair: RegisterHalves { high: 0, low: 0 } },
 
             SP: 0,
             PC: 0
@@ -39,31 +57,31 @@ impl RegisterGroup {
 
     pub fn get_af(&self) -> WideValue { unsafe { return self.AF.as_wide; } }
     pub fn set_af(&mut self, value: WideValue) { self.AF.as_wide = value; }
-    pub fn get_a(&self) -> Value { unsafe { return self.AF.as_pair.0; } }
-    pub fn set_a(&mut self, value: Value) { self.AF.as_pair.0 = value; }
-    pub fn get_f(&self) -> Value { unsafe { return self.AF.as_pair.1; } }
-    pub fn set_f(&mut self, value: Value) { self.AF.as_pair.1 = value; }
+    pub fn get_a(&self) -> Value { unsafe { return self.AF.as_pair.high; } }
+    pub fn set_a(&mut self, value: Value) { self.AF.as_pair.high = value; }
+    pub fn get_f(&self) -> Value { unsafe { return self.AF.as_pair.low; } }
+    pub fn set_f(&mut self, value: Value) { self.AF.as_pair.low = value; }
 
     pub fn get_bc(&self) -> WideValue { unsafe { return self.BC.as_wide; } }
     pub fn set_bc(&mut self, value: WideValue) { self.BC.as_wide = value; }
-    pub fn get_b(&self) -> Value { unsafe { return self.BC.as_pair.0; } }
-    // pub fn set_b(&mut self, value: Value) { self.BC.as_pair.0 = value; }
-    pub fn get_c(&self) -> Value { unsafe { return self.BC.as_pair.1; } }
-    // pub fn set_c(&mut self, value: Value) { self.BC.as_pair.1 = value; }
+    pub fn get_b(&self) -> Value { unsafe { return self.BC.as_pair.high; } }
+    pub fn set_b(&mut self, value: Value) { self.BC.as_pair.high = value; }
+    pub fn get_c(&self) -> Value { unsafe { return self.BC.as_pair.low; } }
+    pub fn set_c(&mut self, value: Value) { self.BC.as_pair.low = value; }
 
     pub fn get_de(&self) -> WideValue { unsafe { return self.DE.as_wide; } }
     pub fn set_de(&mut self, value: WideValue) { self.DE.as_wide = value; }
-    pub fn get_d(&self) -> Value { unsafe { return self.DE.as_pair.0; } }
-    // pub fn set_d(&mut self, value: Value) { self.DE.as_pair.0 = value; }
-    pub fn get_e(&self) -> Value { unsafe { return self.DE.as_pair.1; } }
-    // pub fn set_e(&mut self, value: Value) { self.DE.as_pair.1 = value; }
+    pub fn get_d(&self) -> Value { unsafe { return self.DE.as_pair.high; } }
+    pub fn set_d(&mut self, value: Value) { self.DE.as_pair.high = value; }
+    pub fn get_e(&self) -> Value { unsafe { return self.DE.as_pair.low; } }
+    pub fn set_e(&mut self, value: Value) { self.DE.as_pair.low = value; }
 
     pub fn get_hl(&self) -> WideValue { unsafe { return self.HL.as_wide; } }
     pub fn set_hl(&mut self, value: WideValue) { self.HL.as_wide = value; }
-    pub fn get_h(&self) -> Value { unsafe { return self.HL.as_pair.0; } }
-    // pub fn set_h(&mut self, value: Value) { self.HL.as_pair.0 = value; }
-    pub fn get_l(&self) -> Value { unsafe { return self.HL.as_pair.1; } }
-    // pub fn set_l(&mut self, value: Value) { self.HL.as_pair.1 = value; }
+    pub fn get_h(&self) -> Value { unsafe { return self.HL.as_pair.high; } }
+    pub fn set_h(&mut self, value: Value) { self.HL.as_pair.high = value; }
+    pub fn get_l(&self) -> Value { unsafe { return self.HL.as_pair.low; } }
+    pub fn set_l(&mut self, value: Value) { self.HL.as_pair.low = value; }
 
     pub fn get_zero_flag(&self) -> bool { return get_bit(self.get_f(), ZERO_FLAG_OFFSET); }
     pub fn set_zero_flag(&mut self, status: bool) { self.set_f(assign_bit(self.get_f(), ZERO_FLAG_OFFSET, status) & CLEAR_MASK); }
@@ -78,16 +96,38 @@ impl RegisterGroup {
     pub fn set_carry_flag(&mut self, status: bool) { self.set_f(assign_bit(self.get_f(), CARRY_FLAG_OFFSET, status) & CLEAR_MASK); }
 }
 
+impl Stateful for RegisterGroup {
+    fn save_state(&self, writer: &mut StateWriter) {
+        writer.write_wide(self.get_af());
+        writer.write_wide(self.get_bc());
+        writer.write_wide(self.get_de());
+        writer.write_wide(self.get_hl());
+        writer.write_wide(self.SP);
+        writer.write_wide(self.PC);
+    }
+
+    fn load_state(&mut self, reader: &mut StateReader) -> Result<(), StateError> {
+        self.set_af(reader.read_wide()?);
+        self.set_bc(reader.read_wide()?);
+        self.set_de(reader.read_wide()?);
+        self.set_hl(reader.read_wide()?);
+        self.SP = reader.read_wide()?;
+        self.PC = reader.read_wide()?;
+
+        return Ok(());
+    }
+}
+
 impl std::fmt::Debug for RegisterGroup {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         unsafe {
             f.debug_struct("Registers")
-                .field("AF", &format_args!("({}, {}) | {}", self.AF.as_pair.0, self.AF.as_pair.1, self.AF.as_wide))
-                .field("BC", &format_args!("({}, {}) | {}", self.BC.as_pair.0, self.BC.as_pair.1, self.BC.as_wide))
-                .field("DE", &format_args!("({}, {}) | {}", self.DE.as_pair.0, self.DE.as_pair.1, self.DE.as_wide))
-                .field("HL", &format_args!("({}, {}) | {}", self.HL.as_pair.0, self.HL.as_pair.1, self.HL.as_wide))
-                .field("SP", &self.SP)
-                .field("PC", &self.PC)
+                .field("AF", &format_args!("({:#04X}, {:#04X}) | {:#06X}", self.AF.as_pair.high, self.AF.as_pair.low, self.AF.as_wide))
+                .field("BC", &format_args!("({:#04X}, {:#04X}) | {:#06X}", self.BC.as_pair.high, self.BC.as_pair.low, self.BC.as_wide))
+                .field("DE", &format_args!("({:#04X}, {:#04X}) | {:#06X}", self.DE.as_pair.high, self.DE.as_pair.low, self.DE.as_wide))
+                .field("HL", &format_args!("({:#04X}, {:#04X}) | {:#06X}", self.HL.as_pair.high, self.HL.as_pair.low, self.HL.as_wide))
+                .field("SP", &format_args!("{:#06X}", self.SP))
+                .field("PC", &format_args!("{:#06X}", self.PC))
                 .field("Z", &u8::from(self.get_zero_flag()))
                 .field("S", &u8::from(self.get_subtraction_flag()))
                 .field("HC", &u8::from(self.get_half_carry_flag()))
@@ -95,4 +135,35 @@ impl std::fmt::Debug for RegisterGroup {
                 .finish()
         }
     }
+}
+
+impl std::fmt::Display for RegisterGroup {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let flag = |status: bool, name: char| if status { name } else { '-' };
+
+        writeln!(f, "A: {:02X}  F: {:02X}  [{}{}{}{}]", self.get_a(), self.get_f(),
+                 flag(self.get_zero_flag(), 'Z'), flag(self.get_subtraction_flag(), 'N'), flag(self.get_half_carry_flag(), 'H'), flag(self.get_carry_flag(), 'C'))?;
+        writeln!(f, "B: {:02X}  C: {:02X}  BC: {:04X}", self.get_b(), self.get_c(), self.get_bc())?;
+        writeln!(f, "D: {:02X}  E: {:02X}  DE: {:04X}", self.get_d(), self.get_e(), self.get_de())?;
+        writeln!(f, "H: {:02X}  L: {:02X}  HL: {:04X}", self.get_h(), self.get_l(), self.get_hl())?;
+        write!(f, "SP: {:04X}  PC: {:04X}", self.SP, self.PC)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RegisterGroup;
+
+    #[test]
+    fn test_halves() {
+        let mut registers = RegisterGroup::new();
+        registers.set_a(0x56);
+        registers.set_f(0x70);
+        registers.set_b(0x12);
+        registers.set_c(0x34);
+        assert_eq!((registers.get_af(), registers.get_bc()), (0x5670, 0x1234));
+
+        registers.set_hl(0xC0DE);
+        assert_eq!((registers.get_h(), registers.get_l()), (0xC0, 0xDE));
+    }
 }
\ No newline at end of file
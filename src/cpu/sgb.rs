@@ -0,0 +1,562 @@
+//! Super Game Boy: command packets sent bit by bit through P1, which color the screen by regions, set the border around
+//! it and enable the multiplayer adapter
+
+use crate::cpu::io::{LCDC, LCDC_BG_MAP_BIT, LCDC_TILE_DATA_BIT, P1, VRAM_START};
+use crate::cpu::joypad::{self, LINES_MASK, NO_BUTTONS, P1_UNUSED_BITS};
+use crate::cpu::memory::Memory;
+use crate::state::savestate::{StateError, StateReader, StateWriter, Stateful};
+use crate::utils::bits::get_bit;
+use crate::utils::log::log;
+use crate::utils::types::{Byte, FarAddress, Value, WideValue};
+
+/// SGB flag of the cartridge header, only honored with the new licensee code
+pub const SGB_FLAG_ADDR: FarAddress = 0x0146;
+const SGB_FLAG_SUPPORTED: Byte = 0x03;
+const OLD_LICENSEE_ADDR: FarAddress = 0x014B;
+const USE_NEW_LICENSEE: Byte = 0x33;
+
+/// Size of the picture sent to the TV, the Game Boy screen being in the middle of the border
+pub const BORDER_WIDTH: usize = 256;
+pub const BORDER_HEIGHT: usize = 224;
+pub const SCREEN_LEFT: usize = 48;
+pub const SCREEN_TOP: usize = 40;
+
+const PACKET_SIZE: usize = 16;
+const PACKET_BITS: usize = PACKET_SIZE * 8;
+/// P14 and P15 lines of P1, driven low to send a reset pulse, a 0 (P14 only) or a 1 (P15 only)
+const RESET_PULSE: Value = 0x00;
+const ZERO_PULSE: Value = 0x20;
+const ONE_PULSE: Value = 0x10;
+const P15_BIT: usize = 5;
+
+const COLORS_PER_PALETTE: usize = 4;
+const PALETTE_COUNT: usize = 4;
+const SYSTEM_PALETTE_COUNT: usize = 512;
+/// The 8x8 cells of the screen, each colored by one of the 4 palettes
+const CELL_COLUMNS: usize = 20;
+const CELL_ROWS: usize = 18;
+const CELLS: usize = CELL_COLUMNS * CELL_ROWS;
+/// Attribute files hold the palettes of the 360 cells, 4 per byte
+const ATTRIBUTE_FILE_SIZE: usize = CELLS / 4;
+const ATTRIBUTE_FILES: usize = 45;
+/// Data of `CHR_TRN`, `PAL_TRN`, `PCT_TRN` and `ATTR_TRN`, read from the screen
+const TRANSFER_SIZE: usize = 0x1000;
+/// 256 tiles of 8x8 pixels with 4 bits per pixel, in the SNES format
+const BORDER_TILE_SIZE: usize = 32;
+const BORDER_TILES: usize = 256;
+/// 32x32 map of little-endian SNES tile entries, followed by the border palettes 4-7 of 16 colors
+const BORDER_MAP_COLUMNS: usize = 32;
+const BORDER_PALETTES: usize = 0x800;
+const BORDER_SIZE: usize = BORDER_PALETTES + 4 * 16 * 2;
+
+/// The "1-A" palette, shown until the game sets its own
+const DEFAULT_PALETTE: [WideValue; COLORS_PER_PALETTE] = [0x639E, 0x263A, 0x10D4, 0x2866];
+
+// Commands, in bits 3-7 of the first byte of a packet, the number of packets being in bits 0-2
+const PAL01: Byte = 0x00;
+const PAL23: Byte = 0x01;
+const PAL03: Byte = 0x02;
+const PAL12: Byte = 0x03;
+const ATTR_BLK: Byte = 0x04;
+const ATTR_LIN: Byte = 0x05;
+const ATTR_DIV: Byte = 0x06;
+const ATTR_CHR: Byte = 0x07;
+const PAL_SET: Byte = 0x0A;
+const PAL_TRN: Byte = 0x0B;
+const MLT_REQ: Byte = 0x11;
+const CHR_TRN: Byte = 0x13;
+const PCT_TRN: Byte = 0x14;
+const ATTR_TRN: Byte = 0x15;
+const ATTR_SET: Byte = 0x16;
+const MASK_EN: Byte = 0x17;
+
+/// What `MASK_EN` shows instead of the game screen, usually while a transfer is sent through it
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mask {
+    None,
+    /// The last frame stays on screen
+    Freeze,
+    Black,
+    /// Color 0 of the palettes
+    Blank,
+}
+
+pub struct Sgb {
+    /// The cartridge supports the SGB, which listens to the packets
+    pub enabled: bool,
+    /// Next bit of the packet, `None` until a reset pulse starts one
+    bit: Option<usize>,
+    packet: [Byte; PACKET_SIZE],
+    /// Packets of the command being received
+    command: Vec<Byte>,
+    /// P14 and P15 at the last write
+    lines: Value,
+    palettes: [WideValue; PALETTE_COUNT * COLORS_PER_PALETTE],
+    /// Palette of each cell
+    attributes: [Byte; CELLS],
+    system_palettes: Box<[Byte]>,
+    attribute_files: Box<[Byte]>,
+    border_tiles: Box<[Byte]>,
+    border: Box<[Byte]>,
+    pub mask: Mask,
+    players: u8,
+    player: u8,
+}
+
+impl Default for Sgb {
+    fn default() -> Sgb {
+        return Sgb {
+            enabled: false,
+            bit: None,
+            packet: [0; PACKET_SIZE],
+            command: Vec::new(),
+            lines: LINES_MASK,
+            palettes: [DEFAULT_PALETTE; PALETTE_COUNT].concat().try_into().unwrap_or_default(),
+            attributes: [0; CELLS],
+            system_palettes: vec![0; SYSTEM_PALETTE_COUNT * COLORS_PER_PALETTE * 2].into_boxed_slice(),
+            attribute_files: vec![0; ATTRIBUTE_FILES * ATTRIBUTE_FILE_SIZE].into_boxed_slice(),
+            border_tiles: vec![0; BORDER_TILES * BORDER_TILE_SIZE].into_boxed_slice(),
+            border: vec![0; BORDER_SIZE].into_boxed_slice(),
+            mask: Mask::None,
+            players: 1,
+            player: 0,
+        }
+    }
+}
+
+fn wide(data: &[Byte], offset: usize) -> WideValue {
+    return WideValue::from_le_bytes([data[offset], data[offset + 1]]);
+}
+
+impl Sgb {
+    /// Whether the cartridge header enables the SGB functions
+    pub fn supported(memory: &Memory) -> bool {
+        return memory.peek(SGB_FLAG_ADDR) == SGB_FLAG_SUPPORTED && memory.peek(OLD_LICENSEE_ADDR) == USE_NEW_LICENSEE;
+    }
+
+    /// 15-bit color of a shade at a position of the screen
+    pub fn color(&self, x: usize, y: usize, shade: u8) -> WideValue {
+        let palette = usize::from(self.attributes[(y / 8) * CELL_COLUMNS + x / 8]);
+        return self.palettes[palette * COLORS_PER_PALETTE + usize::from(shade)];
+    }
+
+    /// 15-bit color of a pixel of the border, color 0 of the border palettes showing color 0 of the game palettes
+    pub fn border_color(&self, x: usize, y: usize) -> WideValue {
+        let entry = wide(&self.border, ((y / 8) * BORDER_MAP_COLUMNS + x / 8) * 2);
+        let (column, row) = (if get_bit(entry, 14) { 7 - x % 8 } else { x % 8 }, if get_bit(entry, 15) { 7 - y % 8 } else { y % 8 });
+        let tile = &self.border_tiles[usize::from(entry & 0xFF) * BORDER_TILE_SIZE..];
+
+        // Bit planes 0 and 1 interleaved in the first 16 bytes, 2 and 3 in the next 16
+        let bit = 7 - column;
+        let planes = [tile[row * 2], tile[row * 2 + 1], tile[16 + row * 2], tile[16 + row * 2 + 1]];
+        let color = planes.iter().enumerate().fold(0, |color, (plane, byte)| color | (usize::from((byte >> bit) & 1) << plane));
+        if color == 0 { return self.palettes[0]; }
+
+        // Palettes 4-7
+        let palette = usize::from((entry >> 10) & 0x03);
+        return wide(&self.border, BORDER_PALETTES + (palette * 16 + color) * 2);
+    }
+
+    /// Sets the colors 1-3 of two palettes, and the color 0 shared by all the palettes
+    fn set_palettes(&mut self, (first, second): (usize, usize), data: &[Byte]) {
+        for palette in 0..PALETTE_COUNT { self.palettes[palette * COLORS_PER_PALETTE] = wide(data, 1); }
+        for color in 1..COLORS_PER_PALETTE {
+            self.palettes[first * COLORS_PER_PALETTE + color] = wide(data, 1 + color * 2);
+            self.palettes[second * COLORS_PER_PALETTE + color] = wide(data, 7 + color * 2);
+        }
+    }
+
+    /// Palettes of the cells from an attribute file, 4 cells per byte from the most significant bits
+    fn set_attribute_file(&mut self, file: usize) {
+        let data = &self.attribute_files[file * ATTRIBUTE_FILE_SIZE..(file + 1) * ATTRIBUTE_FILE_SIZE];
+        for (cell, palette) in self.attributes.iter_mut().enumerate() {
+            *palette = (data[cell / 4] >> (6 - (cell % 4) * 2)) & 0x03;
+        }
+    }
+
+    /// `ATTR_BLK`: palettes inside, on and outside the borders of rectangles
+    fn attribute_blocks(&mut self, data: &[Byte]) {
+        let count = usize::from(data[1]);
+        for block in data[2..].chunks_exact(6).take(count) {
+            let (control, palettes) = (block[0] & 0x07, block[1]);
+            let (left, top, right, bottom) = (usize::from(block[2]), usize::from(block[3]), usize::from(block[4]), usize::from(block[5]));
+            let inside = (palettes & 0x03, get_bit(control, 0));
+            // Setting only the inside or the outside also sets the border
+            let border = match control {
+                0b001 => inside,
+                0b100 => ((palettes >> 4) & 0x03, true),
+                _ => ((palettes >> 2) & 0x03, get_bit(control, 1)),
+            };
+            let outside = ((palettes >> 4) & 0x03, get_bit(control, 2));
+
+            for (cell, attribute) in self.attributes.iter_mut().enumerate() {
+                let (x, y) = (cell % CELL_COLUMNS, cell / CELL_COLUMNS);
+                let (palette, enabled) = if x > left && x < right && y > top && y < bottom {
+                    inside
+                } else if x >= left && x <= right && y >= top && y <= bottom {
+                    border
+                } else {
+                    outside
+                };
+                if enabled { *attribute = palette; }
+            }
+        }
+    }
+
+    /// `ATTR_LIN`: palettes of whole lines and columns
+    fn attribute_lines(&mut self, data: &[Byte]) {
+        let count = usize::from(data[1]);
+        for line in data[2..].iter().take(count) {
+            let (index, palette) = (usize::from(line & 0x1F), (line >> 5) & 0x03);
+            for (cell, attribute) in self.attributes.iter_mut().enumerate() {
+                let horizontal = get_bit(*line, 7);
+                if (horizontal && cell / CELL_COLUMNS == index) || (!horizontal && cell % CELL_COLUMNS == index) { *attribute = palette; }
+            }
+        }
+    }
+
+    /// `ATTR_DIV`: palettes of both sides of a line and on it
+    fn attribute_division(&mut self, data: &[Byte]) {
+        let (after, before, on) = (data[1] & 0x03, (data[1] >> 2) & 0x03, (data[1] >> 4) & 0x03);
+        let (horizontal, line) = (get_bit(data[1], 6), usize::from(data[2]));
+
+        for (cell, attribute) in self.attributes.iter_mut().enumerate() {
+            let position = if horizontal { cell / CELL_COLUMNS } else { cell % CELL_COLUMNS };
+            *attribute = match position.cmp(&line) {
+                std::cmp::Ordering::Less => before,
+                std::cmp::Ordering::Equal => on,
+                std::cmp::Ordering::Greater => after,
+            };
+        }
+    }
+
+    /// `ATTR_CHR`: palettes of consecutive cells, left to right or top to bottom
+    fn attribute_cells(&mut self, data: &[Byte]) {
+        let (mut x, mut y) = (usize::from(data[1]) % CELL_COLUMNS, usize::from(data[2]) % CELL_ROWS);
+        let count = usize::from(wide(data, 3)).min(CELLS);
+        let vertical = data[5] & 0x01 != 0;
+
+        for index in 0..count {
+            let Some(byte) = data.get(6 + index / 4) else { break };
+            self.attributes[y * CELL_COLUMNS + x] = (byte >> (6 - (index % 4) * 2)) & 0x03;
+
+            if vertical {
+                y = (y + 1) % CELL_ROWS;
+                if y == 0 { x = (x + 1) % CELL_COLUMNS; }
+            } else {
+                x = (x + 1) % CELL_COLUMNS;
+                if x == 0 { y = (y + 1) % CELL_ROWS; }
+            }
+        }
+    }
+
+    /// `PAL_SET`: palettes 0-3 from the system palettes, color 0 of the first one being shared
+    fn set_system_palettes(&mut self, data: &[Byte]) {
+        for palette in 0..PALETTE_COUNT {
+            let system = usize::from(wide(data, 1 + palette * 2)) % SYSTEM_PALETTE_COUNT;
+            for color in 0..COLORS_PER_PALETTE {
+                self.palettes[palette * COLORS_PER_PALETTE + color] = wide(&self.system_palettes, (system * COLORS_PER_PALETTE + color) * 2);
+            }
+        }
+        for palette in 1..PALETTE_COUNT { self.palettes[palette * COLORS_PER_PALETTE] = self.palettes[0]; }
+
+        self.set_attribute_file_and_mask(data[9]);
+    }
+
+    /// Applies an attribute file if bit 7 is set, and cancels the mask if bit 6 is
+    fn set_attribute_file_and_mask(&mut self, flags: Byte) {
+        let file = usize::from(flags & 0x3F);
+        if get_bit(flags, 7) && file < ATTRIBUTE_FILES { self.set_attribute_file(file); }
+        if get_bit(flags, 6) { self.mask = Mask::None; }
+    }
+
+    /// Joypad read through P1 when both lines are high: the ID of the selected one, 0x0F for the first
+    fn joypad_id(&self) -> Value {
+        return NO_BUTTONS - self.player;
+    }
+}
+
+impl Stateful for Sgb {
+    fn save_state(&self, writer: &mut StateWriter) {
+        let mask = match self.mask {
+            Mask::None => 0,
+            Mask::Freeze => 1,
+            Mask::Black => 2,
+            Mask::Blank => 3,
+        };
+        writer.write_bytes(&[u8::from(self.enabled), mask, self.players, self.player]);
+        for color in self.palettes { writer.write_wide(color); }
+        writer.write_bytes(&self.attributes);
+        writer.write_bytes(&self.system_palettes);
+        writer.write_bytes(&self.attribute_files);
+        writer.write_bytes(&self.border_tiles);
+        writer.write_bytes(&self.border);
+    }
+
+    /// The packet being received isn't kept, games send their packets within a frame
+    fn load_state(&mut self, reader: &mut StateReader) -> Result<(), StateError> {
+        let flags = reader.read_bytes(4)?;
+        self.enabled = flags[0] != 0;
+        self.mask = match flags[1] {
+            1 => Mask::Freeze,
+            2 => Mask::Black,
+            3 => Mask::Blank,
+            _ => Mask::None,
+        };
+        self.players = flags[2].clamp(1, 4);
+        self.player = flags[3] % self.players;
+        for color in &mut self.palettes { *color = reader.read_wide()?; }
+        self.attributes.copy_from_slice(reader.read_bytes(CELLS)?);
+        self.system_palettes.copy_from_slice(reader.read_bytes(self.system_palettes.len())?);
+        self.attribute_files.copy_from_slice(reader.read_bytes(self.attribute_files.len())?);
+        self.border_tiles.copy_from_slice(reader.read_bytes(self.border_tiles.len())?);
+        self.border.copy_from_slice(reader.read_bytes(self.border.len())?);
+
+        self.bit = None;
+        self.command.clear();
+        self.lines = LINES_MASK;
+        return Ok(());
+    }
+}
+
+/// Data sent by the VRAM transfer commands: the SNES reads the screen, where the game shows the 256 tiles of the data
+/// in order, 20 per row
+fn screen_data(memory: &Memory) -> Vec<Byte> {
+    let lcdc = memory.peek(LCDC);
+    let map: FarAddress = if get_bit(lcdc, LCDC_BG_MAP_BIT) { 0x9C00 } else { 0x9800 };
+
+    return (0..TRANSFER_SIZE / 16)
+        .flat_map(|index| {
+            #[allow(clippy::cast_possible_truncation)]
+            let entry = memory.peek(map + ((index / CELL_COLUMNS) * 32 + index % CELL_COLUMNS) as FarAddress);
+            // Signed indexes, relative to tile 256 (0x9000)
+            let tile = if get_bit(lcdc, LCDC_TILE_DATA_BIT) { usize::from(entry) } else { 256_usize.wrapping_add_signed(isize::from(entry.cast_signed())) };
+            #[allow(clippy::cast_possible_truncation)]
+            let addr = VRAM_START + (tile * 16) as FarAddress;
+            return (0..16).map(move |offset| memory.peek(addr + offset));
+        })
+        .collect();
+}
+
+fn execute(memory: &mut Memory, data: &[Byte]) {
+    let command = data[0] >> 3;
+    log!(Debug, "MEMORY", format!("SGB command {command:#04X}"));
+
+    let sgb = &mut memory.sgb;
+    match command {
+        PAL01 => sgb.set_palettes((0, 1), data),
+        PAL23 => sgb.set_palettes((2, 3), data),
+        PAL03 => sgb.set_palettes((0, 3), data),
+        PAL12 => sgb.set_palettes((1, 2), data),
+        ATTR_BLK => sgb.attribute_blocks(data),
+        ATTR_LIN => sgb.attribute_lines(data),
+        ATTR_DIV => sgb.attribute_division(data),
+        ATTR_CHR => sgb.attribute_cells(data),
+        PAL_SET => sgb.set_system_palettes(data),
+        ATTR_SET => sgb.set_attribute_file_and_mask(data[1] | 0x80),
+        MLT_REQ => {
+            sgb.players = match data[1] & 0x03 {
+                1 => 2,
+                3 => 4,
+                _ => 1,
+            };
+            sgb.player = 0;
+        }
+        MASK_EN => {
+            sgb.mask = match data[1] & 0x03 {
+                1 => Mask::Freeze,
+                2 => Mask::Black,
+                3 => Mask::Blank,
+                _ => Mask::None,
+            };
+        }
+        PAL_TRN | ATTR_TRN | CHR_TRN | PCT_TRN => {
+            let screen = screen_data(memory);
+            let sgb = &mut memory.sgb;
+            match command {
+                PAL_TRN => sgb.system_palettes.copy_from_slice(&screen),
+                ATTR_TRN => sgb.attribute_files.copy_from_slice(&screen[..ATTRIBUTE_FILES * ATTRIBUTE_FILE_SIZE]),
+                // Object tiles are for the SNES games using the SGB, not the Game Boy ones
+                CHR_TRN if get_bit(data[1], 1) => { log!(Debug, "MEMORY", String::from("Ignored CHR_TRN of object tiles")); }
+                CHR_TRN => {
+                    let half = usize::from(data[1] & 0x01) * TRANSFER_SIZE;
+                    sgb.border_tiles[half..half + TRANSFER_SIZE].copy_from_slice(&screen);
+                }
+                _ => sgb.border.copy_from_slice(&screen[..BORDER_SIZE]),
+            }
+        }
+        _ => { log!(Debug, "MEMORY", format!("Ignored SGB command {command:#04X}")); }
+    }
+}
+
+/// Receives a packet bit from a write to P1, executing the command once its last packet is received
+fn receive(memory: &mut Memory, lines: Value) {
+    let sgb = &mut memory.sgb;
+    let Some(bit) = sgb.bit else { return };
+
+    if bit == PACKET_BITS {
+        // Stop bit
+        sgb.bit = None;
+        if lines != ZERO_PULSE { return; }
+
+        sgb.command.extend_from_slice(&sgb.packet);
+        let packets = usize::from(sgb.command[0] & 0x07).max(1);
+        if sgb.command.len() < packets * PACKET_SIZE { return; }
+
+        let command = std::mem::take(&mut sgb.command);
+        execute(memory, &command);
+        return;
+    }
+
+    let byte = &mut sgb.packet[bit / 8];
+    if bit % 8 == 0 { *byte = 0; }
+    if lines == ONE_PULSE { *byte |= 1 << (bit % 8); }
+    sgb.bit = Some(bit + 1);
+}
+
+/// Write to P1: sends packets to the SGB and selects the joypads in multiplayer mode
+pub fn write_p1(memory: &mut Memory, value: Value) {
+    let lines = value & LINES_MASK;
+    let previous = memory.sgb.lines;
+    if lines != previous {
+        memory.sgb.lines = lines;
+
+        match lines {
+            RESET_PULSE => {
+                memory.sgb.bit = Some(0);
+                memory.sgb.packet = [0; PACKET_SIZE];
+            }
+            ZERO_PULSE | ONE_PULSE => receive(memory, lines),
+            _ => {
+                // The next joypad is selected when P15 goes high, as when the games read the buttons
+                let sgb = &mut memory.sgb;
+                if sgb.bit.is_none() && !get_bit(previous, P15_BIT) { sgb.player = (sgb.player + 1) % sgb.players; }
+            }
+        }
+    }
+
+    // Only the first joypad is connected
+    let sgb = &memory.sgb;
+    let buttons = match (lines, sgb.player) {
+        (LINES_MASK, _) if sgb.players > 1 => sgb.joypad_id(),
+        (_, 0) => joypad::selected_buttons(memory, lines),
+        _ => NO_BUTTONS,
+    };
+    memory.memory[P1 as usize] = P1_UNUSED_BITS | lines | buttons;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cpu::io::{LCDC, P1};
+    use crate::cpu::memory::Memory;
+    use super::{Mask, BORDER_PALETTES, CELL_COLUMNS};
+
+    fn sgb_memory() -> Memory {
+        let mut memory = Memory::new(0x10000);
+        memory.sgb.enabled = true;
+        return memory;
+    }
+
+    /// Sends the packets of a command through P1, as the games do
+    fn send(memory: &mut Memory, data: &[u8]) {
+        for packet in data.chunks(16) {
+            let mut packet = packet.to_vec();
+            packet.resize(16, 0);
+
+            memory.write_far_addr(P1, 0x00);
+            memory.write_far_addr(P1, 0x30);
+            for byte in packet {
+                for bit in 0..8 {
+                    memory.write_far_addr(P1, if (byte >> bit) & 1 == 1 { 0x10 } else { 0x20 });
+                    memory.write_far_addr(P1, 0x30);
+                }
+            }
+            memory.write_far_addr(P1, 0x20);
+            memory.write_far_addr(P1, 0x30);
+        }
+    }
+
+    #[test]
+    fn test_palettes() {
+        let mut memory = sgb_memory();
+        // PAL12: color 0, then colors 1-3 of palettes 1 and 2
+        send(&mut memory, &[0x19, 0x00, 0x7C, 0x01, 0x00, 0x02, 0x00, 0x03, 0x00, 0x04, 0x00, 0x05, 0x00, 0x06, 0x00]);
+        let sgb = &memory.sgb;
+        assert_eq!((sgb.palettes[0], sgb.palettes[4], sgb.palettes[12]), (0x7C00, 0x7C00, 0x7C00));
+        assert_eq!(&sgb.palettes[5..8], &[0x0001, 0x0002, 0x0003]);
+        assert_eq!(&sgb.palettes[9..12], &[0x0004, 0x0005, 0x0006]);
+        // Palette 3 is kept
+        assert_eq!(sgb.palettes[13], 0x263A);
+
+        // Ignored when the SGB isn't enabled
+        let mut memory = Memory::new(0x10000);
+        send(&mut memory, &[0x19, 0x00, 0x7C]);
+        assert_eq!(memory.sgb.palettes[0], 0x639E);
+    }
+
+    #[test]
+    fn test_attributes() {
+        let mut memory = sgb_memory();
+        // ATTR_BLK: inside of (1, 1)-(4, 3) in palette 1, its border in palette 2, outside in palette 3
+        send(&mut memory, &[0x21, 0x01, 0x07, 0b0011_1001, 1, 1, 4, 3]);
+        let palette = |memory: &Memory, x: usize, y: usize| memory.sgb.attributes[y * CELL_COLUMNS + x];
+        assert_eq!((palette(&memory, 2, 2), palette(&memory, 1, 2), palette(&memory, 4, 3), palette(&memory, 5, 2)), (1, 2, 2, 3));
+
+        // ATTR_LIN: row 5 in palette 1, column 2 in palette 2
+        send(&mut memory, &[0x29, 0x02, 0x80 | 0x20 | 5, 0x40 | 2]);
+        assert_eq!((palette(&memory, 10, 5), palette(&memory, 2, 10)), (1, 2));
+
+        // ATTR_DIV: horizontal line 9 in palette 2, palette 1 above and 3 below
+        send(&mut memory, &[0x31, 0x40 | 0x20 | 0x04 | 0x03, 9]);
+        assert_eq!((palette(&memory, 0, 8), palette(&memory, 0, 9), palette(&memory, 0, 10)), (1, 2, 3));
+
+        // ATTR_CHR: 5 cells from (18, 0), left to right, wrapping to the next row
+        send(&mut memory, &[0x39, 18, 0, 5, 0, 0, 0b0110_1100, 0b1100_0000]);
+        assert_eq!((palette(&memory, 18, 0), palette(&memory, 19, 0), palette(&memory, 0, 1), palette(&memory, 1, 1), palette(&memory, 2, 1)), (1, 2, 3, 0, 3));
+        assert_eq!(memory.sgb.color(19 * 8, 0, 1), memory.sgb.palettes[2 * 4 + 1]);
+    }
+
+    #[test]
+    fn test_transfers() {
+        let mut memory = sgb_memory();
+        // Map showing tiles 0-255 in order from 0x8000
+        memory.memory[LCDC as usize] = 0x91;
+        for (index, tile) in (0..256).zip(0..=0xFF) { memory.memory[0x9800 + (index / 20) * 32 + index % 20] = tile; }
+
+        // PAL_TRN with system palette 1 red, then PAL_SET selecting it for palette 0
+        memory.memory[0x8008..0x800A].copy_from_slice(&[0x1F, 0x00]);
+        send(&mut memory, &[0x59]);
+        send(&mut memory, &[0x51, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]);
+        assert_eq!(memory.sgb.palettes[0], 0x001F);
+
+        // CHR_TRN of the first tiles, then PCT_TRN with tile 1 at the top-left corner of the border, in palette 5
+        memory.memory[0x8000..0x9000].fill(0);
+        memory.memory[0x8020] = 0x80;
+        send(&mut memory, &[0x99, 0x00]);
+        memory.memory[0x8000..0x8002].copy_from_slice(&[0x01, 0x05 << 2]);
+        memory.memory[0x8000 + BORDER_PALETTES + 0x20 + 2..0x8000 + BORDER_PALETTES + 0x20 + 4].copy_from_slice(&[0xE0, 0x03]);
+        send(&mut memory, &[0xA1]);
+        assert_eq!(memory.sgb.border_color(0, 0), 0x03E0);
+        // Color 0 is transparent
+        assert_eq!(memory.sgb.border_color(1, 0), 0x001F);
+
+        // MASK_EN
+        send(&mut memory, &[0xB9, 0x02]);
+        assert_eq!(memory.sgb.mask, Mask::Black);
+    }
+
+    #[test]
+    fn test_multiplayer() {
+        let mut memory = sgb_memory();
+        // MLT_REQ for 2 players
+        send(&mut memory, &[0x89, 0x01]);
+        assert_eq!(memory.peek(P1) & 0x0F, 0x0F);
+
+        memory.write_far_addr(P1, 0x10);
+        memory.write_far_addr(P1, 0x30);
+        assert_eq!(memory.peek(P1) & 0x0F, 0x0E);
+        memory.write_far_addr(P1, 0x10);
+        memory.write_far_addr(P1, 0x30);
+        assert_eq!(memory.peek(P1) & 0x0F, 0x0F);
+    }
+}
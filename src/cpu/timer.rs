@@ -0,0 +1,159 @@
+//! Timer: DIV is the high byte of a counter incremented at every clock tick of the CPU, and TIMA is incremented on the
+//! falling edges of the counter bit selected in TAC, requesting the timer interrupt when it overflows
+
+use crate::cpu::interrupts::{self, TIMER_INTERRUPT_BIT};
+use crate::cpu::io::{DIV, TAC, TAC_CLOCK_MASK, TAC_ENABLE_BIT, TIMA, TMA};
+use crate::cpu::memory::Memory;
+use crate::state::savestate::{StateError, StateReader, StateWriter, Stateful};
+use crate::utils::bits::get_bit;
+use crate::utils::types::{Value, WideValue};
+
+/// The counter is incremented a M-cycle at a time
+const TICKS_PER_M_CYCLE: WideValue = 4;
+/// Bit of the counter whose falling edge increments TIMA, for each clock select of TAC (4096, 262144, 65536 and
+/// 16384 Hz)
+const CLOCK_BITS: [usize; 4] = [9, 3, 5, 7];
+/// Unused bits of TAC, which read as 1
+const TAC_UNUSED_BITS: Value = 0xF8;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Timer {
+    /// System counter, whose high byte is DIV
+    counter: WideValue,
+    /// Last value written to TAC, kept apart from the memory to detect the falling edges caused by its writes
+    tac: Value,
+}
+
+impl Timer {
+    /// Whether the counter bit selected by TAC is set while the timer is enabled, TIMA being incremented when it clears
+    fn input(self) -> bool {
+        return get_bit(self.tac, TAC_ENABLE_BIT) && get_bit(self.counter, CLOCK_BITS[usize::from(self.tac & TAC_CLOCK_MASK)]);
+    }
+}
+
+impl Stateful for Timer {
+    fn save_state(&self, writer: &mut StateWriter) {
+        writer.write_wide(self.counter);
+        writer.write_bytes(&[self.tac]);
+    }
+
+    fn load_state(&mut self, reader: &mut StateReader) -> Result<(), StateError> {
+        self.counter = reader.read_wide()?;
+        self.tac = reader.read_bytes(1)?[0];
+
+        return Ok(());
+    }
+}
+
+/// Increments TIMA, which is reloaded from TMA when it overflows.
+/// The reload and the interrupt aren't delayed by a M-cycle as on hardware, TIMA never reads 0 after an overflow.
+fn increment_tima(memory: &mut Memory) {
+    let (tima, overflow) = memory.memory[TIMA as usize].overflowing_add(1);
+    memory.memory[TIMA as usize] = if overflow { memory.memory[TMA as usize] } else { tima };
+    if overflow { interrupts::request(memory, TIMER_INTERRUPT_BIT); }
+}
+
+/// Sets the counter and TAC, incrementing TIMA if the timer input falls: writing DIV or TAC can increment TIMA too
+fn update(memory: &mut Memory, counter: WideValue, tac: Value) {
+    let input = memory.timer.input();
+    memory.timer = Timer { counter, tac };
+    memory.memory[DIV as usize] = counter.to_be_bytes()[0];
+    memory.memory[TAC as usize] = TAC_UNUSED_BITS | tac;
+
+    if input && !memory.timer.input() { increment_tima(memory); }
+}
+
+/// Advances the timer by the clock ticks elapsed. The counter is driven by the CPU clock, it runs twice as fast in
+/// double speed.
+pub fn tick(memory: &mut Memory, ticks: u64) {
+    let cpu_ticks = ticks << u8::from(memory.cgb.double_speed);
+    for _ in 0..cpu_ticks / u64::from(TICKS_PER_M_CYCLE) {
+        update(memory, memory.timer.counter.wrapping_add(TICKS_PER_M_CYCLE), memory.timer.tac);
+    }
+}
+
+/// Writes to DIV reset the whole counter
+pub fn write_div(memory: &mut Memory) {
+    update(memory, 0, memory.timer.tac);
+}
+
+pub fn write_tac(memory: &mut Memory, value: Value) {
+    update(memory, memory.timer.counter, value & !TAC_UNUSED_BITS);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cpu::execution::step;
+    use crate::cpu::interrupts::pending;
+    use crate::cpu::io::{DIV, IE, TAC, TIMA, TMA};
+    use crate::cpu::memory::Memory;
+    use super::tick;
+
+    #[test]
+    fn test_div() {
+        let mut memory = Memory::new(0x10000);
+        tick(&mut memory, 0x1234);
+        assert_eq!(memory.peek(DIV), 0x12);
+
+        memory.write_far_addr(DIV, 0x42);
+        assert_eq!(memory.peek(DIV), 0x00);
+
+        // Twice as fast in double speed
+        memory.cgb.double_speed = true;
+        tick(&mut memory, 0x100);
+        assert_eq!(memory.peek(DIV), 0x02);
+    }
+
+    #[test]
+    fn test_overflow() {
+        let mut memory = Memory::new(0x10000);
+        memory.memory[IE as usize] = 0x04;
+        memory.write_far_addr(TMA, 0xF0);
+        memory.write_far_addr(TIMA, 0xFE);
+        // 262144 Hz, every 16 ticks
+        memory.write_far_addr(TAC, 0x05);
+        assert_eq!(memory.peek(TAC), 0xFD);
+
+        tick(&mut memory, 16);
+        assert_eq!((memory.peek(TIMA), pending(&memory)), (0xFF, 0x00));
+        tick(&mut memory, 16);
+        assert_eq!((memory.peek(TIMA), pending(&memory)), (0xF0, 0x04));
+
+        // Disabled
+        memory.write_far_addr(TAC, 0x01);
+        tick(&mut memory, 64);
+        assert_eq!(memory.peek(TIMA), 0xF0);
+    }
+
+    #[test]
+    fn test_wakes_halt() {
+        // EI, HALT, until TIMA overflows
+        let mut memory = Memory::new(0x10000);
+        memory.memory[0x0100..0x0102].copy_from_slice(&[0xFB, 0x76]);
+        memory.registers.PC = 0x0100;
+        memory.registers.SP = 0xFFFE;
+        memory.memory[IE as usize] = 0x04;
+        memory.write_far_addr(TIMA, 0xFF);
+        memory.write_far_addr(TAC, 0x05);
+
+        for _ in 0..10 {
+            if memory.registers.PC == 0x0050 { break; }
+            step(&mut memory);
+        }
+        assert_eq!((memory.registers.PC, memory.cycles), (0x0050, 16 + 20));
+    }
+
+    #[test]
+    fn test_falling_edge_writes() {
+        let mut memory = Memory::new(0x10000);
+        memory.write_far_addr(TAC, 0x05);
+        tick(&mut memory, 8);
+
+        // Bit 3 of the counter was set, resetting it is a falling edge
+        memory.write_far_addr(DIV, 0x00);
+        assert_eq!(memory.peek(TIMA), 0x01);
+        tick(&mut memory, 8);
+        memory.write_far_addr(TAC, 0x00);
+        assert_eq!(memory.peek(TIMA), 0x02);
+    }
+}
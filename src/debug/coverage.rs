@@ -0,0 +1,182 @@
+use std::cell::Cell;
+use std::fmt::Write as _;
+use std::path::Path;
+use crate::debug::disassembler::{BANK_SIZE, rom_offset};
+use crate::utils::types::{Byte, FarAddress};
+
+const ROM_END: FarAddress = 0x8000;
+
+/// How a ROM byte was accessed, each kind is a bit of the coverage map
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Access {
+    /// First byte of an instruction, or both bytes of a prefixed one
+    Opcode,
+    Operand,
+    /// Read by an instruction, e.g. LD A, (HL)
+    Data,
+}
+
+impl Access {
+    const ALL: [Access; 3] = [Access::Opcode, Access::Operand, Access::Data];
+
+    fn bit(self) -> Byte {
+        return match self {
+            Access::Opcode => 0x01,
+            Access::Operand => 0x02,
+            Access::Data => 0x04,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        return match self {
+            Access::Opcode => "opcode",
+            Access::Operand => "operand",
+            Access::Data => "data",
+        }
+    }
+}
+
+/// "opcode", "opcode+data"... or "unexplored" for the bits of a coverage map entry
+fn kinds_name(bits: Byte) -> String {
+    let names: Vec<&str> = Access::ALL.iter().filter(|access| bits & access.bit() != 0).map(|access| access.name()).collect();
+    return if names.is_empty() { String::from("unexplored") } else { names.join("+") };
+}
+
+/// Records how each ROM byte was accessed, one entry of `Access` bits per byte of the ROM, bank by bank.
+/// Disabled (and free) unless a ROM size is given.
+#[derive(Default)]
+pub struct Coverage {
+    /// Cells, since reads only borrow the memory immutably
+    map: Vec<Cell<Byte>>,
+}
+
+impl Coverage {
+    pub fn new(bank_count: usize) -> Coverage {
+        return Coverage { map: vec![Cell::new(0); bank_count * BANK_SIZE] };
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        return !self.map.is_empty();
+    }
+
+    /// Marks the address read in the ROM bank mapped there
+    pub fn mark(&self, bank: u8, addr: FarAddress, access: Access) {
+        if self.map.is_empty() || addr >= ROM_END { return; }
+
+        if let Some(entry) = self.map.get(rom_offset(usize::from(bank), addr)) {
+            entry.set(entry.get() | access.bit());
+        }
+    }
+
+    fn bank_count(&self) -> usize {
+        return self.map.len() / BANK_SIZE;
+    }
+
+    /// Percentages of code (opcode or operand), data and unexplored bytes, per bank then for the whole ROM
+    #[allow(clippy::cast_precision_loss)]
+    pub fn summary(&self) -> String {
+        let code_bits = Access::Opcode.bit() | Access::Operand.bit();
+        let line = |name: &str, entries: &[Cell<Byte>]| {
+            let count = |predicate: &dyn Fn(Byte) -> bool| entries.iter().filter(|entry| predicate(entry.get())).count();
+            let percent = |count: usize| count as f64 * 100.0 / entries.len().max(1) as f64;
+
+            let code = count(&|bits| bits & code_bits != 0);
+            let data = count(&|bits| bits & Access::Data.bit() != 0);
+            let unexplored = count(&|bits| bits == 0);
+            return format!("{name:<6} code {:>6.2}%  data {:>6.2}%  unexplored {:>6.2}%\n", percent(code), percent(data), percent(unexplored));
+        };
+
+        let mut summary: String = self.map.chunks(BANK_SIZE).enumerate().map(|(bank, entries)| line(&format!("{bank:02X}"), entries)).collect();
+        summary += &line("Total", &self.map);
+        return summary;
+    }
+
+    /// One byte of `Access` bits per ROM byte, in the order of the ROM file
+    pub fn to_binary(&self) -> Vec<Byte> {
+        return self.map.iter().map(Cell::get).collect();
+    }
+
+    /// One line per run of bytes accessed the same way: "bank,start,end,kind", with inclusive addresses as mapped
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("bank,start,end,kind\n");
+
+        for bank in 0..self.bank_count() {
+            let base: FarAddress = if bank == 0 { 0 } else { BANK_SIZE as FarAddress };
+            let entries = &self.map[bank * BANK_SIZE..(bank + 1) * BANK_SIZE];
+
+            let mut start = 0;
+            while start < entries.len() {
+                let bits = entries[start].get();
+                let length = entries[start..].iter().take_while(|entry| entry.get() == bits).count();
+                let _ = writeln!(
+                    csv, "{bank:02X},{:04X},{:04X},{}",
+                    base + start as FarAddress, base + (start + length - 1) as FarAddress, kinds_name(bits),
+                );
+                start += length;
+            }
+        }
+
+        return csv;
+    }
+
+    /// Writes the map as CSV if the path ends with ".csv", else as binary
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        return if path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("csv")) {
+            std::fs::write(path, self.to_csv())
+        } else {
+            std::fs::write(path, self.to_binary())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Access, Coverage};
+
+    #[test]
+    fn test_coverage_map() {
+        let coverage = Coverage::new(2);
+        coverage.mark(0, 0x0100, Access::Opcode);
+        coverage.mark(0, 0x0101, Access::Operand);
+        coverage.mark(0, 0x0102, Access::Operand);
+        coverage.mark(1, 0x4000, Access::Data);
+        coverage.mark(1, 0x4000, Access::Opcode);
+        // Outside of the ROM
+        coverage.mark(0, 0xC000, Access::Data);
+
+        let map = coverage.to_binary();
+        assert_eq!(map.len(), 0x8000);
+        assert_eq!(&map[0x00FF..0x0104], &[0x00, 0x01, 0x02, 0x02, 0x00]);
+        assert_eq!(map[0x4000], 0x05);
+
+        assert_eq!(coverage.to_csv(), "bank,start,end,kind
+00,0000,00FF,unexplored
+00,0100,0100,opcode
+00,0101,0102,operand
+00,0103,3FFF,unexplored
+01,4000,4000,opcode+data
+01,4001,7FFF,unexplored
+");
+    }
+
+    #[test]
+    fn test_summary() {
+        let coverage = Coverage::new(2);
+        for addr in 0..0x1000 { coverage.mark(0, addr, Access::Opcode); }
+        for addr in 0x4000..0x6000 { coverage.mark(1, addr, Access::Data); }
+
+        assert_eq!(coverage.summary(), "00     code  25.00%  data   0.00%  unexplored  75.00%
+01     code   0.00%  data  50.00%  unexplored  50.00%
+Total  code  12.50%  data  25.00%  unexplored  62.50%
+");
+    }
+
+    #[test]
+    fn test_disabled() {
+        let coverage = Coverage::default();
+        coverage.mark(0, 0x0100, Access::Opcode);
+        assert!(!coverage.is_enabled());
+        assert!(coverage.to_binary().is_empty());
+    }
+}
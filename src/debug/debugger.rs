@@ -0,0 +1,583 @@
+use std::io::{BufRead, Write};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use crate::cpu::execution::{step, TICKS_PER_FRAME};
+use crate::cpu::memory::Memory;
+use crate::debug::disassembler::{decode, disassemble_at, Flow};
+use crate::debug::profiler::{Location, Profiler};
+use crate::debug::symbols::Symbols;
+use crate::debug::trace::Tracer;
+use crate::utils::log::log;
+use crate::utils::types::{FarAddress, Value};
+
+const HELP: &str = "Commands (ADDR and VALUE are hexadecimal, optionally prefixed by $ or 0x, ADDR can also be a symbol
+name, N and LEN are decimal):
+    c, continue                 Resume emulation
+    s, step [N]                 Execute N instructions (default: 1)
+    n, next                     Step over calls and resets
+    finish                      Run until the current routine returns
+    until <ADDR>                Run until PC reaches ADDR
+    b, break [BANK:]ADDR        Add a breakpoint, optionally restricted to a ROM bank
+    delete [BANK:]ADDR          Remove a breakpoint
+    bl, breakpoints             List breakpoints
+    bt, backtrace               Print the call stack
+    watch [r|w|c:]ADDR[-END][=VALUE]
+                                Pause on read, write (default) or change of a value in the range, optionally
+                                only when VALUE is read or written
+    unwatch <N>                 Remove the watchpoint number N
+    wl, watchpoints             List watchpoints
+    coverage                    Print the percentage of ROM executed and read, with --coverage
+    r, regs                     Print the registers
+    x, read <ADDR> [LEN]        Dump LEN bytes of memory (default: 16)
+    w, write <ADDR> <VALUE>     Write a byte to memory
+    set <REG> <VALUE>           Set a register (A, F, B, C, D, E, H, L, AF, BC, DE, HL, SP, PC)
+    flag <Z|N|H|C> <0|1>        Set a flag
+    log [N]                     Print the N latest log lines (default: 20), unless logging to a file
+    d, disas [N] [ADDR]         Disassemble N instructions from ADDR (default: 5 from PC)
+    q, quit                     Exit the emulator";
+
+const DEFAULT_DUMP_LENGTH: u16 = 16;
+const DEFAULT_DISASSEMBLY_LENGTH: u16 = 5;
+const DEFAULT_LOG_LENGTH: usize = 20;
+// Oldest calls are forgotten past this depth, in case the code never returns
+const MAX_CALL_STACK_DEPTH: usize = 256;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Breakpoint {
+    /// `None` to break whatever the bank mapped at the address
+    pub bank: Option<u8>,
+    pub addr: FarAddress,
+}
+
+impl Breakpoint {
+    /// Whether the breakpoint is on `pc`, in the bank mapped there
+    fn matches(self, bank: u8, pc: FarAddress) -> bool {
+        return self.addr == pc && self.bank.is_none_or(|breakpoint_bank| breakpoint_bank == bank);
+    }
+
+    /// Parses a symbol name, or "[BANK:]ADDR"
+    pub fn resolve(s: &str, symbols: &Symbols) -> Result<Breakpoint, String> {
+        return match symbols.lookup(s) {
+            Some((bank, addr)) => Ok(Breakpoint { bank: Some(bank), addr }),
+            None => s.parse(),
+        }
+    }
+}
+
+impl std::str::FromStr for Breakpoint {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        return match s.split_once(':') {
+            Some((bank, addr)) => Ok(Breakpoint { bank: Some(parse_hex(bank)?), addr: parse_hex(addr)? }),
+            None => Ok(Breakpoint { bank: None, addr: parse_hex(s)? }),
+        }
+    }
+}
+
+impl std::fmt::Display for Breakpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        return match self.bank {
+            Some(bank) => write!(f, "{bank:02X}:{:04X}", self.addr),
+            None => write!(f, "{:04X}", self.addr),
+        }
+    }
+}
+
+pub fn parse_hex<T: TryFrom<u32>>(s: &str) -> Result<T, String> {
+    let digits = s.trim_start_matches('$').trim_start_matches("0x");
+    let value = u32::from_str_radix(digits, 16).map_err(|_| format!("Invalid hexadecimal value \"{s}\""))?;
+
+    return T::try_from(value).map_err(|_| format!("Value \"{s}\" is out of range"));
+}
+
+/// Counts and lengths are decimal, unlike addresses and values
+fn parse_count<T: std::str::FromStr>(s: &str) -> Result<T, String> {
+    return s.parse().map_err(|_| format!("Invalid count \"{s}\""));
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum RunMode {
+    Running,
+    Paused,
+    /// Number of instructions left to execute before pausing
+    Step(u32),
+    /// Pause once the call (or reset) returned
+    StepOver { return_pc: FarAddress, sp: FarAddress },
+    /// Pause once a return instruction left the current routine, i.e. popped the return address found above `sp`
+    StepOut { sp: FarAddress },
+    RunTo(FarAddress),
+}
+
+/// Routine entered by a CALL or RST
+#[derive(Clone, Copy, Debug)]
+struct CallFrame {
+    call_site: Location,
+    routine: Location,
+    /// SP right after the return address was pushed, the frame is left once SP goes above it
+    sp: FarAddress,
+}
+
+enum CommandResult {
+    Stay,
+    Resume,
+    Quit,
+}
+
+pub struct Debugger {
+    breakpoints: Vec<Breakpoint>,
+    mode: RunMode,
+    /// Set while the prompt waits for commands, until the emulation is resumed
+    prompting: bool,
+    /// Lines typed on stdin, read by a separate thread so that the windows stay responsive while paused
+    commands: Option<Receiver<String>>,
+    /// End of the frame being run, when the debugger paused in the middle of it
+    frame_end: Option<u64>,
+    /// Set when resuming, so that a breakpoint on the current instruction doesn't trigger again right away
+    skip_breakpoint: bool,
+    tracer: Option<Tracer>,
+    profiler: Option<Profiler>,
+    symbols: Symbols,
+    call_stack: Vec<CallFrame>,
+}
+
+impl Debugger {
+    pub fn new(start_paused: bool, breakpoints: Vec<Breakpoint>, symbols: Symbols) -> Debugger {
+        return Debugger {
+            breakpoints,
+            mode: if start_paused { RunMode::Paused } else { RunMode::Running },
+            prompting: false,
+            commands: None,
+            frame_end: None,
+            skip_breakpoint: false,
+            tracer: None,
+            profiler: None,
+            symbols,
+            call_stack: Vec::new(),
+        }
+    }
+
+    pub fn set_tracer(&mut self, tracer: Tracer) {
+        self.tracer = Some(tracer);
+    }
+
+    pub fn set_profiler(&mut self, profiler: Profiler) {
+        self.profiler = Some(profiler);
+    }
+
+    /// Flushes the trace log and saves the profile, if any
+    pub fn finish(&mut self) {
+        if let Some(Err(error)) = self.tracer.as_mut().map(Tracer::flush) {
+            eprintln!("Couldn't write trace log: {error}");
+        }
+        if let Some(profiler) = &self.profiler {
+            if let Err(error) = profiler.save(&self.symbols) {
+                eprintln!("Couldn't write profile to {}: {error}", profiler.path().display());
+            }
+        }
+    }
+
+    pub fn symbols(&self) -> &Symbols {
+        return &self.symbols;
+    }
+
+    pub fn pause(&mut self) {
+        self.mode = RunMode::Paused;
+    }
+
+    /// Whether the prompt waits for commands, see `poll_commands`
+    pub fn prompting(&self) -> bool {
+        return self.prompting;
+    }
+
+    /// End of the frame the debugger paused in, unless the machine went back in time since
+    fn paused_frame_end(&self, memory: &Memory) -> Option<u64> {
+        return self.frame_end.filter(|end| *end > memory.cycles && *end <= memory.cycles + TICKS_PER_FRAME);
+    }
+
+    /// Whether the debugger paused in the middle of a frame, which `run_frame` finishes before starting a new one
+    pub fn in_frame(&self, memory: &Memory) -> bool {
+        return self.paused_frame_end(memory).is_some();
+    }
+
+    fn should_pause(&mut self, memory: &Memory) -> bool {
+        let pc = memory.registers.PC;
+        let bank = memory.bank(pc);
+        let skip_breakpoint = std::mem::take(&mut self.skip_breakpoint);
+        let breakpoint = self.breakpoints.iter().find(|breakpoint| !skip_breakpoint && breakpoint.matches(bank, pc));
+
+        if let Some(breakpoint) = breakpoint {
+            if !matches!(self.mode, RunMode::Paused | RunMode::Step(_)) {
+                println!("Breakpoint {breakpoint} hit");
+                return true;
+            }
+        }
+
+        return match self.mode {
+            // Step-out is checked after the return instructions, see `run_frame`
+            RunMode::Running | RunMode::StepOut { .. } => false,
+            RunMode::Paused | RunMode::Step(0) => true,
+            RunMode::Step(count) => {
+                self.mode = RunMode::Step(count - 1);
+                false
+            }
+            RunMode::StepOver { return_pc, sp } => pc == return_pc && memory.registers.SP == sp,
+            RunMode::RunTo(addr) => pc == addr,
+        }
+    }
+
+    /// Runs the emulation until the end of the frame, or until the debugger pauses and shows its prompt.
+    /// Returns whether the frame was completed.
+    pub fn run_frame(&mut self, memory: &mut Memory) -> bool {
+        let frame_end = self.paused_frame_end(memory).unwrap_or(memory.cycles + TICKS_PER_FRAME);
+        self.frame_end = None;
+
+        while memory.cycles < frame_end {
+            if self.should_pause(memory) {
+                self.frame_end = Some(frame_end);
+                self.show_prompt(memory);
+                return false;
+            }
+
+            if let Some(Err(error)) = self.tracer.as_mut().map(|tracer| tracer.trace(memory, &self.symbols)) {
+                eprintln!("Couldn't write trace log, tracing stopped: {error}");
+                self.tracer = None;
+            }
+
+            let pc = memory.registers.PC;
+            let location = (memory.bank(pc), pc);
+            let call = decode(|addr| memory.peek(addr), pc);
+            let cycles = memory.cycles;
+            step(memory);
+            let jumped = memory.registers.PC != pc.wrapping_add(call.size);
+            let called = call.flow == Flow::Call && jumped;
+            self.track_calls(memory, location, called);
+
+            // Pushes and pops inside the routine don't leave it, only a taken RET or RETI does
+            if let RunMode::StepOut { sp } = self.mode {
+                if call.disassembly.starts_with("RET") && jumped && memory.registers.SP > sp { self.mode = RunMode::Paused; }
+            }
+
+            if let Some(profiler) = &mut self.profiler {
+                profiler.record(location, memory.cycles - cycles);
+                profiler.update_stack(self.call_stack.iter().map(|frame| frame.routine), called);
+            }
+
+            if let Some(hit) = memory.watchpoints.take_hit() {
+                println!("{hit}, by the instruction at {:02X}:{pc:04X}", location.0);
+                self.mode = RunMode::Paused;
+            }
+        }
+
+        return true;
+    }
+
+    /// Keeps the call stack up to date after executing the instruction at `pc`
+    fn track_calls(&mut self, memory: &Memory, pc: Location, called: bool) {
+        let sp = memory.registers.SP;
+        self.call_stack.retain(|frame| sp <= frame.sp);
+
+        if called {
+            if self.call_stack.len() == MAX_CALL_STACK_DEPTH { self.call_stack.remove(0); }
+            let routine = memory.registers.PC;
+            self.call_stack.push(CallFrame { call_site: pc, routine: (memory.bank(routine), routine), sp });
+        }
+    }
+
+    /// "BANK:ADDR", followed by the nearest symbol if any
+    fn describe(&self, (bank, addr): Location) -> String {
+        let location = format!("{bank:02X}:{addr:04X}");
+        return match self.symbols.describe(bank, addr) {
+            Some(symbol) => format!("{location} <{symbol}>"),
+            None => location,
+        }
+    }
+
+    /// Parses a symbol name or a hexadecimal address
+    fn parse_address(&self, s: &str) -> Result<FarAddress, String> {
+        return match self.symbols.lookup(s) {
+            Some((_, addr)) => Ok(addr),
+            None => parse_hex(s),
+        }
+    }
+
+    fn print_location(&self, memory: &Memory) {
+        let pc = memory.registers.PC;
+        let (text, _) = disassemble_at(memory, pc, &self.symbols);
+        println!("{}  {text}", self.describe((memory.bank(pc), pc)));
+    }
+
+    fn print_prompt() {
+        print!("(lameboy) ");
+        // Nothing to do if the prompt can't be flushed, the command can still be typed
+        let _ = std::io::stdout().flush();
+    }
+
+    /// Pauses the emulation, waiting for commands (see `poll_commands`)
+    fn show_prompt(&mut self, memory: &Memory) {
+        log!("CPU", format!("Paused at {:#06X}", memory.registers.PC));
+
+        self.mode = RunMode::Paused;
+        self.prompting = true;
+        self.print_location(memory);
+        Self::print_prompt();
+    }
+
+    /// Lines of stdin, read by a thread started on the first use
+    fn commands(&mut self) -> &Receiver<String> {
+        return self.commands.get_or_insert_with(|| {
+            let (sender, receiver) = mpsc::channel();
+            std::thread::spawn(move || {
+                for line in std::io::stdin().lock().lines() {
+                    let Ok(line) = line else { break };
+                    if sender.send(line).is_err() { break; }
+                }
+            });
+            receiver
+        });
+    }
+
+    /// Executes the commands typed while the prompt is shown, until the emulation is resumed. Returns right away
+    /// when no command was typed yet, unless `wait` is set. Returns false if the user asked to quit.
+    pub fn poll_commands(&mut self, memory: &mut Memory, wait: bool) -> bool {
+        while self.prompting {
+            let line = match (wait, self.commands()) {
+                (true, commands) => commands.recv().ok(),
+                (false, commands) => match commands.try_recv() {
+                    Ok(line) => Some(line),
+                    Err(TryRecvError::Empty) => return true,
+                    Err(TryRecvError::Disconnected) => None,
+                },
+            };
+            // Stop the emulation when stdin is closed, there is no way to resume it
+            let Some(line) = line else { return false };
+
+            match self.execute_command(memory, &line) {
+                Ok(CommandResult::Stay) => Self::print_prompt(),
+                Ok(CommandResult::Resume) => {
+                    self.skip_breakpoint = true;
+                    self.prompting = false;
+                }
+                Ok(CommandResult::Quit) => return false,
+                Err(message) => {
+                    println!("{message}");
+                    Self::print_prompt();
+                }
+            }
+        }
+
+        return true;
+    }
+
+    #[allow(clippy::too_many_lines)]
+    fn execute_command(&mut self, memory: &mut Memory, line: &str) -> Result<CommandResult, String> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let Some((command, args)) = words.split_first() else { return Ok(CommandResult::Stay) };
+        let arg = |index: usize, name: &str| args.get(index).copied().ok_or(format!("Missing argument <{name}>"));
+
+        match *command {
+            "h" | "help" => println!("{HELP}"),
+            "c" | "continue" => {
+                self.mode = RunMode::Running;
+                return Ok(CommandResult::Resume);
+            }
+            "s" | "step" => {
+                let count: u32 = args.first().map_or(Ok(1), |count| parse_count(count))?;
+                self.mode = RunMode::Step(count.max(1));
+                return Ok(CommandResult::Resume);
+            }
+            "n" | "next" => {
+                let pc = memory.registers.PC;
+                let (text, size) = disassemble_at(memory, pc, &self.symbols);
+                self.mode = if text.starts_with("CALL") || text.starts_with("RST") {
+                    RunMode::StepOver { return_pc: pc.wrapping_add(size), sp: memory.registers.SP }
+                } else {
+                    RunMode::Step(1)
+                };
+                return Ok(CommandResult::Resume);
+            }
+            "finish" => {
+                // SP right after the call of the routine if it was seen, else the current one
+                let sp = self.call_stack.last().map_or(memory.registers.SP, |frame| frame.sp);
+                self.mode = RunMode::StepOut { sp };
+                return Ok(CommandResult::Resume);
+            }
+            "until" => {
+                self.mode = RunMode::RunTo(self.parse_address(arg(0, "ADDR")?)?);
+                return Ok(CommandResult::Resume);
+            }
+            "b" | "break" => {
+                let breakpoint = Breakpoint::resolve(arg(0, "ADDR")?, &self.symbols)?;
+                if !self.breakpoints.contains(&breakpoint) { self.breakpoints.push(breakpoint); }
+                println!("Breakpoint {breakpoint} added");
+            }
+            "delete" => {
+                let breakpoint = Breakpoint::resolve(arg(0, "ADDR")?, &self.symbols)?;
+                let count = self.breakpoints.len();
+                self.breakpoints.retain(|other| *other != breakpoint);
+                if count == self.breakpoints.len() { return Err(format!("No breakpoint at {breakpoint}")); }
+            }
+            "bl" | "breakpoints" => {
+                for breakpoint in &self.breakpoints { println!("{breakpoint}"); }
+            }
+            "watch" => memory.watchpoints.add(arg(0, "WATCHPOINT")?.parse()?),
+            "unwatch" => {
+                let index: usize = arg(0, "N")?.parse().map_err(|_| String::from("Invalid watchpoint number"))?;
+                if memory.watchpoints.remove(index).is_none() { return Err(format!("No watchpoint number {index}")); }
+            }
+            "wl" | "watchpoints" => {
+                for (index, watchpoint) in memory.watchpoints.list().iter().enumerate() { println!("{index}: {watchpoint}"); }
+            }
+            "coverage" => {
+                if !memory.coverage.is_enabled() { return Err(String::from("ROM coverage isn't recorded, see --coverage")); }
+                print!("{}", memory.coverage.summary());
+            }
+            "bt" | "backtrace" => {
+                let pc = memory.registers.PC;
+                println!("#0  {}", self.describe((memory.bank(pc), pc)));
+                for (depth, frame) in self.call_stack.iter().rev().enumerate() {
+                    println!("#{}  {} called from {}", depth + 1, self.describe(frame.routine), self.describe(frame.call_site));
+                }
+            }
+            "r" | "regs" => println!("{}", memory.registers),
+            "x" | "read" => {
+                let start = self.parse_address(arg(0, "ADDR")?)?;
+                let length: FarAddress = args.get(1).map_or(Ok(DEFAULT_DUMP_LENGTH), |length| parse_count(length))?;
+
+                for row_start in (0..length).step_by(16) {
+                    let row: Vec<String> = (row_start..length.min(row_start.saturating_add(16)))
+                        .map(|offset| format!("{:02X}", memory.peek(start.wrapping_add(offset))))
+                        .collect();
+                    println!("{:04X}: {}", start.wrapping_add(row_start), row.join(" "));
+                }
+            }
+            "w" | "write" => {
+                let addr = self.parse_address(arg(0, "ADDR")?)?;
+                let value: Value = parse_hex(arg(1, "VALUE")?)?;
+                if usize::from(addr) >= memory.size { return Err(format!("Address {addr:04X} is outside of memory")); }
+                memory.write_far_addr(addr, value);
+            }
+            "set" => {
+                let register = arg(0, "REG")?.to_uppercase();
+                let value = arg(1, "VALUE")?;
+                let registers = &mut memory.registers;
+
+                match register.as_str() {
+                    "A" => registers.set_a(parse_hex(value)?),
+                    // Lower nibble of F is always 0
+                    "F" => registers.set_f(parse_hex::<Value>(value)? & 0xF0),
+                    "B" => registers.set_b(parse_hex(value)?),
+                    "C" => registers.set_c(parse_hex(value)?),
+                    "D" => registers.set_d(parse_hex(value)?),
+                    "E" => registers.set_e(parse_hex(value)?),
+                    "H" => registers.set_h(parse_hex(value)?),
+                    "L" => registers.set_l(parse_hex(value)?),
+                    "AF" => registers.set_af(parse_hex(value)?),
+                    "BC" => registers.set_bc(parse_hex(value)?),
+                    "DE" => registers.set_de(parse_hex(value)?),
+                    "HL" => registers.set_hl(parse_hex(value)?),
+                    "SP" => registers.SP = parse_hex(value)?,
+                    "PC" => registers.PC = parse_hex(value)?,
+                    _ => return Err(format!("Unknown register \"{register}\"")),
+                }
+            }
+            "flag" => {
+                let flag = arg(0, "FLAG")?.to_uppercase();
+                let status = match arg(1, "STATUS")? {
+                    "0" => false,
+                    "1" => true,
+                    status => return Err(format!("Invalid flag status \"{status}\", expected 0 or 1")),
+                };
+
+                match flag.as_str() {
+                    "Z" => memory.registers.set_zero_flag(status),
+                    "N" => memory.registers.set_subtraction_flag(status),
+                    "H" => memory.registers.set_half_carry_flag(status),
+                    "C" => memory.registers.set_carry_flag(status),
+                    _ => return Err(format!("Unknown flag \"{flag}\"")),
+                }
+            }
+            "d" | "disas" => {
+                let count: u16 = args.first().map_or(Ok(DEFAULT_DISASSEMBLY_LENGTH), |count| parse_count(count))?;
+                let mut addr = args.get(1).map_or(Ok(memory.registers.PC), |addr| self.parse_address(addr))?;
+
+                for _ in 0..count {
+                    let bank = memory.bank(addr);
+                    if let Some(name) = self.symbols.name_at(bank, addr) { println!("{name}:"); }
+
+                    let (text, size) = disassemble_at(memory, addr, &self.symbols);
+                    let marker = if addr == memory.registers.PC { '>' } else { ' ' };
+                    println!("{marker} {bank:02X}:{addr:04X}  {text}");
+                    addr = addr.wrapping_add(size.max(1));
+                }
+            }
+            "log" => {
+                let count: usize = args.first().map_or(Ok(DEFAULT_LOG_LENGTH), |count| parse_count(count))?;
+                for line in crate::utils::log::recent(count) { println!("{line}"); }
+            }
+            "q" | "quit" => return Ok(CommandResult::Quit),
+            _ => return Err(format!("Unknown command \"{command}\", type \"help\" for the list of commands")),
+        }
+
+        return Ok(CommandResult::Stay);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+    use crate::cpu::memory::Memory;
+    use crate::debug::symbols::Symbols;
+    use super::{parse_count, Breakpoint, Debugger};
+
+    #[test]
+    fn test_finish() {
+        // CALL 0200, then PUSH BC, POP BC, RET, with a breakpoint on POP BC
+        let mut memory = Memory::new(0x10000);
+        memory.memory[0x0100..0x0103].copy_from_slice(&[0xCD, 0x00, 0x02]);
+        memory.memory[0x0200..0x0203].copy_from_slice(&[0xC5, 0xC1, 0xC9]);
+        memory.registers.PC = 0x0100;
+        memory.registers.SP = 0xFFFE;
+        let mut debugger = Debugger::new(false, vec![Breakpoint { bank: None, addr: 0x0201 }], Symbols::default());
+
+        assert!(!debugger.run_frame(&mut memory));
+        assert!(debugger.prompting() && debugger.in_frame(&memory));
+        assert_eq!(memory.registers.PC, 0x0201);
+
+        // Typed commands
+        let (sender, receiver) = mpsc::channel();
+        debugger.commands = Some(receiver);
+        assert!(debugger.poll_commands(&mut memory, false));
+        assert!(debugger.prompting());
+        let _ = sender.send(String::from("finish"));
+        assert!(debugger.poll_commands(&mut memory, false));
+        assert!(!debugger.prompting());
+
+        // Not paused by POP BC, which brings SP above its value at the breakpoint
+        assert!(!debugger.run_frame(&mut memory));
+        assert_eq!((memory.registers.PC, memory.registers.SP), (0x0103, 0xFFFE));
+
+        // Quits once stdin is closed
+        drop(sender);
+        assert!(!debugger.poll_commands(&mut memory, true));
+    }
+
+    #[test]
+    fn test_parse_breakpoint() {
+        assert_eq!("$0150".parse(), Ok(Breakpoint { bank: None, addr: 0x0150 }));
+        assert_eq!("01:4000".parse(), Ok(Breakpoint { bank: Some(1), addr: 0x4000 }));
+        assert!("01:10000".parse::<Breakpoint>().is_err());
+        assert!("main".parse::<Breakpoint>().is_err());
+
+        let mut symbols = Symbols::default();
+        symbols.insert(1, 0x4010, "Main.loop");
+        assert_eq!(Breakpoint::resolve("Main.loop", &symbols), Ok(Breakpoint { bank: Some(1), addr: 0x4010 }));
+        assert_eq!(Breakpoint::resolve("0150", &symbols), Ok(Breakpoint { bank: None, addr: 0x0150 }));
+    }
+
+    #[test]
+    fn test_parse_count() {
+        assert_eq!(parse_count::<u16>("16"), Ok(16));
+        assert!(parse_count::<u16>("0x10").is_err());
+        assert!(parse_count::<u16>("1F").is_err());
+    }
+}
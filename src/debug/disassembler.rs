@@ -0,0 +1,465 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use crate::cpu::instruction::{GenericInstruction, instruction_from_opcode, PREFIXED_OPCODE};
+use crate::cpu::memory::Memory;
+use crate::debug::symbols::Symbols;
+use crate::utils::types::{AddressOffset, Byte, FarAddress, Value, WideValue};
+
+pub const BANK_SIZE: usize = 0x4000;
+const ROMX_START: FarAddress = 0x4000;
+const ROM_END: FarAddress = 0x8000;
+// Writes to this range select the ROM bank switched in the ROMX area
+const BANK_SELECT_START: FarAddress = 0x2000;
+const BANK_SELECT_END: FarAddress = 0x3FFF;
+
+const ENTRY_POINT: FarAddress = 0x0100;
+// VBlank, LCD STAT, Timer, Serial, Joypad
+const INTERRUPT_VECTORS: [FarAddress; 5] = [0x40, 0x48, 0x50, 0x58, 0x60];
+
+// Number of data bytes per "DB" line
+const DATA_BYTES_PER_LINE: usize = 8;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Operand {
+    None,
+    Value(Value),
+    Wide(WideValue),
+    Near(Value),
+    Far(FarAddress),
+    Offset(AddressOffset),
+}
+
+/// How the execution continues after the instruction
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Flow {
+    /// Next instruction
+    Continue,
+    /// Unconditional jump to `target`
+    Jump,
+    /// Conditional jump to `target`, or next instruction
+    Branch,
+    /// Call of `target` (CALL or RST), then next instruction
+    Call,
+    /// Unknown destination: RET, RETI or JP HL
+    Return,
+    /// Unused opcode
+    Invalid,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Decoded {
+    /// Opcode(s) and operand, in bytes
+    pub size: FarAddress,
+    pub disassembly: &'static str,
+    pub operand: Operand,
+    /// Absolute destination of jumps, calls and resets
+    pub target: Option<FarAddress>,
+    pub flow: Flow,
+}
+
+impl Decoded {
+    pub fn falls_through(&self) -> bool {
+        return matches!(self.flow, Flow::Continue | Flow::Branch | Flow::Call);
+    }
+
+    /// Substitutes the operand placeholders of the disassembly, `label` may name the target of jumps and calls
+    pub fn text(&self, label: impl Fn(FarAddress) -> Option<String>) -> String {
+        let target = || self.target.and_then(&label);
+
+        return match self.operand {
+            Operand::None => self.disassembly.to_string(),
+            Operand::Value(value) => self.disassembly.replace("d8", &format!("${value:02X}")),
+            Operand::Wide(value) => self.disassembly.replace("d16", &format!("${value:04X}")),
+            Operand::Near(value) => self.disassembly.replace("a8", &format!("$FF{value:02X}")),
+            Operand::Far(addr) => self.disassembly.replace("a16", &target().unwrap_or_else(|| format!("${addr:04X}"))),
+            // Relative jumps are displayed with their absolute target
+            Operand::Offset(offset) => if let Some(addr) = self.target {
+                self.disassembly.replace("r8", &target().unwrap_or_else(|| format!("${addr:04X}")))
+            } else {
+                let signed = if offset < 0 { format!("- {}", offset.unsigned_abs()) } else { format!("+ {offset}") };
+                self.disassembly.replace("+ r8", &signed).replace("r8", &offset.to_string())
+            },
+        }
+    }
+}
+
+/// Decodes the instruction at `addr`, `read` giving the byte at any address
+pub fn decode(read: impl Fn(FarAddress) -> Value, addr: FarAddress) -> Decoded {
+    let opcode = read(addr);
+    let (instruction, opcode_size) = if opcode == PREFIXED_OPCODE {
+        (instruction_from_opcode(read(addr.wrapping_add(1)), true), 2)
+    } else {
+        (instruction_from_opcode(opcode, false), 1)
+    };
+
+    let operand_addr = addr.wrapping_add(opcode_size);
+    let size = opcode_size + instruction.operand_size();
+    let value = read(operand_addr);
+    let wide = WideValue::from_le_bytes([value, read(operand_addr.wrapping_add(1))]);
+
+    let operand = match instruction {
+        GenericInstruction::Void(_) => Operand::None,
+        GenericInstruction::Value(_) => Operand::Value(value),
+        GenericInstruction::Wide(_) => Operand::Wide(wide),
+        GenericInstruction::Near(_) => Operand::Near(value),
+        GenericInstruction::Far(_) => Operand::Far(wide),
+        GenericInstruction::Offset(_) => Operand::Offset(AddressOffset::from_le_bytes([value])),
+    };
+
+    let disassembly = instruction.disassembly();
+    let mnemonic = disassembly.split_whitespace().next().unwrap_or_default();
+    let conditional = disassembly.contains(',');
+
+    let (flow, target) = match (mnemonic, operand) {
+        ("JP", Operand::Far(target)) => (if conditional { Flow::Branch } else { Flow::Jump }, Some(target)),
+        ("JR", Operand::Offset(offset)) => (if conditional { Flow::Branch } else { Flow::Jump }, Some(addr.wrapping_add(size).wrapping_add_signed(i16::from(offset)))),
+        ("CALL", Operand::Far(target)) => (Flow::Call, Some(target)),
+        // Reset vectors are encoded in bits 3-5 of the opcode
+        ("RST", _) => (Flow::Call, Some(FarAddress::from(opcode & 0b0011_1000))),
+        ("JP" | "RETI", _) => (Flow::Return, None),
+        ("RET", _) => (if disassembly.len() > 3 { Flow::Continue } else { Flow::Return }, None),
+        ("X", _) => (Flow::Invalid, None),
+        _ => (Flow::Continue, None),
+    };
+
+    return Decoded { size, disassembly, operand, target, flow };
+}
+
+/// Disassembles the instruction at the address in memory, naming targets after the symbols. Returns the text and the instruction size.
+pub fn disassemble_at(memory: &Memory, addr: FarAddress, symbols: &Symbols) -> (String, FarAddress) {
+    let decoded = decode(|addr| memory.peek(addr), addr);
+    let text = decoded.text(|target| symbols.name_at(memory.bank(target), target).map(ToString::to_string));
+    return (text, decoded.size);
+}
+
+//  #############################
+//  #            ROM            #
+//  #############################
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ByteKind {
+    Data,
+    Opcode,
+    Operand,
+}
+
+/// Bank in which `target` lies, when jumping from code in `bank` while `mapped` is switched in the ROMX area.
+/// `None` outside of the ROM, or for jumps from bank 0 to the switchable area when the mapped bank is unknown.
+fn target_bank(bank: usize, mapped: Option<usize>, target: FarAddress) -> Option<usize> {
+    return match target {
+        0..=0x3FFF => Some(0),
+        ROMX_START..=0x7FFF => if bank == 0 { mapped } else { Some(bank) },
+        _ => None,
+    }
+}
+
+/// Code position while tracing: bank, address, bank switched in the ROMX area and value loaded in A just before, when known
+type TracePoint = (usize, FarAddress, Option<usize>, Option<Value>);
+
+pub fn rom_offset(bank: usize, addr: FarAddress) -> usize {
+    return if addr < ROMX_START { usize::from(addr) } else { bank * BANK_SIZE + usize::from(addr - ROMX_START) };
+}
+
+pub fn label_name(bank: usize, addr: FarAddress) -> String {
+    return format!("L{bank:02X}_{addr:04X}");
+}
+
+pub struct RomDisassembly<'a> {
+    rom: &'a [Byte],
+    kinds: Vec<ByteKind>,
+    labels: BTreeSet<(usize, FarAddress)>,
+    /// Bank of the ROMX targets of bank 0 instructions, by ROM offset, when the bank switch could be followed
+    switched_targets: BTreeMap<usize, usize>,
+}
+
+impl<'a> RomDisassembly<'a> {
+    /// Recursively traces the code reachable from the entry point and the interrupt vectors.
+    /// Bank switches done with a `LD A, d8` followed by a `LD (a16), A` are followed, jumps from bank 0 to the ROMX area
+    /// with an unknown bank are traced in every switchable bank.
+    pub fn trace(rom: &'a [Byte]) -> RomDisassembly<'a> {
+        let mut disassembly = RomDisassembly { rom, kinds: vec![ByteKind::Data; rom.len()], labels: BTreeSet::new(), switched_targets: BTreeMap::new() };
+
+        // The bank 1 is switched in at power on, but is unknown when an interrupt occurs
+        let mut queue: Vec<TracePoint> = INTERRUPT_VECTORS.iter().map(|addr| (0, *addr, None, None)).collect();
+        queue.push((0, ENTRY_POINT, Some(1), None));
+        for (bank, addr, _, _) in &queue { disassembly.labels.insert((*bank, *addr)); }
+        // Targets in the ROMX area with an unknown bank, only labelled in the banks where they turned out to be code
+        let mut entry_points: BTreeSet<(usize, FarAddress)> = BTreeSet::new();
+
+        while let Some((bank, addr, mapped, loaded)) = queue.pop() {
+            let offset = rom_offset(bank, addr);
+            if disassembly.kinds.get(offset) != Some(&ByteKind::Data) { continue; }
+
+            let decoded = disassembly.decode(bank, addr);
+            // The operand bytes past the end of the bank aren't the next bank's, they are listed as truncated
+            let end = (offset + usize::from(decoded.size)).min(disassembly.bank_end(bank));
+            if decoded.flow == Flow::Invalid || disassembly.kinds[offset..end].iter().any(|kind| *kind != ByteKind::Data) {
+                continue;
+            }
+
+            disassembly.kinds[offset] = ByteKind::Opcode;
+            disassembly.kinds[offset + 1..end].fill(ByteKind::Operand);
+
+            let mapped = match (decoded.disassembly, decoded.operand, loaded) {
+                ("LD (a16), A", Operand::Far(BANK_SELECT_START..=BANK_SELECT_END), Some(value)) => Some(disassembly.selected_bank(value)),
+                _ if bank != 0 => Some(bank),
+                _ => mapped,
+            };
+            let loaded = match (decoded.disassembly, decoded.operand) {
+                ("LD A, d8", Operand::Value(value)) => Some(value),
+                _ => None,
+            };
+
+            if let Some(target) = decoded.target {
+                if let Some(target_bank) = target_bank(bank, mapped, target) {
+                    if bank == 0 && target >= ROMX_START { disassembly.switched_targets.insert(offset, target_bank); }
+                    disassembly.labels.insert((target_bank, target));
+                    queue.push((target_bank, target, mapped, None));
+                } else if target < ROM_END {
+                    for target_bank in 1..disassembly.bank_count() {
+                        entry_points.insert((target_bank, target));
+                        queue.push((target_bank, target, Some(target_bank), None));
+                    }
+                }
+            }
+            if decoded.falls_through() {
+                let next = addr.wrapping_add(decoded.size);
+                if let Some(next_bank) = target_bank(bank, mapped, next) { queue.push((next_bank, next, mapped, loaded)); }
+            }
+        }
+
+        for (bank, addr) in entry_points {
+            if disassembly.kinds.get(rom_offset(bank, addr)) == Some(&ByteKind::Opcode) { disassembly.labels.insert((bank, addr)); }
+        }
+
+        return disassembly;
+    }
+
+    /// Bank switched in by writing `value` to the bank select range, the bank 0 selecting the bank 1 instead
+    fn selected_bank(&self, value: Value) -> usize {
+        let bank = usize::from(value) % self.bank_count().max(1);
+        return bank.max(1);
+    }
+
+    /// Bank of the target of the instruction at `offset`, lying in `bank`
+    fn instruction_target_bank(&self, bank: usize, offset: usize, target: FarAddress) -> Option<usize> {
+        if let Some(target_bank) = self.switched_targets.get(&offset) { return Some(*target_bank); }
+        return target_bank(bank, None, target);
+    }
+
+    pub fn bank_count(&self) -> usize {
+        return self.rom.len().div_ceil(BANK_SIZE);
+    }
+
+    /// ROM offset following the last byte of the bank
+    fn bank_end(&self, bank: usize) -> usize {
+        return self.rom.len().min((bank + 1) * BANK_SIZE);
+    }
+
+    fn read(&self, bank: usize, addr: FarAddress) -> Value {
+        if addr >= ROM_END { return 0xFF; }
+        return self.rom.get(rom_offset(bank, addr)).copied().unwrap_or(0xFF);
+    }
+
+    fn decode(&self, bank: usize, addr: FarAddress) -> Decoded {
+        return decode(|operand_addr| self.read(target_bank(bank, Some(1), operand_addr).unwrap_or(bank), operand_addr), addr);
+    }
+
+    pub fn code_size(&self) -> usize {
+        return self.kinds.iter().filter(|kind| **kind != ByteKind::Data).count();
+    }
+
+    /// Name of the symbol at the address, else of the label generated while tracing
+    fn label(&self, bank: usize, addr: FarAddress, symbols: &Symbols) -> Option<String> {
+        let symbol = u8::try_from(bank).ok().and_then(|bank| symbols.name_at(bank, addr));
+        if let Some(symbol) = symbol { return Some(symbol.to_string()); }
+
+        return self.labels.contains(&(bank, addr)).then(|| label_name(bank, addr));
+    }
+
+    /// Listing of the whole ROM, bank by bank, in a RGBDS-like syntax. Labels are named after the symbols when known.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+    pub fn listing(&self, symbols: &Symbols) -> String {
+        let mut output = String::new();
+        let percentage = if self.rom.is_empty() { 0.0 } else { self.code_size() as f64 * 100.0 / self.rom.len() as f64 };
+
+        // Writing to a String never fails
+        let _ = writeln!(output, "; {} banks, {} bytes of code ({percentage:.2}%)", self.bank_count(), self.code_size());
+
+        for bank in 0..self.bank_count() {
+            let base = if bank == 0 { 0 } else { ROMX_START };
+            let bank_end = self.bank_end(bank);
+            let mut offset = bank * BANK_SIZE;
+            let mut data: Vec<Byte> = Vec::new();
+            let mut data_start = base;
+
+            let _ = writeln!(output, "\nSECTION \"ROM Bank ${bank:02X}\", {}[${base:04X}]", if bank == 0 { "ROM0" } else { "ROMX" });
+
+            while offset < bank_end {
+                let addr = base + (offset - bank * BANK_SIZE) as FarAddress;
+                let label = self.label(bank, addr, symbols);
+
+                if (label.is_some() || self.kinds[offset] == ByteKind::Opcode || data.len() == DATA_BYTES_PER_LINE) && !data.is_empty() {
+                    Self::write_data(&mut output, bank, data_start, &data);
+                    data.clear();
+                }
+                if let Some(label) = label { let _ = writeln!(output, "{label}:"); }
+
+                let decoded = (self.kinds[offset] == ByteKind::Opcode).then(|| self.decode(bank, addr));
+                let text = decoded.map(|decoded| decoded.text(|target| {
+                    self.instruction_target_bank(bank, offset, target).and_then(|target_bank| self.label(target_bank, target, symbols))
+                }));
+                // A label falling inside the operand of the instruction, the instruction is written as data around the label
+                let overlapped = decoded.is_some_and(|decoded| (1..decoded.size).any(|index| self.label(bank, addr + index, symbols).is_some()));
+                // Cut by the end of the bank, the instruction is written as data too
+                let truncated = decoded.is_some_and(|decoded| offset + usize::from(decoded.size) > bank_end);
+
+                if let (Some(decoded), Some(text), false, false) = (decoded, &text, overlapped, truncated) {
+                    let size = usize::from(decoded.size);
+                    let bytes: Vec<String> = self.rom[offset..offset + size].iter().map(|byte| format!("{byte:02X}")).collect();
+
+                    let _ = writeln!(output, "    {text:<32} ; {bank:02X}:{addr:04X}  {}", bytes.join(" "));
+                    offset += size;
+                } else {
+                    if let Some(text) = text {
+                        let _ = writeln!(output, "    ; {text}{}", if truncated { " (truncated by the end of the bank)" } else { "" });
+                    }
+                    if data.is_empty() { data_start = addr; }
+                    data.push(self.rom[offset]);
+                    offset += 1;
+                }
+            }
+
+            if !data.is_empty() { Self::write_data(&mut output, bank, data_start, &data); }
+        }
+
+        return output;
+    }
+
+    fn write_data(output: &mut String, bank: usize, addr: FarAddress, data: &[Byte]) {
+        let bytes: Vec<String> = data.iter().map(|byte| format!("${byte:02X}")).collect();
+        let _ = writeln!(output, "    {:<32} ; {bank:02X}:{addr:04X}", format!("DB {}", bytes.join(", ")));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::debug::symbols::Symbols;
+    use super::{decode, Flow, RomDisassembly};
+
+    #[test]
+    fn test_decode_operands() {
+        let bytes = [0x3E, 0x42, 0xC3, 0x50, 0x01, 0x18, 0xFE, 0xCB, 0x37, 0xF8, 0xFE, 0xE0, 0x40];
+        let read = |addr: u16| bytes.get(usize::from(addr)).copied().unwrap_or(0);
+        let text = |addr| { let decoded = decode(read, addr); (decoded.text(|_| None), decoded.size) };
+
+        assert_eq!(text(0), (String::from("LD A, $42"), 2));
+        assert_eq!(text(2), (String::from("JP $0150"), 3));
+        // Infinite loop, jumps back onto itself
+        assert_eq!(text(5), (String::from("JR $0005"), 2));
+        assert_eq!(text(7), (String::from("SWAP A"), 2));
+        assert_eq!(text(9), (String::from("LD HL, SP - 2"), 2));
+        assert_eq!(text(11), (String::from("LDH ($FF40), A"), 2));
+    }
+
+    #[test]
+    fn test_decode_flow() {
+        let flow = |bytes: [u8; 3]| { let decoded = decode(|addr| bytes[usize::from(addr) % 3], 0); (decoded.flow, decoded.target) };
+
+        assert_eq!(flow([0xC3, 0x00, 0x02]), (Flow::Jump, Some(0x0200)));
+        assert_eq!(flow([0xC2, 0x00, 0x02]), (Flow::Branch, Some(0x0200)));
+        assert_eq!(flow([0xCD, 0x00, 0x02]), (Flow::Call, Some(0x0200)));
+        assert_eq!(flow([0xFF, 0x00, 0x00]), (Flow::Call, Some(0x0038)));
+        assert_eq!(flow([0xC9, 0x00, 0x00]), (Flow::Return, None));
+        assert_eq!(flow([0xC0, 0x00, 0x00]), (Flow::Continue, None));
+        assert_eq!(flow([0xE9, 0x00, 0x00]), (Flow::Return, None));
+        assert_eq!(flow([0xD3, 0x00, 0x00]), (Flow::Invalid, None));
+    }
+
+    #[test]
+    fn test_trace() {
+        let mut rom = vec![0xFF_u8; 0x8000];
+        // Entry point jumps over data into a subroutine call, then loops forever
+        rom[0x100..0x104].copy_from_slice(&[0x00, 0xC3, 0x50, 0x01]);
+        rom[0x150..0x156].copy_from_slice(&[0xCD, 0x00, 0x40, 0x18, 0xFE, 0x00]);
+        rom[0x4000] = 0xC9;
+        // Interrupt vectors return right away
+        for vector in [0x40, 0x48, 0x50, 0x58, 0x60] { rom[vector] = 0xD9; }
+
+        let disassembly = RomDisassembly::trace(&rom);
+        // 4 + 5 + 1 + 5 bytes of code
+        assert_eq!(disassembly.code_size(), 15);
+
+        let listing = disassembly.listing(&Symbols::default());
+        assert!(listing.contains("    JP L00_0150 "));
+        assert!(listing.contains("    CALL L01_4000 "));
+        assert!(listing.contains("L01_4000:\n    RET "));
+        // Unreached byte after the infinite loop
+        assert!(listing.contains("    DB $00, $FF, "));
+
+        let mut symbols = Symbols::default();
+        symbols.insert(0, 0x0150, "Main");
+        let listing = disassembly.listing(&symbols);
+        assert!(listing.contains("Main:\n    CALL L01_4000 "));
+        assert!(listing.contains("    JP Main "));
+    }
+
+    #[test]
+    fn test_trace_banks() {
+        let mut rom = vec![0xFF_u8; 4 * 0x4000];
+        for vector in [0x40, 0x48, 0x50, 0x58, 0x60] { rom[vector] = 0xD9; }
+        // Switches to the bank 3 before calling into it, then jumps to the ROMX area with the bank 3 still switched in
+        rom[0x100..0x10B].copy_from_slice(&[0x3E, 0x03, 0xEA, 0x00, 0x20, 0xCD, 0x00, 0x40, 0xC3, 0x10, 0x40]);
+        rom[3 * 0x4000] = 0xC9;
+        rom[3 * 0x4000 + 0x10] = 0xC9;
+        // Interrupt handler jumping to the ROMX area with an unknown bank
+        rom[0x40..0x43].copy_from_slice(&[0xC3, 0x20, 0x40]);
+        rom[0x4000 + 0x20] = 0xD9;
+        rom[2 * 0x4000 + 0x20] = 0x00;
+        // Unused opcode, so data in the bank 3
+        rom[3 * 0x4000 + 0x20] = 0xD3;
+
+        let disassembly = RomDisassembly::trace(&rom);
+        let listing = disassembly.listing(&Symbols::default());
+        assert!(listing.contains("    CALL L03_4000 "));
+        assert!(listing.contains("    JP L03_4010 "));
+        assert!(listing.contains("L03_4000:\n    RET "));
+        assert!(!listing.contains("L01_4000:"));
+        // Traced in every switchable bank, and labelled where it's code
+        assert!(listing.contains("L01_4020:\n    RETI "));
+        assert!(listing.contains("L02_4020:\n    NOP "));
+        assert!(!listing.contains("L03_4020:"));
+        assert!(listing.contains("    JP $4020 "));
+    }
+
+    #[test]
+    fn test_truncated_instruction() {
+        let mut rom = vec![0xFF_u8; 3 * 0x4000];
+        for vector in [0x40, 0x48, 0x50, 0x58, 0x60] { rom[vector] = 0xD9; }
+        // LD A, d8 on the last byte of the bank 1
+        rom[0x100..0x103].copy_from_slice(&[0xC3, 0xFF, 0x7F]);
+        rom[0x7FFF] = 0x3E;
+        // Interrupt handler jumping to the first byte of every switchable bank
+        rom[0x40..0x43].copy_from_slice(&[0xC3, 0x00, 0x40]);
+        rom[0x4000] = 0xC9;
+        rom[0x8000] = 0xC9;
+
+        let listing = RomDisassembly::trace(&rom).listing(&Symbols::default());
+        assert!(listing.contains("L01_7FFF:\n    ; LD A, $FF (truncated by the end of the bank)\n    DB $3E "));
+        // Not taken for the operand of the truncated instruction
+        assert!(listing.contains("L02_4000:\n    RET "));
+    }
+
+    #[test]
+    fn test_overlapped_label() {
+        let mut rom = vec![0xFF_u8; 0x8000];
+        for vector in [0x40, 0x48, 0x50, 0x58, 0x60] { rom[vector] = 0xD9; }
+        // LD A, $AF at 0x0100, jumped into past its opcode to execute XOR A
+        rom[0x100..0x105].copy_from_slice(&[0x3E, 0xAF, 0xC3, 0x01, 0x01]);
+
+        let listing = RomDisassembly::trace(&rom).listing(&Symbols::default());
+        assert!(listing.contains("L00_0100:\n    ; LD A, $AF\n    DB $3E "));
+        assert!(listing.contains("L00_0101:\n    DB $AF "));
+        assert!(listing.contains("    JP L00_0101 "));
+    }
+}
@@ -0,0 +1,7 @@
+pub mod coverage;
+pub mod debugger;
+pub mod disassembler;
+pub mod profiler;
+pub mod symbols;
+pub mod trace;
+pub mod watchpoint;
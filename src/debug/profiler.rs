@@ -0,0 +1,225 @@
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use crate::debug::symbols::Symbols;
+use crate::utils::types::FarAddress;
+
+/// Name of the code running outside of any tracked call
+const ROOT_NAME: &str = "(root)";
+// Number of lines of the hot spot table in the report
+const HOT_SPOT_COUNT: usize = 50;
+
+/// Bank and address of an instruction or a routine
+pub type Location = (u8, FarAddress);
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ProfileFormat {
+    /// Sorted tables of the hot spots and routines
+    #[default]
+    Report,
+    /// One "ROOT;CALLER;ROUTINE CYCLES" line per call stack, as read by flamegraph tools
+    Collapsed,
+}
+
+impl std::str::FromStr for ProfileFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        return match s {
+            "report" => Ok(ProfileFormat::Report),
+            "collapsed" => Ok(ProfileFormat::Collapsed),
+            _ => Err(format!("Unknown profile format \"{s}\", expected report or collapsed")),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct RoutineStats {
+    calls: u64,
+    /// Cycles spent in the routine itself
+    exclusive: u64,
+    /// Cycles spent in the routine and everything it called
+    inclusive: u64,
+}
+
+/// Attributes the T-cycles of every executed instruction to its PC and to the routines on the call stack
+pub struct Profiler {
+    path: PathBuf,
+    format: ProfileFormat,
+    total: u64,
+    by_pc: HashMap<Location, u64>,
+    /// `None` stands for the root
+    routines: HashMap<Option<Location>, RoutineStats>,
+    stacks: HashMap<Vec<Location>, u64>,
+    /// Routines entered by the calls in progress, outermost first
+    stack: Vec<Location>,
+}
+
+impl Profiler {
+    pub fn new(path: &Path, format: ProfileFormat) -> Profiler {
+        return Profiler {
+            path: path.to_path_buf(),
+            format,
+            total: 0,
+            by_pc: HashMap::new(),
+            routines: HashMap::new(),
+            stacks: HashMap::new(),
+            stack: Vec::new(),
+        }
+    }
+
+    /// Must be called after executing the instruction at `pc`, with the call stack as it was before executing it
+    pub fn record(&mut self, pc: Location, cycles: u64) {
+        self.total += cycles;
+        *self.by_pc.entry(pc).or_default() += cycles;
+
+        self.routines.entry(self.stack.last().copied()).or_default().exclusive += cycles;
+        self.routines.entry(None).or_default().inclusive += cycles;
+        for (depth, routine) in self.stack.iter().enumerate() {
+            // Recursive routines are only counted once
+            if !self.stack[..depth].contains(routine) {
+                self.routines.entry(Some(*routine)).or_default().inclusive += cycles;
+            }
+        }
+
+        match self.stacks.get_mut(self.stack.as_slice()) {
+            Some(stack_cycles) => *stack_cycles += cycles,
+            None => { self.stacks.insert(self.stack.clone(), cycles); }
+        }
+    }
+
+    /// Follows the call stack of the debugger, `called` being set if the last instruction entered a routine
+    pub fn update_stack(&mut self, routines: impl Iterator<Item = Location>, called: bool) {
+        self.stack.clear();
+        self.stack.extend(routines);
+
+        if called {
+            if let Some(routine) = self.stack.last() { self.routines.entry(Some(*routine)).or_default().calls += 1; }
+        }
+    }
+
+    fn name(symbols: &Symbols, routine: Option<Location>) -> String {
+        let Some((bank, addr)) = routine else { return String::from(ROOT_NAME) };
+        // Routines without a symbol of their own are not named after the previous one
+        return symbols.name_at(bank, addr).map_or_else(|| format!("{bank:02X}:{addr:04X}"), String::from);
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn percent(&self, cycles: u64) -> f64 {
+        return if self.total == 0 { 0.0 } else { cycles as f64 * 100.0 / self.total as f64 };
+    }
+
+    /// Flat hot spots by PC, then routines sorted by inclusive cycles
+    pub fn report(&self, symbols: &Symbols) -> String {
+        let mut report = format!("Profile of {} T-cycles\n\nHot spots:\n{:>12} {:>7}  Location\n", self.total, "Cycles", "%");
+
+        let mut hot_spots: Vec<(&Location, &u64)> = self.by_pc.iter().collect();
+        hot_spots.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+        for ((bank, pc), cycles) in hot_spots.into_iter().take(HOT_SPOT_COUNT) {
+            let location = format!("{bank:02X}:{pc:04X}");
+            let _ = match symbols.describe(*bank, *pc) {
+                Some(symbol) => writeln!(report, "{cycles:>12} {:>6.2}%  {location} <{symbol}>", self.percent(*cycles)),
+                None => writeln!(report, "{cycles:>12} {:>6.2}%  {location}", self.percent(*cycles)),
+            };
+        }
+
+        let _ = write!(report, "\nRoutines:\n{:>12} {:>7} {:>12} {:>7} {:>8}  Routine\n", "Inclusive", "%", "Self", "%", "Calls");
+        let mut routines: Vec<(&Option<Location>, &RoutineStats)> = self.routines.iter().collect();
+        routines.sort_by(|a, b| b.1.inclusive.cmp(&a.1.inclusive).then(b.1.exclusive.cmp(&a.1.exclusive)).then(a.0.cmp(b.0)));
+        for (routine, stats) in routines {
+            let _ = writeln!(
+                report, "{:>12} {:>6.2}% {:>12} {:>6.2}% {:>8}  {}",
+                stats.inclusive, self.percent(stats.inclusive), stats.exclusive, self.percent(stats.exclusive), stats.calls, Self::name(symbols, *routine),
+            );
+        }
+
+        return report;
+    }
+
+    /// Stacks in the collapsed format of flamegraph tools, sorted for stable output
+    pub fn collapsed(&self, symbols: &Symbols) -> String {
+        let mut lines: Vec<String> = self.stacks.iter()
+            .map(|(stack, cycles)| {
+                let frames: Vec<String> = std::iter::once(None).chain(stack.iter().copied().map(Some))
+                    .map(|routine| Self::name(symbols, routine).replace([';', ' '], "_"))
+                    .collect();
+                format!("{} {cycles}", frames.join(";"))
+            })
+            .collect();
+        lines.sort();
+
+        return lines.into_iter().map(|line| line + "\n").collect();
+    }
+
+    /// Writes the profile in the chosen format
+    pub fn save(&self, symbols: &Symbols) -> std::io::Result<()> {
+        let output = match self.format {
+            ProfileFormat::Report => self.report(symbols),
+            ProfileFormat::Collapsed => self.collapsed(symbols),
+        };
+
+        return std::fs::write(&self.path, output);
+    }
+
+    pub fn path(&self) -> &Path {
+        return &self.path;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use crate::debug::symbols::Symbols;
+    use super::{Profiler, ProfileFormat};
+
+    /// Main loop at 0x0150 calling 0x0200, which calls 0x0300
+    fn profile() -> Profiler {
+        let mut profiler = Profiler::new(Path::new("profile.txt"), ProfileFormat::Report);
+
+        profiler.record((0, 0x0150), 24);
+        profiler.update_stack([(0, 0x0200)].into_iter(), true);
+        profiler.record((0, 0x0200), 4);
+        profiler.record((0, 0x0201), 24);
+        profiler.update_stack([(0, 0x0200), (0, 0x0300)].into_iter(), true);
+        profiler.record((0, 0x0300), 8);
+        profiler.record((0, 0x0301), 16);
+        profiler.update_stack([(0, 0x0200)].into_iter(), false);
+        profiler.record((0, 0x0204), 16);
+        profiler.update_stack(std::iter::empty(), false);
+        profiler.record((0, 0x0153), 8);
+
+        return profiler;
+    }
+
+    #[test]
+    fn test_report() {
+        let mut symbols = Symbols::default();
+        symbols.insert(0, 0x0200, "Update");
+        let report = profile().report(&symbols);
+
+        assert!(report.starts_with("Profile of 100 T-cycles"));
+        // Hot spots sorted by cycles, then by address
+        assert!(report.contains("          24  24.00%  00:0150\n          24  24.00%  00:0201 <Update+1>\n          16  16.00%  00:0204 <Update+4>\n"));
+        assert!(report.contains("         100 100.00%           32  32.00%        0  (root)\n"));
+        assert!(report.contains("          68  68.00%           44  44.00%        1  Update\n"));
+        assert!(report.contains("          24  24.00%           24  24.00%        1  00:0300\n"));
+    }
+
+    #[test]
+    fn test_collapsed() {
+        let mut symbols = Symbols::default();
+        symbols.insert(0, 0x0200, "Update");
+
+        assert_eq!(profile().collapsed(&symbols), "(root) 32\n(root);Update 44\n(root);Update;00:0300 24\n");
+    }
+
+    #[test]
+    fn test_recursion() {
+        let mut profiler = Profiler::new(Path::new("profile.txt"), ProfileFormat::Collapsed);
+        profiler.update_stack([(0, 0x0200), (0, 0x0200)].into_iter(), true);
+        profiler.record((0, 0x0200), 12);
+
+        let report = profiler.report(&Symbols::default());
+        assert!(report.contains("          12 100.00%           12 100.00%        1  00:0200\n"));
+    }
+}
@@ -0,0 +1,110 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use crate::cpu::memory::Memory;
+use crate::utils::types::FarAddress;
+
+/// Symbols loaded from a RGBDS or no$gmb ".sym" file, made of "BANK:ADDR Name" lines (hexadecimal) and ";" comments
+#[derive(Debug, Default)]
+pub struct Symbols {
+    by_name: HashMap<String, (u8, FarAddress)>,
+    by_addr: BTreeMap<(u8, FarAddress), String>,
+}
+
+impl Symbols {
+    pub fn parse(text: &str) -> Result<Symbols, String> {
+        let mut symbols = Symbols::default();
+
+        for (number, line) in text.lines().enumerate() {
+            let line = line.split(';').next().unwrap_or_default().trim();
+            if line.is_empty() { continue; }
+
+            let error = || format!("Invalid symbol on line {}: \"{line}\"", number + 1);
+            let (location, name) = line.split_once(char::is_whitespace).ok_or_else(error)?;
+            let (bank, addr) = location.split_once(':').ok_or_else(error)?;
+            let bank = u8::from_str_radix(bank, 16).map_err(|_| error())?;
+            let addr = FarAddress::from_str_radix(addr, 16).map_err(|_| error())?;
+
+            symbols.insert(bank, addr, name.trim());
+        }
+
+        return Ok(symbols);
+    }
+
+    pub fn load(path: &Path) -> Result<Symbols, String> {
+        let text = std::fs::read_to_string(path).map_err(|error| format!("Couldn't read symbols from {}: {error}", path.display()))?;
+        return Self::parse(&text);
+    }
+
+    /// Symbol file of the ROM, if there is one next to it
+    pub fn load_for_rom(rom: &Path) -> Result<Symbols, String> {
+        let path = rom.with_extension("sym");
+        if !path.exists() { return Ok(Symbols::default()); }
+
+        return Self::load(&path);
+    }
+
+    pub fn insert(&mut self, bank: u8, addr: FarAddress, name: &str) {
+        self.by_name.insert(name.to_string(), (bank, addr));
+        // Keep the first name given to an address
+        self.by_addr.entry((bank, addr)).or_insert_with(|| name.to_string());
+    }
+
+    pub fn lookup(&self, name: &str) -> Option<(u8, FarAddress)> {
+        return self.by_name.get(name).copied();
+    }
+
+    pub fn name_at(&self, bank: u8, addr: FarAddress) -> Option<&str> {
+        return self.by_addr.get(&(bank, addr)).map(String::as_str);
+    }
+
+    /// Closest symbol at or before the address, in the same bank and memory region, with the offset from it
+    pub fn nearest(&self, bank: u8, addr: FarAddress) -> Option<(&str, FarAddress)> {
+        let (region_start, _) = Memory::region(addr);
+        let ((_, symbol_addr), name) = self.by_addr.range((bank, region_start)..=(bank, addr)).next_back()?;
+
+        return Some((name, addr - symbol_addr));
+    }
+
+    /// "Name" or "Name+offset" of the address in the bank, `None` without nearby symbol
+    pub fn describe(&self, bank: u8, addr: FarAddress) -> Option<String> {
+        return self.nearest(bank, addr).map(|(name, offset)| match offset {
+            0 => name.to_string(),
+            offset => format!("{name}+{offset:X}"),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Symbols;
+
+    const SYMBOLS: &str = "; File generated by rgblink
+00:0150 Main
+00:0158 Main.loop
+01:4000 Graphics
+00:c000 wBuffer
+";
+
+    #[test]
+    fn test_parse_symbols() {
+        let Ok(symbols) = Symbols::parse(SYMBOLS) else { panic!("Valid symbols were rejected") };
+
+        assert_eq!(symbols.lookup("Main.loop"), Some((0, 0x0158)));
+        assert_eq!(symbols.lookup("Graphics"), Some((1, 0x4000)));
+        assert_eq!(symbols.name_at(0, 0xC000), Some("wBuffer"));
+        assert!(Symbols::parse("00:0150").is_err());
+        assert!(Symbols::parse("0150 Main").is_err());
+    }
+
+    #[test]
+    fn test_nearest_symbol() {
+        let Ok(symbols) = Symbols::parse(SYMBOLS) else { panic!("Valid symbols were rejected") };
+
+        assert_eq!(symbols.nearest(0, 0x015A), Some(("Main.loop", 2)));
+        assert_eq!(symbols.describe(0, 0x0150), Some(String::from("Main")));
+        assert_eq!(symbols.describe(1, 0x4010), Some(String::from("Graphics+10")));
+        // Different bank, and different region
+        assert_eq!(symbols.nearest(2, 0x4010), None);
+        assert_eq!(symbols.nearest(0, 0x8000), None);
+    }
+}
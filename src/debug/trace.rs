@@ -0,0 +1,120 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use crate::cpu::memory::Memory;
+use crate::debug::symbols::Symbols;
+use crate::utils::types::FarAddress;
+
+/// Path standing for the standard output
+pub const STDOUT_PATH: &str = "-";
+
+/// Restricts the traced instructions, both conditions must hold when set
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TraceFilter {
+    /// Inclusive range of PC values
+    pub range: Option<(FarAddress, FarAddress)>,
+    pub bank: Option<u8>,
+}
+
+impl TraceFilter {
+    /// Whether the instruction at `pc`, in the bank mapped there, is traced
+    pub fn matches(self, bank: u8, pc: FarAddress) -> bool {
+        return self.range.is_none_or(|(start, end)| (start..=end).contains(&pc))
+            && self.bank.is_none_or(|filtered| bank == filtered);
+    }
+}
+
+/// Parses a "START-END" range of hexadecimal addresses
+pub fn parse_range(s: &str) -> Result<(FarAddress, FarAddress), String> {
+    let parse = |addr: &str| FarAddress::from_str_radix(addr.trim_start_matches('$').trim_start_matches("0x"), 16);
+
+    let Some((start, end)) = s.split_once('-') else { return Err(format!("Invalid range \"{s}\", expected START-END")) };
+    let (Ok(start), Ok(end)) = (parse(start), parse(end)) else { return Err(format!("Invalid range \"{s}\"")) };
+    if start > end { return Err(format!("Range \"{s}\" is empty")); }
+
+    return Ok((start, end));
+}
+
+/// One line in the Gameboy Doctor format, describing the state before the instruction at PC is executed
+pub fn trace_line(memory: &Memory) -> String {
+    let registers = &memory.registers;
+    let pc = registers.PC;
+    let pcmem = |offset| memory.peek(pc.wrapping_add(offset));
+
+    return format!(
+        "A:{:02X} F:{:02X} B:{:02X} C:{:02X} D:{:02X} E:{:02X} H:{:02X} L:{:02X} SP:{:04X} PC:{pc:04X} PCMEM:{:02X},{:02X},{:02X},{:02X}",
+        registers.get_a(), registers.get_f(), registers.get_b(), registers.get_c(),
+        registers.get_d(), registers.get_e(), registers.get_h(), registers.get_l(),
+        registers.SP, pcmem(0), pcmem(1), pcmem(2), pcmem(3),
+    );
+}
+
+pub struct Tracer {
+    output: Box<dyn Write>,
+    filter: TraceFilter,
+    /// Appends the nearest symbol to each line, which breaks the compatibility with Gameboy Doctor
+    annotate: bool,
+}
+
+impl Tracer {
+    /// Traces to the file, or to the standard output if the path is `STDOUT_PATH`
+    pub fn new(path: &Path, filter: TraceFilter, annotate: bool) -> std::io::Result<Tracer> {
+        let output: Box<dyn Write> = if path == Path::new(STDOUT_PATH) {
+            Box::new(BufWriter::new(std::io::stdout()))
+        } else {
+            Box::new(BufWriter::new(File::create(path)?))
+        };
+
+        return Ok(Tracer { output, filter, annotate });
+    }
+
+    /// Must be called before executing each instruction
+    pub fn trace(&mut self, memory: &Memory, symbols: &Symbols) -> std::io::Result<()> {
+        let pc = memory.registers.PC;
+        let bank = memory.bank(pc);
+        if !self.filter.matches(bank, pc) { return Ok(()); }
+
+        return match symbols.describe(bank, pc).filter(|_| self.annotate) {
+            Some(symbol) => writeln!(self.output, "{} ; {symbol}", trace_line(memory)),
+            None => writeln!(self.output, "{}", trace_line(memory)),
+        }
+    }
+
+    pub fn flush(&mut self) -> std::io::Result<()> {
+        return self.output.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cpu::memory::Memory;
+    use super::{parse_range, trace_line, TraceFilter};
+
+    #[test]
+    fn test_trace_line() {
+        let mut memory = Memory::new(0x10000);
+        memory.registers.set_a(0x01);
+        memory.registers.set_f(0xB0);
+        memory.registers.SP = 0xFFFE;
+        memory.registers.PC = 0x0100;
+        memory.memory[0x0100..0x0104].copy_from_slice(&[0x00, 0xC3, 0x50, 0x01]);
+
+        assert_eq!(trace_line(&memory), "A:01 F:B0 B:00 C:00 D:00 E:00 H:00 L:00 SP:FFFE PC:0100 PCMEM:00,C3,50,01");
+    }
+
+    #[test]
+    fn test_filter() {
+        assert_eq!(parse_range("$0100-$0150"), Ok((0x0100, 0x0150)));
+        assert!(parse_range("0150-0100").is_err());
+        assert!(parse_range("0100").is_err());
+
+        let filter = TraceFilter { range: Some((0x0100, 0x0150)), bank: None };
+        assert!(filter.matches(0, 0x0150));
+        assert!(!filter.matches(0, 0x0151));
+
+        let filter = TraceFilter { range: None, bank: Some(1) };
+        assert!(filter.matches(1, 0x4000));
+        assert!(!filter.matches(0, 0x3FFF));
+        assert!(!filter.matches(2, 0x4000));
+    }
+}
@@ -0,0 +1,189 @@
+use std::cell::Cell;
+use crate::debug::debugger::parse_hex;
+use crate::utils::types::{FarAddress, Value};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WatchKind {
+    Read,
+    Write,
+    /// Write of a value different from the current one
+    Change,
+}
+
+/// What performed the memory access
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AccessSource {
+    Cpu,
+    /// Push or pop, by PUSH/POP, CALL/RET or RST
+    Stack,
+    /// CGB general-purpose or H-Blank DMA to VRAM
+    Hdma,
+    /// Copy to the OAM started by a write to 0xFF46
+    OamDma,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Watchpoint {
+    pub kind: WatchKind,
+    /// Inclusive range of watched addresses
+    pub start: FarAddress,
+    pub end: FarAddress,
+    /// Only trigger when this value is read or written
+    pub value: Option<Value>,
+}
+
+impl Watchpoint {
+    fn matches(self, kind: WatchKind, addr: FarAddress, value: Value) -> bool {
+        return self.kind == kind && (self.start..=self.end).contains(&addr) && self.value.is_none_or(|expected| expected == value);
+    }
+}
+
+/// "[r|w|c:]ADDR[-END][=VALUE]", watching writes by default
+impl std::str::FromStr for Watchpoint {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (kind, s) = match s.split_once(':') {
+            Some(("r", s)) => (WatchKind::Read, s),
+            Some(("w", s)) => (WatchKind::Write, s),
+            Some(("c", s)) => (WatchKind::Change, s),
+            Some((kind, _)) => return Err(format!("Invalid watchpoint kind \"{kind}\", expected r, w or c")),
+            None => (WatchKind::Write, s),
+        };
+
+        let (range, value) = match s.split_once('=') {
+            Some((range, value)) => (range, Some(parse_hex(value)?)),
+            None => (s, None),
+        };
+
+        let (start, end) = match range.split_once('-') {
+            Some((start, end)) => (parse_hex(start)?, parse_hex(end)?),
+            None => (parse_hex(range)?, parse_hex(range)?),
+        };
+        if start > end { return Err(format!("Range \"{range}\" is empty")); }
+
+        return Ok(Watchpoint { kind, start, end, value });
+    }
+}
+
+impl std::fmt::Display for Watchpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kind = match self.kind {
+            WatchKind::Read => "r",
+            WatchKind::Write => "w",
+            WatchKind::Change => "c",
+        };
+
+        write!(f, "{kind}:{:04X}", self.start)?;
+        if self.end != self.start { write!(f, "-{:04X}", self.end)?; }
+        if let Some(value) = self.value { write!(f, "={value:02X}")?; }
+        return Ok(());
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WatchHit {
+    pub watchpoint: Watchpoint,
+    pub addr: FarAddress,
+    pub source: AccessSource,
+    /// Value before the access
+    pub old: Value,
+    /// Value read or written
+    pub value: Value,
+}
+
+impl std::fmt::Display for WatchHit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let source = match self.source {
+            AccessSource::Cpu => "",
+            AccessSource::Stack => " (stack)",
+            AccessSource::Hdma => " (HDMA)",
+            AccessSource::OamDma => " (OAM DMA)",
+        };
+
+        return match self.watchpoint.kind {
+            WatchKind::Read => write!(f, "Watchpoint {} hit: read ${:02X} at {:04X}{source}", self.watchpoint, self.value, self.addr),
+            WatchKind::Write | WatchKind::Change => {
+                write!(f, "Watchpoint {} hit: wrote ${:02X} at {:04X}{source}, was ${:02X}", self.watchpoint, self.value, self.addr, self.old)
+            }
+        }
+    }
+}
+
+/// Checked by the memory on every access, the debugger collects the hit after each instruction
+#[derive(Default)]
+pub struct Watchpoints {
+    list: Vec<Watchpoint>,
+    /// First hit since the last check. A cell, since reads only borrow the memory immutably
+    hit: Cell<Option<WatchHit>>,
+}
+
+impl Watchpoints {
+    pub fn list(&self) -> &[Watchpoint] {
+        return &self.list;
+    }
+
+    pub fn add(&mut self, watchpoint: Watchpoint) {
+        self.list.push(watchpoint);
+    }
+
+    pub fn remove(&mut self, index: usize) -> Option<Watchpoint> {
+        return (index < self.list.len()).then(|| self.list.remove(index));
+    }
+
+    pub fn take_hit(&self) -> Option<WatchHit> {
+        return self.hit.take();
+    }
+
+    fn check(&self, kind: WatchKind, addr: FarAddress, old: Value, value: Value, source: AccessSource) {
+        if self.list.is_empty() { return; }
+
+        let watchpoint = self.list.iter().find(|watchpoint| watchpoint.matches(kind, addr, value));
+        if let (Some(watchpoint), None) = (watchpoint, self.hit.get()) {
+            self.hit.set(Some(WatchHit { watchpoint: *watchpoint, addr, source, old, value }));
+        }
+    }
+
+    pub fn check_read(&self, addr: FarAddress, value: Value, source: AccessSource) {
+        self.check(WatchKind::Read, addr, value, value, source);
+    }
+
+    pub fn check_write(&self, addr: FarAddress, old: Value, value: Value, source: AccessSource) {
+        self.check(WatchKind::Write, addr, old, value, source);
+        if old != value { self.check(WatchKind::Change, addr, old, value, source); }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AccessSource, Watchpoint, Watchpoints, WatchKind};
+
+    #[test]
+    fn test_parse_watchpoint() {
+        assert_eq!("C000".parse(), Ok(Watchpoint { kind: WatchKind::Write, start: 0xC000, end: 0xC000, value: None }));
+        assert_eq!("c:$C000-$C0FF=42".parse(), Ok(Watchpoint { kind: WatchKind::Change, start: 0xC000, end: 0xC0FF, value: Some(0x42) }));
+        assert_eq!("r:FF44".parse::<Watchpoint>().map(|watchpoint| watchpoint.to_string()), Ok(String::from("r:FF44")));
+        assert!("x:C000".parse::<Watchpoint>().is_err());
+        assert!("C0FF-C000".parse::<Watchpoint>().is_err());
+    }
+
+    #[test]
+    fn test_watchpoint_hits() {
+        let mut watchpoints = Watchpoints::default();
+        watchpoints.add("c:C000-C0FF".parse().unwrap_or_else(|error| panic!("{error}")));
+        watchpoints.add("r:FF00=0F".parse().unwrap_or_else(|error| panic!("{error}")));
+
+        // Same value written, no change
+        watchpoints.check_write(0xC010, 0x12, 0x12, AccessSource::Cpu);
+        assert_eq!(watchpoints.take_hit(), None);
+
+        watchpoints.check_write(0xC010, 0x12, 0x34, AccessSource::Stack);
+        let hit = watchpoints.take_hit();
+        assert_eq!(hit.map(|hit| (hit.addr, hit.old, hit.value, hit.source)), Some((0xC010, 0x12, 0x34, AccessSource::Stack)));
+
+        watchpoints.check_read(0xFF00, 0x0E, AccessSource::Cpu);
+        assert_eq!(watchpoints.take_hit(), None);
+        watchpoints.check_read(0xFF00, 0x0F, AccessSource::Cpu);
+        assert!(watchpoints.take_hit().is_some());
+    }
+}
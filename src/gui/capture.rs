@@ -0,0 +1,413 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use crate::cpu::apu::{SAMPLE_RATE, StereoSample};
+use crate::cpu::execution::{CPU_FREQUENCY, TICKS_PER_FRAME};
+use crate::gui::screen::{BYTES_PER_PIXEL, Framebuffer, Rgb, SCREEN_HEIGHT, SCREEN_WIDTH};
+use crate::gui::sound::WavRecorder;
+use crate::utils::checksum::{adler32, crc32};
+use crate::utils::log::log;
+use crate::utils::types::Byte;
+
+// GIF delays are in hundredths of a second, 2 is the closest to 60 fps most viewers honor
+const GIF_FRAME_DELAY: u16 = 2;
+
+//  #############################
+//  #            PNG            #
+//  #############################
+
+pub const PNG_SIGNATURE: [Byte; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1A, b'\n'];
+
+/// Zlib stream made of stored (uncompressed) deflate blocks, which is enough for lossless captures
+#[allow(clippy::cast_possible_truncation)]
+fn zlib_stored(data: &[Byte]) -> Vec<Byte> {
+    const MAX_BLOCK_SIZE: usize = 0xFFFF;
+
+    let mut output = vec![0x78, 0x01];
+    let mut blocks = data.chunks(MAX_BLOCK_SIZE).peekable();
+
+    if blocks.peek().is_none() { output.extend_from_slice(&[0x01, 0x00, 0x00, 0xFF, 0xFF]); }
+    while let Some(block) = blocks.next() {
+        let length = block.len() as u16;
+        output.push(u8::from(blocks.peek().is_none()));
+        output.extend_from_slice(&length.to_le_bytes());
+        output.extend_from_slice(&(!length).to_le_bytes());
+        output.extend_from_slice(block);
+    }
+    output.extend_from_slice(&adler32(data).to_be_bytes());
+
+    return output;
+}
+
+fn png_chunk(output: &mut Vec<Byte>, kind: [Byte; 4], data: &[Byte]) {
+    #[allow(clippy::cast_possible_truncation)]
+    output.extend_from_slice(&(data.len() as u32).to_be_bytes());
+
+    let start = output.len();
+    output.extend_from_slice(&kind);
+    output.extend_from_slice(data);
+    let crc = crc32(&output[start..]);
+    output.extend_from_slice(&crc.to_be_bytes());
+}
+
+/// Encodes RGB24 pixels as a 8-bit truecolor PNG
+pub fn encode_png(width: usize, height: usize, pixels: &[Byte]) -> Vec<Byte> {
+    debug_assert!(pixels.len() == width * height * BYTES_PER_PIXEL);
+
+    let mut header = Vec::with_capacity(13);
+    #[allow(clippy::cast_possible_truncation)]
+    {
+        header.extend_from_slice(&(width as u32).to_be_bytes());
+        header.extend_from_slice(&(height as u32).to_be_bytes());
+    }
+    // Bit depth 8, truecolor, deflate, adaptive filtering, no interlace
+    header.extend_from_slice(&[8, 2, 0, 0, 0]);
+
+    // Every scanline is prefixed with its filter type (0 => None)
+    let mut scanlines = Vec::with_capacity(pixels.len() + height);
+    for row in pixels.chunks_exact(width * BYTES_PER_PIXEL) {
+        scanlines.push(0);
+        scanlines.extend_from_slice(row);
+    }
+
+    let mut output = PNG_SIGNATURE.to_vec();
+    png_chunk(&mut output, *b"IHDR", &header);
+    png_chunk(&mut output, *b"IDAT", &zlib_stored(&scanlines));
+    png_chunk(&mut output, *b"IEND", &[]);
+
+    return output;
+}
+
+pub fn save_screenshot(framebuffer: &Framebuffer, path: &Path, scale: usize) -> std::io::Result<()> {
+    let scale = scale.max(1);
+
+    log!("GUI", format!("Saving screenshot to {} (x{scale})", path.display()));
+
+    return std::fs::write(path, encode_png(SCREEN_WIDTH * scale, SCREEN_HEIGHT * scale, &framebuffer.scaled(scale)));
+}
+
+/// First "<prefix>-<n>.<extension>" which doesn't exist yet in the directory
+pub fn next_free_path(directory: &Path, prefix: &str, extension: &str) -> PathBuf {
+    let mut index = 0_u32;
+
+    loop {
+        let path = directory.join(format!("{prefix}-{index}.{extension}"));
+        if !path.exists() { return path; }
+        index += 1;
+    }
+}
+
+//  #############################
+//  #            GIF            #
+//  #############################
+
+/// Palette of the frame and the index of each pixel. Frames with more than 256 colors are quantized to RGB 3-3-2.
+#[allow(clippy::cast_possible_truncation)]
+fn gif_palette(framebuffer: &Framebuffer) -> (Vec<Rgb>, Vec<Byte>) {
+    let mut palette: Vec<Rgb> = Vec::new();
+    let mut lookup: HashMap<Rgb, Byte> = HashMap::new();
+    let mut indices = Vec::with_capacity(SCREEN_WIDTH * SCREEN_HEIGHT);
+
+    for pixel in framebuffer.pixels.chunks_exact(BYTES_PER_PIXEL) {
+        let color = (pixel[0], pixel[1], pixel[2]);
+        let index = if let Some(index) = lookup.get(&color) { *index } else {
+            if palette.len() == 256 { return gif_quantized_palette(framebuffer); }
+            let index = palette.len() as Byte;
+            palette.push(color);
+            lookup.insert(color, index);
+            index
+        };
+        indices.push(index);
+    }
+
+    return (palette, indices);
+}
+
+fn gif_quantized_palette(framebuffer: &Framebuffer) -> (Vec<Rgb>, Vec<Byte>) {
+    let palette = (0..=255_u8).map(|index| ((index >> 5) * 36, ((index >> 2) & 0b111) * 36, (index & 0b11) * 85)).collect();
+    let indices = framebuffer.pixels.chunks_exact(BYTES_PER_PIXEL)
+        .map(|pixel| (pixel[0] & 0b1110_0000) | ((pixel[1] >> 3) & 0b0001_1100) | (pixel[2] >> 6))
+        .collect();
+
+    return (palette, indices);
+}
+
+struct BitWriter {
+    bytes: Vec<Byte>,
+    buffer: u32,
+    bit_count: u32,
+}
+
+impl BitWriter {
+    fn write(&mut self, code: u16, size: u32) {
+        self.buffer |= u32::from(code) << self.bit_count;
+        self.bit_count += size;
+
+        while self.bit_count >= 8 {
+            #[allow(clippy::cast_possible_truncation)]
+            self.bytes.push(self.buffer as Byte);
+            self.buffer >>= 8;
+            self.bit_count -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<Byte> {
+        #[allow(clippy::cast_possible_truncation)]
+        if self.bit_count > 0 { self.bytes.push(self.buffer as Byte); }
+        return self.bytes;
+    }
+}
+
+/// Variable-length LZW, as specified by the GIF (version 89a) standard, with a minimum code size of 8 bits
+fn gif_lzw(indices: &[Byte]) -> Vec<Byte> {
+    const MIN_CODE_SIZE: u32 = 8;
+    const CLEAR_CODE: u16 = 1 << MIN_CODE_SIZE;
+    const END_CODE: u16 = CLEAR_CODE + 1;
+    const MAX_CODE: u16 = 4095;
+
+    let mut writer = BitWriter { bytes: Vec::new(), buffer: 0, bit_count: 0 };
+    let mut dictionary: HashMap<(u16, Byte), u16> = HashMap::new();
+    let mut code_size = MIN_CODE_SIZE + 1;
+    let mut next_code = END_CODE + 1;
+
+    writer.write(CLEAR_CODE, code_size);
+
+    let Some((first, rest)) = indices.split_first() else {
+        writer.write(END_CODE, code_size);
+        return writer.finish();
+    };
+    let mut prefix = u16::from(*first);
+
+    for index in rest {
+        if let Some(code) = dictionary.get(&(prefix, *index)) {
+            prefix = *code;
+            continue;
+        }
+
+        writer.write(prefix, code_size);
+
+        if next_code >= MAX_CODE {
+            writer.write(CLEAR_CODE, code_size);
+            dictionary.clear();
+            code_size = MIN_CODE_SIZE + 1;
+            next_code = END_CODE + 1;
+        } else {
+            dictionary.insert((prefix, *index), next_code);
+            // The decoder adds its entries one code late, so the code size only grows once the inserted code doesn't fit
+            if next_code == (1 << code_size) && code_size < 12 { code_size += 1; }
+            next_code += 1;
+        }
+        prefix = u16::from(*index);
+    }
+
+    writer.write(prefix, code_size);
+    writer.write(END_CODE, code_size);
+
+    return writer.finish();
+}
+
+//  #############################
+//  #         Recording         #
+//  #############################
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RecordingFormat {
+    /// One PNG file per frame, in a directory
+    PngSequence,
+    /// Animated GIF
+    Gif,
+    /// YUV4MPEG2 stream, 4:4:4 chroma
+    Y4m,
+    /// Headerless RGB24 frames, one after the other
+    Raw,
+}
+
+impl RecordingFormat {
+    pub fn from_path(path: &Path) -> RecordingFormat {
+        return match path.extension().and_then(std::ffi::OsStr::to_str) {
+            Some("gif") => RecordingFormat::Gif,
+            Some("y4m") => RecordingFormat::Y4m,
+            Some("raw" | "rgb") => RecordingFormat::Raw,
+            _ => RecordingFormat::PngSequence,
+        }
+    }
+}
+
+/// Records the screen, with the sound alongside in a WAV file (see `audio_path`)
+pub struct Recorder {
+    format: RecordingFormat,
+    path: PathBuf,
+    writer: Option<BufWriter<File>>,
+    audio: WavRecorder,
+    frame_count: u32,
+}
+
+impl Recorder {
+    pub fn start(path: &Path) -> std::io::Result<Recorder> {
+        let format = RecordingFormat::from_path(path);
+
+        log!("GUI", format!("Recording {format:?} to {}", path.display()));
+
+        let writer = if format == RecordingFormat::PngSequence {
+            std::fs::create_dir_all(path)?;
+            None
+        } else {
+            let mut writer = BufWriter::new(File::create(path)?);
+            Self::write_header(&mut writer, format)?;
+            Some(writer)
+        };
+        let audio = WavRecorder::start(&Self::audio_path(path, format))?;
+
+        return Ok(Recorder { format, path: path.to_path_buf(), writer, audio, frame_count: 0 });
+    }
+
+    /// The sound goes next to the video with the WAV extension, e.g. "game.wav" for "game.gif", or in the directory of
+    /// the PNG frames as "audio.wav"
+    fn audio_path(path: &Path, format: RecordingFormat) -> PathBuf {
+        return if format == RecordingFormat::PngSequence { path.join("audio.wav") } else { path.with_extension("wav") };
+    }
+
+    /// Records the samples mixed since the last frame, which come before it
+    pub fn record_samples(&mut self, samples: &[StereoSample]) -> std::io::Result<()> {
+        return self.audio.push_samples(samples);
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn write_header(writer: &mut BufWriter<File>, format: RecordingFormat) -> std::io::Result<()> {
+        match format {
+            RecordingFormat::Gif => {
+                writer.write_all(b"GIF89a")?;
+                writer.write_all(&(SCREEN_WIDTH as u16).to_le_bytes())?;
+                writer.write_all(&(SCREEN_HEIGHT as u16).to_le_bytes())?;
+                // No global color table, every frame has its own
+                writer.write_all(&[0x00, 0x00, 0x00])?;
+                // NETSCAPE2.0 application extension, loop forever
+                writer.write_all(&[0x21, 0xFF, 0x0B])?;
+                writer.write_all(b"NETSCAPE2.0")?;
+                writer.write_all(&[0x03, 0x01, 0x00, 0x00, 0x00])?;
+            }
+            RecordingFormat::Y4m => {
+                writeln!(writer, "YUV4MPEG2 W{SCREEN_WIDTH} H{SCREEN_HEIGHT} F{CPU_FREQUENCY}:{TICKS_PER_FRAME} Ip A1:1 C444")?;
+            }
+            RecordingFormat::PngSequence | RecordingFormat::Raw => {}
+        }
+
+        return Ok(());
+    }
+
+    pub fn record_frame(&mut self, framebuffer: &Framebuffer) -> std::io::Result<()> {
+        match (self.format, &mut self.writer) {
+            (RecordingFormat::PngSequence, _) => {
+                let path = self.path.join(format!("frame-{:06}.png", self.frame_count));
+                std::fs::write(path, encode_png(SCREEN_WIDTH, SCREEN_HEIGHT, &framebuffer.pixels))?;
+            }
+            (RecordingFormat::Raw, Some(writer)) => writer.write_all(&framebuffer.pixels)?,
+            (RecordingFormat::Y4m, Some(writer)) => Self::write_y4m_frame(writer, framebuffer)?,
+            (RecordingFormat::Gif, Some(writer)) => Self::write_gif_frame(writer, framebuffer)?,
+            (_, None) => unreachable!("Stream formats always have a writer"),
+        }
+
+        self.frame_count += 1;
+
+        // Frames recorded while paused or rewinding have no sound, it is filled with silence to keep it in sync
+        let expected = u64::from(self.frame_count) * TICKS_PER_FRAME * u64::from(SAMPLE_RATE) / CPU_FREQUENCY;
+        let missing = usize::try_from(expected.saturating_sub(u64::from(self.audio.sample_count()))).unwrap_or(0);
+        if missing > 0 { self.audio.push_samples(&vec![(0, 0); missing])?; }
+
+        return Ok(());
+    }
+
+    // BT.601 full range conversion
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn write_y4m_frame(writer: &mut BufWriter<File>, framebuffer: &Framebuffer) -> std::io::Result<()> {
+        let mut planes = [Vec::with_capacity(SCREEN_WIDTH * SCREEN_HEIGHT), Vec::with_capacity(SCREEN_WIDTH * SCREEN_HEIGHT), Vec::with_capacity(SCREEN_WIDTH * SCREEN_HEIGHT)];
+
+        for pixel in framebuffer.pixels.chunks_exact(BYTES_PER_PIXEL) {
+            let (r, g, b) = (f32::from(pixel[0]), f32::from(pixel[1]), f32::from(pixel[2]));
+            planes[0].push((0.299 * r + 0.587 * g + 0.114 * b).round().clamp(0.0, 255.0) as Byte);
+            planes[1].push((128.0 - 0.168_736 * r - 0.331_264 * g + 0.5 * b).round().clamp(0.0, 255.0) as Byte);
+            planes[2].push((128.0 + 0.5 * r - 0.418_688 * g - 0.081_312 * b).round().clamp(0.0, 255.0) as Byte);
+        }
+
+        writer.write_all(b"FRAME\n")?;
+        for plane in planes { writer.write_all(&plane)?; }
+
+        return Ok(());
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn write_gif_frame(writer: &mut BufWriter<File>, framebuffer: &Framebuffer) -> std::io::Result<()> {
+        let (palette, indices) = gif_palette(framebuffer);
+
+        // Graphic control extension, no transparency
+        writer.write_all(&[0x21, 0xF9, 0x04, 0x00])?;
+        writer.write_all(&GIF_FRAME_DELAY.to_le_bytes())?;
+        writer.write_all(&[0x00, 0x00])?;
+
+        // Image descriptor, covering the whole screen, with a local color table of 256 entries
+        writer.write_all(&[0x2C, 0x00, 0x00, 0x00, 0x00])?;
+        writer.write_all(&(SCREEN_WIDTH as u16).to_le_bytes())?;
+        writer.write_all(&(SCREEN_HEIGHT as u16).to_le_bytes())?;
+        writer.write_all(&[0b1000_0111])?;
+        for index in 0..256 {
+            let (r, g, b) = palette.get(index).copied().unwrap_or_default();
+            writer.write_all(&[r, g, b])?;
+        }
+
+        // Image data, split in sub-blocks of at most 255 bytes
+        writer.write_all(&[8])?;
+        for block in gif_lzw(&indices).chunks(255) {
+            writer.write_all(&[block.len() as Byte])?;
+            writer.write_all(block)?;
+        }
+        writer.write_all(&[0x00])?;
+
+        return Ok(());
+    }
+
+    pub fn finish(mut self) -> std::io::Result<()> {
+        log!("GUI", format!("Recorded {} frames to {}", self.frame_count, self.path.display()));
+
+        if let Some(mut writer) = self.writer.take() {
+            if self.format == RecordingFormat::Gif { writer.write_all(&[0x3B])?; }
+            writer.flush()?;
+        }
+
+        return self.audio.finish();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::gui::screen::Framebuffer;
+    use super::{encode_png, PNG_SIGNATURE, Recorder};
+
+    #[test]
+    fn test_png_layout() {
+        let png = encode_png(2, 1, &[0xFF, 0x00, 0x00, 0x00, 0xFF, 0x00]);
+        assert_eq!(&png[..8], &PNG_SIGNATURE);
+        assert_eq!(&png[12..16], b"IHDR");
+        assert_eq!(&png[png.len() - 8..png.len() - 4], b"IEND");
+    }
+
+    #[test]
+    fn test_sound_track() {
+        let path = std::env::temp_dir().join(format!("lameboy-test-{}.raw", std::process::id()));
+        let Ok(mut recorder) = Recorder::start(&path) else { panic!("Couldn't create {}", path.display()) };
+        let framebuffer = Framebuffer::new();
+        // An emulated frame, then one recorded while paused
+        let finished = recorder.record_samples(&[(1, 1); 803]).and_then(|()| recorder.record_frame(&framebuffer))
+            .and_then(|()| recorder.record_frame(&framebuffer))
+            .and_then(|()| recorder.finish());
+        assert!(finished.is_ok());
+
+        let audio = std::fs::read(path.with_extension("wav")).unwrap_or_default();
+        let video = std::fs::read(&path).map(|data| data.len()).unwrap_or_default();
+        let _ = (std::fs::remove_file(&path), std::fs::remove_file(path.with_extension("wav")));
+        assert_eq!(video, 2 * framebuffer.pixels.len());
+        // 803.6 samples per frame, the paused one being silent
+        assert_eq!(audio.len(), 44 + 1607 * 4);
+        assert_eq!((&audio[44..48], &audio[audio.len() - 4..]), (&[1, 0, 1, 0][..], &[0, 0, 0, 0][..]));
+    }
+}
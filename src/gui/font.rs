@@ -0,0 +1,120 @@
+use crate::gui::screen::{Image, Rgb};
+
+/// Glyphs are 3x5 pixels, drawn in 4x6 cells to leave a space between characters and lines
+pub const GLYPH_WIDTH: usize = 4;
+pub const GLYPH_HEIGHT: usize = 6;
+
+const FIRST_GLYPH: char = ' ';
+// One row per line, from top to bottom, the leftmost pixel being bit 2
+const GLYPHS: [[u8; 5]; 64] = [
+    [0, 0, 0, 0, 0], // ' '
+    [2, 2, 2, 0, 2], // !
+    [5, 5, 0, 0, 0], // "
+    [5, 7, 5, 7, 5], // #
+    [3, 6, 7, 3, 6], // $
+    [5, 1, 2, 4, 5], // %
+    [2, 5, 2, 5, 3], // &
+    [2, 2, 0, 0, 0], // '
+    [1, 2, 2, 2, 1], // (
+    [4, 2, 2, 2, 4], // )
+    [0, 5, 2, 5, 0], // *
+    [0, 2, 7, 2, 0], // +
+    [0, 0, 0, 2, 4], // ,
+    [0, 0, 7, 0, 0], // -
+    [0, 0, 0, 0, 2], // .
+    [1, 1, 2, 4, 4], // /
+    [7, 5, 5, 5, 7], // 0
+    [2, 6, 2, 2, 7], // 1
+    [7, 1, 7, 4, 7], // 2
+    [7, 1, 3, 1, 7], // 3
+    [5, 5, 7, 1, 1], // 4
+    [7, 4, 7, 1, 7], // 5
+    [7, 4, 7, 5, 7], // 6
+    [7, 1, 1, 2, 2], // 7
+    [7, 5, 7, 5, 7], // 8
+    [7, 5, 7, 1, 7], // 9
+    [0, 2, 0, 2, 0], // :
+    [0, 2, 0, 2, 4], // ;
+    [1, 2, 4, 2, 1], // <
+    [0, 7, 0, 7, 0], // =
+    [4, 2, 1, 2, 4], // >
+    [7, 1, 3, 0, 2], // ?
+    [2, 5, 7, 4, 3], // @
+    [2, 5, 7, 5, 5], // A
+    [6, 5, 6, 5, 6], // B
+    [3, 4, 4, 4, 3], // C
+    [6, 5, 5, 5, 6], // D
+    [7, 4, 6, 4, 7], // E
+    [7, 4, 6, 4, 4], // F
+    [3, 4, 5, 5, 3], // G
+    [5, 5, 7, 5, 5], // H
+    [7, 2, 2, 2, 7], // I
+    [1, 1, 1, 5, 2], // J
+    [5, 5, 6, 5, 5], // K
+    [4, 4, 4, 4, 7], // L
+    [5, 7, 7, 5, 5], // M
+    [6, 5, 5, 5, 5], // N
+    [2, 5, 5, 5, 2], // O
+    [6, 5, 6, 4, 4], // P
+    [2, 5, 5, 6, 3], // Q
+    [6, 5, 6, 5, 5], // R
+    [3, 4, 2, 1, 6], // S
+    [7, 2, 2, 2, 2], // T
+    [5, 5, 5, 5, 7], // U
+    [5, 5, 5, 5, 2], // V
+    [5, 5, 7, 7, 5], // W
+    [5, 5, 2, 5, 5], // X
+    [5, 5, 2, 2, 2], // Y
+    [7, 1, 2, 4, 7], // Z
+    [3, 2, 2, 2, 3], // [
+    [4, 4, 2, 1, 1], // \
+    [6, 2, 2, 2, 6], // ]
+    [2, 5, 0, 0, 0], // ^
+    [0, 0, 0, 0, 7], // _
+];
+
+/// Lowercase letters are drawn uppercase, characters without glyph as "?"
+fn glyph(character: char) -> &'static [u8; 5] {
+    let index = (character.to_ascii_uppercase() as usize).wrapping_sub(FIRST_GLYPH as usize);
+    return GLYPHS.get(index).unwrap_or(&GLYPHS['?' as usize - FIRST_GLYPH as usize]);
+}
+
+/// Draws a single line of text with its top-left corner at (x, y), clipped to the image
+pub fn draw_text(image: &mut Image, x: usize, y: usize, text: &str, color: Rgb) {
+    for (index, character) in text.chars().enumerate() {
+        let left = x + index * GLYPH_WIDTH;
+
+        for (row, bits) in glyph(character).iter().enumerate() {
+            for column in 0..3 {
+                if bits & (0b100 >> column) != 0 { image.set_pixel(left + column, y + row, color); }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::gui::screen::Image;
+    use super::{draw_text, glyph, GLYPHS};
+
+    #[test]
+    fn test_glyphs() {
+        assert_eq!(glyph('0'), &[7, 5, 5, 5, 7]);
+        assert_eq!(glyph('a'), glyph('A'));
+        assert_eq!(glyph('~'), glyph('?'));
+        assert_eq!(glyph('_'), &GLYPHS[63]);
+    }
+
+    #[test]
+    fn test_draw_text() {
+        let mut image = Image::new(8, 6);
+        draw_text(&mut image, 0, 0, "-1", (0xFF, 0xFF, 0xFF));
+
+        // Middle row of "-", then the second column
+        assert_eq!(image.pixel(0, 2), (0xFF, 0xFF, 0xFF));
+        assert_eq!(image.pixel(0, 1), (0, 0, 0));
+        assert_eq!(image.pixel(5, 0), (0xFF, 0xFF, 0xFF));
+        // Clipped outside of the image
+        draw_text(&mut image, 6, 4, "8", (0xFF, 0xFF, 0xFF));
+    }
+}
@@ -1,40 +1,391 @@
-use sdl2::pixels::Color;
-use sdl2::event::Event;
-use sdl2::keyboard::Keycode;
+use sdl2::audio::{AudioQueue, AudioSpecDesired};
+use sdl2::event::{Event, WindowEvent};
+use sdl2::keyboard::{Keycode, Scancode};
+use sdl2::pixels::PixelFormatEnum;
+use sdl2::video::Window;
+use std::path::Path;
 use std::time::Duration;
+use crate::cpu::apu::SAMPLE_RATE;
+use crate::cpu::colorization::{button_combination, colorize, SELECTION_TICKS};
+use crate::cpu::joypad;
+use crate::cpu::memory::Memory;
+use crate::cpu::sgb::{BORDER_HEIGHT, BORDER_WIDTH};
+use crate::debug::debugger::Debugger;
+use crate::gui::capture::{next_free_path, Recorder, save_screenshot};
+use crate::gui::hexview::MemoryViewer;
+use crate::gui::input::{joypad_state, palette_buttons, JoypadState};
+use crate::gui::screen::{Framebuffer, SCREEN_HEIGHT, SCREEN_WIDTH};
+use crate::gui::sound::WavRecorder;
+use crate::gui::viewer::Viewers;
+use crate::gui::vram::{render_border, render_screen};
 use crate::log;
+use crate::state::movie::Movie;
+use crate::state::rewind::RewindBuffer;
+use crate::state::savestate::{load_from_slot, save_to_slot};
+use crate::utils::args::Arguments;
+use crate::utils::types::WideValue;
 
-pub fn launch_gui() {
-    let Ok(sdl_context) = sdl2::init() else { todo!() };
-    let Ok(video_subsystem) = sdl_context.video() else { todo!() };
+const WINDOW_SCALE: u32 = 4;
+
+// Held to step the game backwards
+const REWIND_KEY: Scancode = Scancode::Backspace;
+const SCREENSHOT_KEY: Keycode = Keycode::F12;
+const RECORD_KEY: Keycode = Keycode::F11;
+const RECORD_AUDIO_KEY: Keycode = Keycode::F10;
+const DEBUGGER_KEY: Keycode = Keycode::F9;
+// Stops the emulation while keeping the windows responsive
+const PAUSE_KEY: Keycode = Keycode::F6;
+// Save to and load from the save state slot selected with the number keys
+const SAVE_STATE_KEY: Keycode = Keycode::F7;
+const LOAD_STATE_KEY: Keycode = Keycode::F8;
+const SLOT_KEYS: [(Keycode, u8); 9] = [
+    (Keycode::Num1, 1), (Keycode::Num2, 2), (Keycode::Num3, 3), (Keycode::Num4, 4), (Keycode::Num5, 5),
+    (Keycode::Num6, 6), (Keycode::Num7, 7), (Keycode::Num8, 8), (Keycode::Num9, 9),
+];
+// Sound queued to the audio device (100 ms of 16-bit stereo samples, in bytes) beyond which the emulation waits for it
+const AUDIO_LATENCY: u32 = SAMPLE_RATE / 10 * 4;
+
+/// What the GUI loop does with the machine at each iteration
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Activity {
+    /// Frozen, the debug windows keep being refreshed
+    Paused,
+    Rewinding,
+    Running,
+}
+
+/// Outcome of an iteration of the emulation
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Frame {
+    /// Paused or rewound, no input was consumed
+    Skipped,
+    /// Emulated with this joypad input
+    Emulated(JoypadState),
+    /// The debugger was quit
+    Quit,
+}
+
+/// Runs an iteration of the emulation. Only emulated frames take an input from the movie, so that pausing doesn't
+/// desync it, and rewinding goes back in the movie too.
+fn advance(memory: &mut Memory, rewind: &mut RewindBuffer, movie: &mut Option<Movie>, debugger: &mut Debugger, activity: Activity, live_input: JoypadState) -> Frame {
+    match activity {
+        Activity::Paused => return Frame::Skipped,
+        Activity::Rewinding => {
+            rewind.rewind(memory);
+            if let Some(movie) = movie { movie.rewind_to(rewind.frame()); }
+            return Frame::Skipped;
+        }
+        Activity::Running => {}
+    }
+
+    if debugger.prompting() {
+        return if debugger.poll_commands(memory, false) { Frame::Skipped } else { Frame::Quit };
+    }
+
+    // The input of a frame the debugger paused in stays held until the frame is done
+    let joypad = if debugger.in_frame(memory) {
+        memory.joypad
+    } else {
+        let joypad = movie.as_mut().map_or(live_input, |movie| movie.next_input(live_input));
+        joypad::press(memory, joypad);
+        joypad
+    };
+    if !debugger.run_frame(memory) { return Frame::Skipped; }
+    rewind.capture(memory);
+
+    return Frame::Emulated(joypad);
+}
+
+fn toggle_recording(recorder: &mut Option<Recorder>, arguments: &Arguments) {
+    if let Some(recording) = recorder.take() {
+        if let Err(error) = recording.finish() { eprintln!("Couldn't finish recording: {error}"); }
+        return;
+    }
+
+    let path = arguments.record.clone().unwrap_or_else(|| next_free_path(&arguments.screenshot_dir, "lameboy", "gif"));
+    match Recorder::start(&path) {
+        Ok(recording) => *recorder = Some(recording),
+        Err(error) => eprintln!("Couldn't start recording to {}: {error}", path.display()),
+    }
+}
+
+fn toggle_audio_recording(recorder: &mut Option<WavRecorder>, arguments: &Arguments) {
+    if let Some(recording) = recorder.take() {
+        if let Err(error) = recording.finish() { eprintln!("Couldn't finish audio recording: {error}"); }
+        return;
+    }
+
+    let path = arguments.record_audio.clone().unwrap_or_else(|| next_free_path(&arguments.screenshot_dir, "lameboy-audio", "wav"));
+    match WavRecorder::start(&path) {
+        Ok(recording) => *recorder = Some(recording),
+        Err(error) => eprintln!("Couldn't start audio recording to {}: {error}", path.display()),
+    }
+}
+
+/// Takes the samples mixed by the APU since the last iteration, to record them, alone and alongside the video, and
+/// play them on the audio device if it is open. Only emulated clock ticks produce samples, so that the recordings
+/// depend neither on the pauses nor on the device.
+fn output_sound(memory: &mut Memory, recorder: &mut Option<WavRecorder>, video_recorder: &mut Option<Recorder>, audio: Option<&AudioQueue<i16>>) {
+    let samples = std::mem::take(&mut memory.apu.samples);
+
+    if let Some(recording) = recorder {
+        if let Err(error) = recording.push_samples(&samples) {
+            eprintln!("Couldn't record audio, stopping recording: {error}");
+            *recorder = None;
+        }
+    }
+    if let Some(recording) = video_recorder {
+        if let Err(error) = recording.record_samples(&samples) {
+            eprintln!("Couldn't record the sound of the video, stopping recording: {error}");
+            *video_recorder = None;
+        }
+    }
+
+    let Some(queue) = audio else { return };
+    let interleaved: Vec<i16> = samples.iter().flat_map(|(left, right)| [*left, *right]).collect();
+    if let Err(error) = queue.queue_audio(&interleaved) { log!(Error, "GUI", format!("Couldn't play the sound: {error}")); }
+}
+
+/// Opens the default audio device at the output rate of the APU. The emulation runs silently without it.
+fn open_audio(sdl_context: &sdl2::Sdl) -> Option<AudioQueue<i16>> {
+    let spec = AudioSpecDesired { freq: i32::try_from(SAMPLE_RATE).ok(), channels: Some(2), samples: None };
+    let queue = sdl_context.audio().and_then(|audio_subsystem| audio_subsystem.open_queue::<i16, _>(None, &spec));
+    return match queue {
+        Ok(queue) => {
+            queue.resume();
+            Some(queue)
+        },
+        Err(error) => {
+            log!(Warn, "GUI", format!("Couldn't open the audio device, the sound won't be played: {error}"));
+            None
+        },
+    }
+}
+
+/// Waits before the next iteration. While sound is played, the audio device paces the emulation.
+fn wait(audio: Option<&AudioQueue<i16>>) {
+    match audio {
+        Some(queue) if queue.size() > 0 => {
+            while queue.size() > AUDIO_LATENCY { std::thread::sleep(Duration::from_millis(1)); }
+        },
+        _ => std::thread::sleep(Duration::from_millis(10)),
+    }
+}
+
+/// Saves to, loads from or selects the save state slot, which is shown in the title of the window
+fn handle_slot_key(keycode: Keycode, slot: &mut u8, memory: &mut Memory, movie_running: bool, rom: (&Path, WideValue), window: &mut Window) {
+    let (rom_path, rom_checksum) = rom;
+
+    match keycode {
+        SAVE_STATE_KEY => {
+            if let Err(error) = save_to_slot(memory, rom_checksum, rom_path, *slot) { eprintln!("Couldn't save state to slot {slot}: {error}"); }
+        },
+        // The inputs of the movie wouldn't match the loaded state anymore
+        LOAD_STATE_KEY if movie_running => eprintln!("Couldn't load state from slot {slot}: a movie is being recorded or played"),
+        LOAD_STATE_KEY => {
+            if let Err(error) = load_from_slot(memory, rom_checksum, rom_path, *slot) { eprintln!("Couldn't load state from slot {slot}: {error}"); }
+        },
+        _ => {
+            let Some(&(_, selected)) = SLOT_KEYS.iter().find(|(key, _)| *key == keycode) else { return };
+            *slot = selected;
+            log!(Info, "GUI", format!("Save state slot {slot} selected"));
+            if let Err(error) = window.set_title(&format!("LameBoy - slot {slot}")) {
+                log!(Error, "GUI", format!("Couldn't show the selected slot in the title: {error}"));
+            }
+        },
+    }
+}
+
+/// Runs the emulation without window for a number of frames, as fast as possible. The movie being played back, if
+/// any, is the only input.
+pub fn run_headless(memory: &mut Memory, rewind: &mut RewindBuffer, movie: &mut Option<Movie>, debugger: &mut Debugger, arguments: &Arguments, frames: u32) {
+    log!("GUI", format!("Running {frames} frames headless"));
+
+    let mut framebuffer = Framebuffer::new();
+    let mut recorder: Option<Recorder> = None;
+    if arguments.record.is_some() { toggle_recording(&mut recorder, arguments); }
+    let mut audio_recorder: Option<WavRecorder> = None;
+    if arguments.record_audio.is_some() { toggle_audio_recording(&mut audio_recorder, arguments); }
+
+    let mut emulated = 0;
+    while emulated < frames {
+        // Without windows to keep responsive, the debugger prompt waits for the commands
+        if !debugger.poll_commands(memory, true) { break; }
+        let frame = advance(memory, rewind, movie, debugger, Activity::Running, 0);
+        output_sound(memory, &mut audio_recorder, &mut recorder, None);
+        match frame {
+            Frame::Quit => break,
+            Frame::Skipped => continue,
+            Frame::Emulated(_) => emulated += 1,
+        }
+
+        if let Some(recording) = &mut recorder {
+            render_screen(memory, &mut framebuffer, arguments.color_correction);
+            if let Err(error) = recording.record_frame(&framebuffer) {
+                eprintln!("Couldn't record frame, stopping recording: {error}");
+                recorder = None;
+            }
+        }
+    }
+
+    if recorder.is_some() { toggle_recording(&mut recorder, arguments); }
+    if audio_recorder.is_some() { toggle_audio_recording(&mut audio_recorder, arguments); }
+}
+
+/// Fails if the screen can't be drawn at all. Errors while drawing a frame only skip that frame. The save state slots
+/// are stored next to the ROM.
+#[allow(clippy::cast_possible_truncation)]
+pub fn launch_gui(memory: &mut Memory, rewind: &mut RewindBuffer, movie: &mut Option<Movie>, debugger: &mut Debugger, arguments: &Arguments, rom: (&Path, WideValue)) -> Result<(), String> {
+    let sdl_context = sdl2::init().map_err(|error| format!("Couldn't initialize SDL: {error}"))?;
+    let video_subsystem = sdl_context.video().map_err(|error| format!("Couldn't initialize the video subsystem: {error}"))?;
 
     log!("GUI", format!("Platform is \"{}\"", sdl2::get_platform()));
 
-    let Ok(window) = video_subsystem.window("rust-sdl2 demo", 800, 600).position_centered().build() else { todo!() };
+    // The SGB shows its border around the screen
+    let sgb = memory.sgb.enabled;
+    let (width, height) = if sgb { (BORDER_WIDTH as u32, BORDER_HEIGHT as u32) } else { (SCREEN_WIDTH as u32, SCREEN_HEIGHT as u32) };
+    let window = video_subsystem.window("LameBoy", width * WINDOW_SCALE, height * WINDOW_SCALE).position_centered().build()
+        .map_err(|error| format!("Couldn't create the window: {error}"))?;
+
+    let main_window_id = window.id();
+    let mut canvas = window.into_canvas().build().map_err(|error| format!("Couldn't create the canvas: {error}"))?;
+    let texture_creator = canvas.texture_creator();
+    let mut texture = texture_creator.create_texture_streaming(PixelFormatEnum::RGB24, width, height)
+        .map_err(|error| format!("Couldn't create the screen texture: {error}"))?;
 
-    let Ok(mut canvas) = window.into_canvas().build() else { todo!() };
+    let mut framebuffer = Framebuffer::new();
+    let mut recorder: Option<Recorder> = None;
+    if arguments.record.is_some() { toggle_recording(&mut recorder, arguments); }
+    let mut audio_recorder: Option<WavRecorder> = None;
+    if arguments.record_audio.is_some() { toggle_audio_recording(&mut audio_recorder, arguments); }
+    let audio = open_audio(&sdl_context);
 
-    canvas.set_draw_color(Color::RGB(0, 255, 0));
-    canvas.clear();
-    canvas.present();
-    let Ok(mut event_pump) = sdl_context.event_pump() else { todo!() };
-    let mut i: u8 = 0;
+    let mut viewers = Viewers::new();
+    for kind in &arguments.viewers { viewers.toggle(&video_subsystem, memory, *kind); }
+    let mut memory_viewer = MemoryViewer::new();
+    let mut paused = false;
+    let mut palette_selection = None;
+    let mut slot = arguments.load_slot.unwrap_or(1);
+
+    let mut event_pump = sdl_context.event_pump().map_err(|error| format!("Couldn't get the event pump: {error}"))?;
     'running: loop {
-        i = i.wrapping_add(1);
-        canvas.set_draw_color(Color::RGB(i, 65, 255 - i));
-        canvas.clear();
         for event in event_pump.poll_iter() {
+            if viewers.handle_event(&event, &video_subsystem, memory) { continue; }
+            if memory_viewer.handle_event(&event, &video_subsystem, memory, debugger.symbols()) { continue; }
+
             match event {
                 Event::Quit {..} |
                 Event::KeyDown { keycode: Some(Keycode::Escape), .. } => {
                     break 'running
                 },
+                // With viewers open, closing the main window doesn't send Quit
+                Event::Window { window_id, win_event: WindowEvent::Close, .. } if window_id == main_window_id => break 'running,
+                Event::KeyDown { keycode: Some(SCREENSHOT_KEY), repeat: false, .. } => {
+                    let path = next_free_path(&arguments.screenshot_dir, "lameboy", "png");
+                    if let Err(error) = save_screenshot(&framebuffer, &path, arguments.screenshot_scale) {
+                        eprintln!("Couldn't save screenshot to {}: {error}", path.display());
+                    }
+                },
+                Event::KeyDown { keycode: Some(RECORD_KEY), repeat: false, .. } => toggle_recording(&mut recorder, arguments),
+                Event::KeyDown { keycode: Some(RECORD_AUDIO_KEY), repeat: false, .. } => toggle_audio_recording(&mut audio_recorder, arguments),
+                Event::KeyDown { keycode: Some(DEBUGGER_KEY), repeat: false, .. } => debugger.pause(),
+                Event::KeyDown { keycode: Some(PAUSE_KEY), repeat: false, .. } => paused = !paused,
+                Event::KeyDown { keycode: Some(keycode), repeat: false, .. } => handle_slot_key(keycode, &mut slot, memory, movie.is_some(), rom, canvas.window_mut()),
                 _ => {}
             }
         }
         // The rest of the game loop goes here...
 
-        canvas.present();
-        std::thread::sleep(Duration::from_millis(10));
+        let activity = if paused {
+            Activity::Paused
+        } else if event_pump.keyboard_state().is_scancode_pressed(REWIND_KEY) {
+            Activity::Rewinding
+        } else {
+            Activity::Running
+        };
+
+        let frame = advance(memory, rewind, movie, debugger, activity, joypad_state(&event_pump.keyboard_state()));
+        output_sound(memory, &mut audio_recorder, &mut recorder, audio.as_ref());
+        match frame {
+            Frame::Quit => break 'running,
+            Frame::Skipped => {}
+            Frame::Emulated(joypad) => {
+                // As the CGB boot ROM does while the logo is shown
+                if memory.cgb.compatibility && memory.cycles < SELECTION_TICKS {
+                    let combination = palette_buttons(joypad).map(|(direction, button)| button_combination(direction, button));
+                    if let Some(combination) = combination.filter(|combination| palette_selection != Some(*combination)) {
+                        palette_selection = Some(combination);
+                        colorize(memory, combination);
+                    }
+                }
+            }
+        }
+        render_screen(memory, &mut framebuffer, arguments.color_correction);
+
+        if let Some(recording) = &mut recorder {
+            if let Err(error) = recording.record_frame(&framebuffer) {
+                eprintln!("Couldn't record frame, stopping recording: {error}");
+                recorder = None;
+            }
+        }
+
+        let updated = if sgb {
+            let image = render_border(memory, &framebuffer);
+            texture.update(None, &image.pixels, image.pitch())
+        } else {
+            texture.update(None, &framebuffer.pixels, Framebuffer::pitch())
+        };
+        match updated.map_err(|error| error.to_string()).and_then(|()| canvas.copy(&texture, None, None)) {
+            Ok(()) => canvas.present(),
+            Err(error) => { log!(Error, "GUI", format!("Couldn't draw the screen, frame skipped: {error}")); }
+        }
+        viewers.update(memory);
+        memory_viewer.update(memory, debugger.symbols());
+        wait(audio.as_ref());
+    }
+
+    if recorder.is_some() { toggle_recording(&mut recorder, arguments); }
+    if audio_recorder.is_some() { toggle_audio_recording(&mut audio_recorder, arguments); }
+    return Ok(());
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use crate::cpu::memory::Memory;
+    use crate::debug::debugger::Debugger;
+    use crate::debug::symbols::Symbols;
+    use crate::gui::input::JoypadState;
+    use crate::state::movie::Movie;
+    use crate::state::rewind::RewindBuffer;
+    use super::{advance, Activity, Frame};
+
+    /// Inputs of the frames emulated by the iterations, given the activity and live input of each
+    fn run(movie: &mut Option<Movie>, iterations: &[(Activity, JoypadState)]) -> Vec<JoypadState> {
+        let mut memory = Memory::new(0x10000);
+        let mut rewind = RewindBuffer::new(1024 * 1024, 1);
+        let mut debugger = Debugger::new(false, Vec::new(), Symbols::default());
+
+        return iterations.iter()
+            .filter_map(|(activity, live)| match advance(&mut memory, &mut rewind, movie, &mut debugger, *activity, *live) {
+                Frame::Emulated(input) => Some(input),
+                _ => None,
+            })
+            .collect();
+    }
+
+    #[test]
+    fn test_movie_sync() {
+        use Activity::{Paused, Rewinding, Running};
+
+        // Pausing while recording doesn't record frames, rewinding drops the rewound ones
+        let mut movie = Some(Movie::record(Path::new("test.lmv"), 0xBEEF, None));
+        let recording = [(Running, 0x01), (Paused, 0x02), (Running, 0x04), (Rewinding, 0x08), (Running, 0x10), (Paused, 0x20), (Running, 0x40)];
+        assert_eq!(run(&mut movie, &recording), vec![0x01, 0x04, 0x10, 0x40]);
+
+        // Nor does it skip inputs during playback, whenever the pauses happen
+        let mut movie = movie.map(|movie| movie.replay(true));
+        let playback = [(Paused, 0xFF), (Running, 0xFF), (Running, 0xFF), (Paused, 0xFF), (Paused, 0xFF), (Running, 0xFF)];
+        assert_eq!(run(&mut movie, &playback), vec![0x01, 0x10, 0x40]);
     }
 }
\ No newline at end of file
@@ -0,0 +1,370 @@
+use sdl2::event::{Event, WindowEvent};
+use sdl2::keyboard::Keycode;
+use sdl2::pixels::PixelFormatEnum;
+use sdl2::render::Canvas;
+use sdl2::video::Window;
+use sdl2::VideoSubsystem;
+use crate::cpu::memory::Memory;
+use crate::debug::debugger::parse_hex;
+use crate::debug::symbols::Symbols;
+use crate::gui::font::{draw_text, GLYPH_HEIGHT, GLYPH_WIDTH};
+use crate::gui::screen::{Image, Rgb};
+use crate::utils::types::{FarAddress, Value};
+
+pub const HEX_VIEW_KEY: Keycode = Keycode::F5;
+const JUMP_KEY: Keycode = Keycode::G;
+const PREVIOUS_BANK_KEY: Keycode = Keycode::LeftBracket;
+const NEXT_BANK_KEY: Keycode = Keycode::RightBracket;
+
+const BYTES_PER_ROW: usize = 16;
+const VISIBLE_ROWS: usize = 32;
+// Address, region, hex bytes and ASCII
+const COLUMNS: usize = 4 + 1 + 4 + 2 + BYTES_PER_ROW * 3 + 1 + BYTES_PER_ROW;
+const HEX_COLUMN: usize = 11;
+const ASCII_COLUMN: usize = HEX_COLUMN + BYTES_PER_ROW * 3 + 1;
+// Status line and blank line above the rows
+const HEADER_ROWS: usize = 2;
+const WINDOW_SCALE: u32 = 3;
+
+/// Number of frames a changed byte stays highlighted
+const HIGHLIGHT_FRAMES: u8 = 60;
+const BANKED_REGIONS: [&str; 4] = ["ROMX", "VRAM", "SRAM", "WRAM"];
+
+const BACKGROUND: Rgb = (0x10, 0x10, 0x20);
+const TEXT_COLOR: Rgb = (0xE0, 0xE0, 0xE0);
+const ADDRESS_COLOR: Rgb = (0x80, 0xA0, 0xFF);
+const CHANGED_COLOR: Rgb = (0xFF, 0x40, 0x40);
+const CURSOR_COLOR: Rgb = (0x40, 0x60, 0x40);
+
+/// State of the hex viewer, independent from SDL
+pub struct HexView {
+    /// First address shown, always at the start of a row
+    top: FarAddress,
+    cursor: FarAddress,
+    /// Editing the high nibble of the byte under the cursor
+    high_nibble: bool,
+    /// Bank shown for the banked regions, `None` for the mapped one
+    view_bank: Option<u8>,
+    /// Address or symbol being typed after pressing the jump key
+    input: Option<String>,
+    /// The text input of the jump key itself must be ignored
+    skip_text: bool,
+    message: String,
+    previous: Vec<Value>,
+    /// Frames left to highlight each byte
+    highlights: Vec<u8>,
+}
+
+impl HexView {
+    pub fn new(memory: &Memory) -> HexView {
+        let size = usize::from(FarAddress::MAX) + 1;
+        return HexView {
+            top: 0,
+            cursor: 0,
+            high_nibble: true,
+            view_bank: None,
+            input: None,
+            skip_text: false,
+            message: String::new(),
+            previous: (0..=FarAddress::MAX).map(|addr| memory.peek(addr)).collect(),
+            highlights: vec![0; size],
+        }
+    }
+
+    /// Value shown at the address, `None` if the selected bank isn't available
+    fn value(&self, memory: &Memory, addr: FarAddress) -> Option<Value> {
+        let (_, region) = Memory::region(addr);
+        return match self.view_bank {
+            Some(bank) if BANKED_REGIONS.contains(&region) => memory.peek_bank(addr, bank),
+            _ => Some(memory.peek(addr)),
+        }
+    }
+
+    /// Must be called once per frame, to highlight the bytes changed since the previous one
+    pub fn track_changes(&mut self, memory: &Memory) {
+        for (addr, (previous, highlight)) in self.previous.iter_mut().zip(self.highlights.iter_mut()).enumerate() {
+            #[allow(clippy::cast_possible_truncation)]
+            let value = memory.peek(addr as FarAddress);
+
+            *highlight = if value == *previous { highlight.saturating_sub(1) } else { HIGHLIGHT_FRAMES };
+            *previous = value;
+        }
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn move_cursor(&mut self, delta: i32) {
+        self.cursor = self.cursor.wrapping_add_signed(delta as i16);
+        self.high_nibble = true;
+
+        // Scroll so that the cursor stays visible
+        let row_start = self.cursor - self.cursor % BYTES_PER_ROW as FarAddress;
+        let visible = (VISIBLE_ROWS * BYTES_PER_ROW) as FarAddress;
+        if row_start < self.top || row_start.wrapping_sub(self.top) >= visible {
+            self.top = if row_start < self.top { row_start } else { row_start.wrapping_sub(visible - BYTES_PER_ROW as FarAddress) };
+        }
+    }
+
+    /// Moves the cursor to an address or a symbol
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn jump(&mut self, target: &str, symbols: &Symbols) -> Result<(), String> {
+        let addr = match symbols.lookup(target) {
+            Some((bank, addr)) => {
+                let (_, region) = Memory::region(addr);
+                if BANKED_REGIONS.contains(&region) { self.view_bank = Some(bank); }
+                addr
+            }
+            None => parse_hex(target)?,
+        };
+
+        self.top = addr - addr % BYTES_PER_ROW as FarAddress;
+        self.cursor = addr;
+        self.high_nibble = true;
+        return Ok(());
+    }
+
+    /// Replaces the nibble under the cursor, then moves to the next one
+    pub fn type_digit(&mut self, memory: &mut Memory, digit: Value) {
+        let Some(value) = self.value(memory, self.cursor) else {
+            self.message = String::from("Can't edit a bank which isn't mapped");
+            return;
+        };
+
+        let value = if self.high_nibble { (value & 0x0F) | (digit << 4) } else { (value & 0xF0) | digit };
+        memory.write_far_addr(self.cursor, value);
+
+        if self.high_nibble { self.high_nibble = false; } else { self.move_cursor(1); }
+    }
+
+    fn cycle_bank(&mut self, forward: bool) {
+        self.view_bank = match (self.view_bank, forward) {
+            (None, true) => Some(0),
+            (None | Some(0), false) => None,
+            (Some(bank), true) => Some(bank.wrapping_add(1)),
+            (Some(bank), false) => Some(bank - 1),
+        };
+    }
+
+    /// Handles a key press, returns false if the key isn't used by the viewer
+    pub fn key_down(&mut self, memory: &mut Memory, keycode: Keycode, symbols: &Symbols) -> bool {
+        if let Some(input) = &mut self.input {
+            match keycode {
+                Keycode::Return | Keycode::KpEnter => {
+                    let target = input.clone();
+                    self.input = None;
+                    if let Err(error) = self.jump(&target, symbols) { self.message = error; }
+                }
+                Keycode::Backspace => { input.pop(); }
+                Keycode::Escape => self.input = None,
+                // Characters are received as text input
+                _ => {}
+            }
+            return true;
+        }
+
+        self.message.clear();
+        #[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+        let page = (VISIBLE_ROWS * BYTES_PER_ROW) as i32;
+
+        match keycode {
+            Keycode::Left => self.move_cursor(-1),
+            Keycode::Right => self.move_cursor(1),
+            Keycode::Up => self.move_cursor(-16),
+            Keycode::Down => self.move_cursor(16),
+            Keycode::PageUp => self.move_cursor(-page),
+            Keycode::PageDown => self.move_cursor(page),
+            JUMP_KEY => {
+                self.input = Some(String::new());
+                self.skip_text = true;
+            }
+            PREVIOUS_BANK_KEY => self.cycle_bank(false),
+            NEXT_BANK_KEY => self.cycle_bank(true),
+            _ => {
+                let name = keycode.name();
+                let digit = name.strip_prefix("Keypad ").unwrap_or(&name);
+                let Ok(digit) = Value::from_str_radix(digit, 16) else { return false };
+                if digit > 0xF { return false; }
+                self.type_digit(memory, digit);
+            }
+        }
+
+        return true;
+    }
+
+    pub fn text_input(&mut self, text: &str) {
+        if std::mem::take(&mut self.skip_text) { return; }
+        if let Some(input) = &mut self.input { input.push_str(text); }
+    }
+
+    pub fn render(&self, memory: &Memory, symbols: &Symbols) -> Image {
+        let mut image = Image::new(COLUMNS * GLYPH_WIDTH, (VISIBLE_ROWS + HEADER_ROWS) * GLYPH_HEIGHT);
+        image.fill_rect(0, 0, image.width, image.height, BACKGROUND);
+
+        let bank = self.view_bank.map_or(String::from("mapped"), |bank| format!("{bank:02X}"));
+        let location = symbols.describe(self.view_bank.unwrap_or_else(|| memory.bank(self.cursor)), self.cursor).map(|symbol| format!(" <{symbol}>")).unwrap_or_default();
+        let status = match &self.input {
+            Some(input) => format!("Go to: {input}_"),
+            None if !self.message.is_empty() => self.message.clone(),
+            None => format!("{:04X}{location}  Bank: {bank}  [G]o to  [ ] bank", self.cursor),
+        };
+        draw_text(&mut image, 0, 0, &status, TEXT_COLOR);
+
+        for row in 0..VISIBLE_ROWS {
+            #[allow(clippy::cast_possible_truncation)]
+            let row_addr = self.top.wrapping_add((row * BYTES_PER_ROW) as FarAddress);
+            let y = (row + HEADER_ROWS) * GLYPH_HEIGHT;
+            let (_, region) = Memory::region(row_addr);
+
+            draw_text(&mut image, 0, y, &format!("{row_addr:04X} {region}"), ADDRESS_COLOR);
+
+            for column in 0..BYTES_PER_ROW {
+                #[allow(clippy::cast_possible_truncation)]
+                let addr = row_addr.wrapping_add(column as FarAddress);
+                let value = self.value(memory, addr);
+                let color = if self.highlights[usize::from(addr)] > 0 { CHANGED_COLOR } else { TEXT_COLOR };
+                let hex_x = (HEX_COLUMN + column * 3) * GLYPH_WIDTH;
+
+                if addr == self.cursor {
+                    let nibble_x = if self.high_nibble { hex_x } else { hex_x + GLYPH_WIDTH };
+                    image.fill_rect(nibble_x, y, GLYPH_WIDTH, GLYPH_HEIGHT, CURSOR_COLOR);
+                }
+
+                let (hex, ascii) = match value {
+                    Some(value) => (format!("{value:02X}"), if value.is_ascii_graphic() { char::from(value) } else { '.' }),
+                    None => (String::from("--"), ' '),
+                };
+                draw_text(&mut image, hex_x, y, &hex, color);
+                draw_text(&mut image, (ASCII_COLUMN + column) * GLYPH_WIDTH, y, &ascii.to_string(), color);
+            }
+        }
+
+        return image;
+    }
+}
+
+/// Window of the hex viewer, toggled with `HEX_VIEW_KEY`
+pub struct MemoryViewer {
+    canvas: Option<Canvas<Window>>,
+    view: Option<HexView>,
+}
+
+impl MemoryViewer {
+    pub fn new() -> MemoryViewer {
+        return MemoryViewer { canvas: None, view: None };
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn toggle(&mut self, video: &VideoSubsystem, memory: &Memory) {
+        if self.canvas.take().is_some() { return; }
+
+        let width = (COLUMNS * GLYPH_WIDTH) as u32 * WINDOW_SCALE;
+        let height = ((VISIBLE_ROWS + HEADER_ROWS) * GLYPH_HEIGHT) as u32 * WINDOW_SCALE;
+        let canvas = video.window("LameBoy - Memory", width, height).build()
+            .map_err(|error| error.to_string())
+            .and_then(|window| window.into_canvas().build().map_err(|error| error.to_string()));
+
+        match canvas {
+            Ok(canvas) => {
+                self.canvas = Some(canvas);
+                if self.view.is_none() { self.view = Some(HexView::new(memory)); }
+            }
+            Err(error) => eprintln!("Couldn't open the memory viewer: {error}"),
+        }
+    }
+
+    /// Handles the toggle key and the events targeting the viewer window. Returns false if the event must be handled by the main window.
+    pub fn handle_event(&mut self, event: &Event, video: &VideoSubsystem, memory: &mut Memory, symbols: &Symbols) -> bool {
+        if let Event::KeyDown { keycode: Some(HEX_VIEW_KEY), repeat: false, .. } = event {
+            self.toggle(video, memory);
+            return true;
+        }
+
+        let (Some(canvas), Some(view)) = (&self.canvas, &mut self.view) else { return false };
+        let id = canvas.window().id();
+
+        match event {
+            Event::Window { window_id, win_event: WindowEvent::Close, .. } if *window_id == id => self.canvas = None,
+            Event::KeyDown { window_id, keycode: Some(keycode), .. } if *window_id == id => return view.key_down(memory, *keycode, symbols),
+            Event::TextInput { window_id, text, .. } if *window_id == id => view.text_input(text),
+            _ => return false,
+        }
+
+        return true;
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn update(&mut self, memory: &Memory, symbols: &Symbols) {
+        let Some(view) = &mut self.view else { return };
+        view.track_changes(memory);
+
+        let Some(canvas) = &mut self.canvas else { return };
+        let image = view.render(memory, symbols);
+        let texture_creator = canvas.texture_creator();
+
+        let drawn = texture_creator.create_texture_streaming(PixelFormatEnum::RGB24, image.width as u32, image.height as u32)
+            .map_err(|error| error.to_string())
+            .and_then(|mut texture| {
+                texture.update(None, &image.pixels, image.pitch()).map_err(|error| error.to_string())?;
+                canvas.copy(&texture, None, None)
+            });
+
+        match drawn {
+            Ok(()) => canvas.present(),
+            Err(error) => eprintln!("Couldn't draw the memory viewer: {error}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cpu::memory::Memory;
+    use crate::debug::symbols::Symbols;
+    use super::{HexView, HIGHLIGHT_FRAMES};
+
+    #[test]
+    fn test_edit_nibbles() {
+        let mut memory = Memory::new(0x10000);
+        let mut view = HexView::new(&memory);
+        assert_eq!(view.jump("C000", &Symbols::default()), Ok(()));
+
+        view.type_digit(&mut memory, 0xA);
+        view.type_digit(&mut memory, 0x5);
+        view.type_digit(&mut memory, 0xF);
+
+        assert_eq!(memory.memory[0xC000], 0xA5);
+        assert_eq!(memory.memory[0xC001], 0xF0);
+        assert_eq!(view.cursor, 0xC001);
+    }
+
+    #[test]
+    fn test_jump_and_scroll() {
+        let memory = Memory::new(0x10000);
+        let mut view = HexView::new(&memory);
+        let mut symbols = Symbols::default();
+        symbols.insert(2, 0x4123, "Graphics");
+
+        assert_eq!(view.jump("Graphics", &symbols), Ok(()));
+        assert_eq!((view.top, view.cursor, view.view_bank), (0x4120, 0x4123, Some(2)));
+        // Bank 2 isn't mapped
+        assert_eq!(view.value(&memory, 0x4123), None);
+        assert!(view.jump("Unknown", &symbols).is_err());
+
+        view.move_cursor(-16);
+        assert_eq!(view.top, 0x4110);
+        view.move_cursor(16 * 40);
+        // Cursor on the last visible row
+        assert_eq!((view.top, view.cursor), (0x41A0, 0x4393));
+    }
+
+    #[test]
+    fn test_highlight_changes() {
+        let mut memory = Memory::new(0x10000);
+        let mut view = HexView::new(&memory);
+
+        memory.memory[0xC000] = 1;
+        view.track_changes(&memory);
+        assert_eq!(view.highlights[0xC000], HIGHLIGHT_FRAMES);
+        view.track_changes(&memory);
+        assert_eq!(view.highlights[0xC000], HIGHLIGHT_FRAMES - 1);
+        assert_eq!(view.highlights[0xC001], 0);
+    }
+}
@@ -0,0 +1,39 @@
+use sdl2::keyboard::{KeyboardState, Scancode};
+use crate::cpu::colorization::{Button, Direction};
+use crate::utils::bits::{assign_bit, get_bit};
+
+/// One bit per button, set when pressed
+pub type JoypadState = u8;
+
+const RIGHT_BIT: usize = 0;
+const LEFT_BIT: usize = 1;
+const UP_BIT: usize = 2;
+const DOWN_BIT: usize = 3;
+const A_BIT: usize = 4;
+const B_BIT: usize = 5;
+const SELECT_BIT: usize = 6;
+const START_BIT: usize = 7;
+
+const KEY_MAPPING: [(Scancode, usize); 8] = [
+    (Scancode::Right, RIGHT_BIT),
+    (Scancode::Left, LEFT_BIT),
+    (Scancode::Up, UP_BIT),
+    (Scancode::Down, DOWN_BIT),
+    (Scancode::X, A_BIT),
+    (Scancode::Z, B_BIT),
+    (Scancode::RShift, SELECT_BIT),
+    (Scancode::Return, START_BIT),
+];
+
+pub fn joypad_state(keyboard: &KeyboardState) -> JoypadState {
+    return KEY_MAPPING.iter().fold(0, |state, (scancode, bit)| assign_bit(state, *bit, keyboard.is_scancode_pressed(*scancode)));
+}
+
+/// Buttons picking a colorization palette, if a direction is held
+pub fn palette_buttons(state: JoypadState) -> Option<(Direction, Button)> {
+    let directions = [(RIGHT_BIT, Direction::Right), (LEFT_BIT, Direction::Left), (UP_BIT, Direction::Up), (DOWN_BIT, Direction::Down)];
+    let direction = directions.iter().find(|(bit, _)| get_bit(state, *bit)).map(|(_, direction)| *direction)?;
+
+    let button = if get_bit(state, A_BIT) { Button::A } else if get_bit(state, B_BIT) { Button::B } else { Button::None };
+    return Some((direction, button));
+}
@@ -1,3 +1,9 @@
-pub mod sound;
 pub mod gui;
-pub mod input;
\ No newline at end of file
+pub mod input;
+pub mod screen;
+pub mod sound;
+pub mod capture;
+pub mod font;
+pub mod hexview;
+pub mod vram;
+pub mod viewer;
\ No newline at end of file
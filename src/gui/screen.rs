@@ -0,0 +1,114 @@
+use crate::utils::types::{Byte, WideValue};
+
+pub const SCREEN_WIDTH: usize = 160;
+pub const SCREEN_HEIGHT: usize = 144;
+/// Pixels are stored as RGB24
+pub const BYTES_PER_PIXEL: usize = 3;
+
+pub type Rgb = (Byte, Byte, Byte);
+
+/// Shades of the original 4-color LCD, from color 0 (lightest) to 3 (darkest)
+pub const DMG_SHADES: [Rgb; 4] = [(0xFF, 0xFF, 0xFF), (0xAA, 0xAA, 0xAA), (0x55, 0x55, 0x55), (0x00, 0x00, 0x00)];
+
+/// RGB of a 15-bit CGB color (red in bits 0-4, green 5-9 and blue 10-14). Without correction the channels are scaled
+/// linearly to 8 bits, which looks more saturated and brighter than the CGB LCD. The correction mixes the channels
+/// and lowers the maximum brightness as the LCD does, with the curve of the higan emulator.
+#[allow(clippy::cast_possible_truncation)]
+pub fn cgb_rgb(color: WideValue, corrected: bool) -> Rgb {
+    let channel = |shift: u16| u32::from((color >> shift) & 0x1F);
+    let (red, green, blue) = (channel(0), channel(5), channel(10));
+
+    if !corrected {
+        let scale = |channel: u32| ((channel << 3) | (channel >> 2)) as Byte;
+        return (scale(red), scale(green), scale(blue));
+    }
+
+    let clamp = |channel: u32| (channel.min(960) >> 2) as Byte;
+    return (clamp(red * 26 + green * 4 + blue * 2), clamp(green * 24 + blue * 8), clamp(red * 6 + green * 4 + blue * 22));
+}
+
+/// RGB24 image of any size, for the debug windows
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<Byte>,
+}
+
+impl Image {
+    pub fn new(width: usize, height: usize) -> Image {
+        return Image { width, height, pixels: vec![0; width * height * BYTES_PER_PIXEL] };
+    }
+
+    pub fn pitch(&self) -> usize {
+        return self.width * BYTES_PER_PIXEL;
+    }
+
+    /// Pixels outside of the image are ignored
+    pub fn set_pixel(&mut self, x: usize, y: usize, color: Rgb) {
+        if x >= self.width || y >= self.height { return; }
+
+        let index = (y * self.width + x) * BYTES_PER_PIXEL;
+        self.pixels[index..index + BYTES_PER_PIXEL].copy_from_slice(&[color.0, color.1, color.2]);
+    }
+
+    #[cfg(test)]
+    pub fn pixel(&self, x: usize, y: usize) -> Rgb {
+        let index = (y * self.width + x) * BYTES_PER_PIXEL;
+        return (self.pixels[index], self.pixels[index + 1], self.pixels[index + 2]);
+    }
+
+    pub fn fill_rect(&mut self, x: usize, y: usize, width: usize, height: usize, color: Rgb) {
+        for row in y..y + height {
+            for column in x..x + width { self.set_pixel(column, row, color); }
+        }
+    }
+}
+
+pub struct Framebuffer {
+    pub pixels: Box<[Byte]>,
+}
+
+impl Framebuffer {
+    pub fn new() -> Framebuffer {
+        return Framebuffer {
+            pixels: vec![0; SCREEN_WIDTH * SCREEN_HEIGHT * BYTES_PER_PIXEL].into_boxed_slice(),
+        }
+    }
+
+    pub fn fill(&mut self, color: Rgb) {
+        for pixel in self.pixels.chunks_exact_mut(BYTES_PER_PIXEL) {
+            pixel.copy_from_slice(&[color.0, color.1, color.2]);
+        }
+    }
+
+    pub fn pitch() -> usize {
+        return SCREEN_WIDTH * BYTES_PER_PIXEL;
+    }
+
+    pub fn set_pixel(&mut self, x: usize, y: usize, color: Rgb) {
+        let index = (y * SCREEN_WIDTH + x) * BYTES_PER_PIXEL;
+        self.pixels[index..index + BYTES_PER_PIXEL].copy_from_slice(&[color.0, color.1, color.2]);
+    }
+
+    #[cfg(test)]
+    pub fn pixel(&self, x: usize, y: usize) -> Rgb {
+        let index = (y * SCREEN_WIDTH + x) * BYTES_PER_PIXEL;
+        return (self.pixels[index], self.pixels[index + 1], self.pixels[index + 2]);
+    }
+
+    /// Nearest-neighbour upscale by an integer factor, returns the RGB24 pixels of a (160 * scale)x(144 * scale) image
+    pub fn scaled(&self, scale: usize) -> Vec<Byte> {
+        let scale = scale.max(1);
+        let mut output = Vec::with_capacity(self.pixels.len() * scale * scale);
+
+        for row in self.pixels.chunks_exact(Self::pitch()) {
+            let mut scaled_row = Vec::with_capacity(row.len() * scale);
+            for pixel in row.chunks_exact(BYTES_PER_PIXEL) {
+                for _ in 0..scale { scaled_row.extend_from_slice(pixel); }
+            }
+            for _ in 0..scale { output.extend_from_slice(&scaled_row); }
+        }
+
+        return output;
+    }
+}
@@ -0,0 +1,101 @@
+use std::fs::File;
+use std::io::{BufWriter, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use crate::cpu::apu::{SAMPLE_RATE, StereoSample};
+use crate::utils::log::log;
+
+const CHANNELS: u16 = 2;
+const BITS_PER_SAMPLE: u16 = 16;
+
+// Offsets of the two size fields of the RIFF header, patched when the recording is finished
+const RIFF_SIZE_OFFSET: u64 = 4;
+const DATA_SIZE_OFFSET: u64 = 40;
+const HEADER_SIZE: u32 = 44;
+
+/// Records the mixed output of the APU, as produced by the emulator (i.e. independently of the audio device), to a
+/// 16-bit PCM WAV file
+pub struct WavRecorder {
+    path: PathBuf,
+    writer: BufWriter<File>,
+    sample_count: u32,
+}
+
+impl WavRecorder {
+    pub fn start(path: &Path) -> std::io::Result<WavRecorder> {
+        log!("GUI", format!("Recording audio to {}", path.display()));
+
+        let mut writer = BufWriter::new(File::create(path)?);
+        let block_align = CHANNELS * BITS_PER_SAMPLE / 8;
+
+        // Sizes are unknown yet, they are written by finish()
+        writer.write_all(b"RIFF")?;
+        writer.write_all(&0_u32.to_le_bytes())?;
+        writer.write_all(b"WAVE")?;
+
+        writer.write_all(b"fmt ")?;
+        writer.write_all(&16_u32.to_le_bytes())?;
+        // PCM
+        writer.write_all(&1_u16.to_le_bytes())?;
+        writer.write_all(&CHANNELS.to_le_bytes())?;
+        writer.write_all(&SAMPLE_RATE.to_le_bytes())?;
+        writer.write_all(&(SAMPLE_RATE * u32::from(block_align)).to_le_bytes())?;
+        writer.write_all(&block_align.to_le_bytes())?;
+        writer.write_all(&BITS_PER_SAMPLE.to_le_bytes())?;
+
+        writer.write_all(b"data")?;
+        writer.write_all(&0_u32.to_le_bytes())?;
+
+        return Ok(WavRecorder { path: path.to_path_buf(), writer, sample_count: 0 });
+    }
+
+    pub fn sample_count(&self) -> u32 {
+        return self.sample_count;
+    }
+
+    pub fn push_samples(&mut self, samples: &[StereoSample]) -> std::io::Result<()> {
+        for (left, right) in samples {
+            self.writer.write_all(&left.to_le_bytes())?;
+            self.writer.write_all(&right.to_le_bytes())?;
+        }
+
+        #[allow(clippy::cast_possible_truncation)]
+        { self.sample_count += samples.len() as u32; }
+        return Ok(());
+    }
+
+    pub fn finish(mut self) -> std::io::Result<()> {
+        log!("GUI", format!("Recorded {} samples to {}", self.sample_count, self.path.display()));
+
+        let data_size = self.sample_count * u32::from(CHANNELS * BITS_PER_SAMPLE / 8);
+
+        self.writer.seek(SeekFrom::Start(RIFF_SIZE_OFFSET))?;
+        self.writer.write_all(&(HEADER_SIZE - 8 + data_size).to_le_bytes())?;
+        self.writer.seek(SeekFrom::Start(DATA_SIZE_OFFSET))?;
+        self.writer.write_all(&data_size.to_le_bytes())?;
+
+        return self.writer.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::WavRecorder;
+
+    #[test]
+    fn test_wav() {
+        let path = std::env::temp_dir().join(format!("lameboy-test-{}.wav", std::process::id()));
+        let Ok(mut recorder) = WavRecorder::start(&path) else { panic!("Couldn't create {}", path.display()) };
+        assert!(recorder.push_samples(&[(1, -1), (0x1234, 0)]).is_ok());
+        assert!(recorder.finish().is_ok());
+
+        let data = std::fs::read(&path).unwrap_or_default();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(data.len(), 44 + 8);
+        assert_eq!((&data[0..4], &data[4..8], &data[8..12]), (&b"RIFF"[..], &44_u32.to_le_bytes()[..], &b"WAVE"[..]));
+        // 2 channels at 48000 Hz, 4 bytes per sample
+        assert_eq!(&data[22..24], &2_u16.to_le_bytes());
+        assert_eq!(&data[24..32], &[0x80, 0xBB, 0x00, 0x00, 0x00, 0xEE, 0x02, 0x00]);
+        assert_eq!((&data[36..40], &data[40..44]), (&b"data"[..], &8_u32.to_le_bytes()[..]));
+        assert_eq!(&data[44..], &[0x01, 0x00, 0xFF, 0xFF, 0x34, 0x12, 0x00, 0x00]);
+    }
+}
@@ -0,0 +1,157 @@
+use sdl2::event::{Event, WindowEvent};
+use sdl2::keyboard::Keycode;
+use sdl2::pixels::PixelFormatEnum;
+use sdl2::render::Canvas;
+use sdl2::video::Window;
+use sdl2::VideoSubsystem;
+use crate::cpu::memory::Memory;
+use crate::gui::screen::Image;
+use crate::gui::vram::{render_oam, render_palettes, render_tilemaps, render_tiles, TilePalette};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ViewerKind {
+    Tiles,
+    Tilemaps,
+    Oam,
+    Palettes,
+}
+
+/// Keys toggling each viewer window, from any window
+const VIEWER_KEYS: [(Keycode, ViewerKind); 4] = [
+    (Keycode::F1, ViewerKind::Tiles),
+    (Keycode::F2, ViewerKind::Tilemaps),
+    (Keycode::F3, ViewerKind::Oam),
+    (Keycode::F4, ViewerKind::Palettes),
+];
+// Cycles the palette of the tile viewer
+const PALETTE_KEY: Keycode = Keycode::P;
+
+impl ViewerKind {
+    fn title(self) -> &'static str {
+        return match self {
+            ViewerKind::Tiles => "Tiles",
+            ViewerKind::Tilemaps => "Tile maps",
+            ViewerKind::Oam => "OAM",
+            ViewerKind::Palettes => "Palettes",
+        }
+    }
+
+    fn scale(self) -> u32 {
+        return match self {
+            ViewerKind::Tiles | ViewerKind::Oam => 3,
+            ViewerKind::Tilemaps => 2,
+            ViewerKind::Palettes => 4,
+        }
+    }
+
+    fn render(self, memory: &Memory, palette: usize) -> Image {
+        return match self {
+            ViewerKind::Tiles => {
+                let palettes = TilePalette::all(memory);
+                render_tiles(memory, palettes[palette % palettes.len()])
+            }
+            ViewerKind::Tilemaps => render_tilemaps(memory),
+            ViewerKind::Oam => render_oam(memory),
+            ViewerKind::Palettes => render_palettes(memory),
+        }
+    }
+}
+
+impl std::str::FromStr for ViewerKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        return match s {
+            "tiles" => Ok(ViewerKind::Tiles),
+            "maps" => Ok(ViewerKind::Tilemaps),
+            "oam" => Ok(ViewerKind::Oam),
+            "palettes" => Ok(ViewerKind::Palettes),
+            _ => Err(format!("Unknown viewer \"{s}\", expected tiles, maps, oam or palettes")),
+        }
+    }
+}
+
+struct ViewerWindow {
+    kind: ViewerKind,
+    canvas: Canvas<Window>,
+    /// Index in `TilePalette::all`, for the tile viewer
+    palette: usize,
+}
+
+/// Optional debug windows, showing the video memory live
+pub struct Viewers {
+    windows: Vec<ViewerWindow>,
+}
+
+impl Viewers {
+    pub fn new() -> Viewers {
+        return Viewers { windows: Vec::new() };
+    }
+
+    /// Opens the viewer, or closes it if it is already open
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn toggle(&mut self, video: &VideoSubsystem, memory: &Memory, kind: ViewerKind) {
+        if let Some(index) = self.windows.iter().position(|window| window.kind == kind) {
+            self.windows.remove(index);
+            return;
+        }
+
+        let image = kind.render(memory, 0);
+        let (width, height) = (image.width as u32 * kind.scale(), image.height as u32 * kind.scale());
+        let canvas = video.window(&format!("LameBoy - {}", kind.title()), width, height).build()
+            .map_err(|error| error.to_string())
+            .and_then(|window| window.into_canvas().build().map_err(|error| error.to_string()));
+
+        match canvas {
+            Ok(canvas) => self.windows.push(ViewerWindow { kind, canvas, palette: 0 }),
+            Err(error) => eprintln!("Couldn't open the {} viewer: {error}", kind.title()),
+        }
+    }
+
+    /// Handles the viewer keys and the events targeting a viewer window. Returns false if the event must be handled by the main window.
+    pub fn handle_event(&mut self, event: &Event, video: &VideoSubsystem, memory: &Memory) -> bool {
+        if let Event::KeyDown { keycode: Some(keycode), repeat: false, .. } = event {
+            if let Some((_, kind)) = VIEWER_KEYS.iter().find(|(key, _)| key == keycode) {
+                self.toggle(video, memory, *kind);
+                return true;
+            }
+        }
+
+        let (Event::Window { window_id, .. } | Event::KeyDown { window_id, .. }) = event else { return false };
+        let Some(index) = self.windows.iter().position(|window| window.canvas.window().id() == *window_id) else { return false };
+
+        match event {
+            Event::Window { win_event: WindowEvent::Close, .. } => { self.windows.remove(index); }
+            Event::KeyDown { keycode: Some(PALETTE_KEY), repeat: false, .. } => {
+                let window = &mut self.windows[index];
+                let palettes = TilePalette::all(memory);
+                window.palette = (window.palette + 1) % palettes.len();
+                window.canvas.window_mut().set_title(&format!("LameBoy - {} ({})", window.kind.title(), palettes[window.palette].name())).ok();
+            }
+            _ => return false,
+        }
+
+        return true;
+    }
+
+    /// Redraws every open viewer from the current memory
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn update(&mut self, memory: &Memory) {
+        for window in &mut self.windows {
+            let image = window.kind.render(memory, window.palette);
+            let texture_creator = window.canvas.texture_creator();
+
+            let drawn = texture_creator.create_texture_streaming(PixelFormatEnum::RGB24, image.width as u32, image.height as u32)
+                .map_err(|error| error.to_string())
+                .and_then(|mut texture| {
+                    texture.update(None, &image.pixels, image.pitch()).map_err(|error| error.to_string())?;
+                    window.canvas.copy(&texture, None, None)
+                });
+
+            match drawn {
+                Ok(()) => window.canvas.present(),
+                Err(error) => eprintln!("Couldn't draw the {} viewer: {error}", window.kind.title()),
+            }
+        }
+    }
+}
@@ -0,0 +1,633 @@
+use crate::cpu::io::{BGP, LCDC, LCDC_BG_ENABLE_BIT, LCDC_BG_MAP_BIT, LCDC_LCD_ENABLE_BIT, LCDC_OBJ_ENABLE_BIT, LCDC_OBJ_SIZE_BIT, LCDC_TILE_DATA_BIT, LCDC_WINDOW_ENABLE_BIT, LCDC_WINDOW_MAP_BIT, OAM_START, OBP0, OBP1, SCX, SCY, VRAM_START, WX, WY};
+use crate::cpu::cgb::{COLORS_PER_PALETTE, PALETTE_COUNT, VRAM_BANKS};
+use crate::cpu::memory::Memory;
+use crate::cpu::sgb::{Mask, BORDER_HEIGHT, BORDER_WIDTH, SCREEN_LEFT, SCREEN_TOP};
+use crate::gui::font::{draw_text, GLYPH_HEIGHT, GLYPH_WIDTH};
+use crate::gui::screen::{cgb_rgb, BYTES_PER_PIXEL, DMG_SHADES, Framebuffer, Image, Rgb, SCREEN_HEIGHT, SCREEN_WIDTH};
+use crate::utils::bits::get_bit;
+use crate::utils::types::{FarAddress, Value};
+
+pub const TILE_COUNT: usize = 384;
+const TILE_SIZE: usize = 8;
+const TILE_BYTES: usize = 16;
+const TILES_PER_ROW: usize = 16;
+
+const MAP_TILES: usize = 32;
+const MAP_SIZE: usize = MAP_TILES * TILE_SIZE;
+const MAP_0: FarAddress = 0x9800;
+const MAP_1: FarAddress = 0x9C00;
+const MAPS_GAP: usize = 8;
+
+const OAM_ENTRIES: usize = 40;
+const OAM_ENTRY_SIZE: usize = 4;
+const OAM_ROW_HEIGHT: usize = 18;
+const OAM_COLUMN_WIDTH: usize = 88;
+const OAM_PALETTE_BIT: usize = 4;
+/// CGB only, VRAM bank of the tile
+const OAM_BANK_BIT: usize = 3;
+/// CGB only, palette number in bits 0-2
+const OAM_CGB_PALETTE_MASK: Value = 0x07;
+const OAM_X_FLIP_BIT: usize = 5;
+const OAM_Y_FLIP_BIT: usize = 6;
+const OAM_PRIORITY_BIT: usize = 7;
+/// Objects drawn on a line at most, the next ones in OAM order being ignored
+const OBJECTS_PER_LINE: usize = 10;
+
+// Attributes of the CGB map entries, in VRAM bank 1
+const ATTRIBUTE_PALETTE_MASK: Value = 0x07;
+const ATTRIBUTE_BANK_BIT: usize = 3;
+const ATTRIBUTE_X_FLIP_BIT: usize = 5;
+const ATTRIBUTE_Y_FLIP_BIT: usize = 6;
+/// Background colors 1-3 drawn over the objects
+const ATTRIBUTE_PRIORITY_BIT: usize = 7;
+
+const SWATCH_SIZE: usize = 12;
+
+const BACKGROUND: Rgb = (0x20, 0x20, 0x40);
+const TEXT_COLOR: Rgb = (0xFF, 0xFF, 0xFF);
+const VIEWPORT_COLOR: Rgb = (0xFF, 0x00, 0x00);
+const WINDOW_COLOR: Rgb = (0x00, 0xC0, 0xFF);
+
+const DMG_PALETTES: [(FarAddress, &str); 3] = [(BGP, "BGP"), (OBP0, "OBP0"), (OBP1, "OBP1")];
+
+/// Palette the tile viewer draws the tiles with
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TilePalette {
+    /// DMG palette register, and its name
+    Dmg(FarAddress, &'static str),
+    /// CGB palettes, by number
+    Background(usize),
+    Object(usize),
+}
+
+impl TilePalette {
+    /// Palettes selectable in the tile viewer: the DMG palette registers, then the CGB palettes in CGB mode
+    pub fn all(memory: &Memory) -> Vec<TilePalette> {
+        let mut palettes: Vec<TilePalette> = DMG_PALETTES.iter().map(|(register, name)| TilePalette::Dmg(*register, name)).collect();
+        if memory.cgb.enabled {
+            palettes.extend((0..PALETTE_COUNT).map(TilePalette::Background));
+            palettes.extend((0..PALETTE_COUNT).map(TilePalette::Object));
+        }
+
+        return palettes;
+    }
+
+    pub fn name(self) -> String {
+        return match self {
+            TilePalette::Dmg(_, name) => name.to_string(),
+            TilePalette::Background(palette) => format!("BG{palette}"),
+            TilePalette::Object(palette) => format!("OBJ{palette}"),
+        }
+    }
+
+    fn colors(self, memory: &Memory) -> [Rgb; COLORS_PER_PALETTE] {
+        return match self {
+            TilePalette::Dmg(register, _) => dmg_colors(memory.peek(register)),
+            TilePalette::Background(palette) => [0, 1, 2, 3].map(|color| cgb_rgb(memory.cgb.background_palettes.color(palette, color), false)),
+            TilePalette::Object(palette) => [0, 1, 2, 3].map(|color| cgb_rgb(memory.cgb.object_palettes.color(palette, color), false)),
+        }
+    }
+}
+
+/// 2-bit color of a tile pixel, tiles being numbered from 0x8000 in the VRAM bank
+pub fn tile_color(memory: &Memory, bank: usize, tile: usize, x: usize, y: usize) -> u8 {
+    #[allow(clippy::cast_possible_truncation)]
+    let addr = VRAM_START + (tile * TILE_BYTES + y * 2) as FarAddress;
+    let low = memory.peek_vram(bank, addr);
+    let high = memory.peek_vram(bank, addr + 1);
+    let bit = 7 - x;
+
+    return u8::from(get_bit(high, bit)) << 1 | u8::from(get_bit(low, bit));
+}
+
+/// Index of the shade a DMG palette register gives to a color
+fn shade_index(palette: Value, color: u8) -> u8 {
+    return (palette >> (color * 2)) & 0b11;
+}
+
+pub fn shade(palette: Value, color: u8) -> Rgb {
+    return DMG_SHADES[usize::from(shade_index(palette, color))];
+}
+
+/// Shades of the 4 colors under a DMG palette register
+fn dmg_colors(palette: Value) -> [Rgb; COLORS_PER_PALETTE] {
+    return [0, 1, 2, 3].map(|color| shade(palette, color));
+}
+
+/// Tile number of a map entry, following the addressing mode selected in LCDC
+pub fn map_tile(memory: &Memory, map: FarAddress, column: usize, row: usize) -> usize {
+    #[allow(clippy::cast_possible_truncation)]
+    let index = memory.peek_vram(0, map + (row * MAP_TILES + column) as FarAddress);
+
+    if get_bit(memory.peek(LCDC), LCDC_TILE_DATA_BIT) { return usize::from(index); }
+    // Signed indexes, relative to tile 256 (0x9000)
+    return 256_usize.wrapping_add_signed(isize::from(index.cast_signed()));
+}
+
+/// CGB attributes of a map entry, 0 in DMG mode
+fn map_attributes(memory: &Memory, map: FarAddress, column: usize, row: usize) -> Value {
+    if !memory.cgb.enabled { return 0; }
+
+    #[allow(clippy::cast_possible_truncation)]
+    return memory.peek_vram(1, map + (row * MAP_TILES + column) as FarAddress);
+}
+
+struct TileDraw {
+    bank: usize,
+    tile: usize,
+    colors: [Rgb; COLORS_PER_PALETTE],
+    flip_x: bool,
+    flip_y: bool,
+    /// Color 0 isn't drawn, as for objects
+    transparent: bool,
+}
+
+fn draw_tile(image: &mut Image, memory: &Memory, left: usize, top: usize, draw: &TileDraw) {
+    for y in 0..TILE_SIZE {
+        for x in 0..TILE_SIZE {
+            let source_x = if draw.flip_x { TILE_SIZE - 1 - x } else { x };
+            let source_y = if draw.flip_y { TILE_SIZE - 1 - y } else { y };
+            let color = tile_color(memory, draw.bank, draw.tile, source_x, source_y);
+
+            if !(draw.transparent && color == 0) { image.set_pixel(left + x, top + y, draw.colors[usize::from(color)]); }
+        }
+    }
+}
+
+/// Outline of a rectangle wrapping around a map, drawn at `left` in the image
+fn outline_wrapped(image: &mut Image, left: usize, (x, y): (usize, usize), (width, height): (usize, usize), color: Rgb) {
+    for offset in 0..width {
+        image.set_pixel(left + (x + offset) % MAP_SIZE, y % MAP_SIZE, color);
+        image.set_pixel(left + (x + offset) % MAP_SIZE, (y + height - 1) % MAP_SIZE, color);
+    }
+    for offset in 0..height {
+        image.set_pixel(left + x % MAP_SIZE, (y + offset) % MAP_SIZE, color);
+        image.set_pixel(left + (x + width - 1) % MAP_SIZE, (y + offset) % MAP_SIZE, color);
+    }
+}
+
+/// All the tiles of VRAM, 16 per row, under the palette. In CGB mode, the tiles of bank 1 are on the right.
+pub fn render_tiles(memory: &Memory, palette: TilePalette) -> Image {
+    let colors = palette.colors(memory);
+    let banks = if memory.cgb.enabled { VRAM_BANKS } else { 1 };
+    let bank_width = TILES_PER_ROW * TILE_SIZE;
+    let mut image = Image::new(banks * bank_width + (banks - 1) * MAPS_GAP, TILE_COUNT / TILES_PER_ROW * TILE_SIZE);
+    image.fill_rect(0, 0, image.width, image.height, BACKGROUND);
+
+    for bank in 0..banks {
+        for tile in 0..TILE_COUNT {
+            let draw = TileDraw { bank, tile, colors, flip_x: false, flip_y: false, transparent: false };
+            draw_tile(&mut image, memory, bank * (bank_width + MAPS_GAP) + tile % TILES_PER_ROW * TILE_SIZE, tile / TILES_PER_ROW * TILE_SIZE, &draw);
+        }
+    }
+
+    return image;
+}
+
+/// Both background maps side by side, with the viewport of the screen and the window outlined
+pub fn render_tilemaps(memory: &Memory) -> Image {
+    let mut image = Image::new(MAP_SIZE * 2 + MAPS_GAP, MAP_SIZE);
+    let colors = dmg_colors(memory.peek(BGP));
+    let lcdc = memory.peek(LCDC);
+    let map_left = |map_bit: usize| if get_bit(lcdc, map_bit) { MAP_SIZE + MAPS_GAP } else { 0 };
+
+    image.fill_rect(MAP_SIZE, 0, MAPS_GAP, MAP_SIZE, BACKGROUND);
+    for (index, map) in [MAP_0, MAP_1].into_iter().enumerate() {
+        for row in 0..MAP_TILES {
+            for column in 0..MAP_TILES {
+                let attributes = map_attributes(memory, map, column, row);
+                let draw = TileDraw {
+                    bank: usize::from(get_bit(attributes, ATTRIBUTE_BANK_BIT)),
+                    tile: map_tile(memory, map, column, row),
+                    colors,
+                    flip_x: get_bit(attributes, ATTRIBUTE_X_FLIP_BIT),
+                    flip_y: get_bit(attributes, ATTRIBUTE_Y_FLIP_BIT),
+                    transparent: false,
+                };
+                draw_tile(&mut image, memory, index * (MAP_SIZE + MAPS_GAP) + column * TILE_SIZE, row * TILE_SIZE, &draw);
+            }
+        }
+    }
+
+    let scroll = (usize::from(memory.peek(SCX)), usize::from(memory.peek(SCY)));
+    outline_wrapped(&mut image, map_left(LCDC_BG_MAP_BIT), scroll, (SCREEN_WIDTH, SCREEN_HEIGHT), VIEWPORT_COLOR);
+
+    // The visible part of the window is drawn from the top-left corner of its map
+    let window_x = usize::from(memory.peek(WX)).saturating_sub(7);
+    let window_y = usize::from(memory.peek(WY));
+    if get_bit(lcdc, LCDC_WINDOW_ENABLE_BIT) && window_x < SCREEN_WIDTH && window_y < SCREEN_HEIGHT {
+        outline_wrapped(&mut image, map_left(LCDC_WINDOW_MAP_BIT), (0, 0), (SCREEN_WIDTH - window_x, SCREEN_HEIGHT - window_y), WINDOW_COLOR);
+    }
+
+    return image;
+}
+
+/// The 40 objects, in two columns: preview, position (X, Y), tile number and flags
+pub fn render_oam(memory: &Memory) -> Image {
+    let mut image = Image::new(OAM_COLUMN_WIDTH * 2, OAM_ENTRIES / 2 * OAM_ROW_HEIGHT);
+    let tall = get_bit(memory.peek(LCDC), LCDC_OBJ_SIZE_BIT);
+    image.fill_rect(0, 0, image.width, image.height, BACKGROUND);
+
+    for entry in 0..OAM_ENTRIES {
+        #[allow(clippy::cast_possible_truncation)]
+        let addr = OAM_START + (entry * OAM_ENTRY_SIZE) as FarAddress;
+        let (y, x, tile, flags) = (memory.peek(addr), memory.peek(addr + 1), memory.peek(addr + 2), memory.peek(addr + 3));
+        let left = entry / (OAM_ENTRIES / 2) * OAM_COLUMN_WIDTH;
+        let top = entry % (OAM_ENTRIES / 2) * OAM_ROW_HEIGHT + 1;
+
+        let colors = dmg_colors(memory.peek(if get_bit(flags, OAM_PALETTE_BIT) { OBP1 } else { OBP0 }));
+        let bank = usize::from(memory.cgb.enabled && get_bit(flags, OAM_BANK_BIT));
+        let (flip_x, flip_y) = (get_bit(flags, OAM_X_FLIP_BIT), get_bit(flags, OAM_Y_FLIP_BIT));
+        // 8x16 objects ignore bit 0 of the tile number, the bottom tile coming first when flipped vertically
+        let tiles = if tall { vec![usize::from(tile & 0xFE), usize::from(tile | 0x01)] } else { vec![usize::from(tile)] };
+        let order: Vec<usize> = if flip_y { tiles.into_iter().rev().collect() } else { tiles };
+
+        for (index, tile) in order.into_iter().enumerate() {
+            draw_tile(&mut image, memory, left, top + index * TILE_SIZE, &TileDraw { bank, tile, colors, flip_x, flip_y, transparent: true });
+        }
+
+        let flag = |bit: usize, name: char| if get_bit(flags, bit) { name } else { '-' };
+        let text = format!(
+            "{entry:02} {x:02X},{y:02X} T{tile:02X} {}{}{}{}",
+            flag(OAM_PRIORITY_BIT, 'P'), flag(OAM_Y_FLIP_BIT, 'Y'), flag(OAM_X_FLIP_BIT, 'X'), flag(OAM_PALETTE_BIT, '1'),
+        );
+        draw_text(&mut image, left + TILE_SIZE + 4, top + (OAM_ROW_HEIGHT - GLYPH_HEIGHT) / 2, &text, TEXT_COLOR);
+    }
+
+    return image;
+}
+
+/// Palette a pixel is drawn with: the palette number in CGB mode, else 0 for BGP and 0 or 1 for OBP0 and OBP1
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Palette {
+    Background(Value),
+    Object(Value),
+}
+
+fn dmg_palette(memory: &Memory, palette: Palette) -> Value {
+    return match palette {
+        Palette::Background(_) => memory.peek(BGP),
+        Palette::Object(0) => memory.peek(OBP0),
+        Palette::Object(_) => memory.peek(OBP1),
+    }
+}
+
+/// Color of a pixel of the screen, from the palette RAM in CGB mode, and in compatibility mode once through the DMG
+/// palette registers. On SGB the shade picks a color of the palette of the screen cell at the position.
+fn pixel_rgb(memory: &Memory, palette: Palette, color: u8, (x, y): (usize, usize), corrected: bool) -> Rgb {
+    if memory.sgb.enabled { return cgb_rgb(memory.sgb.color(x, y, shade_index(dmg_palette(memory, palette), color)), false); }
+
+    let cgb = &memory.cgb;
+    if !cgb.enabled && !cgb.compatibility { return shade(dmg_palette(memory, palette), color); }
+
+    let color = usize::from(if cgb.compatibility { shade_index(dmg_palette(memory, palette), color) } else { color });
+    return match palette {
+        Palette::Background(palette) => cgb_rgb(cgb.background_palettes.color(usize::from(palette), color), corrected),
+        Palette::Object(palette) => cgb_rgb(cgb.object_palettes.color(usize::from(palette), color), corrected),
+    }
+}
+
+struct Object {
+    x: usize,
+    /// Line of the object drawn on the current line
+    line: usize,
+    tile: Value,
+    flags: Value,
+}
+
+/// Objects on a line, from the highest priority to the lowest: the leftmost first on DMG (then the first in OAM), in
+/// OAM order on CGB
+fn line_objects(memory: &Memory, y: usize, height: usize) -> Vec<Object> {
+    let mut objects: Vec<Object> = (0..OAM_ENTRIES)
+        .filter_map(|entry| {
+            #[allow(clippy::cast_possible_truncation)]
+            let addr = OAM_START + (entry * OAM_ENTRY_SIZE) as FarAddress;
+            // Positions are offset by (8, 16) so that objects can be partially off screen
+            let line = (y + 16).checked_sub(usize::from(memory.peek(addr))).filter(|line| *line < height)?;
+            return Some(Object { x: usize::from(memory.peek(addr + 1)), line, tile: memory.peek(addr + 2), flags: memory.peek(addr + 3) });
+        })
+        .take(OBJECTS_PER_LINE)
+        .collect();
+
+    // Stable, objects at the same X keep their OAM order
+    if !memory.cgb.enabled { objects.sort_by_key(|object| object.x); }
+    return objects;
+}
+
+/// Color and palette of the first opaque object pixel at the screen X, and whether it is behind background colors 1-3
+fn object_pixel(memory: &Memory, objects: &[Object], x: usize, height: usize) -> Option<(u8, Palette, bool)> {
+    let cgb = memory.cgb.enabled;
+
+    return objects.iter().find_map(|object| {
+        let column = (x + 8).checked_sub(object.x).filter(|column| *column < TILE_SIZE)?;
+        let column = if get_bit(object.flags, OAM_X_FLIP_BIT) { TILE_SIZE - 1 - column } else { column };
+        let line = if get_bit(object.flags, OAM_Y_FLIP_BIT) { height - 1 - object.line } else { object.line };
+        // 8x16 objects ignore bit 0 of the tile number
+        let tile = if height > TILE_SIZE { object.tile & 0xFE } else { object.tile };
+        let bank = usize::from(cgb && get_bit(object.flags, OAM_BANK_BIT));
+
+        let color = tile_color(memory, bank, usize::from(tile) + line / TILE_SIZE, column, line % TILE_SIZE);
+        if color == 0 { return None; }
+
+        let palette = if cgb { object.flags & OAM_CGB_PALETTE_MASK } else { Value::from(get_bit(object.flags, OAM_PALETTE_BIT)) };
+        return Some((color, Palette::Object(palette), get_bit(object.flags, OAM_PRIORITY_BIT)));
+    });
+}
+
+/// Color, palette and priority over the objects of a background or window pixel, at a position of the map
+fn map_pixel(memory: &Memory, map: FarAddress, x: usize, y: usize) -> (u8, Palette, bool) {
+    let (column, row) = (x / TILE_SIZE, y / TILE_SIZE);
+    let attributes = map_attributes(memory, map, column, row);
+    let tile_x = if get_bit(attributes, ATTRIBUTE_X_FLIP_BIT) { TILE_SIZE - 1 - x % TILE_SIZE } else { x % TILE_SIZE };
+    let tile_y = if get_bit(attributes, ATTRIBUTE_Y_FLIP_BIT) { TILE_SIZE - 1 - y % TILE_SIZE } else { y % TILE_SIZE };
+    let bank = usize::from(get_bit(attributes, ATTRIBUTE_BANK_BIT));
+
+    let color = tile_color(memory, bank, map_tile(memory, map, column, row), tile_x, tile_y);
+    return (color, Palette::Background(attributes & ATTRIBUTE_PALETTE_MASK), get_bit(attributes, ATTRIBUTE_PRIORITY_BIT));
+}
+
+/// Whole screen from the current state of VRAM, OAM and the LCD registers, as if they hadn't changed during the frame.
+/// CGB colors go through the LCD color correction if `corrected`. The SGB mask hides the screen or keeps the last frame.
+// TODO: Replace with the scanline renderer of the PPU once it is emulated, mid-frame effects are missing until then
+pub fn render_screen(memory: &Memory, framebuffer: &mut Framebuffer, corrected: bool) {
+    match memory.sgb.mask {
+        Mask::None => {}
+        Mask::Freeze => return,
+        Mask::Black | Mask::Blank => {
+            framebuffer.fill(if memory.sgb.mask == Mask::Black { DMG_SHADES[3] } else { cgb_rgb(memory.sgb.color(0, 0, 0), false) });
+            return;
+        }
+    }
+
+    let lcdc = memory.peek(LCDC);
+    if !get_bit(lcdc, LCDC_LCD_ENABLE_BIT) {
+        framebuffer.fill(DMG_SHADES[0]);
+        return;
+    }
+
+    let cgb = memory.cgb.enabled;
+    let (scroll_x, scroll_y) = (usize::from(memory.peek(SCX)), usize::from(memory.peek(SCY)));
+    let (window_x, window_y) = (usize::from(memory.peek(WX)), usize::from(memory.peek(WY)));
+    let background_map = if get_bit(lcdc, LCDC_BG_MAP_BIT) { MAP_1 } else { MAP_0 };
+    let window_map = if get_bit(lcdc, LCDC_WINDOW_MAP_BIT) { MAP_1 } else { MAP_0 };
+    // On CGB, LCDC bit 0 doesn't hide the background and window but puts the objects over them
+    let background_enabled = cgb || get_bit(lcdc, LCDC_BG_ENABLE_BIT);
+    let background_priority = !cgb || get_bit(lcdc, LCDC_BG_ENABLE_BIT);
+    let window_enabled = background_enabled && get_bit(lcdc, LCDC_WINDOW_ENABLE_BIT);
+    let object_height = if get_bit(lcdc, LCDC_OBJ_SIZE_BIT) { TILE_SIZE * 2 } else { TILE_SIZE };
+
+    for y in 0..SCREEN_HEIGHT {
+        let objects = if get_bit(lcdc, LCDC_OBJ_ENABLE_BIT) { line_objects(memory, y, object_height) } else { Vec::new() };
+
+        for x in 0..SCREEN_WIDTH {
+            let (background, palette, priority) = if window_enabled && y >= window_y && x + 7 >= window_x {
+                map_pixel(memory, window_map, x + 7 - window_x, y - window_y)
+            } else if background_enabled {
+                map_pixel(memory, background_map, (x + scroll_x) % MAP_SIZE, (y + scroll_y) % MAP_SIZE)
+            } else {
+                (0, Palette::Background(0), false)
+            };
+
+            let color = match object_pixel(memory, &objects, x, object_height) {
+                Some((color, object_palette, behind)) if !(background_priority && (behind || priority) && background != 0) => {
+                    pixel_rgb(memory, object_palette, color, (x, y), corrected)
+                }
+                // Blank rather than color 0 of the palette
+                _ if !background_enabled => DMG_SHADES[0],
+                _ => pixel_rgb(memory, palette, background, (x, y), corrected),
+            };
+            framebuffer.set_pixel(x, y, color);
+        }
+    }
+}
+
+/// Screen in the middle of the SGB border, as shown on the TV
+pub fn render_border(memory: &Memory, framebuffer: &Framebuffer) -> Image {
+    let mut image = Image::new(BORDER_WIDTH, BORDER_HEIGHT);
+    for y in 0..BORDER_HEIGHT {
+        for x in 0..BORDER_WIDTH { image.set_pixel(x, y, cgb_rgb(memory.sgb.border_color(x, y), false)); }
+    }
+
+    let (left, pitch) = (SCREEN_LEFT * BYTES_PER_PIXEL, image.pitch());
+    for (y, row) in framebuffer.pixels.chunks_exact(Framebuffer::pitch()).enumerate() {
+        let start = (SCREEN_TOP + y) * pitch + left;
+        image.pixels[start..start + row.len()].copy_from_slice(row);
+    }
+
+    return image;
+}
+
+/// The 4 shades of each DMG palette, or the 8 background and 8 object palettes in CGB and compatibility modes (without
+/// color correction)
+pub fn render_palettes(memory: &Memory) -> Image {
+    if memory.cgb.enabled || memory.cgb.compatibility { return render_cgb_palettes(memory); }
+
+    let label_width = 10 * GLYPH_WIDTH;
+    let mut image = Image::new(label_width + 4 * SWATCH_SIZE + 4, DMG_PALETTES.len() * (SWATCH_SIZE + 2) + 2);
+    image.fill_rect(0, 0, image.width, image.height, BACKGROUND);
+
+    for (row, (register, name)) in DMG_PALETTES.iter().enumerate() {
+        let palette = memory.peek(*register);
+        let top = row * (SWATCH_SIZE + 2) + 2;
+
+        draw_text(&mut image, 2, top + (SWATCH_SIZE - GLYPH_HEIGHT) / 2 + 1, &format!("{name} {palette:02X}"), TEXT_COLOR);
+        for color in 0..4 {
+            image.fill_rect(label_width + usize::from(color) * SWATCH_SIZE, top, SWATCH_SIZE, SWATCH_SIZE, shade(palette, color));
+        }
+    }
+
+    return image;
+}
+
+/// Background palettes on the left and object palettes on the right, one per row
+fn render_cgb_palettes(memory: &Memory) -> Image {
+    let label_width = 5 * GLYPH_WIDTH;
+    let column_width = label_width + COLORS_PER_PALETTE * SWATCH_SIZE + 8;
+    let mut image = Image::new(2 * column_width, PALETTE_COUNT * (SWATCH_SIZE + 2) + 2);
+    image.fill_rect(0, 0, image.width, image.height, BACKGROUND);
+
+    let columns = [("BG", &memory.cgb.background_palettes), ("OBJ", &memory.cgb.object_palettes)];
+    for (column, (name, palettes)) in columns.into_iter().enumerate() {
+        for palette in 0..PALETTE_COUNT {
+            let (left, top) = (column * column_width + 2, palette * (SWATCH_SIZE + 2) + 2);
+
+            draw_text(&mut image, left, top + (SWATCH_SIZE - GLYPH_HEIGHT) / 2 + 1, &format!("{name}{palette}"), TEXT_COLOR);
+            for color in 0..COLORS_PER_PALETTE {
+                let rgb = cgb_rgb(palettes.color(palette, color), false);
+                image.fill_rect(left + label_width + color * SWATCH_SIZE, top, SWATCH_SIZE, SWATCH_SIZE, rgb);
+            }
+        }
+    }
+
+    return image;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cpu::colorization::{button_combination, colorize, Button, Direction};
+    use crate::cpu::memory::Memory;
+    use crate::gui::screen::{cgb_rgb, DMG_SHADES};
+    use crate::gui::screen::Framebuffer;
+    use crate::cpu::sgb::Mask;
+    use super::{map_tile, render_border, render_screen, render_tilemaps, render_tiles, shade, tile_color, TilePalette, MAP_0, VIEWPORT_COLOR};
+
+    #[test]
+    fn test_tile_color() {
+        let mut memory = Memory::new(0x10000);
+        // Row 1 of tile 1: low bits 0b1000_0001, high bits 0b1000_0000
+        memory.memory[0x8012] = 0b1000_0001;
+        memory.memory[0x8013] = 0b1000_0000;
+
+        assert_eq!(tile_color(&memory, 0, 1, 0, 1), 3);
+        assert_eq!(tile_color(&memory, 0, 1, 7, 1), 1);
+        assert_eq!(tile_color(&memory, 0, 1, 3, 1), 0);
+    }
+
+    #[test]
+    fn test_palette_and_addressing() {
+        assert_eq!(shade(0b1110_0100, 0), DMG_SHADES[0]);
+        assert_eq!(shade(0b1110_0100, 3), DMG_SHADES[3]);
+        assert_eq!(shade(0b0001_1011, 0), DMG_SHADES[3]);
+
+        let mut memory = Memory::new(0x10000);
+        memory.memory[0x9801] = 0x80;
+        assert_eq!(map_tile(&memory, MAP_0, 1, 0), 128);
+        memory.memory[0xFF40] = 0b0001_0000;
+        assert_eq!(map_tile(&memory, MAP_0, 1, 0), 0x80);
+    }
+
+    #[test]
+    fn test_render_screen() {
+        let mut memory = Memory::new(0x10000);
+        // LCD, background and objects on, unsigned tile indexes, identity palettes
+        memory.memory[0xFF40] = 0b1001_0011;
+        memory.memory[0xFF47] = 0b1110_0100;
+        memory.memory[0xFF48] = 0b1110_0100;
+        // Tile 1 is color 3, tile 2 color 1
+        memory.memory[0x8010..0x8020].fill(0xFF);
+        memory.memory[0x8020..0x8030].copy_from_slice(&[0xFF, 0x00].repeat(8));
+        // Map entry (1, 0) is tile 1, scrolled by 4 pixels
+        memory.memory[0x9801] = 1;
+        memory.memory[0xFF43] = 4;
+        // Object with tile 2 at the top-left corner of the screen, behind the background colors 1-3
+        memory.memory[0xFE00..0xFE04].copy_from_slice(&[16, 8, 2, 0x80]);
+
+        let mut framebuffer = Framebuffer::new();
+        render_screen(&memory, &mut framebuffer, false);
+        assert_eq!(framebuffer.pixel(3, 0), DMG_SHADES[1]);
+        assert_eq!(framebuffer.pixel(4, 0), DMG_SHADES[3]);
+        assert_eq!(framebuffer.pixel(11, 0), DMG_SHADES[3]);
+        assert_eq!(framebuffer.pixel(12, 0), DMG_SHADES[0]);
+        assert_eq!(framebuffer.pixel(3, 8), DMG_SHADES[0]);
+
+        // LCD off
+        memory.memory[0xFF40] = 0;
+        memory.memory[0xFF47] = 0xFF;
+        render_screen(&memory, &mut framebuffer, false);
+        assert_eq!(framebuffer.pixel(4, 0), DMG_SHADES[0]);
+    }
+
+    #[test]
+    fn test_render_cgb_attributes() {
+        let mut memory = Memory::new(0x10000);
+        memory.set_cgb_mode(true);
+        // LCD and background on, unsigned tile indexes
+        memory.memory[0xFF40] = 0b1001_0001;
+        // Tile 1 of bank 1 has its leftmost column set to color 3
+        memory.write_far_addr(0xFF4F, 1);
+        memory.memory[0x8010..0x8020].fill(0x80);
+        // Map entry 0 uses tile 1 of bank 1, flipped horizontally
+        memory.memory[0x9800] = 0b0010_1000;
+        memory.write_far_addr(0xFF4F, 0);
+        memory.memory[0x9800] = 1;
+        // Background palette 0: white, then red as color 3
+        memory.write_far_addr(0xFF68, 0x86);
+        memory.write_far_addr(0xFF69, 0x1F);
+        memory.write_far_addr(0xFF69, 0x00);
+
+        let mut framebuffer = Framebuffer::new();
+        render_screen(&memory, &mut framebuffer, false);
+        assert_eq!(framebuffer.pixel(0, 0), (0xFF, 0xFF, 0xFF));
+        assert_eq!(framebuffer.pixel(7, 0), (0xFF, 0x00, 0x00));
+
+        render_screen(&memory, &mut framebuffer, true);
+        assert_eq!(framebuffer.pixel(0, 0), (0xF0, 0xF0, 0xF0));
+        assert_eq!(framebuffer.pixel(7, 0), (0xC9, 0x00, 0x2E));
+    }
+
+    #[test]
+    fn test_render_colorized() {
+        let mut memory = Memory::new(0x10000);
+        colorize(&mut memory, button_combination(Direction::Up, Button::A));
+        // LCD and background on, unsigned tile indexes, BGP mapping color 0 to shade 1
+        memory.memory[0xFF40] = 0b1001_0001;
+        memory.memory[0xFF47] = 0b1110_0101;
+
+        let mut framebuffer = Framebuffer::new();
+        render_screen(&memory, &mut framebuffer, false);
+        assert_eq!(framebuffer.pixel(0, 0), cgb_rgb(0x421F, false));
+    }
+
+    #[test]
+    fn test_render_sgb() {
+        let mut memory = Memory::new(0x10000);
+        memory.sgb.enabled = true;
+        // LCD and background on, BGP mapping color 0 to shade 1
+        memory.memory[0xFF40] = 0b1001_0001;
+        memory.memory[0xFF47] = 0b1110_0101;
+
+        let mut framebuffer = Framebuffer::new();
+        render_screen(&memory, &mut framebuffer, false);
+        assert_eq!(framebuffer.pixel(0, 0), cgb_rgb(memory.sgb.color(0, 0, 1), false));
+
+        // The screen is in the middle of the border, whose color 0 is the backdrop
+        let image = render_border(&memory, &framebuffer);
+        assert_eq!((image.width, image.height), (256, 224));
+        assert_eq!(image.pixel(48, 40), framebuffer.pixel(0, 0));
+        assert_eq!(image.pixel(0, 0), cgb_rgb(memory.sgb.color(0, 0, 0), false));
+
+        memory.sgb.mask = Mask::Black;
+        render_screen(&memory, &mut framebuffer, false);
+        assert_eq!(framebuffer.pixel(0, 0), DMG_SHADES[3]);
+    }
+
+    #[test]
+    fn test_tile_palettes() {
+        let mut memory = Memory::new(0x10000);
+        assert_eq!(TilePalette::all(&memory).len(), 3);
+
+        memory.set_cgb_mode(true);
+        let palettes = TilePalette::all(&memory);
+        assert_eq!(palettes.len(), 19);
+        assert_eq!((palettes[3].name(), palettes[18].name()), (String::from("BG0"), String::from("OBJ7")));
+
+        // Tile 0 is color 0, red in object palette 2
+        memory.cgb.object_palettes.set_color(2, 0, 0x001F);
+        assert_eq!(render_tiles(&memory, TilePalette::Object(2)).pixel(0, 0), (0xFF, 0x00, 0x00));
+        assert_eq!(render_tiles(&memory, TilePalette::Background(2)).pixel(0, 0), (0xFF, 0xFF, 0xFF));
+    }
+
+    #[test]
+    fn test_cgb_colors() {
+        assert_eq!(cgb_rgb(0x0000, false), (0x00, 0x00, 0x00));
+        assert_eq!(cgb_rgb(0x7FFF, false), (0xFF, 0xFF, 0xFF));
+        // Red 0x10, green 0x08, blue 0x01, bit 15 ignored
+        assert_eq!(cgb_rgb(0x8510, false), (0x84, 0x42, 0x08));
+
+        // The correction bleeds the channels into each other and caps the brightness
+        assert_eq!(cgb_rgb(0x7FFF, true), (0xF0, 0xF0, 0xF0));
+        assert_eq!(cgb_rgb(0x03E0, true), (0x1F, 0xBA, 0x1F));
+    }
+
+    #[test]
+    fn test_viewport_wraps() {
+        let mut memory = Memory::new(0x10000);
+        memory.memory[0xFF43] = 200;
+
+        let image = render_tilemaps(&memory);
+        // Left edge at SCX, right edge wrapped around to (200 + 159) % 256
+        assert_eq!(image.pixel(200, 50), VIEWPORT_COLOR);
+        assert_eq!(image.pixel(103, 50), VIEWPORT_COLOR);
+        assert_ne!(image.pixel(150, 50), VIEWPORT_COLOR);
+    }
+}
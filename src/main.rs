@@ -1,61 +1,195 @@
-use crate::cpu::instruction::{GenericInstruction, instruction_from_opcode, PREFIXED_OPCODE, OpCode};
-use crate::cpu::memory::Memory;
-use crate::gui::gui::launch_gui;
-use crate::utils::log::log;
+use std::path::Path;
+use crate::cpu::cartridge::Cartridge;
+use crate::cpu::memory::{Memory, PowerOnOptions};
+use crate::debug::coverage::Coverage;
+use crate::debug::debugger::{Breakpoint, Debugger};
+use crate::debug::disassembler::RomDisassembly;
+use crate::debug::profiler::Profiler;
+use crate::debug::symbols::Symbols;
+use crate::debug::trace::Tracer;
+use crate::gui::capture::save_screenshot;
+use crate::gui::gui::{launch_gui, run_headless};
+use crate::gui::screen::Framebuffer;
+use crate::gui::vram::render_screen;
+use crate::state::movie::Movie;
+use crate::state::rewind::RewindBuffer;
+use crate::state::savestate::{load_from_slot, rom_checksum, save, save_to_slot};
+use crate::utils::args::{Arguments, Command};
+use crate::utils::log::{log, LOG_ENV_VAR, LogFilter, Logger};
+use crate::utils::types::Byte;
 
 mod cpu;
+mod debug;
 mod gui;
+mod state;
+#[cfg(test)]
+mod testroms;
 mod utils;
 
 const PROGRAM_NAME: &str = "LameBoy";
 const PROGRAM_VERSION: &str = "0.0.1";
+const DEFAULT_LOG_FILTER: &str = "warn";
+
+/// Loads the symbol file given on the command line, else the one next to the ROM if any
+fn load_symbols(path: Option<&Path>, rom: &Path) -> Symbols {
+    let symbols = path.map_or_else(|| Symbols::load_for_rom(rom), Symbols::load);
+
+    return symbols.unwrap_or_else(|error| {
+        eprintln!("{error}");
+        std::process::exit(1);
+    });
+}
+
+/// Logs according to the command line, else to the environment variable
+fn init_logging(arguments: &Arguments) {
+    let filter = arguments.log_filter.clone().map_or_else(
+        || std::env::var(LOG_ENV_VAR).unwrap_or_else(|_| String::from(DEFAULT_LOG_FILTER)).parse::<LogFilter>(),
+        Ok,
+    );
+    let filter = filter.unwrap_or_else(|error| {
+        eprintln!("Invalid {LOG_ENV_VAR}: {error}");
+        std::process::exit(1);
+    });
+
+    let logger = match &arguments.log_file {
+        Some(path) => Logger::to_file(filter, path).unwrap_or_else(|error| {
+            eprintln!("Couldn't create log file {}: {error}", path.display());
+            std::process::exit(1);
+        }),
+        None => Logger::to_buffer(filter),
+    };
+    utils::log::init(logger);
+}
+
+fn read_rom(path: &Path) -> Vec<Byte> {
+    return std::fs::read(path).unwrap_or_else(|error| {
+        eprintln!("Couldn't read ROM {}: {error}", path.display());
+        std::process::exit(1);
+    });
+}
+
+fn disassemble(rom: &Path, output: Option<&Path>, symbols: &Symbols) {
+    let listing = RomDisassembly::trace(&read_rom(rom)).listing(symbols);
+
+    let Some(path) = output else {
+        print!("{listing}");
+        return;
+    };
+    if let Err(error) = std::fs::write(path, listing) {
+        eprintln!("Couldn't write disassembly to {}: {error}", path.display());
+        std::process::exit(1);
+    }
+}
 
 fn main() {
-    log!("PROGRAM", format!("{PROGRAM_NAME} v{PROGRAM_VERSION}"));
-
-    // Allocate 1024 bytes of memory
-    let mut memory = Memory::new(1024);
-    let mut prefixed_encountered = false;
-
-    // TODO: Remove
-    // LD A => PREFIX => SWAP A
-    let temp_opcodes: Vec<OpCode> = vec![0x3E, 0xCB, 0x37];
-
-
-    // TODO fetch opcode from GB game
-    for opcode in temp_opcodes {
-
-        if opcode == PREFIXED_OPCODE {
-            log!("INSTRUCTION", "Encountered prefix instruction");
-            prefixed_encountered = true;
-        } else {
-            match instruction_from_opcode(opcode, prefixed_encountered) {
-                GenericInstruction::Void(instr) => instr.execute(&mut memory, ()),
-                GenericInstruction::Wide(instr) => {
-                    // TODO: Parse next two bytes as value
-                    instr.execute(&mut memory, 1);
-                }
-                GenericInstruction::Value(instr) => {
-                    // TODO: Parse next byte as value
-                    instr.execute(&mut memory, 0b1111_0000);
-                }
-                GenericInstruction::Far(instr) => {
-                    // TODO: Parse next two bytes as value
-                    instr.execute(&mut memory, 0xFF00);
-                }
-                GenericInstruction::Near(instr) => {
-                    // TODO: Parse next byte as value
-                    instr.execute(&mut memory, 0x0F);
-                }
-                GenericInstruction::Offset(instr) => {
-                    // TODO: Parse next byte as value
-                    instr.execute(&mut memory, -2);
-                }
+    let arguments = Arguments::parse();
+    init_logging(&arguments);
+
+    let rom_path = match &arguments.command {
+        Command::Disassemble { rom, output } => {
+            disassemble(rom, output.as_deref(), &load_symbols(arguments.symbols.as_deref(), rom));
+            return;
+        }
+        Command::Run { rom } => rom,
+    };
+
+    log!(Info, "UTILS", format!("{PROGRAM_NAME} v{PROGRAM_VERSION}"));
+
+    let rom = read_rom(rom_path);
+    let checksum = rom_checksum(&rom);
+
+    let options = PowerOnOptions { sgb: arguments.sgb, colorize: arguments.colorize };
+    let mut memory = match Memory::power_on(&rom, options) {
+        Ok(memory) => memory,
+        Err(error) => {
+            eprintln!("Couldn't run {}: {error}", rom_path.display());
+            std::process::exit(1);
+        }
+    };
+
+    if let Some(slot) = arguments.load_slot {
+        if let Err(error) = load_from_slot(&mut memory, checksum, rom_path, slot) {
+            eprintln!("Couldn't load save state from slot {slot}: {error}");
+            std::process::exit(1);
+        }
+    }
+
+    let mut movie = if let Some(path) = &arguments.play_movie {
+        let movie = match Movie::play(path, checksum, !arguments.movie_read_write) {
+            Ok(movie) => movie,
+            Err(error) => {
+                eprintln!("Couldn't play movie {}: {error}", path.display());
+                std::process::exit(1);
+            }
+        };
+        if let Err(error) = movie.apply_start(&mut memory, &rom, options) {
+            eprintln!("Couldn't restore the start state of movie {}: {error}", path.display());
+            std::process::exit(1);
+        }
+        Some(movie)
+    } else {
+        arguments.record_movie.as_ref().map(|path| Movie::record(path, checksum, arguments.load_slot.map(|_| save(&memory, checksum))))
+    };
+
+    let mut rewind = RewindBuffer::new(arguments.rewind_budget, arguments.rewind_interval);
+
+    memory.stub_ly = arguments.stub_ly;
+    for watchpoint in &arguments.watchpoints { memory.watchpoints.add(*watchpoint); }
+    if arguments.coverage.is_some() { memory.coverage = Coverage::new(memory.cartridge.as_ref().map_or(0, Cartridge::rom_bank_count)); }
+
+    let symbols = load_symbols(arguments.symbols.as_deref(), rom_path);
+    let breakpoints: Result<Vec<Breakpoint>, String> = arguments.breakpoints.iter().map(|breakpoint| Breakpoint::resolve(breakpoint, &symbols)).collect();
+    let breakpoints = breakpoints.unwrap_or_else(|error| {
+        eprintln!("Invalid breakpoint: {error}");
+        std::process::exit(1);
+    });
+
+    let mut debugger = Debugger::new(arguments.debug, breakpoints, symbols);
+    if let Some(path) = &arguments.trace {
+        match Tracer::new(path, arguments.trace_filter, arguments.trace_symbols) {
+            Ok(tracer) => debugger.set_tracer(tracer),
+            Err(error) => {
+                eprintln!("Couldn't create trace log {}: {error}", path.display());
+                std::process::exit(1);
             }
+        }
+    }
+
+    if let Some(path) = &arguments.profile { debugger.set_profiler(Profiler::new(path, arguments.profile_format)); }
+
+    if let Some(frames) = arguments.headless {
+        run_headless(&mut memory, &mut rewind, &mut movie, &mut debugger, &arguments, frames);
+    } else if let Err(error) = launch_gui(&mut memory, &mut rewind, &mut movie, &mut debugger, &arguments, (rom_path, checksum)) {
+        eprintln!("{error}");
+    }
+    debugger.finish();
+
+    if let Some(path) = &arguments.screenshot {
+        let mut framebuffer = Framebuffer::new();
+        render_screen(&memory, &mut framebuffer, arguments.color_correction);
+        if let Err(error) = save_screenshot(&framebuffer, path, arguments.screenshot_scale) {
+            eprintln!("Couldn't save screenshot to {}: {error}", path.display());
+        }
+    }
+
+    if let Some(path) = &arguments.coverage {
+        print!("ROM coverage:\n{}", memory.coverage.summary());
+        if let Err(error) = memory.coverage.save(path) {
+            eprintln!("Couldn't write coverage map to {}: {error}", path.display());
+        }
+    }
+
+    if let Some(slot) = arguments.save_slot {
+        if let Err(error) = save_to_slot(&memory, checksum, rom_path, slot) {
+            eprintln!("Couldn't save state to slot {slot}: {error}");
+        }
+    }
 
-            prefixed_encountered = false;
+    if let Some(movie) = movie {
+        if let Err(error) = movie.finish() {
+            eprintln!("Couldn't save movie: {error}");
         }
     }
 
-    launch_gui();
-}
\ No newline at end of file
+    utils::log::flush();
+}
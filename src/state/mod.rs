@@ -0,0 +1,3 @@
+pub mod savestate;
+pub mod rewind;
+pub mod movie;
\ No newline at end of file
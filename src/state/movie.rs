@@ -0,0 +1,290 @@
+use std::path::{Path, PathBuf};
+use crate::cpu::memory::{Memory, PowerOnOptions};
+use crate::gui::input::JoypadState;
+use crate::state::savestate::{load, StateError, StateReader, StateWriter};
+use crate::utils::log::log;
+use crate::utils::types::{Byte, WideValue};
+use crate::{PROGRAM_NAME, PROGRAM_VERSION};
+
+//  #############################
+//  #          Format           #
+//  #############################
+
+// Every multi-byte field is stored little-endian.
+//
+//  Offset  Size    Field
+//  0x00    4       Magic, always "LMBM"
+//  0x04    2       Format version (see MOVIE_VERSION)
+//  0x06    2       Global checksum of the ROM the movie was recorded with (see savestate::rom_checksum)
+//  0x08    1 + n   Length of the emulator name & version which recorded the movie, followed by the n bytes of text
+//  ...     4       Rerecord count
+//  ...     4 + n   Length of the start save state, followed by the n bytes of the save state (0 => starts from power-on)
+//  ...     4 + n   Number of frames, followed by the joypad state of each frame (1 byte per frame)
+//
+// Playback is deterministic as long as the emulation is: the machine starts from power-on (with the same ROM and
+// options) or the embedded save state, no RNG is involved, and the only wall-clock dependency is the frame pacing of
+// the GUI, which only emulated frames consume inputs from.
+//
+// Version history:
+//  1 => Initial format
+
+const MOVIE_MAGIC: [Byte; 4] = *b"LMBM";
+const MOVIE_VERSION: u16 = 1;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MovieMode {
+    Recording,
+    /// In read-write mode, pressing any button takes over the playback, truncating the movie at the current frame
+    Playing { read_only: bool },
+    /// Read-only playback reached the end of the movie
+    Finished,
+}
+
+pub struct Movie {
+    path: PathBuf,
+    mode: MovieMode,
+    rom_checksum: WideValue,
+    start_state: Vec<Byte>,
+    rerecords: u32,
+    inputs: Vec<JoypadState>,
+    frame: usize,
+}
+
+fn emulator_version() -> String {
+    return format!("{PROGRAM_NAME} v{PROGRAM_VERSION}");
+}
+
+impl Movie {
+    /// `start_state` is the complete save state the movie starts from, `None` to start from power-on
+    pub fn record(path: &Path, rom_checksum: WideValue, start_state: Option<Vec<Byte>>) -> Movie {
+        log!("MEMORY", format!("Recording movie to {}", path.display()));
+
+        return Movie {
+            path: path.to_path_buf(),
+            mode: MovieMode::Recording,
+            rom_checksum,
+            start_state: start_state.unwrap_or_default(),
+            rerecords: 0,
+            inputs: Vec::new(),
+            frame: 0,
+        }
+    }
+
+    pub fn play(path: &Path, rom_checksum: WideValue, read_only: bool) -> Result<Movie, StateError> {
+        log!("MEMORY", format!("Playing movie {} ({})", path.display(), if read_only { "read-only" } else { "read-write" }));
+
+        let data = std::fs::read(path)?;
+        let mut reader = StateReader::new(&data);
+
+        if reader.read_bytes(MOVIE_MAGIC.len())? != MOVIE_MAGIC { return Err(StateError::BadMagic); }
+
+        let version = reader.read_wide()?;
+        if version != MOVIE_VERSION { return Err(StateError::UnsupportedVersion(version)); }
+
+        let checksum = reader.read_wide()?;
+        if checksum != rom_checksum { return Err(StateError::ChecksumMismatch { expected: rom_checksum, found: checksum }); }
+
+        let version_length = usize::from(reader.read_bytes(1)?[0]);
+        let recorded_with = String::from_utf8_lossy(reader.read_bytes(version_length)?).to_string();
+        if recorded_with != emulator_version() {
+            log!(Warn, "MEMORY", format!("Movie was recorded with {recorded_with}, playback may desync"));
+        }
+
+        let rerecords = reader.read_u32()?;
+        let state_length = reader.read_u32()? as usize;
+        let start_state = reader.read_bytes(state_length)?.to_vec();
+        let frame_count = reader.read_u32()? as usize;
+        let inputs = reader.read_bytes(frame_count)?.to_vec();
+
+        return Ok(Movie { path: path.to_path_buf(), mode: MovieMode::Playing { read_only }, rom_checksum, start_state, rerecords, inputs, frame: 0 });
+    }
+
+    /// Puts the machine in the state the movie starts from, powering it on with the ROM if the movie has no start state
+    pub fn apply_start(&self, memory: &mut Memory, rom: &[Byte], options: PowerOnOptions) -> Result<(), StateError> {
+        if self.start_state.is_empty() {
+            *memory = Memory::power_on(rom, options).map_err(StateError::UnsupportedCartridge)?;
+            return Ok(());
+        }
+
+        return load(memory, self.rom_checksum, &self.start_state);
+    }
+
+    /// Must be called once per frame with the input of the player, returns the input to feed to the emulation
+    pub fn next_input(&mut self, live: JoypadState) -> JoypadState {
+        let input = match self.mode {
+            MovieMode::Playing { read_only } => {
+                let recorded = self.inputs.get(self.frame).copied();
+
+                match (recorded, read_only) {
+                    (Some(recorded), true) => recorded,
+                    (Some(recorded), false) if live == 0 => recorded,
+                    (_, false) => {
+                        log!("MEMORY", format!("Rerecording from frame {}", self.frame));
+                        self.inputs.truncate(self.frame);
+                        self.rerecords += 1;
+                        self.mode = MovieMode::Recording;
+                        self.inputs.push(live);
+                        live
+                    }
+                    (None, true) => {
+                        log!("MEMORY", format!("Movie finished after {} frames", self.frame));
+                        self.mode = MovieMode::Finished;
+                        live
+                    }
+                }
+            }
+            MovieMode::Recording => {
+                self.inputs.push(live);
+                live
+            }
+            MovieMode::Finished => live,
+        };
+
+        self.frame += 1;
+        return input;
+    }
+
+    /// Must be called when rewinding with the frame the machine went back to. While recording, the inputs from that
+    /// frame on are dropped and every step back counts as a rerecord.
+    pub fn rewind_to(&mut self, frame: usize) {
+        if frame >= self.frame { return; }
+        self.frame = frame;
+
+        match self.mode {
+            MovieMode::Recording => {
+                log!("MEMORY", format!("Rewound recording to frame {frame}"));
+                self.inputs.truncate(frame);
+                self.rerecords += 1;
+            }
+            MovieMode::Finished => self.mode = MovieMode::Playing { read_only: true },
+            MovieMode::Playing { .. } => {}
+        }
+    }
+
+    /// The movie as it would be loaded back from its file
+    #[cfg(test)]
+    pub fn replay(&self, read_only: bool) -> Movie {
+        return Movie {
+            path: self.path.clone(),
+            mode: MovieMode::Playing { read_only },
+            rom_checksum: self.rom_checksum,
+            start_state: self.start_state.clone(),
+            rerecords: self.rerecords,
+            inputs: self.inputs.clone(),
+            frame: 0,
+        }
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn to_bytes(&self) -> Vec<Byte> {
+        let mut writer = StateWriter::new();
+        let version = emulator_version();
+
+        writer.write_bytes(&MOVIE_MAGIC);
+        writer.write_wide(MOVIE_VERSION);
+        writer.write_wide(self.rom_checksum);
+        writer.write_bytes(&[version.len() as Byte]);
+        writer.write_bytes(version.as_bytes());
+        writer.write_u32(self.rerecords);
+        writer.write_u32(self.start_state.len() as u32);
+        writer.write_bytes(&self.start_state);
+        writer.write_u32(self.inputs.len() as u32);
+        writer.write_bytes(&self.inputs);
+
+        return writer.into_bytes();
+    }
+
+    /// Writes the movie back to its file, unless it was only played back
+    pub fn finish(self) -> std::io::Result<()> {
+        if self.mode != MovieMode::Recording { return Ok(()); }
+
+        log!("MEMORY", format!("Saving movie of {} frames ({} rerecords) to {}", self.inputs.len(), self.rerecords, self.path.display()));
+
+        return std::fs::write(&self.path, self.to_bytes());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use crate::cpu::memory::{Memory, PowerOnOptions};
+    use super::{Movie, MovieMode};
+
+    fn recorded_movie() -> Movie {
+        let mut movie = Movie::record(Path::new("test.lmv"), 0xBEEF, None);
+        for input in [0b0001, 0b0010, 0b0100, 0b1000] { movie.next_input(input); }
+        return movie;
+    }
+
+    #[test]
+    fn test_read_only_playback() {
+        let mut movie = recorded_movie().replay(true);
+
+        // Live input is ignored until the end of the movie
+        assert_eq!(movie.next_input(0xFF), 0b0001);
+        assert_eq!(movie.next_input(0xFF), 0b0010);
+        assert_eq!(movie.next_input(0), 0b0100);
+        assert_eq!(movie.next_input(0), 0b1000);
+        assert_eq!(movie.next_input(0xFF), 0xFF);
+        assert_eq!(movie.mode, MovieMode::Finished);
+    }
+
+    #[test]
+    fn test_rerecording() {
+        let mut movie = recorded_movie().replay(false);
+
+        assert_eq!(movie.next_input(0), 0b0001);
+        assert_eq!(movie.next_input(0xF0), 0xF0);
+        assert_eq!(movie.mode, MovieMode::Recording);
+        assert_eq!(movie.inputs, vec![0b0001, 0xF0]);
+        assert_eq!(movie.rerecords, 1);
+    }
+
+    #[test]
+    fn test_rewind() {
+        let mut movie = recorded_movie();
+        movie.rewind_to(2);
+        movie.rewind_to(1);
+        movie.next_input(0xF0);
+        assert_eq!(movie.inputs, vec![0b0001, 0xF0]);
+        assert_eq!(movie.rerecords, 2);
+
+        // Playback goes back to the rewound frame, even once finished
+        let mut movie = movie.replay(true);
+        for _ in 0..3 { movie.next_input(0); }
+        assert_eq!(movie.mode, MovieMode::Finished);
+        movie.rewind_to(1);
+        assert_eq!(movie.next_input(0), 0xF0);
+    }
+
+    #[test]
+    fn test_power_on_start() {
+        // CGB cartridge, also supporting the SGB
+        let mut rom = vec![0; 0x8000];
+        rom[..4].copy_from_slice(&[0x3E, 0xF0, 0xCB, 0x37]);
+        rom[0x0143] = 0x80;
+        rom[0x0146] = 0x03;
+        rom[0x014B] = 0x33;
+
+        // Dirtied by a previous run
+        let Ok(mut memory) = Memory::power_on(&rom, PowerOnOptions::default()) else { panic!("ROM only cartridge was rejected") };
+        memory.memory[0xC000] = 0x42;
+        memory.cycles = 1234;
+
+        let Ok(()) = recorded_movie().apply_start(&mut memory, &rom, PowerOnOptions::default()) else { panic!("Power-on start failed") };
+        assert!((0..0x8000).all(|addr: u16| memory.peek(addr) == rom[usize::from(addr)]));
+        assert_eq!((memory.peek(0xC000), memory.cycles), (0x00, 0));
+        assert!(memory.cgb.enabled && !memory.sgb.enabled);
+
+        let options = PowerOnOptions { sgb: true, colorize: false };
+        let Ok(()) = recorded_movie().apply_start(&mut memory, &rom, options) else { panic!("Power-on start failed") };
+        assert!(!memory.cgb.enabled && memory.sgb.enabled);
+    }
+
+    #[test]
+    fn test_header() {
+        let bytes = recorded_movie().to_bytes();
+        assert_eq!(&bytes[..4], b"LMBM");
+        assert_eq!(&bytes[bytes.len() - 4..], &[0b0001, 0b0010, 0b0100, 0b1000]);
+    }
+}
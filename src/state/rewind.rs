@@ -0,0 +1,271 @@
+use std::collections::VecDeque;
+use crate::cpu::memory::Memory;
+use crate::state::savestate::{restore, StateReader, StateWriter, Stateful};
+use crate::utils::log::log;
+use crate::utils::types::Byte;
+
+pub const DEFAULT_REWIND_BUDGET: usize = 32 * 1024 * 1024;
+pub const DEFAULT_REWIND_INTERVAL: u32 = 4;
+
+// Only the most recent snapshot is kept in full. Every older snapshot is stored as the XOR of itself with the next
+// (more recent) one, which is mostly zeros since few bytes change between two snapshots. Those deltas are then
+// run-length encoded as a sequence of:
+//  - Number of unchanged (zero) bytes, as a LEB128 varint
+//  - Number of changed bytes, as a LEB128 varint
+//  - The changed bytes (XOR-ed values)
+
+pub struct RewindBuffer {
+    /// Deltas to go back in time, the oldest at the front, with the frame of the snapshot each one restores
+    deltas: VecDeque<(usize, Vec<Byte>)>,
+    /// Most recent snapshot, in full
+    latest: Vec<Byte>,
+    latest_frame: usize,
+    /// Frames emulated since the creation of the buffer, minus the rewound ones
+    frame: usize,
+    /// Memory budget in bytes, for the latest snapshot and all the deltas
+    budget: usize,
+    /// Number of frames between two snapshots
+    interval: u32,
+    frame_counter: u32,
+    deltas_size: usize,
+}
+
+impl RewindBuffer {
+    pub fn new(budget: usize, interval: u32) -> RewindBuffer {
+        log!("MEMORY", format!("Creating rewind buffer of {budget} bytes, with a snapshot every {interval} frames"));
+
+        return RewindBuffer {
+            deltas: VecDeque::new(),
+            latest: Vec::new(),
+            latest_frame: 0,
+            frame: 0,
+            budget,
+            interval: interval.max(1),
+            frame_counter: 0,
+            deltas_size: 0,
+        }
+    }
+
+    fn frame_elapsed(&mut self) -> bool {
+        self.frame_counter += 1;
+        if self.frame_counter < self.interval { return false; }
+
+        self.frame_counter = 0;
+        return true;
+    }
+
+    /// Must be called once per emulated frame, takes a snapshot every `interval` frames
+    pub fn capture(&mut self, memory: &Memory) {
+        self.frame += 1;
+        if !self.frame_elapsed() { return; }
+
+        let mut writer = StateWriter::new();
+        memory.save_state(&mut writer);
+        let snapshot = writer.into_bytes();
+
+        if self.latest.len() == snapshot.len() {
+            let delta = compress_delta(&self.latest, &snapshot);
+            self.deltas_size += delta.len();
+            self.deltas.push_back((self.latest_frame, delta));
+        } else {
+            // The machine layout changed, older snapshots can't be restored anymore
+            self.deltas.clear();
+            self.deltas_size = 0;
+        }
+        self.latest = snapshot;
+        self.latest_frame = self.frame;
+
+        while self.deltas_size + self.latest.len() > self.budget {
+            let Some((_, oldest)) = self.deltas.pop_front() else { break };
+            self.deltas_size -= oldest.len();
+        }
+    }
+
+    /// Must be called once per frame while rewinding, steps back one snapshot every `interval` frames, starting with
+    /// the latest one if frames were emulated since. Returns false once the oldest snapshot has been reached.
+    pub fn rewind(&mut self, memory: &mut Memory) -> bool {
+        if self.latest.is_empty() { return false; }
+        if !self.frame_elapsed() { return true; }
+
+        if self.frame == self.latest_frame {
+            let Some((frame, delta)) = self.deltas.pop_back() else { return false };
+            self.deltas_size -= delta.len();
+            apply_delta(&mut self.latest, &delta);
+            self.latest_frame = frame;
+        }
+        self.frame = self.latest_frame;
+
+        if restore(memory, &mut StateReader::new(&self.latest)).is_err() {
+            log!(Warn, "MEMORY", "Couldn't restore snapshot, dropping history");
+            self.deltas.clear();
+            self.deltas_size = 0;
+            return false;
+        }
+
+        return true;
+    }
+
+    /// Number of the frame the machine is at, the emulated frames being counted from 0 at the creation of the buffer
+    pub fn frame(&self) -> usize {
+        return self.frame;
+    }
+}
+
+fn write_varint(output: &mut Vec<Byte>, mut value: usize) {
+    loop {
+        #[allow(clippy::cast_possible_truncation)]
+        let byte = (value & 0x7F) as Byte;
+        value >>= 7;
+
+        if value == 0 {
+            output.push(byte);
+            return;
+        }
+        output.push(byte | 0x80);
+    }
+}
+
+fn read_varint(input: &[Byte], position: &mut usize) -> usize {
+    let mut value = 0;
+    let mut shift = 0;
+
+    while let Some(byte) = input.get(*position) {
+        *position += 1;
+        value |= usize::from(byte & 0x7F) << shift;
+        shift += 7;
+
+        if byte & 0x80 == 0 { break; }
+    }
+
+    return value;
+}
+
+fn compress_delta(previous: &[Byte], current: &[Byte]) -> Vec<Byte> {
+    debug_assert!(previous.len() == current.len());
+
+    let mut output = Vec::new();
+    let mut index = 0;
+
+    while index < current.len() {
+        let zeros = previous[index..].iter().zip(&current[index..]).take_while(|(old, new)| old == new).count();
+        index += zeros;
+        let literals = previous[index..].iter().zip(&current[index..]).take_while(|(old, new)| old != new).count();
+
+        write_varint(&mut output, zeros);
+        write_varint(&mut output, literals);
+        output.extend(previous[index..index + literals].iter().zip(&current[index..index + literals]).map(|(old, new)| old ^ new));
+        index += literals;
+    }
+
+    return output;
+}
+
+fn apply_delta(snapshot: &mut [Byte], delta: &[Byte]) {
+    let mut index = 0;
+    let mut position = 0;
+
+    while position < delta.len() {
+        index += read_varint(delta, &mut position);
+        let literals = read_varint(delta, &mut position);
+
+        for (byte, xor) in snapshot[index..index + literals].iter_mut().zip(&delta[position..position + literals]) {
+            *byte ^= xor;
+        }
+        index += literals;
+        position += literals;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cpu::memory::Memory;
+    use super::{apply_delta, compress_delta, RewindBuffer};
+
+    #[test]
+    fn test_delta_round_trip() {
+        let previous = vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9];
+        let mut current = previous.clone();
+        current[0] = 0xFF;
+        current[5] = 0xAA;
+        current[6] = 0xBB;
+
+        let delta = compress_delta(&previous, &current);
+        apply_delta(&mut current, &delta);
+        assert_eq!(current, previous);
+
+        // Identical snapshots only cost the two varints
+        assert_eq!(compress_delta(&previous, &previous).len(), 2);
+    }
+
+    #[test]
+    fn test_rewind() {
+        let mut memory = Memory::new(16);
+        let mut rewind = RewindBuffer::new(usize::MAX, 1);
+
+        for cycles in 0..5 {
+            memory.cycles = cycles;
+            rewind.capture(&memory);
+        }
+
+        assert_eq!(rewind.frame(), 5);
+        assert!(rewind.rewind(&mut memory));
+        assert_eq!((memory.cycles, rewind.frame()), (3, 4));
+        assert!(rewind.rewind(&mut memory));
+        assert_eq!((memory.cycles, rewind.frame()), (2, 3));
+
+        // Emulated frames count from the rewound one
+        rewind.capture(&memory);
+        assert_eq!(rewind.frame(), 4);
+    }
+
+    #[test]
+    fn test_rewind_to_latest() {
+        let mut memory = Memory::new(16);
+        let mut rewind = RewindBuffer::new(usize::MAX, 2);
+
+        // Snapshots of frames 2 and 4, then one more frame emulated
+        for cycles in 0..5 {
+            memory.cycles = cycles;
+            rewind.capture(&memory);
+        }
+
+        assert!(rewind.rewind(&mut memory));
+        assert_eq!((memory.cycles, rewind.frame()), (3, 4));
+        assert!(rewind.rewind(&mut memory));
+        assert_eq!(memory.cycles, 3);
+        assert!(rewind.rewind(&mut memory));
+        assert_eq!((memory.cycles, rewind.frame()), (1, 2));
+    }
+
+    #[test]
+    fn test_failed_restore() {
+        let mut memory = Memory::new(16);
+        let mut rewind = RewindBuffer::new(usize::MAX, 1);
+        for cycles in 0..3 {
+            memory.cycles = cycles;
+            rewind.capture(&memory);
+        }
+
+        // Memory size read after the registers and cycles: the machine is only replaced once the whole snapshot is read
+        rewind.latest[20] ^= 0xFF;
+        memory.cycles = 42;
+        assert!(!rewind.rewind(&mut memory));
+        assert_eq!(memory.cycles, 42);
+    }
+
+    #[test]
+    fn test_budget() {
+        let mut memory = Memory::new(16);
+        let mut rewind = RewindBuffer::new(0, 1);
+
+        for cycles in 0..5 {
+            memory.cycles = cycles;
+            rewind.capture(&memory);
+        }
+
+        // Only the latest snapshot is kept when the budget is exceeded
+        assert!(rewind.deltas.is_empty());
+        assert_eq!(rewind.deltas_size, 0);
+        assert!(!rewind.rewind(&mut memory));
+    }
+}
@@ -0,0 +1,315 @@
+use std::path::{Path, PathBuf};
+use crate::cpu::cartridge::Cartridge;
+use crate::cpu::memory::Memory;
+use crate::utils::log::log;
+use crate::utils::types::{Byte, WideValue};
+
+//  #############################
+//  #          Format           #
+//  #############################
+
+// Every multi-byte field is stored little-endian.
+//
+//  Offset  Size  Field
+//  0x00    4     Magic, always "LMBY"
+//  0x04    2     Format version (see STATE_VERSION)
+//  0x06    2     Global checksum of the ROM the state was taken with (see rom_checksum)
+//  0x08    ...   Machine sections, in the following order
+//
+//  Registers   12 bytes    AF, BC, DE, HL, SP, PC
+//  Cycles      8 bytes     Number of clock ticks elapsed since power-on
+//  Memory      4 + n       Memory size, followed by the n bytes of memory
+//  CGB         5 + n       CGB mode, compatibility mode, double speed, VRAM and WRAM banks, followed by the 2 VRAM and
+//              8 WRAM banks (16 KiB + 32 KiB, the copies of the mapped banks being stale), then the background and object
+//              palette RAM, each as its index register followed by the 64 bytes of colors, then the VRAM DMA
+//              source and destination (2 + 2), remaining blocks and whether an HBlank transfer is active (1 + 1)
+//  SGB         4 + n       SGB mode, screen mask, players and selected player, then the 16 colors of palettes 0-3
+//              (32), the palettes of the 360 cells, the 512 system palettes (4 KiB), the 45 attribute files (4050),
+//              the 256 border tiles (8 KiB) and the border map and palettes 4-7 (0x880)
+//  Interrupts  5 bytes     IME, instructions left before EI sets IME, halted, HALT bug pending, stopped
+//  Timer       3 bytes     Clock counter (DIV being its high byte) and TAC
+//  LCD         5 bytes     Clock ticks since the start of the frame (4) and STAT interrupt line
+//  APU         78 bytes    For each of the 4 channels: enabled, waveform position, volume, envelope timer (4),
+//              length (2), noise LFSR (2) and clock ticks until the next waveform step (4), then the power, the
+//              sweep enabled and timer and the frame sequencer step (4), the sweep shadow frequency (2), the clock ticks of the
+//              frame sequencer (8) and of the sample clock (8), and the high-pass filter capacitors (4 + 4)
+//  Cartridge   1 + ...     Whether there is a cartridge, then its RAM enable, BANK1, BANK2, banking mode and MBC5
+//              high ROM bank registers (5), followed by the RAM size and the n bytes of RAM (4 + n). The ROM isn't saved.
+//
+// The PPU drawing line by line doesn't exist yet: its state will be appended as a new section, with a version bump,
+// once it is emulated.
+
+const STATE_MAGIC: [Byte; 4] = *b"LMBY";
+const STATE_VERSION: u16 = 4;
+
+// Offsets of the cartridge header global checksum, which are excluded from the ROM checksum
+const GLOBAL_CHECKSUM_START: usize = 0x14E;
+const GLOBAL_CHECKSUM_END: usize = 0x14F;
+
+#[derive(Debug)]
+pub enum StateError {
+    Io(std::io::Error),
+    BadMagic,
+    UnsupportedVersion(u16),
+    ChecksumMismatch { expected: WideValue, found: WideValue },
+    Truncated,
+    SizeMismatch { expected: usize, found: usize },
+    /// Taken with a cartridge inserted while there is none, or the other way around
+    CartridgeMismatch,
+    /// The ROM to power on with has a cartridge type which isn't emulated
+    UnsupportedCartridge(String),
+}
+
+impl std::fmt::Display for StateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StateError::Io(error) => write!(f, "I/O error: {error}"),
+            StateError::BadMagic => write!(f, "not a {} save state", String::from_utf8_lossy(&STATE_MAGIC)),
+            StateError::UnsupportedVersion(version) => write!(f, "unsupported save state version {version} (current is {STATE_VERSION})"),
+            StateError::ChecksumMismatch { expected, found } => write!(f, "save state was taken with another ROM (checksum {found:#06X}, expected {expected:#06X})"),
+            StateError::Truncated => write!(f, "save state is truncated"),
+            StateError::SizeMismatch { expected, found } => write!(f, "save state memory size is {found} bytes, expected {expected}"),
+            StateError::CartridgeMismatch => write!(f, "save state cartridge doesn't match the inserted one"),
+            StateError::UnsupportedCartridge(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl From<std::io::Error> for StateError {
+    fn from(error: std::io::Error) -> Self {
+        return StateError::Io(error);
+    }
+}
+
+//  #############################
+//  #      Writer / Reader      #
+//  #############################
+
+pub struct StateWriter {
+    buffer: Vec<Byte>,
+}
+
+impl StateWriter {
+    pub fn new() -> StateWriter {
+        return StateWriter { buffer: Vec::new() };
+    }
+
+    pub fn write_wide(&mut self, value: WideValue) { self.buffer.extend_from_slice(&value.to_le_bytes()); }
+    pub fn write_u32(&mut self, value: u32) { self.buffer.extend_from_slice(&value.to_le_bytes()); }
+    pub fn write_u64(&mut self, value: u64) { self.buffer.extend_from_slice(&value.to_le_bytes()); }
+    pub fn write_bytes(&mut self, values: &[Byte]) { self.buffer.extend_from_slice(values); }
+
+    pub fn into_bytes(self) -> Vec<Byte> {
+        return self.buffer;
+    }
+}
+
+pub struct StateReader<'a> {
+    data: &'a [Byte],
+    position: usize,
+}
+
+impl<'a> StateReader<'a> {
+    pub fn new(data: &'a [Byte]) -> StateReader<'a> {
+        return StateReader { data, position: 0 };
+    }
+
+    pub fn read_bytes(&mut self, count: usize) -> Result<&'a [Byte], StateError> {
+        let end = self.position.checked_add(count).ok_or(StateError::Truncated)?;
+        let bytes = self.data.get(self.position..end).ok_or(StateError::Truncated)?;
+        self.position = end;
+
+        return Ok(bytes);
+    }
+
+    pub fn read_wide(&mut self) -> Result<WideValue, StateError> {
+        let bytes = self.read_bytes(2)?;
+        return Ok(WideValue::from_le_bytes([bytes[0], bytes[1]]));
+    }
+
+    pub fn read_u32(&mut self) -> Result<u32, StateError> {
+        let mut bytes = [0; 4];
+        bytes.copy_from_slice(self.read_bytes(4)?);
+        return Ok(u32::from_le_bytes(bytes));
+    }
+
+    pub fn read_u64(&mut self) -> Result<u64, StateError> {
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(self.read_bytes(8)?);
+        return Ok(u64::from_le_bytes(bytes));
+    }
+}
+
+/// Implemented by every component which is part of the machine state
+pub trait Stateful {
+    fn save_state(&self, writer: &mut StateWriter);
+    fn load_state(&mut self, reader: &mut StateReader) -> Result<(), StateError>;
+}
+
+//  #############################
+//  #         Save/Load         #
+//  #############################
+
+/// Wrapping sum of every byte of the ROM, except the two bytes of the header global checksum
+pub fn rom_checksum(rom: &[Byte]) -> WideValue {
+    return rom.iter()
+        .enumerate()
+        .filter(|(index, _)| !(GLOBAL_CHECKSUM_START..=GLOBAL_CHECKSUM_END).contains(index))
+        .fold(0, |checksum: WideValue, (_, byte)| checksum.wrapping_add(WideValue::from(*byte)));
+}
+
+pub fn save(memory: &Memory, rom_checksum: WideValue) -> Vec<Byte> {
+    let mut writer = StateWriter::new();
+
+    writer.write_bytes(&STATE_MAGIC);
+    writer.write_wide(STATE_VERSION);
+    writer.write_wide(rom_checksum);
+    memory.save_state(&mut writer);
+
+    return writer.into_bytes();
+}
+
+pub fn load(memory: &mut Memory, rom_checksum: WideValue, data: &[Byte]) -> Result<(), StateError> {
+    let mut reader = StateReader::new(data);
+
+    if reader.read_bytes(STATE_MAGIC.len())? != STATE_MAGIC { return Err(StateError::BadMagic); }
+
+    let version = reader.read_wide()?;
+    // Older versions must be migrated here, before reading the sections
+    if version != STATE_VERSION { return Err(StateError::UnsupportedVersion(version)); }
+
+    let checksum = reader.read_wide()?;
+    if checksum != rom_checksum { return Err(StateError::ChecksumMismatch { expected: rom_checksum, found: checksum }); }
+
+    return restore(memory, &mut reader);
+}
+
+/// Reads the machine sections into a fresh machine with the same cartridge, which replaces the running one once the
+/// whole state is read, so that a corrupted state leaves it untouched
+pub fn restore(memory: &mut Memory, reader: &mut StateReader) -> Result<(), StateError> {
+    let mut loaded = Memory::new(memory.size);
+    loaded.cartridge = memory.cartridge.as_ref().map(Cartridge::reinserted);
+    loaded.load_state(reader)?;
+    // Debugging tools, inputs and outputs of the host, which aren't part of the state
+    loaded.watchpoints = std::mem::take(&mut memory.watchpoints);
+    loaded.coverage = std::mem::take(&mut memory.coverage);
+    loaded.serial_output = std::mem::take(&mut memory.serial_output);
+    loaded.joypad = memory.joypad;
+    loaded.flat = memory.flat;
+    loaded.stub_ly = memory.stub_ly;
+    loaded.apu.samples = std::mem::take(&mut memory.apu.samples);
+    *memory = loaded;
+
+    return Ok(());
+}
+
+/// File of the slot next to the ROM, named after it: "game.ss1" for the slot 1 of "game.gb"
+pub fn slot_path(rom: &Path, slot: u8) -> PathBuf {
+    return rom.with_extension(format!("ss{slot}"));
+}
+
+pub fn save_to_slot(memory: &Memory, rom_checksum: WideValue, rom: &Path, slot: u8) -> Result<(), StateError> {
+    let path = slot_path(rom, slot);
+
+    log!("MEMORY", format!("Saving state to {}", path.display()));
+
+    std::fs::write(path, save(memory, rom_checksum))?;
+    return Ok(());
+}
+
+pub fn load_from_slot(memory: &mut Memory, rom_checksum: WideValue, rom: &Path, slot: u8) -> Result<(), StateError> {
+    let path = slot_path(rom, slot);
+
+    log!("MEMORY", format!("Loading state from {}", path.display()));
+
+    let data = std::fs::read(path)?;
+    return load(memory, rom_checksum, &data);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+    use crate::cpu::memory::{Memory, PowerOnOptions};
+    use super::{load, rom_checksum, save, slot_path, StateError};
+
+    #[test]
+    fn test_round_trip() {
+        let mut memory = Memory::new(16);
+        memory.write_far_addr(0x0A, 0x42);
+        memory.registers.set_af(0x12F0);
+        memory.registers.SP = 0xFFFE;
+        memory.registers.PC = 0x0150;
+        memory.cycles = 1234;
+
+        let data = save(&memory, 0xBEEF);
+
+        let mut restored = Memory::new(16);
+        restored.serial_output = b"Passed".to_vec();
+        restored.joypad = 0x81;
+        restored.flat = true;
+        assert!(load(&mut restored, 0xBEEF, &data).is_ok());
+        assert_eq!((restored.serial_output.as_slice(), restored.joypad, restored.flat), (&b"Passed"[..], 0x81, true));
+        assert_eq!(restored.read_far_addr(0x0A), 0x42);
+        assert_eq!(restored.registers.get_af(), 0x12F0);
+        assert_eq!(restored.registers.SP, 0xFFFE);
+        assert_eq!(restored.registers.PC, 0x0150);
+        assert_eq!(restored.cycles, 1234);
+    }
+
+    #[test]
+    fn test_cartridge_round_trip() {
+        // MBC1 with 8 KiB of RAM, 4 banks numbered in their first byte
+        let mut rom = vec![0; 4 * 0x4000];
+        for bank in 0..4 { rom[bank * 0x4000] = u8::try_from(bank).unwrap_or(0xFF); }
+        rom[0x0147] = 0x03;
+        rom[0x0149] = 0x02;
+
+        let Ok(mut memory) = Memory::power_on(&rom, PowerOnOptions::default()) else { panic!("MBC1 was rejected") };
+        memory.write_far_addr(0x0000, 0x0A);
+        memory.write_far_addr(0x2000, 0x03);
+        memory.write_far_addr(0xA000, 0x42);
+        let data = save(&memory, rom_checksum(&rom));
+
+        let Ok(mut restored) = Memory::power_on(&rom, PowerOnOptions::default()) else { panic!("MBC1 was rejected") };
+        assert!(load(&mut restored, rom_checksum(&rom), &data).is_ok());
+        assert_eq!((restored.peek(0x4000), restored.peek(0xA000)), (0x03, 0x42));
+
+        // Without a cartridge
+        let mut flat = Memory::new(0x10000);
+        assert!(matches!(load(&mut flat, rom_checksum(&rom), &data), Err(StateError::CartridgeMismatch)));
+    }
+
+    #[test]
+    fn test_rejections() {
+        let memory = Memory::new(16);
+        let data = save(&memory, 0xBEEF);
+
+        let mut restored = Memory::new(16);
+        assert!(matches!(load(&mut restored, 0xCAFE, &data), Err(StateError::ChecksumMismatch { .. })));
+        assert!(matches!(load(&mut restored, 0xBEEF, &data[..data.len() - 1]), Err(StateError::Truncated)));
+        assert!(matches!(load(&mut restored, 0xBEEF, b"NOPE"), Err(StateError::BadMagic)));
+
+        let mut future = data.clone();
+        future[4] = 0xFF;
+        assert!(matches!(load(&mut restored, 0xBEEF, &future), Err(StateError::UnsupportedVersion(_))));
+
+        let mut smaller = Memory::new(8);
+        assert!(matches!(load(&mut smaller, 0xBEEF, &data), Err(StateError::SizeMismatch { .. })));
+    }
+
+    #[test]
+    fn test_slot_path() {
+        assert_eq!(slot_path(Path::new("roms/game.gb"), 2), PathBuf::from("roms/game.ss2"));
+        assert_eq!(slot_path(Path::new("game"), 0), PathBuf::from("game.ss0"));
+    }
+
+    #[test]
+    fn test_rom_checksum() {
+        let mut rom = vec![1; 0x150];
+        assert_eq!(rom_checksum(&rom), 0x150 - 2);
+        rom[0x14E] = 0xFF;
+        assert_eq!(rom_checksum(&rom), 0x150 - 2);
+        rom[0x100] = 3;
+        assert_eq!(rom_checksum(&rom), 0x150);
+    }
+}
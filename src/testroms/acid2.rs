@@ -0,0 +1,158 @@
+use std::path::{Path, PathBuf};
+use crate::cpu::execution::TICKS_PER_FRAME;
+use crate::cpu::memory::Memory;
+use crate::gui::capture::encode_png;
+use crate::gui::screen::{Framebuffer, Image, Rgb, SCREEN_HEIGHT, SCREEN_WIDTH};
+use crate::gui::vram::render_screen;
+use crate::testroms::mooneye::Model;
+use crate::testroms::png::decode_png;
+use crate::testroms::{find_roms, rom_name, run_until};
+use crate::utils::types::Value;
+
+/// Frames after which a ROM waiting for the breakpoint fails
+const TIMEOUT_FRAMES: u64 = 600;
+/// LD B, B, executed by the acid2 ROMs once the screen is drawn
+const BREAKPOINT_OPCODE: Value = 0x40;
+const MISMATCH_COLOR: Rgb = (0xFF, 0x00, 0x00);
+
+/// When the screen is compared
+#[derive(Clone, Copy, Debug)]
+pub enum Trigger {
+    /// After this many frames
+    Frames(u64),
+    /// Once PC reaches LD B, B
+    Breakpoint,
+}
+
+/// ROMs of a suite compared against the PNG of the same name, e.g. `acid2/dmg-acid2.gb` against `acid2/dmg-acid2.png`
+pub struct Case {
+    pub path: &'static str,
+    pub model: Model,
+    pub trigger: Trigger,
+}
+
+pub const DMG_ACID2: Case = Case { path: "acid2/dmg-acid2", model: Model::Dmg, trigger: Trigger::Breakpoint };
+pub const CGB_ACID2: Case = Case { path: "acid2/cgb-acid2", model: Model::Cgb, trigger: Trigger::Breakpoint };
+
+/// Runs the ROM headlessly until the trigger, then renders the screen
+fn screenshot(rom: &[u8], model: Model, trigger: Trigger) -> Result<Image, String> {
+    let mut memory = Memory::from_rom(rom)?;
+    model.boot_registers(&mut memory);
+
+    let reached = match trigger {
+        Trigger::Frames(frames) => run_until(&mut memory, frames * TICKS_PER_FRAME, 0, |_| false).map(|_| true)?,
+        Trigger::Breakpoint => run_until(&mut memory, TIMEOUT_FRAMES * TICKS_PER_FRAME, 0, |memory: &Memory| memory.peek(memory.registers.PC) == BREAKPOINT_OPCODE)?,
+    };
+    if !reached { return Err(format!("Timeout: LD B, B wasn't reached within {TIMEOUT_FRAMES} frames")); }
+
+    let mut framebuffer = Framebuffer::new();
+    render_screen(&memory, &mut framebuffer, false);
+    return Ok(Image { width: SCREEN_WIDTH, height: SCREEN_HEIGHT, pixels: framebuffer.pixels.to_vec() });
+}
+
+/// Number of pixels which differ, and an image of the expected one with the differences in red
+pub fn diff(expected: &Image, actual: &Image) -> (usize, Image) {
+    let mut image = Image::new(expected.width, expected.height);
+    let mut mismatches = 0;
+
+    for y in 0..expected.height {
+        for x in 0..expected.width {
+            let (red, green, blue) = expected.pixel(x, y);
+            if actual.pixel(x, y) == (red, green, blue) {
+                // Faded, so that the differences stand out
+                image.set_pixel(x, y, (red / 4 + 0xC0, green / 4 + 0xC0, blue / 4 + 0xC0));
+            } else {
+                mismatches += 1;
+                image.set_pixel(x, y, MISMATCH_COLOR);
+            }
+        }
+    }
+
+    return (mismatches, image);
+}
+
+fn save_png(path: &Path, image: &Image) -> Result<(), String> {
+    return std::fs::write(path, encode_png(image.width, image.height, &image.pixels)).map_err(|error| format!("Couldn't write {}: {error}", path.display()));
+}
+
+/// Compares the screen of the ROM against its reference. On mismatch, the screen and the differences are written next to
+/// the ROM as `NAME-actual.png` and `NAME-diff.png`.
+pub fn run_rom(rom: &Path, model: Model, trigger: Trigger) -> Result<(), String> {
+    let reference = rom.with_extension("png");
+    let data = std::fs::read(&reference).map_err(|error| format!("Couldn't read reference {}: {error}", reference.display()))?;
+    let expected = decode_png(&data).map_err(|error| format!("Invalid reference {}: {error}", reference.display()))?;
+    if (expected.width, expected.height) != (SCREEN_WIDTH, SCREEN_HEIGHT) {
+        return Err(format!("Reference is {}x{}, expected {SCREEN_WIDTH}x{SCREEN_HEIGHT}", expected.width, expected.height));
+    }
+
+    let data = std::fs::read(rom).map_err(|error| format!("Couldn't read ROM: {error}"))?;
+    let actual = screenshot(&data, model, trigger)?;
+    let (mismatches, image) = diff(&expected, &actual);
+    if mismatches == 0 { return Ok(()); }
+
+    let output = |suffix: &str| -> PathBuf {
+        let stem = rom.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+        return rom.with_file_name(format!("{stem}-{suffix}.png"));
+    };
+    save_png(&output("actual"), &actual)?;
+    save_png(&output("diff"), &image)?;
+    return Err(format!("{mismatches} pixels differ, see {}", output("diff").display()));
+}
+
+fn run_case(case: &Case) {
+    let roms = find_roms(case.path);
+    let failures: Vec<String> = roms.iter()
+        .filter_map(|rom| run_rom(rom, case.model, case.trigger).err().map(|error| format!("{}: {error}", rom_name(rom))))
+        .collect();
+
+    for failure in &failures { println!("{failure}"); }
+    assert!(failures.is_empty(), "{} of {} screenshots didn't match in {}", failures.len(), roms.len(), case.path);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::gui::screen::{DMG_SHADES, Image};
+    use crate::testroms::mooneye::Model;
+    use super::{CGB_ACID2, diff, DMG_ACID2, MISMATCH_COLOR, run_case, screenshot, Trigger};
+
+    // The reference screenshots are the output of the PPU, drawing line by line with the registers changed by the
+    // STAT interrupts. `render_screen` draws the whole frame at once from the current VRAM and registers instead.
+    // Run with `cargo test -- --ignored` to see where it stands.
+    #[test]
+    #[ignore = "needs a PPU drawing line by line"]
+    fn test_dmg_acid2() {
+        run_case(&DMG_ACID2);
+    }
+
+    #[test]
+    #[ignore = "needs a PPU drawing line by line"]
+    fn test_cgb_acid2() {
+        run_case(&CGB_ACID2);
+    }
+
+    #[test]
+    fn test_diff() {
+        let expected = Image::new(2, 1);
+        let mut actual = Image::new(2, 1);
+        actual.set_pixel(1, 0, (0xFF, 0xFF, 0xFF));
+
+        let (mismatches, image) = diff(&expected, &actual);
+        assert_eq!(mismatches, 1);
+        assert_eq!(image.pixel(0, 0), (0xC0, 0xC0, 0xC0));
+        assert_eq!(image.pixel(1, 0), MISMATCH_COLOR);
+    }
+
+    #[test]
+    fn test_triggers() {
+        // JR -2 at the entry point, with the LCD off
+        let mut rom = vec![0; 0x8000];
+        rom[0x0100..0x0102].copy_from_slice(&[0x18, 0xFE]);
+
+        let Ok(image) = screenshot(&rom, Model::Dmg, Trigger::Frames(1)) else { panic!("Frames trigger failed") };
+        assert_eq!(image.pixel(80, 72), DMG_SHADES[0]);
+        assert!(screenshot(&rom, Model::Dmg, Trigger::Breakpoint).is_err_and(|error| error.starts_with("Timeout")));
+
+        rom[0x0100] = 0x40;
+        assert!(screenshot(&rom, Model::Dmg, Trigger::Breakpoint).is_ok());
+    }
+}
@@ -0,0 +1,176 @@
+use std::path::Path;
+use crate::cpu::execution::{CPU_FREQUENCY, TICKS_PER_FRAME};
+use crate::cpu::memory::Memory;
+use crate::testroms::{find_roms, rom_name, run_until};
+use crate::utils::types::FarAddress;
+
+/// Emulated time after which a ROM which didn't print its result fails, `cpu_instrs` takes about a minute
+const TIMEOUT_SECONDS: u64 = 120;
+
+// Results are also written to the cartridge RAM, for the ROMs which don't print them through the serial port
+const STATUS_ADDR: FarAddress = 0xA000;
+const SIGNATURE_ADDR: FarAddress = 0xA001;
+const SIGNATURE: [u8; 3] = [0xDE, 0xB0, 0x61];
+const TEXT_ADDR: FarAddress = 0xA004;
+/// Status while the test is running, any other value being the result code (0 for success)
+const STATUS_RUNNING: u8 = 0x80;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    Passed,
+    Failed,
+    Timeout,
+    Crashed(String),
+}
+
+impl std::fmt::Display for Outcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        return match self {
+            Outcome::Passed => write!(f, "Passed"),
+            Outcome::Failed => write!(f, "Failed"),
+            Outcome::Timeout => write!(f, "Timeout"),
+            Outcome::Crashed(message) => write!(f, "{message}"),
+        }
+    }
+}
+
+fn has_signature(memory: &Memory) -> bool {
+    return (0..).zip(SIGNATURE).all(|(offset, byte)| memory.peek(SIGNATURE_ADDR + offset) == byte);
+}
+
+/// Text printed through the serial port, else written to the cartridge RAM
+pub fn output(memory: &Memory) -> String {
+    if !memory.serial_output.is_empty() || !has_signature(memory) {
+        return String::from_utf8_lossy(&memory.serial_output).into_owned();
+    }
+
+    let text: Vec<u8> = (TEXT_ADDR..STATUS_ADDR + 0x1000).map(|addr| memory.peek(addr)).take_while(|byte| *byte != 0).collect();
+    return String::from_utf8_lossy(&text).into_owned();
+}
+
+fn outcome(memory: &Memory) -> Option<Outcome> {
+    let text = output(memory);
+    if text.contains("Passed") { return Some(Outcome::Passed); }
+    if text.contains("Failed") { return Some(Outcome::Failed); }
+
+    let status = memory.peek(STATUS_ADDR);
+    if !has_signature(memory) || status == STATUS_RUNNING { return None; }
+    return Some(if status == 0 { Outcome::Passed } else { Outcome::Failed });
+}
+
+/// "NN:ok" or "NN:CODE" results printed by the ROMs combining several sub-tests, e.g. `cpu_instrs`
+pub fn sub_tests(output: &str) -> Vec<(String, bool)> {
+    return output.split_whitespace()
+        .filter_map(|word| word.split_once(':'))
+        .filter(|(number, result)| number.len() == 2 && number.chars().all(|c| c.is_ascii_digit()) && !result.is_empty())
+        .map(|(number, result)| (number.to_string(), result == "ok"))
+        .collect();
+}
+
+pub struct RomReport {
+    pub name: String,
+    pub outcome: Outcome,
+    pub output: String,
+}
+
+impl std::fmt::Display for RomReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:<50} {}", self.name, self.outcome)?;
+
+        let failed: Vec<String> = sub_tests(&self.output).into_iter().filter(|(_, ok)| !ok).map(|(number, _)| number).collect();
+        if !failed.is_empty() { write!(f, " (sub-tests {})", failed.join(", "))?; }
+        return Ok(());
+    }
+}
+
+pub fn run_rom(path: &Path) -> RomReport {
+    let name = rom_name(path);
+    let rom = match std::fs::read(path) {
+        Ok(rom) => rom,
+        Err(error) => return RomReport { name, outcome: Outcome::Crashed(format!("Couldn't read ROM: {error}")), output: String::new() },
+    };
+
+    let mut memory = match Memory::from_rom(&rom) {
+        Ok(memory) => memory,
+        Err(error) => return RomReport { name, outcome: Outcome::Crashed(error), output: String::new() },
+    };
+    let mut result = None;
+    let outcome = match run_until(&mut memory, TIMEOUT_SECONDS * CPU_FREQUENCY, TICKS_PER_FRAME, |memory| { result = outcome(memory); result.is_some() }) {
+        Ok(_) => result.unwrap_or(Outcome::Timeout),
+        Err(message) => Outcome::Crashed(message),
+    };
+
+    return RomReport { name, outcome, output: output(&memory) };
+}
+
+/// Runs every ROM of the suite, printing a line per ROM, and fails if any of them didn't pass
+fn run_suite(path: &str) {
+    let reports: Vec<RomReport> = find_roms(path).iter().map(|rom| run_rom(rom)).collect();
+    for report in &reports { println!("{report}"); }
+
+    let failed: Vec<&RomReport> = reports.iter().filter(|report| report.outcome != Outcome::Passed).collect();
+    for report in &failed { println!("--- {} ---\n{}", report.name, report.output); }
+    assert!(failed.is_empty(), "{} of {} ROMs didn't pass in {path}", failed.len(), reports.len());
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cpu::io::{SB, SC};
+    use crate::cpu::memory::Memory;
+    use super::{outcome, Outcome, output, run_suite, sub_tests};
+
+    #[test]
+    fn test_cpu_instrs() {
+        run_suite("blargg/cpu_instrs");
+    }
+
+    #[test]
+    fn test_instr_timing() {
+        run_suite("blargg/instr_timing");
+    }
+
+    // The timer is read and written at the end of the instructions, not on the M-cycle of the access
+    #[test]
+    #[ignore = "needs M-cycle accurate memory accesses"]
+    fn test_mem_timing() {
+        run_suite("blargg/mem_timing");
+    }
+
+    #[test]
+    fn test_halt_bug() {
+        run_suite("blargg/halt_bug");
+    }
+
+    #[test]
+    fn test_serial_output() {
+        let mut memory = Memory::new(0x10000);
+        for byte in b"Passed" {
+            memory.write_far_addr(SB, *byte);
+            memory.write_far_addr(SC, 0x81);
+            // Transfer done
+            assert_eq!(memory.peek(SC), 0x01);
+        }
+        // Externally clocked, never completes
+        memory.write_far_addr(SC, 0x80);
+
+        assert_eq!(output(&memory), "Passed");
+        assert_eq!(outcome(&memory), Some(Outcome::Passed));
+    }
+
+    #[test]
+    fn test_memory_output() {
+        let mut memory = Memory::new(0x10000);
+        memory.memory[0xA000..0xA00A].copy_from_slice(&[0x80, 0xDE, 0xB0, 0x61, b'h', b'a', b'l', b't', b'\n', 0]);
+        assert_eq!(outcome(&memory), None);
+
+        memory.memory[0xA000] = 0x01;
+        assert_eq!(output(&memory), "halt\n");
+        assert_eq!(outcome(&memory), Some(Outcome::Failed));
+    }
+
+    #[test]
+    fn test_sub_tests() {
+        let output = "cpu_instrs\n\n01:ok  02:04  03:ok  \n\nFailed 1 tests.\n";
+        assert_eq!(sub_tests(output), vec![(String::from("01"), true), (String::from("02"), false), (String::from("03"), true)]);
+    }
+}
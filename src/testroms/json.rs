@@ -0,0 +1,218 @@
+//! Minimal JSON reader for the test case files, the emulator doesn't depend on a JSON crate
+
+use std::collections::BTreeMap;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(BTreeMap<String, Json>),
+}
+
+impl Json {
+    pub fn parse(text: &str) -> Result<Json, String> {
+        let mut parser = Parser { bytes: text.as_bytes(), position: 0 };
+        let value = parser.value()?;
+        parser.skip_whitespace();
+        if parser.position != parser.bytes.len() { return Err(parser.error("Trailing characters")); }
+
+        return Ok(value);
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        return match self {
+            Json::Object(members) => members.get(key),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Json]> {
+        return match self {
+            Json::Array(elements) => Some(elements),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        return match self {
+            Json::String(string) => Some(string),
+            _ => None,
+        }
+    }
+
+    /// Integers only, e.g. addresses and register values
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub fn as_u64(&self) -> Option<u64> {
+        return match self {
+            Json::Number(number) if number.fract() == 0.0 && *number >= 0.0 => Some(*number as u64),
+            _ => None,
+        }
+    }
+}
+
+struct Parser<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: &str) -> String {
+        return format!("{message} at byte {}", self.position);
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.bytes.get(self.position).is_some_and(u8::is_ascii_whitespace) { self.position += 1; }
+    }
+
+    fn expect(&mut self, literal: &str, value: Json) -> Result<Json, String> {
+        if !self.bytes[self.position..].starts_with(literal.as_bytes()) { return Err(self.error("Unexpected character")); }
+        self.position += literal.len();
+        return Ok(value);
+    }
+
+    fn value(&mut self) -> Result<Json, String> {
+        self.skip_whitespace();
+
+        return match self.bytes.get(self.position) {
+            Some(b'{') => self.object(),
+            Some(b'[') => self.array(),
+            Some(b'"') => Ok(Json::String(self.string()?)),
+            Some(b't') => self.expect("true", Json::Bool(true)),
+            Some(b'f') => self.expect("false", Json::Bool(false)),
+            Some(b'n') => self.expect("null", Json::Null),
+            Some(b'-' | b'0'..=b'9') => self.number(),
+            Some(_) => Err(self.error("Unexpected character")),
+            None => Err(self.error("Unexpected end")),
+        }
+    }
+
+    /// Elements separated by commas until `end`, the opening character being already consumed
+    fn list(&mut self, end: u8, mut element: impl FnMut(&mut Self) -> Result<(), String>) -> Result<(), String> {
+        self.skip_whitespace();
+        if self.bytes.get(self.position) == Some(&end) {
+            self.position += 1;
+            return Ok(());
+        }
+
+        loop {
+            element(self)?;
+            self.skip_whitespace();
+            match self.bytes.get(self.position) {
+                Some(b',') => self.position += 1,
+                Some(byte) if *byte == end => {
+                    self.position += 1;
+                    return Ok(());
+                }
+                _ => return Err(self.error("Expected a comma")),
+            }
+        }
+    }
+
+    fn object(&mut self) -> Result<Json, String> {
+        self.position += 1;
+        let mut members = BTreeMap::new();
+
+        self.list(b'}', |parser| {
+            parser.skip_whitespace();
+            if parser.bytes.get(parser.position) != Some(&b'"') { return Err(parser.error("Expected a key")); }
+            let key = parser.string()?;
+
+            parser.skip_whitespace();
+            if parser.bytes.get(parser.position) != Some(&b':') { return Err(parser.error("Expected a colon")); }
+            parser.position += 1;
+
+            members.insert(key, parser.value()?);
+            return Ok(());
+        })?;
+
+        return Ok(Json::Object(members));
+    }
+
+    fn array(&mut self) -> Result<Json, String> {
+        self.position += 1;
+        let mut elements = Vec::new();
+
+        self.list(b']', |parser| {
+            elements.push(parser.value()?);
+            return Ok(());
+        })?;
+
+        return Ok(Json::Array(elements));
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        self.position += 1;
+        let mut string = String::new();
+
+        loop {
+            let Some(byte) = self.bytes.get(self.position).copied() else { return Err(self.error("Unterminated string")) };
+            self.position += 1;
+
+            match byte {
+                b'"' => return Ok(string),
+                b'\\' => {
+                    let Some(escaped) = self.bytes.get(self.position).copied() else { return Err(self.error("Unterminated string")) };
+                    self.position += 1;
+                    match escaped {
+                        b'n' => string.push('\n'),
+                        b't' => string.push('\t'),
+                        b'r' => string.push('\r'),
+                        b'b' => string.push('\u{8}'),
+                        b'f' => string.push('\u{c}'),
+                        b'u' => {
+                            let digits = self.bytes.get(self.position..self.position + 4).and_then(|digits| std::str::from_utf8(digits).ok());
+                            let code = digits.and_then(|digits| u32::from_str_radix(digits, 16).ok()).ok_or_else(|| self.error("Invalid escape"))?;
+                            string.push(char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER));
+                            self.position += 4;
+                        }
+                        _ => string.push(char::from(escaped)),
+                    }
+                }
+                _ => {
+                    // Copy the whole UTF-8 sequence
+                    let start = self.position - 1;
+                    while self.bytes.get(self.position).is_some_and(|byte| byte & 0xC0 == 0x80) { self.position += 1; }
+                    string.push_str(&String::from_utf8_lossy(&self.bytes[start..self.position]));
+                }
+            }
+        }
+    }
+
+    fn number(&mut self) -> Result<Json, String> {
+        let start = self.position;
+        while self.bytes.get(self.position).is_some_and(|byte| matches!(byte, b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9')) { self.position += 1; }
+
+        let text = std::str::from_utf8(&self.bytes[start..self.position]).map_err(|_| self.error("Invalid number"))?;
+        return text.parse().map(Json::Number).map_err(|_| self.error("Invalid number"));
+    }
+}
+
+mod tests {
+    use super::Json;
+
+    #[test]
+    fn test_parse_json() {
+        let Ok(json) = Json::parse(r#" [{"name": "00 0000", "ok": true, "ram": [[49152, 0], []], "cycles": [[1, 2, "r-m"], null], "e": "\"é\"", "x": -1.5e1}] "#) else {
+            panic!("Valid JSON was rejected")
+        };
+
+        let Some([case]) = json.as_array() else { panic!("Expected one case") };
+        assert_eq!(case.get("name").and_then(Json::as_str), Some("00 0000"));
+        assert_eq!(case.get("ok"), Some(&Json::Bool(true)));
+        assert_eq!(case.get("ram").and_then(Json::as_array).and_then(|ram| ram[0].as_array()).and_then(|entry| entry[0].as_u64()), Some(49152));
+        assert_eq!(case.get("cycles").and_then(Json::as_array).map(|cycles| cycles[1].clone()), Some(Json::Null));
+        assert_eq!(case.get("e").and_then(Json::as_str), Some("\"é\""));
+        assert_eq!(case.get("x"), Some(&Json::Number(-15.0)));
+    }
+
+    #[test]
+    fn test_invalid_json() {
+        assert!(Json::parse("[1, 2").is_err());
+        assert!(Json::parse("{\"a\" 1}").is_err());
+        assert!(Json::parse("[1] 2").is_err());
+        assert!(Json::parse("tru").is_err());
+    }
+}
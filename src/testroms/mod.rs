@@ -0,0 +1,89 @@
+//! Harnesses running the public test ROM suites under `cargo test`.
+//! The ROMs aren't distributed with the emulator, they are looked up in the directory given by the `LAMEBOY_TEST_ROMS`
+//! environment variable (default: "test-roms" at the root of the repository). Missing suites are skipped.
+
+pub mod acid2;
+pub mod blargg;
+pub mod json;
+pub mod mooneye;
+pub mod png;
+pub mod sm83;
+
+use std::panic::AssertUnwindSafe;
+use std::path::{Path, PathBuf};
+use crate::cpu::execution::step;
+use crate::cpu::memory::Memory;
+
+pub const TEST_ROMS_ENV_VAR: &str = "LAMEBOY_TEST_ROMS";
+const DEFAULT_TEST_ROMS_DIR: &str = "test-roms";
+
+pub fn test_roms_dir() -> PathBuf {
+    return std::env::var_os(TEST_ROMS_ENV_VAR)
+        .map_or_else(|| Path::new(env!("CARGO_MANIFEST_DIR")).join(DEFAULT_TEST_ROMS_DIR), PathBuf::from);
+}
+
+/// Files of a suite with one of the extensions, sorted: the files under `test_roms_dir()/PATH`, or the file `PATH.EXTENSION`
+/// itself. Empty if the suite isn't there, after telling that it is skipped.
+pub fn find_files(path: &str, extensions: &[&str]) -> Vec<PathBuf> {
+    fn collect(dir: &Path, extensions: &[&str], files: &mut Vec<PathBuf>) {
+        let Ok(entries) = std::fs::read_dir(dir) else { return };
+        for path in entries.filter_map(|entry| entry.ok().map(|entry| entry.path())) {
+            if path.is_dir() {
+                collect(&path, extensions, files);
+            } else if path.extension().is_some_and(|extension| extensions.iter().any(|expected| extension == *expected)) {
+                files.push(path);
+            }
+        }
+    }
+
+    let base = test_roms_dir().join(path);
+    let mut files = Vec::new();
+    collect(&base, extensions, &mut files);
+    files.extend(extensions.iter().map(|extension| base.with_extension(extension)).filter(|file| file.is_file()));
+    files.sort();
+
+    if files.is_empty() { eprintln!("Skipping {path}: nothing found in {}", base.display()); }
+    return files;
+}
+
+/// ".gb" and ".gbc" files of a suite, see `find_files`
+pub fn find_roms(path: &str) -> Vec<PathBuf> {
+    return find_files(path, &["gb", "gbc"]);
+}
+
+/// Path of the ROM relative to the test ROMs directory, for the reports
+pub fn rom_name(rom: &Path) -> String {
+    return rom.strip_prefix(test_roms_dir()).unwrap_or(rom).display().to_string();
+}
+
+/// Emulates until `done` holds or `max_cycles` elapsed, `done` being checked every `interval` cycles (before every
+/// instruction if 0). Returns whether `done` held, or the message of the panic raised by an instruction which isn't
+/// implemented yet.
+pub fn run_until(memory: &mut Memory, max_cycles: u64, interval: u64, mut done: impl FnMut(&Memory) -> bool) -> Result<bool, String> {
+    let result = std::panic::catch_unwind(AssertUnwindSafe(|| {
+        let mut next_check = memory.cycles;
+
+        while memory.cycles < max_cycles {
+            if memory.cycles >= next_check {
+                if done(memory) { return true; }
+                next_check = memory.cycles + interval;
+            }
+
+            step(memory);
+        }
+
+        return done(memory);
+    }));
+
+    return result.map_err(|payload| crash_message(memory, payload.as_ref()));
+}
+
+/// Executes a single instruction, catching the panic of the instructions which aren't implemented yet
+pub fn try_step(memory: &mut Memory) -> Result<(), String> {
+    return std::panic::catch_unwind(AssertUnwindSafe(|| step(memory))).map_err(|payload| crash_message(memory, payload.as_ref()));
+}
+
+fn crash_message(memory: &Memory, payload: &(dyn std::any::Any + Send)) -> String {
+    let message = payload.downcast_ref::<&str>().map(ToString::to_string).or_else(|| payload.downcast_ref::<String>().cloned());
+    return format!("Crashed at {:04X}: {}", memory.registers.PC, message.unwrap_or_default());
+}
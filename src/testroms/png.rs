@@ -0,0 +1,329 @@
+//! Minimal PNG reader for the reference screenshots: non-interlaced, 8-bit (or less for grayscale and indexed) images
+
+use crate::gui::capture::PNG_SIGNATURE;
+use crate::gui::screen::{BYTES_PER_PIXEL, Image};
+use crate::utils::checksum::{adler32, crc32};
+use crate::utils::types::Byte;
+
+//  #############################
+//  #          Inflate          #
+//  #############################
+
+const LENGTH_BASES: [u16; 29] = [3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131, 163, 195, 227, 258];
+const LENGTH_EXTRA_BITS: [u8; 29] = [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0];
+const DISTANCE_BASES: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537, 2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA_BITS: [u8; 30] = [0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13, 13];
+/// Order in which the code lengths of the code length alphabet are stored
+const CODE_LENGTH_ORDER: [usize; 19] = [16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15];
+const MAX_CODE_BITS: usize = 15;
+
+struct BitReader<'a> {
+    bytes: &'a [Byte],
+    /// In bits
+    position: usize,
+}
+
+impl BitReader<'_> {
+    /// Bits are read from the least significant one of each byte
+    fn bits(&mut self, count: u8) -> Result<usize, String> {
+        let mut value = 0;
+        for index in 0..count {
+            let byte = self.bytes.get(self.position / 8).ok_or("Truncated deflate stream")?;
+            value |= usize::from((byte >> (self.position % 8)) & 1) << index;
+            self.position += 1;
+        }
+
+        return Ok(value);
+    }
+
+    fn align(&mut self) {
+        self.position = self.position.div_ceil(8) * 8;
+    }
+}
+
+/// Canonical Huffman code, as the number of codes per length and the symbols sorted by code
+struct Huffman {
+    counts: [u16; MAX_CODE_BITS + 1],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Huffman {
+        let mut counts = [0; MAX_CODE_BITS + 1];
+        for length in lengths { counts[usize::from(*length)] += 1; }
+        counts[0] = 0;
+
+        let mut symbols: Vec<(u8, u16)> = (0..).zip(lengths).filter(|(_, length)| **length != 0).map(|(symbol, length)| (*length, symbol)).collect();
+        symbols.sort_unstable();
+
+        return Huffman { counts, symbols: symbols.into_iter().map(|(_, symbol)| symbol).collect() };
+    }
+
+    fn decode(&self, reader: &mut BitReader) -> Result<u16, String> {
+        // First code and index of the current length
+        let (mut code, mut first, mut index) = (0, 0, 0);
+
+        for count in &self.counts[1..] {
+            code |= reader.bits(1)?;
+            let count = usize::from(*count);
+            if code < first + count { return Ok(self.symbols[index + code - first]); }
+
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+
+        return Err(String::from("Invalid Huffman code"));
+    }
+}
+
+fn fixed_codes() -> (Huffman, Huffman) {
+    let mut lengths = [8; 288];
+    lengths[144..256].fill(9);
+    lengths[256..280].fill(7);
+
+    return (Huffman::new(&lengths), Huffman::new(&[5; 30]));
+}
+
+fn dynamic_codes(reader: &mut BitReader) -> Result<(Huffman, Huffman), String> {
+    let literal_count = reader.bits(5)? + 257;
+    let distance_count = reader.bits(5)? + 1;
+    let code_length_count = reader.bits(4)? + 4;
+
+    let mut code_lengths = [0; 19];
+    for index in &CODE_LENGTH_ORDER[..code_length_count] {
+        #[allow(clippy::cast_possible_truncation)]
+        { code_lengths[*index] = reader.bits(3)? as u8; }
+    }
+    let code_length_code = Huffman::new(&code_lengths);
+
+    let mut lengths = Vec::with_capacity(literal_count + distance_count);
+    while lengths.len() < literal_count + distance_count {
+        let (value, repeat) = match code_length_code.decode(reader)? {
+            #[allow(clippy::cast_possible_truncation)]
+            symbol @ 0..=15 => (symbol as u8, 1),
+            16 => (*lengths.last().ok_or("Repeated code length without a previous one")?, reader.bits(2)? + 3),
+            17 => (0, reader.bits(3)? + 3),
+            _ => (0, reader.bits(7)? + 11),
+        };
+        lengths.extend(std::iter::repeat_n(value, repeat));
+    }
+    if lengths.len() != literal_count + distance_count { return Err(String::from("Code lengths overflow")); }
+
+    return Ok((Huffman::new(&lengths[..literal_count]), Huffman::new(&lengths[literal_count..])));
+}
+
+fn read_be_u32(bytes: Option<&[Byte]>) -> Option<u32> {
+    return bytes.map(|bytes| u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]));
+}
+
+/// Decompresses a zlib stream, checking its Adler-32
+fn inflate(data: &[Byte]) -> Result<Vec<Byte>, String> {
+    let [method, flags, ..] = data else { return Err(String::from("Truncated zlib stream")) };
+    if method & 0x0F != 8 || (u16::from(*method) << 8 | u16::from(*flags)) % 31 != 0 { return Err(String::from("Invalid zlib header")); }
+
+    let mut reader = BitReader { bytes: &data[2..], position: 0 };
+    let mut output = Vec::new();
+    let mut last = false;
+
+    while !last {
+        last = reader.bits(1)? == 1;
+        let (literals, distances) = match reader.bits(2)? {
+            0 => {
+                reader.align();
+                let start = reader.position / 8;
+                let header = reader.bytes.get(start..start + 4).ok_or("Truncated stored block")?;
+                let length = usize::from(u16::from_le_bytes([header[0], header[1]]));
+                output.extend_from_slice(reader.bytes.get(start + 4..start + 4 + length).ok_or("Truncated stored block")?);
+                reader.position = (start + 4 + length) * 8;
+                continue;
+            }
+            1 => fixed_codes(),
+            2 => dynamic_codes(&mut reader)?,
+            _ => return Err(String::from("Invalid block type")),
+        };
+
+        loop {
+            let symbol = usize::from(literals.decode(&mut reader)?);
+            if symbol == 256 { break; }
+            #[allow(clippy::cast_possible_truncation)]
+            if symbol < 256 { output.push(symbol as Byte); continue; }
+
+            let index = symbol - 257;
+            let length = usize::from(*LENGTH_BASES.get(index).ok_or("Invalid length")?) + reader.bits(LENGTH_EXTRA_BITS[index])?;
+            let index = usize::from(distances.decode(&mut reader)?);
+            let distance = usize::from(*DISTANCE_BASES.get(index).ok_or("Invalid distance")?) + reader.bits(DISTANCE_EXTRA_BITS[index])?;
+
+            let start = output.len().checked_sub(distance).ok_or("Distance before the start of the output")?;
+            // The copy can overlap the bytes it produces
+            for offset in 0..length { output.push(output[start + offset]); }
+        }
+    }
+
+    reader.align();
+    let start = reader.position / 8;
+    let checksum = read_be_u32(reader.bytes.get(start..start + 4)).ok_or("Missing Adler-32")?;
+    if checksum != adler32(&output) { return Err(String::from("Adler-32 mismatch")); }
+
+    return Ok(output);
+}
+
+//  #############################
+//  #            PNG            #
+//  #############################
+
+fn paeth(left: Byte, up: Byte, up_left: Byte) -> Byte {
+    let estimate = i16::from(left) + i16::from(up) - i16::from(up_left);
+    let (distance_left, distance_up, distance_up_left) = ((estimate - i16::from(left)).abs(), (estimate - i16::from(up)).abs(), (estimate - i16::from(up_left)).abs());
+
+    if distance_left <= distance_up && distance_left <= distance_up_left { return left; }
+    if distance_up <= distance_up_left { return up; }
+    return up_left;
+}
+
+/// Removes the filter of every scanline, `stride` being the bytes per pixel (at least 1)
+fn unfilter(data: &[Byte], height: usize, line_size: usize, stride: usize) -> Result<Vec<Byte>, String> {
+    let mut output = vec![0; height * line_size];
+
+    for row in 0..height {
+        let line = data.get(row * (line_size + 1)..(row + 1) * (line_size + 1)).ok_or("Truncated image data")?;
+        let (filter, line) = (line[0], &line[1..]);
+
+        for index in 0..line_size {
+            let left = if index >= stride { output[row * line_size + index - stride] } else { 0 };
+            let up = if row > 0 { output[(row - 1) * line_size + index] } else { 0 };
+            let up_left = if row > 0 && index >= stride { output[(row - 1) * line_size + index - stride] } else { 0 };
+
+            #[allow(clippy::cast_possible_truncation)]
+            let predictor = match filter {
+                0 => 0,
+                1 => left,
+                2 => up,
+                3 => ((u16::from(left) + u16::from(up)) / 2) as Byte,
+                4 => paeth(left, up, up_left),
+                _ => return Err(format!("Invalid filter {filter}")),
+            };
+            output[row * line_size + index] = line[index].wrapping_add(predictor);
+        }
+    }
+
+    return Ok(output);
+}
+
+/// Decodes a PNG into RGB24, transparency is ignored
+pub fn decode_png(data: &[Byte]) -> Result<Image, String> {
+    let mut chunks = data.strip_prefix(&PNG_SIGNATURE).ok_or("Not a PNG file")?;
+    let (mut header, mut palette, mut compressed) = (None, Vec::new(), Vec::new());
+
+    while chunks.len() >= 12 {
+        let length = usize::try_from(u32::from_be_bytes([chunks[0], chunks[1], chunks[2], chunks[3]])).map_err(|_| "Invalid chunk length")?;
+        let kind = &chunks[4..8];
+        let content = chunks.get(8..8 + length).ok_or("Truncated chunk")?;
+        let crc = read_be_u32(chunks.get(8 + length..12 + length)).ok_or("Truncated chunk")?;
+        if crc != crc32(&chunks[4..8 + length]) { return Err(format!("CRC mismatch in the {} chunk", String::from_utf8_lossy(kind))); }
+
+        match kind {
+            b"IHDR" => header = Some(content.to_vec()),
+            b"PLTE" => palette = content.to_vec(),
+            b"IDAT" => compressed.extend_from_slice(content),
+            b"IEND" => break,
+            _ => {}
+        }
+        chunks = &chunks[(12 + length).min(chunks.len())..];
+    }
+
+    let header = header.ok_or("Missing IHDR chunk")?;
+    let [w0, w1, w2, w3, h0, h1, h2, h3, depth, color_type, _, _, interlace] = header[..] else { return Err(String::from("Invalid IHDR chunk")) };
+    let width = usize::try_from(u32::from_be_bytes([w0, w1, w2, w3])).map_err(|_| "Invalid width")?;
+    let height = usize::try_from(u32::from_be_bytes([h0, h1, h2, h3])).map_err(|_| "Invalid height")?;
+    if interlace != 0 { return Err(String::from("Interlaced images aren't supported")); }
+
+    let channels = match (color_type, depth) {
+        (0 | 3, 1 | 2 | 4 | 8) => 1,
+        (4, 8) => 2,
+        (2, 8) => 3,
+        (6, 8) => 4,
+        _ => return Err(format!("Unsupported color type {color_type} with depth {depth}")),
+    };
+    let bits_per_pixel = channels * usize::from(depth);
+    let pixels = unfilter(&inflate(&compressed)?, height, (width * bits_per_pixel).div_ceil(8), bits_per_pixel.div_ceil(8))?;
+
+    let line_size = (width * bits_per_pixel).div_ceil(8);
+    let mut image = Image::new(width, height);
+    for y in 0..height {
+        for x in 0..width {
+            let index = y * line_size + x * bits_per_pixel / 8;
+            let color = match channels {
+                1 => {
+                    // Samples of less than 8 bits are packed from the most significant bit
+                    let shift = 8 - usize::from(depth) - x * usize::from(depth) % 8;
+                    let sample = (pixels[index] >> shift) & (0xFF >> (8 - depth));
+                    if color_type == 3 {
+                        let entry = palette.get(usize::from(sample) * 3..usize::from(sample) * 3 + 3).ok_or("Palette index out of range")?;
+                        (entry[0], entry[1], entry[2])
+                    } else {
+                        let gray = sample * (0xFF / (0xFF >> (8 - depth)));
+                        (gray, gray, gray)
+                    }
+                }
+                2 => (pixels[index], pixels[index], pixels[index]),
+                _ => (pixels[index], pixels[index + 1], pixels[index + 2]),
+            };
+            image.set_pixel(x, y, color);
+        }
+    }
+
+    debug_assert!(image.pixels.len() == width * height * BYTES_PER_PIXEL);
+    return Ok(image);
+}
+
+mod tests {
+    use crate::gui::capture::encode_png;
+    use super::{decode_png, inflate};
+
+    #[test]
+    fn test_round_trip() {
+        let pixels: Vec<u8> = (0..=255).cycle().take(70 * 3 * 50).collect();
+        let Ok(image) = decode_png(&encode_png(70, 50, &pixels)) else { panic!("Encoded image was rejected") };
+        assert_eq!((image.width, image.height), (70, 50));
+        assert_eq!(image.pixels, pixels);
+    }
+
+    #[test]
+    fn test_inflate_fixed_codes() {
+        // zlib.compress(b"abcabcabcabc hello hello"), with back-references
+        let data = [0x78, 0x9C, 0x4B, 0x4C, 0x4A, 0x4E, 0x84, 0x21, 0x85, 0x8C, 0xD4, 0x9C, 0x9C, 0x7C, 0x08, 0x09, 0x00, 0x70, 0x12, 0x09, 0x01];
+        assert_eq!(inflate(&data).as_deref(), Ok(&b"abcabcabcabc hello hello"[..]));
+    }
+
+    #[test]
+    fn test_inflate_dynamic_codes() {
+        // zlib.compress(expected, 9), skewed enough for zlib to pick dynamic codes
+        let data = [
+            0x78, 0xDA, 0x9D, 0xCE, 0x51, 0x0E, 0xC0, 0x20, 0x0C, 0x02, 0xD0, 0xB3, 0xB6, 0xC0, 0xFD, 0xAF, 0x30, 0xEA, 0xAC, 0xA9, 0xFB, 0x1C,
+            0x3F, 0xFA, 0xA2, 0x09, 0x44, 0x46, 0x82, 0x84, 0x8F, 0x58, 0x29, 0x4B, 0x97, 0x09, 0x70, 0x5A, 0x80, 0x8E, 0x19, 0x48, 0x07, 0xBE,
+            0xAC, 0xA8, 0xAD, 0xD7, 0x7E, 0x28, 0xFB, 0x5B, 0x5B, 0x65, 0x6D, 0x5F, 0xBD, 0x3F, 0x76, 0x9C, 0x5E, 0x7E, 0x7A, 0xF7, 0x8E, 0xD1,
+            0xCB, 0xD9, 0xDB, 0x3B, 0x1E, 0x2C, 0x57, 0x61, 0xF1,
+        ];
+        let expected: Vec<u8> = (0..256).map(|i| b"aaaaaaaabbbbccde"[((i * i * 7 + i * 3) % 16) ^ (i / 16 % 16)]).collect();
+        assert_eq!(inflate(&data), Ok(expected));
+    }
+
+    #[test]
+    fn test_invalid_png() {
+        assert!(decode_png(b"GIF89a").is_err());
+        assert!(inflate(&[0x78, 0x9C, 0xFF]).is_err());
+
+        // Corrupted pixel, in the IDAT chunk
+        let mut png = encode_png(2, 2, &[0x80; 12]);
+        png[0x30] ^= 0x01;
+        assert_eq!(decode_png(&png).err().as_deref(), Some("CRC mismatch in the IDAT chunk"));
+        // Corrupted stored byte of a zlib stream
+        let mut data = [0x78, 0x01, 0x01, 0x01, 0x00, 0xFE, 0xFF, 0x42, 0x00, 0x43, 0x00, 0x43];
+        assert_eq!(inflate(&data), Ok(vec![0x42]));
+        data[7] = 0x41;
+        assert!(inflate(&data).is_err());
+    }
+}
@@ -0,0 +1,240 @@
+use std::fmt::Write as _;
+use std::path::Path;
+use crate::cpu::memory::Memory;
+use crate::testroms::json::Json;
+use crate::testroms::{find_files, rom_name, try_step};
+use crate::utils::types::{FarAddress, Value};
+
+/// Each entry of the "cycles" list is a machine cycle
+const TICKS_PER_M_CYCLE: u64 = 4;
+// Failures printed per opcode, the other ones are only counted
+const PRINTED_FAILURES: usize = 1;
+
+/// CPU and RAM state of a test case, the RAM only lists the addresses the instruction accesses
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct State {
+    a: Value,
+    b: Value,
+    c: Value,
+    d: Value,
+    e: Value,
+    f: Value,
+    h: Value,
+    l: Value,
+    sp: FarAddress,
+    pc: FarAddress,
+    ram: Vec<(FarAddress, Value)>,
+}
+
+impl State {
+    fn parse(json: &Json) -> Result<State, String> {
+        let field = |name: &str| json.get(name).and_then(Json::as_u64).ok_or(format!("Missing or invalid \"{name}\""));
+        let byte = |name: &str| field(name).and_then(|value| Value::try_from(value).map_err(|_| format!("\"{name}\" is out of range")));
+        let wide = |name: &str| field(name).and_then(|value| FarAddress::try_from(value).map_err(|_| format!("\"{name}\" is out of range")));
+
+        let ram = json.get("ram").and_then(Json::as_array).ok_or("Missing or invalid \"ram\"")?;
+        let ram: Option<Vec<(FarAddress, Value)>> = ram.iter()
+            .map(|entry| match entry.as_array() {
+                Some([addr, value]) => Some((FarAddress::try_from(addr.as_u64()?).ok()?, Value::try_from(value.as_u64()?).ok()?)),
+                _ => None,
+            })
+            .collect();
+
+        return Ok(State {
+            a: byte("a")?, b: byte("b")?, c: byte("c")?, d: byte("d")?, e: byte("e")?, f: byte("f")?, h: byte("h")?, l: byte("l")?,
+            sp: wide("sp")?,
+            pc: wide("pc")?,
+            ram: ram.ok_or("Invalid \"ram\" entry")?,
+        });
+    }
+
+    /// Flat 64 KiB bus holding the state
+    fn to_memory(&self) -> Memory {
+        let mut memory = Memory::flat_bus();
+        for (addr, value) in &self.ram { memory.memory[usize::from(*addr)] = *value; }
+
+        let registers = &mut memory.registers;
+        registers.set_a(self.a);
+        registers.set_f(self.f);
+        registers.set_b(self.b);
+        registers.set_c(self.c);
+        registers.set_d(self.d);
+        registers.set_e(self.e);
+        registers.set_h(self.h);
+        registers.set_l(self.l);
+        registers.SP = self.sp;
+        registers.PC = self.pc;
+
+        return memory;
+    }
+}
+
+/// "Z-H-" for the flags set in F
+fn flags(f: Value) -> String {
+    return "ZNHC".chars().enumerate().map(|(index, flag)| if f & (0x80 >> index) != 0 { flag } else { '-' }).collect();
+}
+
+/// Differences between the expected state and the memory after the instruction, empty if it matches
+fn diff(expected: &State, memory: &Memory, expected_ticks: u64) -> Vec<String> {
+    let registers = &memory.registers;
+    let mut differences = Vec::new();
+
+    let bytes = [
+        ("A", expected.a, registers.get_a()), ("B", expected.b, registers.get_b()), ("C", expected.c, registers.get_c()),
+        ("D", expected.d, registers.get_d()), ("E", expected.e, registers.get_e()), ("H", expected.h, registers.get_h()),
+        ("L", expected.l, registers.get_l()),
+    ];
+    for (name, expected, found) in bytes {
+        if expected != found { differences.push(format!("{name}: expected ${expected:02X}, got ${found:02X}")); }
+    }
+    if expected.f != registers.get_f() {
+        differences.push(format!("F: expected {} (${:02X}), got {} (${:02X})", flags(expected.f), expected.f, flags(registers.get_f()), registers.get_f()));
+    }
+    for (name, expected, found) in [("SP", expected.sp, registers.SP), ("PC", expected.pc, registers.PC)] {
+        if expected != found { differences.push(format!("{name}: expected ${expected:04X}, got ${found:04X}")); }
+    }
+
+    for (addr, value) in &expected.ram {
+        let found = memory.peek(*addr);
+        if found != *value { differences.push(format!("[{addr:04X}]: expected ${value:02X}, got ${found:02X}")); }
+    }
+
+    if memory.cycles != expected_ticks { differences.push(format!("Cycles: expected {expected_ticks}, got {}", memory.cycles)); }
+    return differences;
+}
+
+struct CaseResult {
+    name: String,
+    /// Empty if the case passed
+    differences: Vec<String>,
+}
+
+/// Runs one case of a test file, an error meaning the case itself is invalid
+fn run_case(case: &Json) -> Result<CaseResult, String> {
+    let name = case.get("name").and_then(Json::as_str).unwrap_or_default().to_string();
+    let initial = State::parse(case.get("initial").ok_or("Missing \"initial\"")?)?;
+    let expected = State::parse(case.get("final").ok_or("Missing \"final\"")?)?;
+    let m_cycles = case.get("cycles").and_then(Json::as_array).ok_or("Missing \"cycles\"")?.len() as u64;
+
+    let mut memory = initial.to_memory();
+    let differences = match try_step(&mut memory) {
+        Ok(()) => diff(&expected, &memory, m_cycles * TICKS_PER_M_CYCLE),
+        Err(message) => vec![message],
+    };
+
+    return Ok(CaseResult { name, differences });
+}
+
+/// Number of passed cases and the failures of a test file, stopping at the first crash since it would repeat for every case
+fn run_file(path: &Path) -> Result<(usize, Vec<CaseResult>), String> {
+    let text = std::fs::read_to_string(path).map_err(|error| format!("Couldn't read {}: {error}", path.display()))?;
+    let json = Json::parse(&text)?;
+    let cases = json.as_array().ok_or("Expected a list of test cases")?;
+
+    let mut passed = 0;
+    let mut failures = Vec::new();
+    for case in cases {
+        let result = run_case(case)?;
+        if result.differences.is_empty() {
+            passed += 1;
+            continue;
+        }
+
+        let crashed = result.differences.iter().any(|difference| difference.starts_with("Crashed"));
+        failures.push(result);
+        if crashed { break; }
+    }
+
+    return Ok((passed, failures));
+}
+
+/// Runs every JSON file of the folder (one per opcode), printing the passed cases per opcode and the first failures
+fn run_suite(path: &str) {
+    let files = find_files(path, &["json"]);
+    if files.is_empty() { return; }
+
+    let mut report = String::new();
+    let mut failed_files = 0;
+    for file in &files {
+        let opcode = file.file_stem().map(|stem| stem.to_string_lossy().to_uppercase()).unwrap_or_default();
+
+        match run_file(file) {
+            Ok((passed, failures)) if failures.is_empty() => { let _ = writeln!(report, "{opcode:<6} ok ({passed} cases)"); }
+            Ok((passed, failures)) => {
+                failed_files += 1;
+                let _ = writeln!(report, "{opcode:<6} FAIL ({passed} passed, {} failed)", failures.len());
+                for failure in failures.iter().take(PRINTED_FAILURES) {
+                    let _ = writeln!(report, "       \"{}\": {}", failure.name, failure.differences.join(", "));
+                }
+            }
+            Err(error) => {
+                failed_files += 1;
+                let _ = writeln!(report, "{opcode:<6} INVALID {}: {error}", rom_name(file));
+            }
+        }
+    }
+
+    println!("{report}");
+    assert_eq!(failed_files, 0, "{failed_files} of {} opcodes didn't pass in {path}", files.len());
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testroms::json::Json;
+    use super::{flags, run_case, run_suite};
+
+    #[test]
+    fn test_single_step() {
+        run_suite("sm83/v1");
+    }
+
+    #[test]
+    fn test_flags() {
+        assert_eq!(flags(0xA0), "Z-H-");
+        assert_eq!(flags(0x50), "-N-C");
+    }
+
+    #[test]
+    fn test_run_case() {
+        let Ok(case) = Json::parse(&case_with_b(3)) else { panic!("Valid case was rejected") };
+        let Ok(result) = run_case(&case) else { panic!("Valid case was rejected") };
+        assert_eq!(result.differences, Vec::<String>::new());
+
+        // Expecting B to be unchanged
+        let Ok(wrong) = Json::parse(&case_with_b(2)) else { panic!("Valid case was rejected") };
+        let Ok(result) = run_case(&wrong) else { panic!("Valid case was rejected") };
+        assert_eq!(result.differences, vec![String::from("B: expected $02, got $03")]);
+
+        for case in [LOAD_FROM_HL, STORE_TO_SC] {
+            let Ok(case) = Json::parse(case) else { panic!("Valid case was rejected") };
+            let Ok(result) = run_case(&case) else { panic!("Valid case was rejected") };
+            assert_eq!(result.differences, Vec::<String>::new());
+        }
+    }
+
+    /// LD A, (HL) at C000, with HL pointing to D012
+    const LOAD_FROM_HL: &str = r#"{
+        "name": "7e 0001",
+        "initial": {"pc": 49152, "sp": 65534, "a": 1, "b": 2, "c": 3, "d": 4, "e": 5, "f": 176, "h": 208, "l": 18, "ram": [[49152, 126], [53266, 153]]},
+        "final": {"pc": 49153, "sp": 65534, "a": 153, "b": 2, "c": 3, "d": 4, "e": 5, "f": 176, "h": 208, "l": 18, "ram": [[49152, 126], [53266, 153]]},
+        "cycles": [[49152, 126, "r-m"], [53266, 153, "r-m"]]
+    }"#;
+
+    /// LD (HL), A at C000 starting a serial transfer, which stays plain RAM on the test bus
+    const STORE_TO_SC: &str = r#"{
+        "name": "77 0001",
+        "initial": {"pc": 49152, "sp": 65534, "a": 129, "b": 2, "c": 3, "d": 4, "e": 5, "f": 176, "h": 255, "l": 2, "ram": [[49152, 119]]},
+        "final": {"pc": 49153, "sp": 65534, "a": 129, "b": 2, "c": 3, "d": 4, "e": 5, "f": 176, "h": 255, "l": 2, "ram": [[49152, 119], [65282, 129]]},
+        "cycles": [[49152, 119, "r-m"], [65282, 129, "-wm"]]
+    }"#;
+
+    /// LD B, C at C000, expecting B to be `b` afterwards
+    fn case_with_b(b: u8) -> String {
+        return format!(r#"{{
+            "name": "41 0001",
+            "initial": {{"pc": 49152, "sp": 65534, "a": 1, "b": 2, "c": 3, "d": 4, "e": 5, "f": 176, "h": 6, "l": 7, "ram": [[49152, 65]]}},
+            "final": {{"pc": 49153, "sp": 65534, "a": 1, "b": {b}, "c": 3, "d": 4, "e": 5, "f": 176, "h": 6, "l": 7, "ram": [[49152, 65]]}},
+            "cycles": [[49152, 65, "r-m"]]
+        }}"#);
+    }
+}
@@ -0,0 +1,370 @@
+use std::path::PathBuf;
+use crate::debug::profiler::ProfileFormat;
+use crate::debug::trace::{parse_range, TraceFilter};
+use crate::debug::watchpoint::Watchpoint;
+use crate::gui::viewer::ViewerKind;
+use crate::utils::log::LogFilter;
+use crate::state::rewind::{DEFAULT_REWIND_BUDGET, DEFAULT_REWIND_INTERVAL};
+
+const USAGE: &str = "Usage: lameboy <ROM> [OPTIONS]
+       lameboy disassemble <ROM> [--output <PATH>] [--symbols <PATH>]
+
+Runs the ROM file, with the symbols of the \".sym\" file next to it if any
+
+Commands:
+    disassemble             Disassemble a ROM file bank by bank, separating code from data by tracing it from the
+                            entry point and the interrupt vectors. Written to PATH, or to the standard output.
+                            Labels are named after the symbols of the \".sym\" file next to the ROM, if any
+
+Options:
+    --load-state <SLOT>     Restore the save state stored in slot SLOT (\"<ROM>.ssSLOT\", next to the ROM) before running.
+                            In the window, 1-9 select the slot, F7 saves to it and F8 loads it
+    --save-state <SLOT>     Store the machine state in slot SLOT after running
+    --rewind-budget <MIB>   Memory used to keep rewind snapshots, in MiB (default: 32)
+    --rewind-interval <N>   Number of frames between two rewind snapshots (default: 4)
+    --screenshot-dir <DIR>  Directory where screenshots are saved with F12 (default: .)
+    --screenshot-scale <N>  Integer upscaling factor of screenshots (default: 1)
+    --screenshot <PATH>     Save the screen to a PNG file on exit
+    --headless <FRAMES>     Run FRAMES frames as fast as possible without opening a window, e.g. to take a screenshot or
+                            record the screen
+    --color-correction      Show the CGB colors as the CGB LCD does, darker and less saturated
+    --colorize              Run DMG cartridges on a CGB, which colorizes Nintendo games from their title. Holding a
+                            direction, optionally with A or B, during the first 2 seconds picks another palette
+    --sgb                   Run on a Super Game Boy, which colors the screen and shows a border around it for the
+                            cartridges supporting it
+    --record <PATH>         Record the screen from launch, toggled with F11. The format depends on the extension:
+                            .gif (animated GIF), .y4m (YUV4MPEG2), .raw (RGB24 frames), else a directory of PNG frames.
+                            The sound is recorded alongside, to the same path with the .wav extension (or audio.wav in
+                            the directory of PNG frames)
+    --record-audio <PATH>   Record the sound mixed by the emulated APU to a WAV file from launch, toggled with F10. The
+                            samples don't depend on the audio device, they are the same when running headless
+    --record-movie <PATH>   Record the joypad input of every frame to a movie file, starting from the loaded save state if any
+    --play-movie <PATH>     Play back a movie file, ignoring the player input
+    --read-write            During movie playback, pressing any button takes over and rerecords from the current frame
+    --debug                 Start paused in the debugger (which can also be entered with F9)
+    --symbols <PATH>        Load a RGBDS or no$gmb symbol file, used by the debugger, disassembler and trace log
+    --break <[BANK:]ADDR>   Add a debugger breakpoint on an address or a symbol, can be repeated
+    --watch <WATCHPOINT>    Add a debugger watchpoint [r|w|c:]ADDR[-END][=VALUE], pausing on read, write (default) or
+                            change of memory, can be repeated
+    --trace <PATH>          Log the CPU state before each instruction in the Gameboy Doctor format, to PATH or - (stdout)
+    --trace-range <S-E>     Only trace instructions with PC between the hexadecimal addresses S and E (inclusive)
+    --trace-bank <BANK>     Only trace instructions executed in the ROM bank BANK
+    --trace-symbols         Append the nearest symbol to each line of the trace log
+    --stub-ly               Make LY always read 0x90, as Gameboy Doctor expects to compare the trace log with its own
+    --profile <PATH>        Count the T-cycles spent at each PC and in each routine (followed through CALL/RST and RET),
+                            written to PATH on exit
+    --profile-format <FMT>  report (default): hot spots and routines sorted by cycles, with their inclusive time,
+                            collapsed: one line per call stack, for flamegraph tools
+    --coverage <PATH>       Record which ROM bytes are executed as opcodes or operands, and read as data. Written on exit
+                            to PATH as CSV ranges if it ends with .csv, else as one byte per ROM byte (bit 0: opcode,
+                            bit 1: operand, bit 2: data). The percentages per bank are printed too
+    --viewers <LIST>        Open debug windows at launch, comma-separated among: tiles, maps, oam, palettes.
+                            They are toggled with F1 (tiles), F2 (maps), F3 (oam) and F4 (palettes),
+                            P cycling the palette of the tile viewer. F5 toggles the memory viewer and editor,
+                            F6 pauses the emulation
+    --log <FILTER>          Log messages, \"[LEVEL][,CATEGORY[=LEVEL]]...\" among the levels error, warn (default), info,
+                            debug and trace, and the categories cpu, memory, stack, utils and gui, e.g. \"info,memory=trace\".
+                            Defaults to the LAMEBOY_LOG environment variable
+    --log-file <PATH>       Write the log to PATH or - (stdout), instead of keeping the latest lines for the debugger
+    --help                  Print this message";
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Command {
+    /// Launch the emulator
+    Run { rom: PathBuf },
+    Disassemble { rom: PathBuf, output: Option<PathBuf> },
+}
+
+#[derive(Debug)]
+pub struct Arguments {
+    pub command: Command,
+    pub load_slot: Option<u8>,
+    pub save_slot: Option<u8>,
+    /// In bytes
+    pub rewind_budget: usize,
+    /// In frames
+    pub rewind_interval: u32,
+    pub screenshot_dir: PathBuf,
+    pub screenshot_scale: usize,
+    pub screenshot: Option<PathBuf>,
+    /// Number of frames to run without window
+    pub headless: Option<u32>,
+    pub color_correction: bool,
+    pub colorize: bool,
+    pub sgb: bool,
+    pub record: Option<PathBuf>,
+    pub record_audio: Option<PathBuf>,
+    pub record_movie: Option<PathBuf>,
+    pub play_movie: Option<PathBuf>,
+    pub movie_read_write: bool,
+    pub debug: bool,
+    pub symbols: Option<PathBuf>,
+    /// Addresses or symbols, resolved once the symbols are loaded
+    pub breakpoints: Vec<String>,
+    pub watchpoints: Vec<Watchpoint>,
+    pub trace: Option<PathBuf>,
+    pub trace_filter: TraceFilter,
+    pub trace_symbols: bool,
+    /// LY reads as 0x90, for Gameboy Doctor
+    pub stub_ly: bool,
+    pub profile: Option<PathBuf>,
+    pub profile_format: ProfileFormat,
+    pub coverage: Option<PathBuf>,
+    pub viewers: Vec<ViewerKind>,
+    pub log_filter: Option<LogFilter>,
+    pub log_file: Option<PathBuf>,
+}
+
+impl Default for Arguments {
+    fn default() -> Self {
+        return Arguments {
+            command: Command::Run { rom: PathBuf::new() },
+            load_slot: None,
+            save_slot: None,
+            rewind_budget: DEFAULT_REWIND_BUDGET,
+            rewind_interval: DEFAULT_REWIND_INTERVAL,
+            screenshot_dir: PathBuf::from("."),
+            screenshot_scale: 1,
+            screenshot: None,
+            headless: None,
+            color_correction: false,
+            colorize: false,
+            sgb: false,
+            record: None,
+            record_audio: None,
+            record_movie: None,
+            play_movie: None,
+            movie_read_write: false,
+            debug: false,
+            symbols: None,
+            breakpoints: Vec::new(),
+            watchpoints: Vec::new(),
+            trace: None,
+            trace_filter: TraceFilter::default(),
+            trace_symbols: false,
+            stub_ly: false,
+            profile: None,
+            profile_format: ProfileFormat::default(),
+            coverage: None,
+            viewers: Vec::new(),
+            log_filter: None,
+            log_file: None,
+        }
+    }
+}
+
+impl Arguments {
+    pub fn parse() -> Arguments {
+        return match Self::parse_from(std::env::args().skip(1)) {
+            Ok(arguments) => arguments,
+            Err(message) => {
+                eprintln!("{message}\n\n{USAGE}");
+                std::process::exit(1);
+            }
+        }
+    }
+
+    fn parse_from(args: impl Iterator<Item = String>) -> Result<Arguments, String> {
+        let mut args = args.peekable();
+        if args.peek().is_some_and(|arg| arg == "disassemble") {
+            args.next();
+            return Self::parse_disassemble(args);
+        }
+
+        let mut arguments = Arguments::default();
+        let mut rom = None;
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--load-state" => arguments.load_slot = Some(parse_value(&arg, args.next())?),
+                "--save-state" => arguments.save_slot = Some(parse_value(&arg, args.next())?),
+                "--rewind-budget" => arguments.rewind_budget = parse_value::<usize>(&arg, args.next())?.saturating_mul(1024 * 1024),
+                "--rewind-interval" => arguments.rewind_interval = parse_value(&arg, args.next())?,
+                "--screenshot-dir" => arguments.screenshot_dir = parse_value(&arg, args.next())?,
+                "--screenshot-scale" => arguments.screenshot_scale = parse_value(&arg, args.next())?,
+                "--screenshot" => arguments.screenshot = Some(parse_value(&arg, args.next())?),
+                "--headless" => arguments.headless = Some(parse_value(&arg, args.next())?),
+                "--color-correction" => arguments.color_correction = true,
+                "--colorize" => arguments.colorize = true,
+                "--sgb" => arguments.sgb = true,
+                "--record" => arguments.record = Some(parse_value(&arg, args.next())?),
+                "--record-audio" => arguments.record_audio = Some(parse_value(&arg, args.next())?),
+                "--record-movie" => arguments.record_movie = Some(parse_value(&arg, args.next())?),
+                "--play-movie" => arguments.play_movie = Some(parse_value(&arg, args.next())?),
+                "--read-write" => arguments.movie_read_write = true,
+                "--debug" => arguments.debug = true,
+                "--symbols" => arguments.symbols = Some(parse_value(&arg, args.next())?),
+                "--break" => arguments.breakpoints.push(parse_value(&arg, args.next())?),
+                "--watch" => arguments.watchpoints.push(parse_value(&arg, args.next())?),
+                "--trace" => arguments.trace = Some(parse_value(&arg, args.next())?),
+                "--trace-range" => arguments.trace_filter.range = Some(parse_range(&parse_value::<String>(&arg, args.next())?)?),
+                "--trace-bank" => arguments.trace_filter.bank = Some(parse_value(&arg, args.next())?),
+                "--trace-symbols" => arguments.trace_symbols = true,
+                "--stub-ly" => arguments.stub_ly = true,
+                "--profile" => arguments.profile = Some(parse_value(&arg, args.next())?),
+                "--profile-format" => arguments.profile_format = parse_value::<String>(&arg, args.next())?.parse()?,
+                "--coverage" => arguments.coverage = Some(parse_value(&arg, args.next())?),
+                "--viewers" => {
+                    let list: String = parse_value(&arg, args.next())?;
+                    arguments.viewers = list.split(',').map(str::parse).collect::<Result<_, _>>()?;
+                }
+                "--log" => arguments.log_filter = Some(parse_value::<String>(&arg, args.next())?.parse()?),
+                "--log-file" => arguments.log_file = Some(parse_value(&arg, args.next())?),
+                "--help" => {
+                    println!("{USAGE}");
+                    std::process::exit(0);
+                }
+                _ if arg.starts_with('-') || rom.is_some() => return Err(format!("Unknown argument \"{arg}\"")),
+                _ => rom = Some(PathBuf::from(arg)),
+            }
+        }
+
+        let Some(rom) = rom else { return Err(String::from("Missing ROM path")) };
+        arguments.command = Command::Run { rom };
+        if arguments.play_movie.is_some() && arguments.record_movie.is_some() {
+            return Err(String::from("\"--play-movie\" and \"--record-movie\" are mutually exclusive"));
+        }
+        if arguments.colorize && arguments.sgb {
+            return Err(String::from("\"--colorize\" and \"--sgb\" are mutually exclusive"));
+        }
+        if arguments.play_movie.is_some() && arguments.load_slot.is_some() {
+            return Err(String::from("\"--play-movie\" can't be combined with \"--load-state\", movies embed their start state"));
+        }
+
+        return Ok(arguments);
+    }
+
+    fn parse_disassemble(mut args: impl Iterator<Item = String>) -> Result<Arguments, String> {
+        let mut rom = None;
+        let mut output = None;
+        let mut symbols = None;
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--output" | "-o" => output = Some(parse_value(&arg, args.next())?),
+                "--symbols" => symbols = Some(parse_value(&arg, args.next())?),
+                _ if arg.starts_with('-') || rom.is_some() => return Err(format!("Unknown argument \"{arg}\"")),
+                _ => rom = Some(PathBuf::from(arg)),
+            }
+        }
+
+        let Some(rom) = rom else { return Err(String::from("Missing ROM path for \"disassemble\"")) };
+        return Ok(Arguments { command: Command::Disassemble { rom, output }, symbols, ..Arguments::default() });
+    }
+}
+
+fn parse_value<T: std::str::FromStr>(name: &str, value: Option<String>) -> Result<T, String> {
+    let Some(value) = value else { return Err(format!("Missing value for \"{name}\"")) };
+    return value.parse().map_err(|_| format!("Invalid value \"{value}\" for \"{name}\""));
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use crate::debug::profiler::ProfileFormat;
+    use crate::gui::viewer::ViewerKind;
+    use crate::utils::log::{Category, Level};
+    use super::{Arguments, Command};
+
+    /// Parses the options of a run of "game.gb"
+    fn parse(args: &[&str]) -> Result<Arguments, String> {
+        return Arguments::parse_from(std::iter::once("game.gb").chain(args.iter().copied()).map(ToString::to_string));
+    }
+
+    #[test]
+    fn test_parse_rom() {
+        let Ok(arguments) = parse(&["--sgb"]) else { panic!("Valid arguments were rejected") };
+        assert_eq!(arguments.command, Command::Run { rom: PathBuf::from("game.gb") });
+
+        assert!(Arguments::parse_from(std::iter::once(String::from("--sgb"))).is_err());
+        assert!(parse(&["other.gb"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_slots() {
+        let Ok(arguments) = parse(&["--load-state", "2", "--save-state", "3"]) else { panic!("Valid arguments were rejected") };
+        assert_eq!(arguments.load_slot, Some(2));
+        assert_eq!(arguments.save_slot, Some(3));
+    }
+
+    #[test]
+    fn test_parse_rewind() {
+        let Ok(arguments) = parse(&[]) else { panic!("Valid arguments were rejected") };
+        assert_eq!(arguments.rewind_budget, 32 * 1024 * 1024);
+
+        let Ok(arguments) = parse(&["--rewind-budget", "8", "--rewind-interval", "10"]) else { panic!("Valid arguments were rejected") };
+        assert_eq!(arguments.rewind_budget, 8 * 1024 * 1024);
+        assert_eq!(arguments.rewind_interval, 10);
+    }
+
+    #[test]
+    fn test_parse_headless() {
+        let Ok(arguments) = parse(&["--headless", "600", "--screenshot", "last.png"]) else { panic!("Valid arguments were rejected") };
+        assert_eq!(arguments.headless, Some(600));
+        assert_eq!(arguments.screenshot, Some(PathBuf::from("last.png")));
+        assert!(parse(&["--headless"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_trace() {
+        let Ok(arguments) = parse(&["--trace", "-", "--trace-range", "0100-3FFF", "--trace-bank", "0", "--stub-ly"]) else { panic!("Valid arguments were rejected") };
+        assert_eq!(arguments.trace, Some(PathBuf::from("-")));
+        assert!(arguments.stub_ly);
+        assert_eq!(arguments.trace_filter.range, Some((0x0100, 0x3FFF)));
+        assert_eq!(arguments.trace_filter.bank, Some(0));
+
+        assert!(parse(&["--trace-range", "0100"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_recording() {
+        let Ok(arguments) = parse(&["--record", "game.gif", "--record-audio", "game.wav"]) else { panic!("Valid arguments were rejected") };
+        assert_eq!((arguments.record, arguments.record_audio), (Some(PathBuf::from("game.gif")), Some(PathBuf::from("game.wav"))));
+        assert!(parse(&["--record-audio"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_profile() {
+        let Ok(arguments) = parse(&["--profile", "profile.txt", "--profile-format", "collapsed"]) else { panic!("Valid arguments were rejected") };
+        assert_eq!(arguments.profile, Some(PathBuf::from("profile.txt")));
+        assert_eq!(arguments.profile_format, ProfileFormat::Collapsed);
+        assert!(parse(&["--profile-format", "svg"]).is_err());
+
+        let Ok(arguments) = parse(&["--coverage", "coverage.csv"]) else { panic!("Valid arguments were rejected") };
+        assert_eq!(arguments.coverage, Some(PathBuf::from("coverage.csv")));
+    }
+
+    #[test]
+    fn test_parse_log() {
+        let Ok(arguments) = parse(&["--log", "info,memory=trace", "--log-file", "-"]) else { panic!("Valid arguments were rejected") };
+        assert_eq!(arguments.log_filter.map(|filter| filter.level(Category::Memory)), Some(Some(Level::Trace)));
+        assert_eq!(arguments.log_file, Some(PathBuf::from("-")));
+        assert!(parse(&["--log", "cpu=loud"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_viewers() {
+        let Ok(arguments) = parse(&["--viewers", "tiles,oam"]) else { panic!("Valid arguments were rejected") };
+        assert_eq!(arguments.viewers, vec![ViewerKind::Tiles, ViewerKind::Oam]);
+        assert!(parse(&["--viewers", "tiles,sound"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_disassemble() {
+        let disassemble = |args: &[&str]| Arguments::parse_from(std::iter::once("disassemble").chain(args.iter().copied()).map(ToString::to_string));
+        let Ok(arguments) = disassemble(&["game.gb", "--output", "game.asm"]) else { panic!("Valid arguments were rejected") };
+        assert_eq!(arguments.command, Command::Disassemble { rom: PathBuf::from("game.gb"), output: Some(PathBuf::from("game.asm")) });
+
+        assert!(disassemble(&[]).is_err());
+        assert!(disassemble(&["a.gb", "b.gb"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse(&["--load-state"]).is_err());
+        assert!(parse(&["--save-state", "a"]).is_err());
+        assert!(parse(&["--unknown"]).is_err());
+        assert!(parse(&["--play-movie", "a.lmv", "--record-movie", "b.lmv"]).is_err());
+        assert!(parse(&["--play-movie", "a.lmv", "--load-state", "1"]).is_err());
+    }
+}
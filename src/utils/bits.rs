@@ -13,7 +13,7 @@ pub fn get_bit<T: Integer + std::ops::Shr<usize, Output = T>>(value: T, bit_inde
 
     let bit = ((value >> bit_index) & 1.into()) != 0.into();
 
-    log!("UTILS", format!("{0:#0width$b}[{1}] ({0}) = {2}", value, bit_index, u8::from(bit), width = bit_size(value) + 2));
+    log!(Trace, "UTILS", format!("{0:#0width$b}[{1}] ({0}) = {2}", value, bit_index, u8::from(bit), width = bit_size(value) + 2));
 
     return bit;
 }
@@ -23,7 +23,7 @@ pub fn set_bit<T: Integer + std::ops::BitOr<T, Output = T> + std::ops::Shl<usize
 
     let new_value = old_value | (<u8 as Into<T>>::into(1) << bit_index);
 
-    log!("UTILS", format!("{0:#0width$b}[{2}] ({0}) => {1:#0width$b} ({1})", old_value, new_value, bit_index, width = bit_size(old_value) + 2));
+    log!(Trace, "UTILS", format!("{0:#0width$b}[{2}] ({0}) => {1:#0width$b} ({1})", old_value, new_value, bit_index, width = bit_size(old_value) + 2));
 
     return new_value;
 }
@@ -33,7 +33,7 @@ pub fn clear_bit<T: Integer + std::ops::BitAnd<T, Output = T> + std::ops::Shl<us
 
     let new_value = old_value & !(<u8 as Into<T>>::into(1) << bit_index);
 
-    log!("UTILS", format!("{0:#0width$b}[{2}] ({0}) => {1:#0width$b} ({1})", old_value, new_value, bit_index,  width = bit_size(old_value) + 2));
+    log!(Trace, "UTILS", format!("{0:#0width$b}[{2}] ({0}) => {1:#0width$b} ({1})", old_value, new_value, bit_index,  width = bit_size(old_value) + 2));
 
     return new_value;
 }
@@ -43,7 +43,7 @@ pub fn assign_bit<T: Integer + std::ops::Shl<usize, Output = T>>(old_value: T, b
 
     let new_value = (old_value & !(<u8 as Into<T>>::into(1) << bit_index)) | (<u8 as Into<T>>::into(u8::from(status)) << bit_index);
 
-    log!("UTILS", format!("{0:#0width$b}[{2}] ({0}) = {3} => {1:#0width$b} ({1})", old_value, new_value, bit_index, u8::from(status), width = bit_size(old_value) + 2));
+    log!(Trace, "UTILS", format!("{0:#0width$b}[{2}] ({0}) = {3} => {1:#0width$b} ({1})", old_value, new_value, bit_index, u8::from(status), width = bit_size(old_value) + 2));
 
     return new_value;
 }
@@ -56,7 +56,7 @@ pub fn swap<T: Integer + std::ops::Shl<usize, Output = T> + std::ops::Shr<usize,
 
     let new_value = (low << bit_size_half) + high;
 
-    log!("UTILS", format!("{:#0width$b} => {:#0width$b}", old_value, new_value, width = bit_size(old_value) + 2));
+    log!(Trace, "UTILS", format!("{:#0width$b} => {:#0width$b}", old_value, new_value, width = bit_size(old_value) + 2));
 
     return new_value;
 }
@@ -68,7 +68,7 @@ fn check_carry_add<T: Integer + std::ops::Shl<usize, Output = T>>(value: T, oper
     let and_mask: T = test_mask - 1.into();
     let result = (((value & and_mask) + (operand & and_mask)) & test_mask) != 0.into();
 
-    log!("UTILS", format!("{0} + {1} ({0:#0width$b} + {1:#0width$b}) would carry on bit {2} ? {3}", value, operand, index, result, width = bit_size(value) + 2));
+    log!(Trace, "UTILS", format!("{0} + {1} ({0:#0width$b} + {1:#0width$b}) would carry on bit {2} ? {3}", value, operand, index, result, width = bit_size(value) + 2));
 
     return result;
 }
@@ -86,7 +86,7 @@ fn check_carry_sub<T: Integer + std::ops::Shl<usize, Output = T>>(value: T, oper
     // let result = ((value & and_mask).wrapping_sub(operand & and_mask) & test_mask) != 0;
     let result = ((std::num::Wrapping(value & and_mask) - std::num::Wrapping(operand & and_mask)).0 & test_mask) != 0.into();
 
-    log!("UTILS", format!("{0} + {1} ({0:#0width$b} - {1:#0width$b}) would carry on bit {2} ? {3}", value, operand, bit_index, result, width = bit_size(value) + 2));
+    log!(Trace, "UTILS", format!("{0} + {1} ({0:#0width$b} - {1:#0width$b}) would carry on bit {2} ? {3}", value, operand, bit_index, result, width = bit_size(value) + 2));
 
     return result;
 }
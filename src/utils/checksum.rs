@@ -0,0 +1,40 @@
+//! Checksums of the PNG chunks and zlib streams, shared by the screenshots and the reference images of the tests
+
+use crate::utils::types::Byte;
+
+/// CRC-32 (ISO-HDLC) closing every PNG chunk
+pub fn crc32(data: &[Byte]) -> u32 {
+    let mut crc = !0_u32;
+
+    for byte in data {
+        crc ^= u32::from(*byte);
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+        }
+    }
+
+    return !crc;
+}
+
+/// Adler-32 of the uncompressed data, closing every zlib stream
+pub fn adler32(data: &[Byte]) -> u32 {
+    let (mut a, mut b) = (1_u32, 0_u32);
+
+    for byte in data {
+        a = (a + u32::from(*byte)) % 65521;
+        b = (b + a) % 65521;
+    }
+
+    return (b << 16) | a;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{adler32, crc32};
+
+    #[test]
+    fn test_checksums() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(adler32(b"Wikipedia"), 0x11E6_0398);
+    }
+}
@@ -1,3 +1,210 @@
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Mutex;
+
+/// Environment variable holding the default log filter, overridden by "--log"
+pub const LOG_ENV_VAR: &str = "LAMEBOY_LOG";
+/// Lines kept in memory when not logging to a file
+pub const LOG_BUFFER_LINES: usize = 10_000;
+/// Path standing for the standard output
+const STDOUT_PATH: &str = "-";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Error = 1,
+    Warn,
+    Info,
+    Debug,
+    /// Every memory access, register write and bit operation
+    Trace,
+}
+
+impl std::str::FromStr for Level {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        return match s.to_lowercase().as_str() {
+            "error" => Ok(Level::Error),
+            "warn" => Ok(Level::Warn),
+            "info" => Ok(Level::Info),
+            "debug" => Ok(Level::Debug),
+            "trace" => Ok(Level::Trace),
+            _ => Err(format!("Unknown log level \"{s}\", expected error, warn, info, debug or trace")),
+        }
+    }
+}
+
+/// Prefix given to `log!`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Category {
+    Cpu,
+    /// Memory, and the state saved from it: save states, rewind and movies
+    Memory,
+    /// Values pushed and popped by PUSH, POP, CALL, RST, RET and the interrupt dispatch
+    Stack,
+    Utils,
+    /// Window and screen capture
+    Gui,
+}
+
+const CATEGORY_COUNT: usize = 5;
+
+impl Category {
+    pub const ALL: [Category; CATEGORY_COUNT] = [Category::Cpu, Category::Memory, Category::Stack, Category::Utils, Category::Gui];
+
+    pub const fn name(self) -> &'static str {
+        return match self {
+            Category::Cpu => "CPU",
+            Category::Memory => "MEMORY",
+            Category::Stack => "STACK",
+            Category::Utils => "UTILS",
+            Category::Gui => "GUI",
+        }
+    }
+
+    /// Category of a `log!` prefix, evaluated at compile time so that an unknown prefix doesn't build
+    pub const fn from_name(name: &str) -> Category {
+        let mut index = 0;
+        while index < CATEGORY_COUNT {
+            let category = Category::ALL[index];
+            if name.eq_ignore_ascii_case(category.name()) { return category; }
+            index += 1;
+        }
+        panic!("Unknown log category, expected CPU, MEMORY, STACK, UTILS or GUI");
+    }
+}
+
+impl std::str::FromStr for Category {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        return Category::ALL.into_iter()
+            .find(|category| s.eq_ignore_ascii_case(category.name()))
+            .ok_or_else(|| format!("Unknown log category \"{s}\", expected cpu, memory, stack, utils or gui"));
+    }
+}
+
+/// "[LEVEL][,CATEGORY[=LEVEL]]...", e.g. "info,memory=trace,gui". A category without level logs everything, the
+/// other categories use the default level. The categories are CPU, MEMORY, STACK, UTILS and GUI.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LogFilter {
+    /// `None` disables the categories which aren't listed
+    default: Option<Level>,
+    categories: Vec<(Category, Level)>,
+}
+
+impl LogFilter {
+    pub fn level(&self, category: Category) -> Option<Level> {
+        return self.categories.iter()
+            .find(|(listed, _)| *listed == category)
+            .map_or(self.default, |(_, level)| Some(*level));
+    }
+}
+
+impl std::str::FromStr for LogFilter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut filter = LogFilter::default();
+
+        for directive in s.split(',').map(str::trim).filter(|directive| !directive.is_empty()) {
+            match directive.split_once('=') {
+                Some((category, level)) => filter.categories.push((category.parse()?, level.parse()?)),
+                None => match directive.parse() {
+                    Ok(level) => filter.default = Some(level),
+                    Err(_) => filter.categories.push((directive.parse()?, Level::Trace)),
+                },
+            }
+        }
+
+        return Ok(filter);
+    }
+}
+
+enum Sink {
+    Writer(Box<dyn Write + Send>),
+    /// Latest lines, the oldest being dropped once full
+    Buffer(VecDeque<String>),
+}
+
+pub struct Logger {
+    filter: LogFilter,
+    sink: Sink,
+}
+
+impl Logger {
+    /// Logs to the file, or to the standard output if the path is "-"
+    pub fn to_file(filter: LogFilter, path: &Path) -> std::io::Result<Logger> {
+        let writer: Box<dyn Write + Send> = if path == Path::new(STDOUT_PATH) {
+            Box::new(std::io::stdout())
+        } else {
+            Box::new(BufWriter::new(File::create(path)?))
+        };
+
+        return Ok(Logger { filter, sink: Sink::Writer(writer) });
+    }
+
+    pub fn to_buffer(filter: LogFilter) -> Logger {
+        return Logger { filter, sink: Sink::Buffer(VecDeque::new()) };
+    }
+
+    fn write(&mut self, line: String) {
+        match &mut self.sink {
+            // Nowhere to report the failure, logging must not stop the emulation
+            Sink::Writer(writer) => { let _ = writeln!(writer, "{line}"); }
+            Sink::Buffer(lines) => {
+                if lines.len() == LOG_BUFFER_LINES { lines.pop_front(); }
+                lines.push_back(line);
+            }
+        }
+    }
+
+    fn recent(&self, count: usize) -> Vec<String> {
+        return match &self.sink {
+            Sink::Writer(_) => Vec::new(),
+            Sink::Buffer(lines) => lines.iter().skip(lines.len().saturating_sub(count)).cloned().collect(),
+        }
+    }
+}
+
+/// Highest enabled level of each category, 0 when disabled. Checked by `log!` before taking the logger lock, so that
+/// disabled categories only cost an atomic load.
+static LEVELS: [AtomicU8; CATEGORY_COUNT] = [const { AtomicU8::new(0) }; CATEGORY_COUNT];
+static LOGGER: Mutex<Option<Logger>> = Mutex::new(None);
+
+/// Replaces the current logger, nothing is logged until this is called
+pub fn init(logger: Logger) {
+    for category in Category::ALL {
+        LEVELS[category as usize].store(logger.filter.level(category).map_or(0, |level| level as u8), Ordering::Relaxed);
+    }
+    if let Ok(mut current) = LOGGER.lock() { *current = Some(logger); }
+}
+
+pub fn enabled(level: Level, category: Category) -> bool {
+    return level as u8 <= LEVELS[category as usize].load(Ordering::Relaxed);
+}
+
+pub fn write(line: String) {
+    if let Ok(mut logger) = LOGGER.lock() {
+        if let Some(logger) = logger.as_mut() { logger.write(line); }
+    }
+}
+
+/// Latest `count` lines, when logging to the ring buffer
+pub fn recent(count: usize) -> Vec<String> {
+    let Ok(logger) = LOGGER.lock() else { return Vec::new() };
+    return logger.as_ref().map(|logger| logger.recent(count)).unwrap_or_default();
+}
+
+pub fn flush() {
+    if let Ok(mut logger) = LOGGER.lock() {
+        if let Some(Logger { sink: Sink::Writer(writer), .. }) = logger.as_mut() { let _ = writer.flush(); }
+    }
+}
+
 macro_rules! function_name {
     () => {{
         fn f() {}
@@ -17,19 +224,56 @@ macro_rules! function_name {
 // Re-export macro to avoid using 'macro_use'
 pub(crate) use function_name;
 
-#[cfg(debug_assertions)]
+/// `log!(CATEGORY, message)` logs at the debug level, `log!(Level, CATEGORY, message)` at any level.
+/// The message is only evaluated if the category is enabled at that level.
 macro_rules! log {
+    ($level:ident, $prefix:literal, $msg:expr) => {
+        // The category is resolved at compile time, which also checks that the prefix is a string
+        if crate::utils::log::enabled(crate::utils::log::Level::$level, const { crate::utils::log::Category::from_name($prefix) }) {
+            crate::utils::log::write(format!("{:<70}\t{}", format!("{:<15} {:<40} {:<20}", format!("[{}]", $prefix), format!("({}:{})", file!(), line!()), format!("<{}>", crate::utils::log::function_name!())), $msg));
+        }
+    };
     ($prefix:literal, $msg:expr) => {
-        // Because macro arguments don't have types, force type by assigning to variable which is typed
-        let _p: &str = $prefix;
-        println!("{:<70}\t{}", format!("{:<15} {:<40} {:<20}", format!("[{}]", $prefix), format!("({}:{})", file!(), line!()), format!("<{}>", crate::utils::log::function_name!())), $msg);
+        crate::utils::log::log!(Debug, $prefix, $msg)
     };
 }
 
-#[cfg(not(debug_assertions))]
-macro_rules! log {
-    ($prefix:literal, $msg:expr) => ();
-}
-
 // Re-export macro to avoid using 'macro_use'
-pub(crate) use log;
\ No newline at end of file
+pub(crate) use log;
+
+#[cfg(test)]
+mod tests {
+    use super::{Category, Level, LogFilter, Logger, LOG_BUFFER_LINES};
+
+    #[test]
+    fn test_parse_filter() {
+        let Ok(filter) = "info,memory=trace,gui".parse::<LogFilter>() else { panic!("Valid filter was rejected") };
+        assert_eq!(filter.level(Category::Memory), Some(Level::Trace));
+        assert_eq!(filter.level(Category::Gui), Some(Level::Trace));
+        assert_eq!(filter.level(Category::Cpu), Some(Level::Info));
+
+        let Ok(filter) = "stack=debug".parse::<LogFilter>() else { panic!("Valid filter was rejected") };
+        assert_eq!(filter.level(Category::Stack), Some(Level::Debug));
+        assert_eq!(filter.level(Category::Cpu), None);
+
+        assert_eq!("".parse::<LogFilter>().map(|filter| filter.level(Category::Utils)), Ok(None));
+        assert!("cpu=loud".parse::<LogFilter>().is_err());
+        assert!("sound=info".parse::<LogFilter>().is_err());
+        assert!("info,sound".parse::<LogFilter>().is_err());
+    }
+
+    #[test]
+    fn test_category_names() {
+        for category in Category::ALL { assert_eq!(Category::from_name(category.name()), category); }
+        assert_eq!(Category::from_name("memory"), Category::Memory);
+    }
+
+    #[test]
+    fn test_ring_buffer() {
+        let mut logger = Logger::to_buffer(LogFilter::default());
+        for index in 0..=LOG_BUFFER_LINES { logger.write(index.to_string()); }
+
+        assert_eq!(logger.recent(2), vec![(LOG_BUFFER_LINES - 1).to_string(), LOG_BUFFER_LINES.to_string()]);
+        assert_eq!(logger.recent(usize::MAX).len(), LOG_BUFFER_LINES);
+    }
+}
\ No newline at end of file
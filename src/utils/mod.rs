@@ -1,5 +1,7 @@
 pub mod traits;
 pub mod bits;
+pub mod checksum;
 pub mod log;
 pub mod types;
-pub mod conversions;
\ No newline at end of file
+pub mod conversions;
+pub mod args;
\ No newline at end of file